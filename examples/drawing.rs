@@ -4,7 +4,7 @@ use embedded_graphics::{
     mono_font::{ascii::FONT_6X10, MonoTextStyle},
     pixelcolor::Rgb888,
     prelude::*,
-    primitives::{Circle, Line, PrimitiveStyle},
+    primitives::{Circle, Line, PrimitiveStyle, Rectangle},
     text::{Alignment, Text},
     Drawable,
 };
@@ -38,6 +38,9 @@ fn main() {
     let diagonal2 = Line::new(Point::new(cols - 1, 0), Point::new(0, rows - 1))
         .into_styled(PrimitiveStyle::with_stroke(Rgb888::MAGENTA, 1));
 
+    let text_frame = Rectangle::with_center(Point::new(cols / 2, rows / 2 - 3), Size::new(34, 24))
+        .into_styled(PrimitiveStyle::with_stroke(Rgb888::CSS_ORANGE, 1));
+
     let text = Text::with_alignment(
         "Hello\nWorld",
         Point::new(cols / 2, rows / 2),
@@ -46,18 +49,19 @@ fn main() {
     );
 
     for step in 0.. {
-        canvas.fill(0, 0, 0);
+        canvas.clear();
         circle.draw(canvas.as_mut()).unwrap();
         [diagonal1, diagonal2, top, bottom, left, right]
             .iter()
             .for_each(|line| line.draw(canvas.as_mut()).unwrap());
         if (step / 100) % 2 == 0 {
+            text_frame.draw(canvas.as_mut()).unwrap();
             text.draw(canvas.as_mut()).unwrap();
         }
         canvas = matrix.update_on_vsync(canvas);
 
         if step % 120 == 0 {
-            print!("\r{:>100}\rFramerate: {}", "", matrix.get_framerate());
+            print!("\r{:>100}\rFramerate: {:.0}", "", matrix.get_framerate());
             std::io::stdout().flush().unwrap();
         }
     }
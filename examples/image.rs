@@ -27,12 +27,12 @@ fn main() {
     );
 
     for step in 0.. {
-        canvas.fill(0, 0, 0);
+        canvas.clear();
         image.draw(canvas.as_mut()).unwrap();
         canvas = matrix.update_on_vsync(canvas);
 
         if step % 120 == 0 {
-            print!("\r{:>100}\rFramerate: {}", "", matrix.get_framerate());
+            print!("\r{:>100}\rFramerate: {:.0}", "", matrix.get_framerate());
             std::io::stdout().flush().unwrap();
         }
     }
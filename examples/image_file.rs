@@ -0,0 +1,43 @@
+use std::io::Write;
+
+use argh::FromArgs;
+use rpi_led_panel::{RGBMatrix, RGBMatrixConfig};
+
+fn main() {
+    // The first argument is the image path, the remaining ones configure the matrix.
+    let mut args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: {} <image> [matrix options]", args[0]);
+        std::process::exit(1);
+    }
+    let path = args.remove(1);
+    let rest: Vec<&str> = args[1..].iter().map(String::as_str).collect();
+    let config = match <RGBMatrixConfig as FromArgs>::from_args(&[&args[0]], &rest) {
+        Ok(config) => config,
+        Err(early_exit) => {
+            println!("{}", early_exit.output);
+            std::process::exit(if early_exit.status.is_ok() { 0 } else { 1 });
+        }
+    };
+
+    let image = image::open(&path)
+        .expect("Failed to load the image")
+        .to_rgba8();
+
+    let (mut matrix, mut canvas) = RGBMatrix::new(config, 0).expect("Matrix initialization failed");
+
+    // Center the image on the canvas.
+    let x = (canvas.width() as i32 - image.width() as i32) / 2;
+    let y = (canvas.height() as i32 - image.height() as i32) / 2;
+
+    for step in 0.. {
+        canvas.clear();
+        canvas.blit_image_rgba(&image, x, y);
+        canvas = matrix.update_on_vsync(canvas);
+
+        if step % 120 == 0 {
+            print!("\r{:>100}\rFramerate: {:.0}", "", matrix.get_framerate());
+            std::io::stdout().flush().unwrap();
+        }
+    }
+}
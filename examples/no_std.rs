@@ -0,0 +1,28 @@
+//! Drawing without the standard library, e.g. to render frames on a microcontroller. Build it with
+//! `cargo build --example no_std --no-default-features`.
+#![no_std]
+
+extern crate alloc;
+
+use rpi_led_panel::{Canvas, Color, NamedPixelMapperType, RGBMatrixConfig};
+
+/// Draw a frame on a rotated 64x32 panel and return the color at its center.
+pub fn render() -> Option<Color> {
+    let config = RGBMatrixConfig {
+        rows: 32,
+        cols: 64,
+        pixelmapper: alloc::vec![NamedPixelMapperType::Rotate(90)],
+        ..Default::default()
+    };
+    let mut canvas = Canvas::from_config(&config).ok()?;
+    let (width, height) = canvas.bounds();
+    canvas.fill_gradient_vertical(Color::BLACK, Color::new(0, 0, 64));
+    canvas.draw_circle(
+        width as i32 / 2,
+        height as i32 / 2,
+        12,
+        Color::new(255, 128, 0),
+    );
+    canvas.set_pixel(width / 2, height / 2, 255, 255, 255);
+    canvas.get_pixel(width / 2, height / 2)
+}
@@ -0,0 +1,23 @@
+use rpi_led_panel::{Color, PreviewMatrix, RGBMatrixConfig};
+
+fn main() {
+    let config: RGBMatrixConfig = argh::from_env();
+    let (mut preview, mut canvas) =
+        PreviewMatrix::new(config, 8, 2).expect("Preview initialization failed");
+    let [width, height] = [canvas.width(), canvas.height()];
+
+    for step in 0.. {
+        if !preview.is_open() {
+            break;
+        }
+        // A rainbow that moves diagonally across the panel.
+        for y in 0..height {
+            for x in 0..width {
+                let hue = ((x + y + step) % 360) as f32;
+                let Color { r, g, b } = Color::from_hsv(hue, 1.0, 1.0);
+                canvas.set_pixel(x, y, r, g, b);
+            }
+        }
+        canvas = preview.update_on_vsync(canvas);
+    }
+}
@@ -62,7 +62,7 @@ fn main() {
         canvas = matrix.update_on_vsync(canvas);
 
         if step % 120 == 0 {
-            print!("\r{:>100}\rFramerate: {}", "", matrix.get_framerate());
+            print!("\r{:>100}\rFramerate: {:.0}", "", matrix.get_framerate());
             std::io::stdout().flush().unwrap();
         }
     }
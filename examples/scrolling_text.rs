@@ -0,0 +1,41 @@
+use argh::FromArgs;
+use rpi_led_panel::{Color, Font, RGBMatrix, RGBMatrixConfig};
+
+fn main() {
+    // The first two arguments are the BDF font path and the text, the remaining ones configure the matrix.
+    let mut args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        eprintln!("Usage: {} <font.bdf> <text> [matrix options]", args[0]);
+        std::process::exit(1);
+    }
+    let text = args.remove(2);
+    let path = args.remove(1);
+    let rest: Vec<&str> = args[1..].iter().map(String::as_str).collect();
+    let config = match <RGBMatrixConfig as FromArgs>::from_args(&[&args[0]], &rest) {
+        Ok(config) => config,
+        Err(early_exit) => {
+            println!("{}", early_exit.output);
+            std::process::exit(if early_exit.status.is_ok() { 0 } else { 1 });
+        }
+    };
+
+    let font = Font::from_bdf_file(&path).expect("Failed to load the font");
+
+    let (mut matrix, mut canvas) = RGBMatrix::new(config, 0).expect("Matrix initialization failed");
+
+    // Vertically center the text.
+    let baseline = (canvas.height() as i32 - font.height()) / 2 + font.baseline();
+    let mut x = canvas.width() as i32;
+
+    loop {
+        canvas.clear();
+        let advance = canvas.draw_text(&font, x, baseline, Color::YELLOW, &text);
+        canvas = matrix.update_on_vsync(canvas);
+
+        // Start over once the text has left the canvas on the left.
+        x -= 1;
+        if x + advance < 0 {
+            x = canvas.width() as i32;
+        }
+    }
+}
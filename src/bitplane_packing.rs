@@ -0,0 +1,133 @@
+use crate::config::K_BIT_PLANES;
+
+/// The GPIO bits of every bit plane for the given output levels and the GPIO bits of the red, green and blue
+/// channel of a pixel. This is repeated for every pixel that is drawn.
+///
+/// With the `simd` feature, all bit planes are packed at once with NEON on 64-bit ARM and SSE2 on x86-64, which
+/// produces the same bits as the scalar code that is used otherwise, e.g. on 32-bit ARM. In a micro-benchmark on
+/// x86-64, both take about 16 ns per pixel, since the compiler already vectorizes the scalar code there. The
+/// NEON code has not been benchmarked on a Raspberry Pi yet.
+#[inline]
+pub(crate) fn pack_planes(levels: [u16; 3], bits: [u32; 3]) -> [u32; K_BIT_PLANES] {
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    {
+        neon::pack_planes(levels, bits)
+    }
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        sse2::pack_planes(levels, bits)
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "aarch64", target_arch = "x86_64"))))]
+    {
+        pack_planes_scalar(levels, bits)
+    }
+}
+
+#[cfg_attr(
+    all(feature = "simd", any(target_arch = "aarch64", target_arch = "x86_64")),
+    allow(dead_code)
+)]
+#[inline]
+fn pack_planes_scalar(levels: [u16; 3], bits: [u32; 3]) -> [u32; K_BIT_PLANES] {
+    core::array::from_fn(|plane| {
+        let mask = 1 << plane;
+        levels
+            .iter()
+            .zip(bits)
+            .filter(|(level, _)| *level & mask != 0)
+            .fold(0, |plane_bits, (_, bits)| plane_bits | bits)
+    })
+}
+
+/// The bit of each bit plane, in vectors of four planes. The last lane is unused.
+#[cfg(all(feature = "simd", any(target_arch = "aarch64", target_arch = "x86_64")))]
+const PLANE_MASKS: [[u32; 4]; 3] = [[1, 2, 4, 8], [16, 32, 64, 128], [256, 512, 1024, 2048]];
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod neon {
+    use core::arch::aarch64::{vandq_u32, vdupq_n_u32, vld1q_u32, vorrq_u32, vst1q_u32, vtstq_u32};
+
+    use super::PLANE_MASKS;
+    use crate::config::K_BIT_PLANES;
+
+    #[inline]
+    pub(super) fn pack_planes(levels: [u16; 3], bits: [u32; 3]) -> [u32; K_BIT_PLANES] {
+        let mut planes = [0u32; 12];
+        // SAFETY: NEON is always available on aarch64, and all loads and stores are within the arrays.
+        unsafe {
+            for (masks, out) in PLANE_MASKS.iter().zip(planes.chunks_exact_mut(4)) {
+                let masks = vld1q_u32(masks.as_ptr());
+                let mut packed = vdupq_n_u32(0);
+                for (level, bits) in levels.into_iter().zip(bits) {
+                    // All ones in the lanes of the planes in which the level has its bit set.
+                    let set = vtstq_u32(vdupq_n_u32(level as u32), masks);
+                    packed = vorrq_u32(packed, vandq_u32(set, vdupq_n_u32(bits)));
+                }
+                vst1q_u32(out.as_mut_ptr(), packed);
+            }
+        }
+        core::array::from_fn(|plane| planes[plane])
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod sse2 {
+    use core::arch::x86_64::{
+        __m128i, _mm_and_si128, _mm_cmpeq_epi32, _mm_loadu_si128, _mm_or_si128, _mm_set1_epi32,
+        _mm_setzero_si128, _mm_storeu_si128,
+    };
+
+    use super::PLANE_MASKS;
+    use crate::config::K_BIT_PLANES;
+
+    #[inline]
+    pub(super) fn pack_planes(levels: [u16; 3], bits: [u32; 3]) -> [u32; K_BIT_PLANES] {
+        let mut planes = [0u32; 12];
+        // SAFETY: SSE2 is always available on x86-64, and all loads and stores are within the arrays.
+        unsafe {
+            for (masks, out) in PLANE_MASKS.iter().zip(planes.chunks_exact_mut(4)) {
+                let masks = _mm_loadu_si128(masks.as_ptr() as *const __m128i);
+                let mut packed = _mm_setzero_si128();
+                for (level, bits) in levels.into_iter().zip(bits) {
+                    // All ones in the lanes of the planes in which the level has its bit set.
+                    let level = _mm_and_si128(_mm_set1_epi32(level as i32), masks);
+                    let set = _mm_cmpeq_epi32(level, masks);
+                    packed = _mm_or_si128(packed, _mm_and_si128(set, _mm_set1_epi32(bits as i32)));
+                }
+                _mm_storeu_si128(out.as_mut_ptr() as *mut __m128i, packed);
+            }
+        }
+        core::array::from_fn(|plane| planes[plane])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A xorshift generator, so that the test is reproducible without a dependency.
+    fn random_numbers(mut state: u32) -> impl Iterator<Item = u32> {
+        core::iter::from_fn(move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            Some(state)
+        })
+    }
+
+    #[test]
+    fn test_pack_planes_matches_scalar() {
+        let mut random = random_numbers(0x1234_5678);
+        for _ in 0..10_000 {
+            let levels = [(); 3].map(|_| (random.next().unwrap() % (1 << K_BIT_PLANES)) as u16);
+            let bits = [(); 3].map(|_| 1 << (random.next().unwrap() % 28));
+            assert_eq!(
+                pack_planes(levels, bits),
+                pack_planes_scalar(levels, bits),
+                "{levels:?} {bits:?}"
+            );
+        }
+        assert_eq!(pack_planes([0; 3], [1, 2, 4]), [0; K_BIT_PLANES]);
+        assert_eq!(pack_planes([2047; 3], [1, 2, 4]), [7; K_BIT_PLANES]);
+    }
+}
@@ -1,10 +1,90 @@
-use std::{error::Error, str::FromStr};
+use alloc::{boxed::Box, format, sync::Arc, vec, vec::Vec};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+    time::Duration,
+};
 
 use crate::{
-    color::ColorLookup, config::K_BIT_PLANES, gpio::Gpio, hardware_mapping::HardwareMapping,
-    row_address_setter::RowAddressSetter, RGBMatrixConfig,
+    bitplane_packing::pack_planes,
+    color::{Color, Color16, ColorLookup},
+    config::K_BIT_PLANES,
+    dithering::Dithering,
+    gpio_output::ScanOutput,
+    hardware_mapping::HardwareMapping,
+    math::round,
+    pixel_mapper::create_shared_mapper,
+    row_address_setter::RowAddressSetter,
+    ConfigError, FrameStats, RGBMatrixConfig,
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanvasError {
+    /// The canvases have different visible sizes, given as `[width, height]`.
+    SizeMismatch {
+        expected: [usize; 2],
+        actual: [usize; 2],
+    },
+}
+
+impl Error for CanvasError {}
+
+impl Display for CanvasError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CanvasError::SizeMismatch { expected, actual } => write!(
+                f,
+                "Expected a canvas of {}x{} pixels, but it has {}x{} pixels.",
+                expected[0], expected[1], actual[0], actual[1]
+            ),
+        }
+    }
+}
+
+/// The current drawn by the panels, for [`Canvas::estimate_power_milliamps`] and
+/// [`RGBMatrixConfig::power_limit_ma`]. The currents of the channels are averages over a whole frame, as measured
+/// for a single pixel at full value, so they already include the share of the time that its row is scanned out.
+/// It can be parsed from the four currents separated by commas, e.g. `"1.5,1,1.25,120"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerProfile {
+    /// The current of the red LED of a pixel at full value, in milliamps.
+    pub red_milliamps: f32,
+    /// The current of the green LED of a pixel at full value, in milliamps.
+    pub green_milliamps: f32,
+    /// The current of the blue LED of a pixel at full value, in milliamps.
+    pub blue_milliamps: f32,
+    /// The current of all panels while every pixel is off, in milliamps.
+    pub idle_milliamps: f32,
+}
+
+impl FromStr for PowerProfile {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let milliamps = s
+            .split(',')
+            .map(|value| value.trim().parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| {
+                format!("Invalid power profile '{s}'. Expected four currents in milliamps.")
+            })?;
+        let [red_milliamps, green_milliamps, blue_milliamps, idle_milliamps] = milliamps[..] else {
+            return Err(format!(
+                "Invalid power profile '{s}'. Expected the red, green, blue and idle current separated by commas."
+            )
+            .into());
+        };
+        Ok(Self {
+            red_milliamps,
+            green_milliamps,
+            blue_milliamps,
+            idle_milliamps,
+        })
+    }
+}
+
 pub(crate) enum Channel {
     First,
     Second,
@@ -39,6 +119,19 @@ impl FromStr for LedSequence {
     }
 }
 
+impl Display for LedSequence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            LedSequence::Rgb => "RGB",
+            LedSequence::Rbg => "RBG",
+            LedSequence::Grb => "GRB",
+            LedSequence::Gbr => "GBR",
+            LedSequence::Brg => "BRG",
+            LedSequence::Bgr => "BGR",
+        })
+    }
+}
+
 impl LedSequence {
     fn get_gpio(&self, channel: Channel, red_bits: u32, green_bits: u32, blue_bits: u32) -> u32 {
         match channel {
@@ -70,9 +163,11 @@ impl LedSequence {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) struct PixelDesignator {
     gpio_word: Option<usize>,
+    /// Index of the pixel in the unmapped matrix.
+    matrix_index: usize,
     r_bit: u32,
     g_bit: u32,
     b_bit: u32,
@@ -87,6 +182,7 @@ impl PixelDesignator {
         let b = h.panels.blue_bits();
         Self {
             gpio_word: None,
+            matrix_index: 0,
             r_bit: sequence.get_gpio(Channel::First, r, g, b),
             g_bit: sequence.get_gpio(Channel::Second, r, g, b),
             b_bit: sequence.get_gpio(Channel::Third, r, g, b),
@@ -101,6 +197,9 @@ pub(crate) struct PixelDesignatorMap {
     height: usize,
     pixel_designator: PixelDesignator,
     buffer: Vec<PixelDesignator>,
+    /// The visible position of every pixel of the unmapped matrix, as an index into the buffer, or `None` if
+    /// the pixel is not visible.
+    visible_indices: Vec<Option<u32>>,
 }
 
 impl PixelDesignatorMap {
@@ -119,6 +218,7 @@ impl PixelDesignatorMap {
                 let d = &mut buffer[position];
                 let offset = (y % double_rows) * (width * K_BIT_PLANES) + x;
                 d.gpio_word = Some(offset);
+                d.matrix_index = position;
 
                 let panel = y / config.rows;
                 let color_bits = h.panels.color_bits[panel];
@@ -134,12 +234,7 @@ impl PixelDesignatorMap {
                 d.mask = !(d.r_bit | d.g_bit | d.b_bit);
             }
         }
-        Self {
-            width,
-            height,
-            pixel_designator,
-            buffer,
-        }
+        Self::from_designators(pixel_designator, width, height, buffer)
     }
 
     pub(crate) fn get(&self, x: usize, y: usize) -> Option<&PixelDesignator> {
@@ -147,9 +242,44 @@ impl PixelDesignatorMap {
         self.buffer.get(position)
     }
 
-    pub(crate) fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut PixelDesignator> {
+    /// # Safety
+    ///
+    /// The pixel must be within the map.
+    unsafe fn get_unchecked(&self, x: usize, y: usize) -> &PixelDesignator {
         let position = (y * self.width) + x;
-        self.buffer.get_mut(position)
+        unsafe { self.buffer.get_unchecked(position) }
+    }
+
+    /// A map of the given designators of the visible pixels, row by row.
+    pub(crate) fn from_designators(
+        pixel_designator: PixelDesignator,
+        width: usize,
+        height: usize,
+        buffer: Vec<PixelDesignator>,
+    ) -> Self {
+        debug_assert_eq!(buffer.len(), width * height);
+        let used = || buffer.iter().filter(|d| d.gpio_word.is_some());
+        let matrix_len = used().map(|d| d.matrix_index + 1).max().unwrap_or(0);
+        let mut visible_indices = vec![None; matrix_len];
+        // Pixels that are visible at several positions are found at the first one.
+        for (index, designator) in buffer.iter().enumerate().rev() {
+            if designator.gpio_word.is_some() {
+                visible_indices[designator.matrix_index] = Some(index as u32);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixel_designator,
+            buffer,
+            visible_indices,
+        }
+    }
+
+    /// The visible position of a pixel of the unmapped matrix, if it is visible.
+    fn visible_position(&self, matrix_index: usize) -> Option<(usize, usize)> {
+        let index = (*self.visible_indices.get(matrix_index)?)? as usize;
+        Some((index % self.width, index / self.width))
     }
 
     fn get_pixel_designator(&self) -> PixelDesignator {
@@ -165,108 +295,671 @@ impl PixelDesignatorMap {
     }
 }
 
-#[derive(Clone)]
+/// The frame buffer that is drawn on and displayed by the matrix.
+///
+/// A clone is independent of the original, with its own colors and bit planes, e.g. to restore a checkpoint. Only
+/// the immutable pixel mapping and color lookup are shared.
 pub struct Canvas {
     #[allow(unused)]
     rows: usize,
     cols: usize,
     double_rows: usize,
+    /// The bit planes of every dither phase, one frame after the other.
     bitplane_buffer: Vec<u32>,
-    shared_mapper: PixelDesignatorMap,
+    dithering: Dithering,
+    /// The number of frames over which the rounding error of the PWM bits is distributed.
+    dither_phases: usize,
+    /// The colors as they were set, indexed like the unmapped matrix.
+    color_buffer: Vec<Color>,
+    shared_mapper: Arc<PixelDesignatorMap>,
     pwm_bits: usize,
     brightness: u8,
-    color_lookup: ColorLookup,
+    color_lookup: Arc<ColorLookup>,
     interlaced: bool,
+    /// The bounding box of the pixels drawn since the last reset, as `[x_start, y_start, x_end, y_end]` with
+    /// exclusive ends.
+    dirty: Option<[usize; 4]>,
+    /// Whether colors were changed through [`Canvas::pixels_mut`] without committing them to the bit planes.
+    colors_changed: bool,
+    /// The lower brightness that the bit planes were drawn with to stay within a power budget, if any.
+    power_limited_brightness: Option<u8>,
+    /// Whether the output levels are inverted for common anode panels.
+    inverse_colors: bool,
+    /// Whether the red, green and blue channels are output.
+    channel_mask: [bool; 3],
+    /// The number of rows of every panel.
+    panel_rows: usize,
+    /// The column after the last one of every panel of a chain.
+    panel_ends: Vec<usize>,
+    /// The brightness of every panel in percent, see
+    /// [`RGBMatrix::set_panel_brightness`](crate::RGBMatrix::set_panel_brightness), or empty if all panels
+    /// have the full brightness.
+    panel_brightness: Vec<u8>,
+}
+
+impl Clone for Canvas {
+    fn clone(&self) -> Self {
+        Self {
+            rows: self.rows,
+            cols: self.cols,
+            double_rows: self.double_rows,
+            bitplane_buffer: self.bitplane_buffer.clone(),
+            dithering: self.dithering,
+            dither_phases: self.dither_phases,
+            color_buffer: self.color_buffer.clone(),
+            shared_mapper: self.shared_mapper.clone(),
+            pwm_bits: self.pwm_bits,
+            brightness: self.brightness,
+            color_lookup: self.color_lookup.clone(),
+            interlaced: self.interlaced,
+            dirty: self.dirty,
+            colors_changed: self.colors_changed,
+            power_limited_brightness: self.power_limited_brightness,
+            inverse_colors: self.inverse_colors,
+            channel_mask: self.channel_mask,
+            panel_rows: self.panel_rows,
+            panel_ends: self.panel_ends.clone(),
+            panel_brightness: self.panel_brightness.clone(),
+        }
+    }
+
+    /// Copy the colors and bit planes into the buffers of this canvas, which only allocates if they are too small,
+    /// e.g. after a pixel mapper was applied to the source.
+    fn clone_from(&mut self, source: &Self) {
+        // Every field is listed, so that new ones are not forgotten.
+        let Self {
+            rows,
+            cols,
+            double_rows,
+            bitplane_buffer,
+            dithering,
+            dither_phases,
+            color_buffer,
+            shared_mapper,
+            pwm_bits,
+            brightness,
+            color_lookup,
+            interlaced,
+            dirty,
+            colors_changed,
+            power_limited_brightness,
+            inverse_colors,
+            channel_mask,
+            panel_rows,
+            panel_ends,
+            panel_brightness,
+        } = source;
+        self.rows = *rows;
+        self.cols = *cols;
+        self.double_rows = *double_rows;
+        self.bitplane_buffer.clone_from(bitplane_buffer);
+        self.dithering = *dithering;
+        self.dither_phases = *dither_phases;
+        self.color_buffer.clone_from(color_buffer);
+        self.shared_mapper = Arc::clone(shared_mapper);
+        self.pwm_bits = *pwm_bits;
+        self.brightness = *brightness;
+        self.color_lookup = Arc::clone(color_lookup);
+        self.interlaced = *interlaced;
+        self.dirty = *dirty;
+        self.colors_changed = *colors_changed;
+        self.power_limited_brightness = *power_limited_brightness;
+        self.inverse_colors = *inverse_colors;
+        self.channel_mask = *channel_mask;
+        self.panel_rows = *panel_rows;
+        self.panel_ends.clone_from(panel_ends);
+        self.panel_brightness.clone_from(panel_brightness);
+    }
 }
 
 impl Canvas {
-    pub(crate) fn new(config: &RGBMatrixConfig, shared_mapper: PixelDesignatorMap) -> Self {
-        let color_lookup = ColorLookup::new_cie1931();
+    pub(crate) fn new(
+        config: &RGBMatrixConfig,
+        shared_mapper: Arc<PixelDesignatorMap>,
+        color_lookup: Arc<ColorLookup>,
+    ) -> Self {
         let rows = config.rows * config.parallel;
-        let cols = config.cols * config.chain_length;
+        let cols = config.chain_width();
         let double_rows = config.double_rows();
-        Self {
+        let dither_phases = config.dithering.phases(config.dither_bits);
+        let mut canvas = Self {
             rows,
             cols,
             double_rows,
-            bitplane_buffer: vec![0u32; double_rows * cols * K_BIT_PLANES],
+            bitplane_buffer: vec![0u32; dither_phases * double_rows * cols * K_BIT_PLANES],
+            dithering: config.dithering,
+            dither_phases,
+            color_buffer: vec![Color::BLACK; rows * cols],
             shared_mapper,
             pwm_bits: config.pwm_bits,
-            brightness: 100,
+            brightness: config.brightness.min(100),
             color_lookup,
             interlaced: config.interlaced,
+            dirty: None,
+            colors_changed: false,
+            power_limited_brightness: None,
+            inverse_colors: config.inverse_colors,
+            channel_mask: config.channel_mask(),
+            panel_rows: config.rows,
+            panel_ends: config
+                .chain_panels()
+                .iter()
+                .scan(0, |end, panel| {
+                    *end += panel.cols;
+                    Some(*end)
+                })
+                .collect(),
+            panel_brightness: Vec::new(),
+        };
+        // The empty bit planes would light up every pixel of an inverted panel.
+        if canvas.inverse_colors {
+            canvas.clear();
+            canvas.dirty = None;
         }
+        canvas
+    }
+
+    /// Create a canvas for the given config without a matrix, e.g. to render frames without the standard library
+    /// and send the bit planes to the panels by other means. The canvas has the same size and pixel mapping as
+    /// the one returned by [`RGBMatrix::new`](crate::RGBMatrix::new).
+    pub fn from_config(config: &RGBMatrixConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        let mut config = config.clone();
+        let (_, shared_mapper) = create_shared_mapper(&mut config)?;
+        Ok(Self::new(
+            &config,
+            shared_mapper,
+            Arc::new(ColorLookup::new(config.gamma, config.white_balance)),
+        ))
     }
 
+    /// The visible height, after the pixel mappers are applied, e.g. the number of columns of a matrix that is
+    /// rotated by 90 degrees.
     pub fn height(&self) -> usize {
         self.shared_mapper.height
     }
 
+    /// The visible width, after the pixel mappers are applied.
     pub fn width(&self) -> usize {
         self.shared_mapper.width
     }
 
-    fn position_at(&self, double_row: usize, column: usize, bit: usize) -> usize {
-        double_row * (self.cols * K_BIT_PLANES) + bit * self.cols + column
+    /// The visible width and height, after the pixel mappers are applied.
+    pub fn bounds(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    /// Whether the pixel is within the visible bounds of the canvas.
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width() && y < self.height()
     }
 
-    fn row_at(&self, double_row: usize, column: usize, bit: usize) -> &[u32] {
-        let start = self.position_at(double_row, column, bit);
+    /// Replace the pixel mapping of this canvas. Does nothing if the canvas already uses this mapping.
+    pub(crate) fn set_shared_mapper(&mut self, shared_mapper: &Arc<PixelDesignatorMap>) {
+        if !Arc::ptr_eq(&self.shared_mapper, shared_mapper) {
+            self.shared_mapper = Arc::clone(shared_mapper);
+        }
+    }
+
+    /// Replace the color lookup of this canvas. Does nothing if the canvas already uses this lookup.
+    pub(crate) fn set_color_lookup(&mut self, color_lookup: &Arc<ColorLookup>) {
+        if !Arc::ptr_eq(&self.color_lookup, color_lookup) {
+            self.color_lookup = Arc::clone(color_lookup);
+        }
+    }
+
+    /// The length of the bit planes of one dither phase.
+    fn phase_len(&self) -> usize {
+        self.double_rows * self.cols * K_BIT_PLANES
+    }
+
+    fn position_at(&self, phase: usize, double_row: usize, column: usize, bit: usize) -> usize {
+        phase * self.phase_len()
+            + double_row * (self.cols * K_BIT_PLANES)
+            + bit * self.cols
+            + column
+    }
+
+    fn row_at(&self, phase: usize, double_row: usize, column: usize, bit: usize) -> &[u32] {
+        let start = self.position_at(phase, double_row, column, bit);
         &self.bitplane_buffer[start..start + self.cols]
     }
 
-    fn row_at_mut(&mut self, double_row: usize, column: usize, bit: usize) -> &mut [u32] {
-        let start = self.position_at(double_row, column, bit);
+    fn row_at_mut(
+        &mut self,
+        phase: usize,
+        double_row: usize,
+        column: usize,
+        bit: usize,
+    ) -> &mut [u32] {
+        let start = self.position_at(phase, double_row, column, bit);
         &mut self.bitplane_buffer[start..start + self.cols]
     }
 
+    /// The output levels of a pixel in a dither phase. A threshold that depends on the dithering is added before
+    /// the bits below the lowest PWM bit plane are dropped, which distributes the rounding error over frames or
+    /// neighboring pixels.
+    fn dithered_levels(&self, levels: [u16; 3], phase: usize, matrix_index: usize) -> [u16; 3] {
+        let quantum = 1 << (K_BIT_PLANES - self.pwm_bits);
+        let position = [matrix_index % self.cols, matrix_index / self.cols];
+        let threshold = self
+            .dithering
+            .threshold(quantum, self.dither_phases, phase, position);
+        let max_level = (1 << K_BIT_PLANES) - 1;
+        levels.map(|level| level.saturating_add(threshold).min(max_level))
+    }
+
     pub fn set_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8) {
-        if x >= self.width() || y >= self.height() {
+        let levels = self.color_lookup.lookup_rgb(self.brightness, r, g, b);
+        self.set_pixel_levels(x, y, Color::new(r, g, b), levels);
+    }
+
+    /// Set a pixel like [`Canvas::set_pixel`], but without checking that it is within the canvas, for tight
+    /// loops that already clip their coordinates. This is only checked by a debug assertion.
+    ///
+    /// # Safety
+    ///
+    /// The pixel must be within the canvas, i.e. [`Canvas::contains`] must return `true` for it.
+    pub unsafe fn set_pixel_unchecked(&mut self, x: usize, y: usize, color: Color) {
+        debug_assert!(
+            self.contains(x, y),
+            "Pixel ({x}, {y}) is outside of the {}x{} canvas.",
+            self.width(),
+            self.height()
+        );
+        let levels = self
+            .color_lookup
+            .lookup_rgb(self.brightness, color.r, color.g, color.b);
+        // SAFETY: The caller guarantees that the pixel is within the canvas, which has the size of the map.
+        let designator = *unsafe { self.shared_mapper.get_unchecked(x, y) };
+        self.draw_pixel_levels(x, y, designator, color, levels);
+    }
+
+    /// Set the pixels of row `y` from column `x_start` on to the given colors, e.g. to copy a row of a video
+    /// frame. Pixels beyond the right edge are clipped, and nothing is drawn if the row is outside of the canvas.
+    /// The bounds are only checked once for the whole row.
+    pub fn set_row(&mut self, y: usize, x_start: usize, colors: &[Color]) {
+        if y >= self.height() || x_start >= self.width() {
+            return;
+        }
+        let visible = colors.len().min(self.width() - x_start);
+        for (x, &color) in (x_start..).zip(&colors[..visible]) {
+            // SAFETY: The row and the columns were clipped to the canvas.
+            unsafe { self.set_pixel_unchecked(x, y, color) };
+        }
+    }
+
+    /// Set many pixels at once, given as `(x, y, color)`. Pixels outside of the canvas are skipped.
+    pub fn set_pixels(&mut self, pixels: &[(usize, usize, Color)]) {
+        let [width, height] = [self.width(), self.height()];
+        for &(x, y, color) in pixels {
+            if x < width && y < height {
+                // SAFETY: The pixel is within the canvas.
+                unsafe { self.set_pixel_unchecked(x, y, color) };
+            }
+        }
+    }
+
+    /// Set a pixel to a color with 16 bits per channel, which is gamma corrected with its full precision. This
+    /// gives smoother gradients than rounding to 8 bits first, mostly in dark colors. The precision of the
+    /// output is still limited by [`RGBMatrixConfig::pwm_bits`], plus the bits kept by dithering.
+    ///
+    /// The canvas stores the color rounded to 8 bits, which is returned by [`Canvas::get_pixel`] and used
+    /// whenever all pixels are drawn again, e.g. when the brightness of the matrix changes.
+    pub fn set_pixel16(&mut self, x: usize, y: usize, color: Color16) {
+        if !self.contains(x, y) {
+            return;
+        }
+        let levels = self.color_lookup.levels16(self.brightness, color);
+        self.set_pixel_levels(x, y, color.into(), levels);
+    }
+
+    /// Store the color of a pixel and draw it with the given output levels.
+    fn set_pixel_levels(&mut self, x: usize, y: usize, color: Color, levels: [u16; 3]) {
+        if !self.contains(x, y) {
             return;
         }
         let designator = match self.shared_mapper.get(x, y) {
-            Some(d) => d,
+            Some(d) => *d,
             None => panic!("Pixel not in designator map. This is a bug."),
         };
+        self.draw_pixel_levels(x, y, designator, color, levels);
+    }
+
+    /// The index of the panel of a pixel of the unmapped matrix, counted along the chains one after the other.
+    fn panel_index(&self, matrix_index: usize) -> usize {
+        let [x, y] = [matrix_index % self.cols, matrix_index / self.cols];
+        let position = self.panel_ends.partition_point(|&end| end <= x);
+        (y / self.panel_rows) * self.panel_ends.len() + position
+    }
+
+    /// Scale the output levels of a pixel of the unmapped matrix with the brightness of its panel.
+    fn panel_levels(&self, levels: [u16; 3], matrix_index: usize) -> [u16; 3] {
+        match self.panel_brightness.get(self.panel_index(matrix_index)) {
+            Some(&percent) if percent < 100 => {
+                levels.map(|level| ((level as u32 * percent as u32 + 50) / 100) as u16)
+            }
+            _ => levels,
+        }
+    }
+
+    /// Store the color of a pixel within the canvas and draw it with the given output levels.
+    fn draw_pixel_levels(
+        &mut self,
+        x: usize,
+        y: usize,
+        designator: PixelDesignator,
+        color: Color,
+        levels: [u16; 3],
+    ) {
+        if designator.gpio_word.is_none() {
+            // non-used pixel marker.
+            return;
+        }
+        if let Some(stored) = self.color_buffer.get_mut(designator.matrix_index) {
+            *stored = color;
+        }
+        self.mark_dirty([x, y, x + 1, y + 1]);
+        self.pack_pixel_levels(designator, levels);
+    }
+
+    /// Write the bit planes of a used pixel for the given output levels.
+    fn pack_pixel_levels(&mut self, designator: PixelDesignator, levels: [u16; 3]) {
         let PixelDesignator {
             gpio_word,
+            matrix_index,
             r_bit,
             g_bit,
             b_bit,
             mask: designator_mask,
-        } = *designator;
+        } = designator;
+        let Some(pos_start) = gpio_word else {
+            return;
+        };
 
-        let pos_start = match gpio_word {
-            Some(w) => w,
-            None => {
-                // non-used pixel marker.
-                return;
+        let min_bit_plane = K_BIT_PLANES - self.pwm_bits;
+        let levels = self.panel_levels(levels, matrix_index);
+
+        (0..self.dither_phases).for_each(|phase| {
+            let levels = self.dithered_levels(levels, phase, matrix_index);
+            let planes = self.pack_levels(levels, [r_bit, g_bit, b_bit]);
+            let phase_start = pos_start + phase * self.phase_len();
+            (min_bit_plane..K_BIT_PLANES).for_each(|plane| {
+                let pos = phase_start + self.cols * plane;
+                self.bitplane_buffer[pos] &= designator_mask;
+                self.bitplane_buffer[pos] |= planes[plane];
+            });
+        });
+    }
+
+    /// The GPIO bits of every bit plane for the output levels of a pixel, without the disabled channels and
+    /// inverted for common anode panels.
+    fn pack_levels(&self, levels: [u16; 3], bits: [u32; 3]) -> [u32; K_BIT_PLANES] {
+        let levels = core::array::from_fn(|channel| {
+            if self.channel_mask[channel] {
+                levels[channel]
+            } else {
+                0
             }
+        });
+        let levels = if self.inverse_colors {
+            levels.map(|level| !level & ((1 << K_BIT_PLANES) - 1))
+        } else {
+            levels
         };
+        pack_planes(levels, bits)
+    }
 
-        let [red, green, blue] = self.color_lookup.lookup_rgb(self.brightness, r, g, b);
+    /// Get the color of a pixel as it was set, before applying brightness and gamma correction. Returns `None`
+    /// if the pixel is outside of the canvas.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        let designator = self.shared_mapper.get(x, y)?;
+        designator.gpio_word?;
+        self.color_buffer.get(designator.matrix_index).copied()
+    }
 
-        let min_bit_plane = K_BIT_PLANES - self.pwm_bits;
+    /// The colors of all pixels as they were set, row by row.
+    pub(crate) fn colors(&self) -> Vec<Color> {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| [x, y]))
+            .map(|[x, y]| self.get_pixel(x, y).unwrap_or_default())
+            .collect()
+    }
 
-        (min_bit_plane..K_BIT_PLANES).for_each(|plane| {
-            let pos = pos_start + self.cols * plane;
-            let mask = 1 << plane;
-            let mut color_bits = 0;
-            if (red & mask) != 0 {
-                color_bits |= r_bit
-            };
-            if (green & mask) != 0 {
-                color_bits |= g_bit
-            };
-            if (blue & mask) != 0 {
-                color_bits |= b_bit
+    /// Iterate over the colors of all visible pixels as they were set, row by row, together with their
+    /// positions.
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize, &Color)> {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .filter_map(|(x, y)| {
+                let designator = self.shared_mapper.get(x, y)?;
+                designator.gpio_word?;
+                Some((x, y, self.color_buffer.get(designator.matrix_index)?))
+            })
+    }
+
+    /// Iterate over the colors of all visible pixels together with their positions, to change them in place.
+    /// The pixels are visited in the order in which their colors are stored, which is row by row unless pixel
+    /// mappers rearrange the pixels, so that no memory needs to be allocated.
+    ///
+    /// The changed colors are packed into the bit planes by [`Canvas::commit_pixels`], which the matrix calls
+    /// when the canvas is passed to it.
+    pub fn pixels_mut(&mut self) -> impl Iterator<Item = (usize, usize, &mut Color)> {
+        let [width, height] = [self.width(), self.height()];
+        self.colors_changed = true;
+        self.mark_dirty([0, 0, width, height]);
+        let mapper = &self.shared_mapper;
+        self.color_buffer
+            .iter_mut()
+            .enumerate()
+            .filter_map(move |(matrix_index, color)| {
+                let (x, y) = mapper.visible_position(matrix_index)?;
+                Some((x, y, color))
+            })
+    }
+
+    /// Pack the colors changed through [`Canvas::pixels_mut`] into the bit planes, e.g. before reading them
+    /// without a matrix. Does nothing if no colors were changed that way.
+    pub fn commit_pixels(&mut self) {
+        if self.colors_changed {
+            self.colors_changed = false;
+            self.redraw();
+        }
+    }
+
+    /// Blend a color over the existing pixel, where an `alpha` of 0 leaves the pixel unchanged and 255 replaces
+    /// it. Pixels outside of the canvas are ignored.
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: Color, alpha: u8) {
+        let Some(background) = self.get_pixel(x, y) else {
+            return;
+        };
+        let alpha = alpha as u32;
+        let blend =
+            |fg: u8, bg: u8| ((fg as u32 * alpha + bg as u32 * (255 - alpha) + 127) / 255) as u8;
+        self.set_pixel(
+            x,
+            y,
+            blend(color.r, background.r),
+            blend(color.g, background.g),
+            blend(color.b, background.b),
+        );
+    }
+
+    /// Multiply the colors of all pixels by `factor` like [`Color::scale`], saturating at 0 and 255. Factors
+    /// below 1 darken the canvas and factors above 1 brighten it. Unlike [`Canvas::set_brightness`], this
+    /// changes the colors themselves, e.g. to dim an imported image.
+    pub fn adjust_brightness(&mut self, factor: f32) {
+        self.map_colors(|color| color.scale(factor));
+    }
+
+    /// Scale the distance of all color channels from the middle value 128 by `factor`, saturating at 0 and
+    /// 255. Factors below 1 reduce the contrast, down to a uniform gray at 0, and factors above 1 increase it.
+    pub fn adjust_contrast(&mut self, factor: f32) {
+        let adjust = |c: u8| round(128.0 + (c as f32 - 128.0) * factor).clamp(0.0, 255.0) as u8;
+        self.map_colors(|color| Color::new(adjust(color.r), adjust(color.g), adjust(color.b)));
+    }
+
+    /// Subtract `amount` from every channel of every pixel, saturating at 0, so that content drawn in earlier
+    /// frames decays to black, e.g. for trails. The stored colors are faded in a single pass, after which the
+    /// bit planes are drawn once.
+    pub fn fade(&mut self, amount: u8) {
+        if amount == 0 {
+            return;
+        }
+        let step = Color::new(amount, amount, amount);
+        for color in &mut self.color_buffer {
+            *color = *color - step;
+        }
+        self.mark_dirty([0, 0, self.width(), self.height()]);
+        self.redraw();
+    }
+
+    /// Blur the canvas with a box blur, which replaces every pixel with the average of the pixels up to `radius`
+    /// away from it, first horizontally and then vertically. Pixels beyond the edges repeat the edge pixels, so
+    /// a uniform canvas stays unchanged. Every pass keeps a running sum, so the time does not grow with the
+    /// radius. A radius of 0 leaves the canvas unchanged.
+    pub fn blur(&mut self, radius: usize) {
+        if radius == 0 {
+            return;
+        }
+        let [width, height] = [self.width(), self.height()];
+        let mut colors = self.colors();
+        Self::box_blur(&mut colors, height, width, |row, x| row * width + x, radius);
+        Self::box_blur(
+            &mut colors,
+            width,
+            height,
+            |column, y| y * width + column,
+            radius,
+        );
+        self.map_colors_at(|x, y| colors[y * width + x]);
+    }
+
+    /// Average the colors of every one of `lines` lines of `length` pixels over a window of `radius` pixels on
+    /// either side, where `index` gives the index of the i-th pixel of a line.
+    fn box_blur(
+        colors: &mut [Color],
+        lines: usize,
+        length: usize,
+        index: impl Fn(usize, usize) -> usize,
+        radius: usize,
+    ) {
+        let window = 2 * radius as u32 + 1;
+        let mut line = Vec::with_capacity(length);
+        for line_index in 0..lines {
+            line.clear();
+            line.extend((0..length).map(|i| colors[index(line_index, i)]));
+            let at = |i: isize| {
+                let Color { r, g, b } = line[i.clamp(0, length as isize - 1) as usize];
+                [r, g, b].map(u32::from)
             };
-            self.bitplane_buffer[pos] &= designator_mask;
-            self.bitplane_buffer[pos] |= color_bits;
-        });
+            let radius = radius as isize;
+            let mut sum = (-radius..=radius).fold([0; 3], |sum, i| {
+                let color = at(i);
+                [0, 1, 2].map(|c| sum[c] + color[c])
+            });
+            for i in 0..length as isize {
+                let [r, g, b] = sum.map(|channel| ((channel + window / 2) / window) as u8);
+                colors[index(line_index, i as usize)] = Color::new(r, g, b);
+                let [entering, leaving] = [at(i + radius + 1), at(i - radius)];
+                sum = [0, 1, 2].map(|c| sum[c] + entering[c] - leaving[c]);
+            }
+        }
+    }
+
+    /// Replace the color of every visible pixel by the color that `f` returns for its position.
+    fn map_colors_at(&mut self, f: impl Fn(usize, usize) -> Color) {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if self.get_pixel(x, y).is_some() {
+                    let Color { r, g, b } = f(x, y);
+                    self.set_pixel(x, y, r, g, b);
+                }
+            }
+        }
+    }
+
+    /// Replace the color of every visible pixel by the color that `f` returns for it.
+    fn map_colors(&mut self, f: impl Fn(Color) -> Color) {
+        let colors = self.colors();
+        let width = self.width();
+        self.map_colors_at(|x, y| f(colors[y * width + x]));
+    }
+
+    /// Draw the linear blend of the `from` and `to` canvases, where a `t` of 0 draws `from` and 1 draws `to`.
+    /// Values outside of this range are clamped. All three canvases must have the same visible size.
+    pub fn crossfade(&mut self, from: &Canvas, to: &Canvas, t: f32) -> Result<(), CanvasError> {
+        for canvas in [from, to] {
+            if canvas.bounds() != self.bounds() {
+                let size = |(width, height)| [width, height];
+                return Err(CanvasError::SizeMismatch {
+                    expected: size(self.bounds()),
+                    actual: size(canvas.bounds()),
+                });
+            }
+        }
+        let t = t.clamp(0.0, 1.0);
+        let blend = |from: u8, to: u8| round(from as f32 + (to as f32 - from as f32) * t) as u8;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let (Some(from), Some(to)) = (from.get_pixel(x, y), to.get_pixel(x, y)) else {
+                    continue;
+                };
+                self.set_pixel(
+                    x,
+                    y,
+                    blend(from.r, to.r),
+                    blend(from.g, to.g),
+                    blend(from.b, to.b),
+                );
+            }
+        }
+        Ok(())
     }
 
-    pub fn fill(&mut self, r: u8, g: u8, b: u8) {
+    /// Copy the whole `src` canvas onto this canvas, with its top left corner at the origin. See
+    /// [`Canvas::copy_region`] for how canvases of different configurations are handled.
+    pub fn copy_from(&mut self, src: &Canvas) {
+        self.copy_region(src, [0, 0, src.width() as i32, src.height() as i32], 0, 0);
+    }
+
+    /// Copy the rectangle `src_rect`, given as `[x, y, width, height]`, of the `src` canvas to this canvas with
+    /// its top left corner at (`dst_x`, `dst_y`). Parts of the rectangle that are outside of either canvas are
+    /// discarded.
+    ///
+    /// The colors are copied as they were set, so the canvases don't need to share a configuration. Brightness,
+    /// gamma correction and pixel mappers of this canvas are applied to the copied pixels.
+    pub fn copy_region(&mut self, src: &Canvas, src_rect: [i32; 4], dst_x: i32, dst_y: i32) {
+        let [src_x, src_y, width, height] = src_rect.map(i64::from);
+        let [dst_x, dst_y] = [dst_x, dst_y].map(i64::from);
+        // Clip the region to the source canvas first and then to the destination canvas.
+        let x_start = src_x.max(0).max(src_x - dst_x);
+        let y_start = src_y.max(0).max(src_y - dst_y);
+        let x_end = (src_x + width)
+            .min(src.width() as i64)
+            .min(src_x - dst_x + self.width() as i64);
+        let y_end = (src_y + height)
+            .min(src.height() as i64)
+            .min(src_y - dst_y + self.height() as i64);
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let Some(Color { r, g, b }) = src.get_pixel(x as usize, y as usize) else {
+                    continue;
+                };
+                let [x, y] = [x - src_x + dst_x, y - src_y + dst_y].map(|v| v as usize);
+                self.set_pixel(x, y, r, g, b);
+            }
+        }
+    }
+
+    /// Set every pixel of the canvas to the given color.
+    pub fn fill(&mut self, color: Color) {
+        self.color_buffer.fill(color);
+        self.mark_dirty([0, 0, self.width(), self.height()]);
+        let Color { r, g, b } = color;
         let designator = self.shared_mapper.get_pixel_designator();
         let PixelDesignator {
             r_bit,
@@ -275,61 +968,91 @@ impl Canvas {
             ..
         } = designator;
 
-        let [red, green, blue] = self.color_lookup.lookup_rgb(self.brightness, r, g, b);
+        let levels = self.color_lookup.lookup_rgb(self.brightness, r, g, b);
 
-        (K_BIT_PLANES - self.pwm_bits..K_BIT_PLANES).for_each(|b| {
-            let mask = 1 << b;
-            let mut plane_bits = 0;
-            if (red & mask) == mask {
-                plane_bits |= r_bit
-            };
-            if (green & mask) == mask {
-                plane_bits |= g_bit
-            };
-            if (blue & mask) == mask {
-                plane_bits |= b_bit
-            };
-            (0..self.double_rows).for_each(|row| {
-                self.row_at_mut(row, 0, b).fill(plane_bits);
+        (0..self.dither_phases).for_each(|phase| {
+            let levels = self.dithered_levels(levels, phase, 0);
+            let planes = self.pack_levels(levels, [r_bit, g_bit, b_bit]);
+            (K_BIT_PLANES - self.pwm_bits..K_BIT_PLANES).for_each(|b| {
+                (0..self.double_rows).for_each(|row| {
+                    self.row_at_mut(phase, row, 0, b).fill(planes[b]);
+                });
             });
         });
+
+        // Ordered dithering and the brightness of the panels depend on the position, so the visible pixels are
+        // drawn again one by one.
+        if self.dithering == Dithering::OrderedBayer || !self.panel_brightness.is_empty() {
+            self.redraw();
+        }
+    }
+
+    /// Set every pixel of the canvas to black.
+    pub fn clear(&mut self) {
+        self.fill(Color::BLACK);
+    }
+
+    /// The bounding box of the pixels drawn since the canvas was last passed to or returned by the matrix, or
+    /// since [`Canvas::reset_dirty`] was called, as `[x, y, width, height]`, or `None` if nothing was drawn.
+    ///
+    /// Every pixel is packed into the bit planes as it is drawn, so there is no packing of the whole frame on a
+    /// swap that this region could narrow down. The matrix only uses it to draw the region again at the limited
+    /// brightness of [`RGBMatrixConfig::power_limit_ma`](crate::RGBMatrixConfig::power_limit_ma). A canvas
+    /// handed back by the matrix can be brought up to date by copying just this region of the newer canvas with
+    /// [`Canvas::copy_region`] instead of drawing the whole frame again.
+    pub fn dirty_rect(&self) -> Option<[usize; 4]> {
+        self.dirty.map(|[x_start, y_start, x_end, y_end]| {
+            [x_start, y_start, x_end - x_start, y_end - y_start]
+        })
+    }
+
+    /// Forget the pixels drawn so far, so that [`Canvas::dirty_rect`] only covers the pixels drawn afterwards.
+    pub fn reset_dirty(&mut self) {
+        self.dirty = None;
+    }
+
+    /// Grow the dirty region to include the given box of `[x_start, y_start, x_end, y_end]`.
+    fn mark_dirty(&mut self, [x_start, y_start, x_end, y_end]: [usize; 4]) {
+        self.dirty = Some(match self.dirty {
+            Some([x0, y0, x1, y1]) => [
+                x0.min(x_start),
+                y0.min(y_start),
+                x1.max(x_end),
+                y1.max(y_end),
+            ],
+            None => [x_start, y_start, x_end, y_end],
+        });
     }
 
-    pub(crate) fn dump_to_matrix(
+    pub(crate) fn dump_to_matrix<G: ScanOutput>(
         &self,
-        gpio: &mut Gpio,
+        gpio: &mut G,
         hardware_mapping: &HardwareMapping,
         row_setter: &mut dyn RowAddressSetter,
-        pwm_low_bit: usize,
+        frame: usize,
         color_clk_mask: u32,
-    ) {
-        // Depending on if we do dithering, we might not always show the lowest bits.
-        let start_bit = (K_BIT_PLANES - self.pwm_bits).max(pwm_low_bit);
-
-        let half_double = self.double_rows / 2;
-        for row_loop in 0..self.double_rows {
-            let d_row = match self.interlaced {
-                false => row_loop,
-                true => {
-                    if row_loop < half_double {
-                        2 * row_loop
-                    } else {
-                        2 * (row_loop - half_double) + 1
-                    }
-                }
-            };
+    ) -> FrameStats {
+        let start_bit = K_BIT_PLANES - self.pwm_bits;
+        // Successive frames cycle through the dither phases.
+        let phase = frame % self.dither_phases;
 
+        let start_time = gpio.time_ns();
+        let mut clock_in_ns = 0;
+
+        for d_row in self.row_scan_order() {
             // Rows can't be switched very quickly without ghosting, so we do the
             // full PWM of one row before switching rows.
             for b in start_bit..K_BIT_PLANES {
                 // While the output enable is still on, we can already clock in the next data.
-                let row = self.row_at(d_row, 0, b);
+                let clock_in_start = gpio.time_ns();
+                let row = self.row_at(phase, d_row, 0, b);
                 row.iter().for_each(|col| {
                     gpio.write_masked_bits(*col, color_clk_mask); // col + reset clock
                     gpio.set_bits(hardware_mapping.clock); // Rising edge: clock color in.
                 });
 
                 gpio.clear_bits(color_clk_mask); // clock back to normal.
+                clock_in_ns += gpio.time_ns() - clock_in_start;
 
                 // OE of the previous row-data must be finished before strobe.
                 gpio.wait_pulse_finished();
@@ -345,6 +1068,22 @@ impl Canvas {
                 gpio.send_pulse(b);
             }
         }
+
+        let scan_ns = gpio.time_ns() - start_time;
+        FrameStats {
+            scan_time: Duration::from_nanos(scan_ns),
+            clock_in_time: Duration::from_nanos(clock_in_ns),
+            pulse_time: Duration::from_nanos(scan_ns - clock_in_ns),
+            bitplanes: self.pwm_bits,
+        }
+    }
+
+    /// The order in which the double rows are output. In interlaced mode, a frame is split into two fields: the
+    /// even rows are output first, followed by the odd rows.
+    fn row_scan_order(&self) -> impl Iterator<Item = usize> {
+        let fields = if self.interlaced { 2 } else { 1 };
+        let double_rows = self.double_rows;
+        (0..fields).flat_map(move |field| (field..double_rows).step_by(fields))
     }
 
     // Set PWM bits used for output. Default is 11, but if you only deal with
@@ -353,15 +1092,1178 @@ impl Canvas {
         self.pwm_bits = pwm_bits;
     }
 
-    /// Set the canvas' brightness in percent.
+    /// Set the canvas' brightness in percent, from 0 to 100. Applies to pixels that are drawn afterwards.
     pub fn set_brightness(&mut self, brightness: u8) {
-        self.brightness = brightness.clamp(1, 100);
+        self.brightness = brightness.min(100);
+    }
+
+    /// Get the canvas' brightness in percent.
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Estimate the current drawn while this canvas is displayed, in milliamps. Every channel contributes in
+    /// proportion to the time it is on, which follows the gamma corrected value and the brightness of the canvas
+    /// and of its panel, so the estimate can be used to pick a brightness that stays within a power budget.
+    /// Pixels of the panels that are not visible are never drawn, so they are off, except on panels with
+    /// inverse colors, on which they are fully on.
+    pub fn estimate_power_milliamps(&self, profile: &PowerProfile) -> u32 {
+        let [drawn, hidden] = self.lit_milliamps(profile);
+        round(profile.idle_milliamps + hidden + drawn) as u32
+    }
+
+    /// The current of the drawn pixels, which follows the brightness, and of the pixels that are not visible.
+    fn lit_milliamps(&self, profile: &PowerProfile) -> [f32; 2] {
+        let max_level = (1 << K_BIT_PLANES) - 1;
+        let channel_milliamps = [
+            profile.red_milliamps,
+            profile.green_milliamps,
+            profile.blue_milliamps,
+        ];
+        // The empty bit planes of pixels that are not visible only light up inverted panels, on all channels.
+        let hidden_milliamps: f32 = if self.inverse_colors {
+            channel_milliamps.iter().sum()
+        } else {
+            0.0
+        };
+        self.color_buffer.iter().enumerate().fold(
+            [0.0, 0.0],
+            |[drawn, hidden], (matrix_index, color)| {
+                if self.shared_mapper.visible_position(matrix_index).is_none() {
+                    return [drawn, hidden + hidden_milliamps];
+                }
+                let levels =
+                    self.color_lookup
+                        .lookup_rgb(self.brightness, color.r, color.g, color.b);
+                let milliamps = self
+                    .panel_levels(levels, matrix_index)
+                    .iter()
+                    .zip(channel_milliamps)
+                    .zip(self.channel_mask)
+                    .filter(|(_, enabled)| *enabled)
+                    .map(|((&level, milliamps), _)| level as f32 / max_level as f32 * milliamps)
+                    .sum::<f32>();
+                [drawn + milliamps, hidden]
+            },
+        )
+    }
+
+    /// Set the brightness and redraw all pixels with it. Does nothing if the canvas already has this
+    /// brightness.
+    pub(crate) fn apply_brightness(&mut self, brightness: u8) {
+        if self.brightness == brightness {
+            return;
+        }
+        self.brightness = brightness;
+        self.redraw();
+    }
+
+    /// Set the brightness of the panels and redraw all pixels with it. Does nothing if the canvas already has
+    /// these brightnesses.
+    pub(crate) fn apply_panel_brightness(&mut self, panel_brightness: &[u8]) {
+        if self.panel_brightness == panel_brightness {
+            return;
+        }
+        self.panel_brightness.clear();
+        self.panel_brightness.extend_from_slice(panel_brightness);
+        self.redraw();
+    }
+
+    /// Set the PWM bits and redraw all pixels with them. Does nothing if the canvas already uses this number of
+    /// bits.
+    pub(crate) fn apply_pwm_bits(&mut self, pwm_bits: usize) {
+        if self.pwm_bits == pwm_bits {
+            return;
+        }
+        self.pwm_bits = pwm_bits;
+        self.redraw();
+    }
+
+    /// Draw the bit planes with the highest brightness at which the estimated current stays within the budget,
+    /// if it is lower than the brightness of the canvas, and with the brightness of the canvas again once the
+    /// budget is no longer exceeded. The brightness and colors of the canvas are kept, so that drawing continues
+    /// at the full brightness and the limit follows the content of every frame.
+    pub(crate) fn limit_power(&mut self, profile: &PowerProfile, budget_milliamps: u32) {
+        let limited_brightness = self.power_limited_brightness_for(profile, budget_milliamps);
+        // Pixels drawn since the last limit were drawn at the full brightness, the others are already drawn with
+        // the same limit.
+        let region = if limited_brightness != self.power_limited_brightness {
+            Some([0, 0, self.width(), self.height()])
+        } else {
+            limited_brightness.and(self.dirty)
+        };
+        if let Some(region) = region {
+            let brightness = self.brightness;
+            self.brightness = limited_brightness.unwrap_or(brightness);
+            self.redraw_region(region);
+            self.brightness = brightness;
+            self.power_limited_brightness = limited_brightness;
+        }
+    }
+
+    /// The brightness that keeps the estimated current within the budget, or `None` if the brightness of the
+    /// canvas already does.
+    fn power_limited_brightness_for(
+        &self,
+        profile: &PowerProfile,
+        budget_milliamps: u32,
+    ) -> Option<u8> {
+        let [drawn, hidden] = self.lit_milliamps(profile);
+        let fixed = profile.idle_milliamps + hidden;
+        if round(fixed + drawn) as u32 <= budget_milliamps {
+            return None;
+        }
+        // The current of the drawn pixels is proportional to the brightness, up to the rounding of the output
+        // levels, which rounding the brightness down makes up for.
+        let scale = ((budget_milliamps as f32 - fixed) / drawn).max(0.0);
+        Some((self.brightness as f32 * scale) as u8)
+    }
+
+    /// Write the bit planes of all pixels again from the stored colors.
+    fn redraw(&mut self) {
+        self.redraw_region([0, 0, self.width(), self.height()]);
+    }
+
+    /// Write the bit planes of the pixels in the box of `[x_start, y_start, x_end, y_end]` again from the stored
+    /// colors. The pixels keep their colors, so they are not marked as drawn.
+    fn redraw_region(&mut self, [x_start, y_start, x_end, y_end]: [usize; 4]) {
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let designator = *self.shared_mapper.get(x, y).unwrap();
+                if designator.gpio_word.is_none() {
+                    continue;
+                }
+                let Color { r, g, b } = self.color_buffer[designator.matrix_index];
+                let levels = self.color_lookup.lookup_rgb(self.brightness, r, g, b);
+                self.pack_pixel_levels(designator, levels);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl Canvas {
+    /// Create a canvas without any pixel mappers, as it would be used for the given config.
+    pub(crate) fn new_for_test(config: &RGBMatrixConfig) -> Self {
+        let pixel_designator = PixelDesignator::new(&config.hardware_mapping, config.led_sequence);
+        let width = config.chain_width();
+        let height = config.rows * config.parallel;
+        let shared_mapper = PixelDesignatorMap::new(pixel_designator, width, height, config);
+        Self::new(
+            config,
+            Arc::new(shared_mapper),
+            Arc::new(ColorLookup::new(config.gamma, config.white_balance)),
+        )
+    }
+
+    /// Whether any color bit of the pixel is set in any bit plane.
+    pub(crate) fn is_pixel_lit(&self, x: usize, y: usize) -> bool {
+        let designator = self.shared_mapper.get(x, y).unwrap();
+        let color_bits = designator.r_bit | designator.g_bit | designator.b_bit;
+        let pos_start = designator.gpio_word.unwrap();
+        (0..K_BIT_PLANES)
+            .any(|plane| self.bitplane_buffer[pos_start + self.cols * plane] & color_bits != 0)
+    }
+
+    /// The output levels of the color channels of a pixel, as stored in the bit planes that are displayed.
+    pub(crate) fn pixel_levels(&self, x: usize, y: usize) -> [u16; 3] {
+        self.pixel_levels_in_frame(x, y, 0)
+    }
+
+    /// The output levels of the color channels of a pixel in the given frame, which differ between frames when
+    /// dithering.
+    pub(crate) fn pixel_levels_in_frame(&self, x: usize, y: usize, frame: usize) -> [u16; 3] {
+        let designator = self.shared_mapper.get(x, y).unwrap();
+        let pos_start =
+            designator.gpio_word.unwrap() + (frame % self.dither_phases) * self.phase_len();
+        [designator.r_bit, designator.g_bit, designator.b_bit].map(|bit| {
+            (K_BIT_PLANES - self.pwm_bits..K_BIT_PLANES)
+                .filter(|plane| self.bitplane_buffer[pos_start + self.cols * plane] & bit != 0)
+                .map(|plane| 1 << plane)
+                .sum()
+        })
+    }
+
+    /// The output levels of all pixels in the given frame, ordered by row and column.
+    pub(crate) fn output_levels(&self, frame: usize) -> Vec<[u16; 3]> {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| [x, y]))
+            .map(|[x, y]| self.pixel_levels_in_frame(x, y, frame))
+            .collect()
+    }
+
+    /// All lit pixels, ordered by row and column.
+    pub(crate) fn lit_pixels(&self) -> Vec<[usize; 2]> {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| [x, y]))
+            .filter(|&[x, y]| self.is_pixel_lit(x, y))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gpio_output::RecordingGpio, NamedPixelMapperType, PanelSpec, WhiteBalance};
+    #[cfg(feature = "std")]
+    use crate::{
+        test_utils::{count_allocations, test_config},
+        RGBMatrix,
+    };
+
+    fn test_canvas() -> Canvas {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            ..Default::default()
+        };
+        Canvas::new_for_test(&config)
+    }
+
+    #[test]
+    fn test_fill_matches_set_pixel() {
+        let mut filled = test_canvas();
+        filled.fill(Color::RED);
+        let mut expected = test_canvas();
+        for y in 0..expected.height() {
+            for x in 0..expected.width() {
+                expected.set_pixel(x, y, 255, 0, 0);
+            }
+        }
+        assert_eq!(filled.bitplane_buffer, expected.bitplane_buffer);
+    }
+
+    #[test]
+    fn test_blend_pixel() {
+        let mut canvas = test_canvas();
+        canvas.fill(Color::new(0, 100, 255));
+        canvas.blend_pixel(0, 0, Color::new(200, 0, 0), 0);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(0, 100, 255)));
+        canvas.blend_pixel(1, 0, Color::new(200, 0, 0), 255);
+        assert_eq!(canvas.get_pixel(1, 0), Some(Color::new(200, 0, 0)));
+        canvas.blend_pixel(2, 0, Color::new(200, 0, 0), 128);
+        assert_eq!(canvas.get_pixel(2, 0), Some(Color::new(100, 50, 127)));
+        // Out of bounds pixels are ignored.
+        canvas.blend_pixel(32, 0, Color::WHITE, 128);
+    }
+
+    #[test]
+    fn test_copy_region() {
+        let config = RGBMatrixConfig {
+            rows: 64,
+            cols: 64,
+            ..Default::default()
+        };
+        let mut src = Canvas::new_for_test(&config);
+        for y in 0..4 {
+            for x in 0..4 {
+                src.set_pixel(10 + x, 20 + y, 100 + x as u8, 200 + y as u8, 0);
+            }
+        }
+        let mut dst = Canvas::new_for_test(&config);
+        dst.copy_region(&src, [10, 20, 4, 4], 62, 1);
+        // Only the two left columns of the region fit onto the destination canvas.
+        assert_eq!(
+            dst.lit_pixels(),
+            [
+                [62, 1],
+                [63, 1],
+                [62, 2],
+                [63, 2],
+                [62, 3],
+                [63, 3],
+                [62, 4],
+                [63, 4]
+            ]
+        );
+        assert_eq!(dst.get_pixel(62, 1), Some(Color::new(100, 200, 0)));
+        assert_eq!(dst.get_pixel(63, 4), Some(Color::new(101, 203, 0)));
+
+        // Clipping on the source side shifts the region like its unclipped origin.
+        let mut dst = Canvas::new_for_test(&config);
+        dst.copy_region(&src, [-2, -2, 15, 23], 0, 0);
+        assert_eq!(dst.lit_pixels(), [[12, 22], [13, 22], [14, 22]]);
+        assert_eq!(dst.get_pixel(14, 22), Some(Color::new(102, 200, 0)));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut canvas = test_canvas();
+        canvas.fill(Color::RED);
+        assert_eq!(canvas.lit_pixels().len(), 32 * 16);
+        for y in 0..canvas.height() {
+            for x in 0..canvas.width() {
+                assert_eq!(canvas.get_pixel(x, y), Some(Color::RED));
+            }
+        }
+        canvas.clear();
+        assert!(canvas.lit_pixels().is_empty());
+        assert!(canvas.bitplane_buffer.iter().all(|&word| word == 0));
+        for y in 0..canvas.height() {
+            for x in 0..canvas.width() {
+                assert_eq!(canvas.get_pixel(x, y), Some(Color::BLACK));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pixels() {
+        let mut canvas = test_canvas();
+        canvas.fill(Color::GREEN);
+        let pixels = canvas.pixels().collect::<Vec<_>>();
+        assert_eq!(pixels.len(), 32 * 16);
+        assert!(pixels.iter().all(|(_, _, &color)| color == Color::GREEN));
+        assert_eq!(pixels[0..2], [(0, 0, &Color::GREEN), (1, 0, &Color::GREEN)]);
+        assert_eq!(pixels[32 * 16 - 1], (31, 15, &Color::GREEN));
+    }
+
+    #[test]
+    fn test_pixels_mut() {
+        let mut canvas = test_canvas();
+        let mut expected = test_canvas();
+        for (x, y, color) in canvas.pixels_mut() {
+            *color = Color::new(x as u8 * 8, y as u8 * 16, 0);
+            expected.set_pixel(x, y, x as u8 * 8, y as u8 * 16, 0);
+        }
+        assert_eq!(canvas.get_pixel(3, 5), Some(Color::new(24, 80, 0)));
+        canvas.commit_pixels();
+        assert_eq!(canvas.output_levels(0), expected.output_levels(0));
+    }
+
+    #[test]
+    fn test_pixels_mut_mapped() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            pixelmapper: vec![NamedPixelMapperType::Rotate(90)],
+            ..Default::default()
+        };
+        let mut canvas = Canvas::from_config(&config).unwrap();
+        let mut count = 0;
+        for (x, y, color) in canvas.pixels_mut() {
+            *color = Color::new(x as u8, y as u8, 0);
+            count += 1;
+        }
+        canvas.commit_pixels();
+        assert_eq!(count, 16 * 32);
+        let mut expected = Canvas::from_config(&config).unwrap();
+        for (x, y, _) in canvas.pixels() {
+            assert_eq!(
+                canvas.get_pixel(x, y),
+                Some(Color::new(x as u8, y as u8, 0))
+            );
+            expected.set_pixel(x, y, x as u8, y as u8, 0);
+        }
+        assert_eq!(canvas.output_levels(0), expected.output_levels(0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_canvas_from_config() {
+        let config = RGBMatrixConfig {
+            rows: 32,
+            cols: 64,
+            multiplexing: Some("Stripe".parse().unwrap()),
+            pixelmapper: vec!["Rotate:90".parse().unwrap()],
+            ..test_config()
+        };
+        let (_matrix, mut canvas) = RGBMatrix::new_mock(config.clone()).unwrap();
+        let mut standalone = Canvas::from_config(&config).unwrap();
+        assert_eq!(standalone.bounds(), canvas.bounds());
+        canvas.draw_line(0, 0, 20, 40, Color::WHITE);
+        standalone.draw_line(0, 0, 20, 40, Color::WHITE);
+        let (width, height) = canvas.bounds();
+        for y in 0..height {
+            for x in 0..width {
+                assert_eq!(standalone.get_pixel(x, y), canvas.get_pixel(x, y));
+            }
+        }
+
+        let invalid = RGBMatrixConfig { rows: 0, ..config };
+        assert!(Canvas::from_config(&invalid).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_pixels_mut_does_not_allocate() {
+        let mut canvas = Canvas::from_config(&test_config()).unwrap();
+        let allocations = count_allocations(|| {
+            for (x, y, color) in canvas.pixels_mut() {
+                *color = Color::new(x as u8, y as u8, 0);
+            }
+            canvas.commit_pixels();
+        });
+
+        assert_eq!(allocations, 0);
+        assert_eq!(canvas.get_pixel(3, 5), Some(Color::new(3, 5, 0)));
+    }
+
+    #[test]
+    fn test_led_sequence() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            led_sequence: LedSequence::Grb,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        canvas.set_pixel(0, 0, 255, 0, 0);
+
+        let designator = canvas.shared_mapper.get(0, 0).unwrap();
+        let top_plane =
+            canvas.bitplane_buffer[designator.gpio_word.unwrap() + 32 * (K_BIT_PLANES - 1)];
+        let panels = &config.hardware_mapping.panels;
+        let color_bits = panels.red_bits() | panels.green_bits() | panels.blue_bits();
+        // Red is output on a green pin.
+        assert_ne!(top_plane & panels.green_bits(), 0);
+        assert_eq!(top_plane & color_bits & !panels.green_bits(), 0);
+        // The stored color is the one that was drawn.
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::RED));
+    }
+
+    #[test]
+    fn test_parallel_chains() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            parallel: 3,
+            hardware_mapping: HardwareMapping::REGULAR,
+            ..Default::default()
+        };
+        config.validate().unwrap();
+        let mut canvas = Canvas::new_for_test(&config);
+        assert_eq!(canvas.height(), 3 * 16);
+        // Every chain gets a pixel in its upper and its lower half, which are output in the same double row.
+        for chain in 0..3 {
+            canvas.set_pixel(chain, chain * 16, 255, 255, 255);
+            canvas.set_pixel(chain + 4, chain * 16 + 8, 255, 255, 255);
+        }
+
+        let h = config.hardware_mapping;
+        let mut gpio = RecordingGpio::default();
+        let mut row_setter = config.row_setter.create(&config);
+        let color_clk_mask = h.get_color_clock_mask(config.parallel);
+        canvas.dump_to_matrix(&mut gpio, &h, row_setter.as_mut(), 0, color_clk_mask);
+
+        // The columns of the top bit plane of the first double row.
+        let clocked = gpio.clocked_states(h.clock);
+        let columns = &clocked[(K_BIT_PLANES - 1) * 32..K_BIT_PLANES * 32];
+        let color_bits = h.panels.used_bits();
+        for chain in 0..3 {
+            let bits = h.panels.color_bits[chain];
+            assert_eq!(columns[chain] & color_bits, bits.r1 | bits.g1 | bits.b1);
+            assert_eq!(columns[chain + 4] & color_bits, bits.r2 | bits.g2 | bits.b2);
+        }
+        assert_eq!(columns[3] & color_bits, 0);
+    }
+
+    #[test]
+    fn test_scan_latch_and_clock() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            hardware_mapping: HardwareMapping::REGULAR,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        canvas.set_pwm_bits(1);
+        canvas.set_pixel(5, 0, 255, 0, 0);
+
+        let h = config.hardware_mapping;
+        let mut gpio = RecordingGpio::default();
+        let mut row_setter = config.row_setter.create(&config);
+        let color_clk_mask = h.get_color_clock_mask(config.parallel);
+        canvas.dump_to_matrix(&mut gpio, &h, row_setter.as_mut(), 0, color_clk_mask);
+
+        // With a single bit plane, every double row is clocked in and then latched once.
+        let strobe = gpio.states.iter().position(|s| s & h.strobe != 0).unwrap();
+        let first_row = RecordingGpio {
+            bits: 0,
+            states: gpio.states[..strobe].to_vec(),
+        };
+        let columns = first_row.clocked_states(h.clock);
+        assert_eq!(columns.len(), 32);
+        let r1 = h.panels.color_bits[0].r1;
+        let lit: Vec<_> = (0..32).filter(|&x| columns[x] & r1 != 0).collect();
+        assert_eq!(lit, [5]);
+        // The clock and the colors are low while latching, and the strobe is cleared right away.
+        assert_eq!(gpio.states[strobe] & color_clk_mask, 0);
+        assert_eq!(gpio.states[strobe + 1] & h.strobe, 0);
+
+        assert_eq!(gpio.clocked_states(h.strobe).len(), 8);
+        assert_eq!(gpio.clocked_states(h.clock).len(), 8 * 32);
+    }
+
+    #[test]
+    fn test_inverse_colors() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            inverse_colors: true,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        let all_on = [(1 << K_BIT_PLANES) - 1; 3];
+        assert!(canvas
+            .output_levels(0)
+            .iter()
+            .all(|levels| *levels == all_on));
+        assert_eq!(canvas.dirty_rect(), None);
+
+        canvas.fill(Color::WHITE);
+        assert!(canvas
+            .output_levels(0)
+            .iter()
+            .all(|levels| *levels == [0; 3]));
+        canvas.set_pixel(3, 4, 0, 0, 0);
+        assert_eq!(canvas.pixel_levels(3, 4), all_on);
+        assert_eq!(canvas.pixel_levels(4, 4), [0; 3]);
+    }
+
+    #[test]
+    fn test_set_pixel_unchecked() {
+        let mut checked = test_canvas();
+        let mut unchecked = test_canvas();
+        let color = Color::new(200, 100, 50);
+        for (x, y) in [(0, 0), (31, 15), (5, 12)] {
+            checked.set_pixel(x, y, color.r, color.g, color.b);
+            // SAFETY: The pixels are within the 32x16 canvas.
+            unsafe { unchecked.set_pixel_unchecked(x, y, color) };
+        }
+        assert_eq!(unchecked.bitplane_buffer, checked.bitplane_buffer);
+        assert_eq!(unchecked.get_pixel(5, 12), Some(color));
+        assert_eq!(unchecked.dirty_rect(), checked.dirty_rect());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "outside of the 32x16 canvas")]
+    fn test_set_pixel_unchecked_debug_assertion() {
+        let mut canvas = test_canvas();
+        // SAFETY: Not sound, but caught by the debug assertion before the pixel is accessed.
+        unsafe { canvas.set_pixel_unchecked(32, 0, Color::WHITE) };
+    }
+
+    #[test]
+    fn test_set_row() {
+        let mut canvas = test_canvas();
+        let colors: Vec<_> = (0..8).map(|i| Color::new(i * 30, 0, 255)).collect();
+        canvas.set_row(3, 28, &colors);
+        // Only the first four colors fit before the right edge.
+        for x in 0..32 {
+            let expected = match x {
+                28..=31 => colors[x - 28],
+                _ => Color::BLACK,
+            };
+            assert_eq!(canvas.get_pixel(x, 3), Some(expected), "{x}");
+        }
+        assert_eq!(canvas.lit_pixels().len(), 4);
+        assert_eq!(canvas.dirty_rect(), Some([28, 3, 4, 1]));
+
+        let mut expected = test_canvas();
+        for (x, color) in (28..32).zip(&colors) {
+            expected.set_pixel(x, 3, color.r, color.g, color.b);
+        }
+        assert_eq!(canvas.bitplane_buffer, expected.bitplane_buffer);
+
+        canvas.set_row(16, 0, &colors);
+        canvas.set_row(0, 32, &colors);
+        assert_eq!(canvas.lit_pixels().len(), 4);
+    }
+
+    #[test]
+    fn test_set_pixels() {
+        let mut canvas = test_canvas();
+        canvas.set_pixels(&[
+            (0, 0, Color::RED),
+            (31, 15, Color::GREEN),
+            (32, 0, Color::BLUE),
+            (0, 16, Color::BLUE),
+        ]);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::RED));
+        assert_eq!(canvas.get_pixel(31, 15), Some(Color::GREEN));
+        assert_eq!(canvas.lit_pixels().len(), 2);
+    }
+
+    #[test]
+    fn test_white_balance_fill() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        canvas.fill(Color::WHITE);
+        let [r, g, b] = canvas.pixel_levels(0, 0);
+
+        let config = RGBMatrixConfig {
+            white_balance: WhiteBalance::new(1.0, 1.0, 0.8),
+            ..config
+        };
+        let mut balanced = Canvas::new_for_test(&config);
+        balanced.fill(Color::WHITE);
+        let [balanced_r, balanced_g, balanced_b] = balanced.pixel_levels(0, 0);
+        assert_eq!([balanced_r, balanced_g], [r, g]);
+        assert!(balanced_b < b);
+    }
+
+    #[test]
+    fn test_channel_mask() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            green_enabled: false,
+            blue_enabled: false,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        canvas.fill(Color::WHITE);
+        let red = [(1 << K_BIT_PLANES) - 1, 0, 0];
+        assert!(canvas.output_levels(0).iter().all(|levels| *levels == red));
+        // The colors are kept as they were set.
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::WHITE));
+
+        let profile = PowerProfile {
+            red_milliamps: 10.0,
+            green_milliamps: 20.0,
+            blue_milliamps: 30.0,
+            idle_milliamps: 0.0,
+        };
+        assert_eq!(canvas.estimate_power_milliamps(&profile), 16 * 32 * 10);
+    }
+
+    #[test]
+    fn test_adjust_brightness() {
+        let mut canvas = test_canvas();
+        canvas.fill_gradient_horizontal(Color::new(0, 50, 100), Color::new(255, 200, 150));
+        let original = canvas.colors();
+        canvas.adjust_brightness(1.0);
+        assert_eq!(canvas.colors(), original);
+
+        canvas.adjust_brightness(2.0);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(0, 100, 200)));
+        assert_eq!(
+            canvas.get_pixel(canvas.width() - 1, 0),
+            Some(Color::new(255, 255, 255))
+        );
+
+        canvas.adjust_brightness(0.0);
+        assert!(canvas.colors().iter().all(|&color| color == Color::BLACK));
+        assert!(canvas.lit_pixels().is_empty());
+    }
+
+    #[test]
+    fn test_adjust_contrast() {
+        let mut canvas = test_canvas();
+        canvas.set_pixel(0, 0, 128, 100, 200);
+        canvas.set_pixel(1, 0, 0, 255, 64);
+        canvas.adjust_contrast(1.0);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(128, 100, 200)));
+
+        // The middle value stays, the others move away from it.
+        canvas.adjust_contrast(2.0);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(128, 72, 255)));
+        assert_eq!(canvas.get_pixel(1, 0), Some(Color::new(0, 255, 0)));
+
+        canvas.adjust_contrast(0.5);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(128, 100, 192)));
+        canvas.adjust_contrast(0.0);
+        assert!(canvas
+            .colors()
+            .iter()
+            .all(|&color| color == Color::new(128, 128, 128)));
+    }
+
+    #[test]
+    fn test_fade() {
+        let mut canvas = test_canvas();
+        canvas.set_pixel(3, 4, 255, 255, 255);
+        canvas.set_pixel(5, 4, 100, 30, 0);
+        canvas.fade(40);
+        assert_eq!(canvas.get_pixel(3, 4), Some(Color::new(215, 215, 215)));
+        assert_eq!(canvas.get_pixel(5, 4), Some(Color::new(60, 0, 0)));
+        assert_eq!(canvas.dirty_rect(), Some([0, 0, 32, 16]));
+        let mut expected = test_canvas();
+        expected.set_pixel(3, 4, 215, 215, 215);
+        expected.set_pixel(5, 4, 60, 0, 0);
+        assert_eq!(canvas.output_levels(0), expected.output_levels(0));
+
+        // 255 = 6 * 40 + 15, so the white pixel is black after the seventh step.
+        for step in 2..=6 {
+            canvas.fade(40);
+            let value = 255 - 40 * step;
+            assert_eq!(
+                canvas.get_pixel(3, 4),
+                Some(Color::new(value, value, value))
+            );
+        }
+        assert_eq!(canvas.lit_pixels(), [[3, 4]]);
+        canvas.fade(40);
+        assert!(canvas.lit_pixels().is_empty());
+        assert!(canvas.colors().iter().all(|&color| color == Color::BLACK));
+    }
+
+    #[test]
+    fn test_blur_single_pixel() {
+        let mut canvas = test_canvas();
+        canvas.set_pixel(10, 8, 255, 255, 255);
+        canvas.blur(1);
+        // The pixel is spread evenly over its 3x3 neighborhood: 255 / 3 / 3 rounds to 28.
+        for y in 0..canvas.height() {
+            for x in 0..canvas.width() {
+                let expected = if (9..=11).contains(&x) && (7..=9).contains(&y) {
+                    Color::new(28, 28, 28)
+                } else {
+                    Color::BLACK
+                };
+                assert_eq!(canvas.get_pixel(x, y), Some(expected), "({x}, {y})");
+            }
+        }
+
+        // At the edge, the clamped window counts the pixel several times.
+        let mut canvas = test_canvas();
+        canvas.set_pixel(0, 0, 90, 0, 0);
+        canvas.blur(2);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(32, 0, 0)));
+        assert_eq!(canvas.get_pixel(2, 2), Some(Color::new(4, 0, 0)));
+        assert_eq!(canvas.get_pixel(3, 0), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn test_blur_uniform() {
+        let mut canvas = test_canvas();
+        canvas.fill(Color::new(12, 200, 77));
+        let original = canvas.colors();
+        for radius in [0, 1, 3, 40] {
+            canvas.blur(radius);
+            assert_eq!(canvas.colors(), original);
+        }
+    }
+
+    #[test]
+    fn test_crossfade() {
+        let mut from = test_canvas();
+        from.fill(Color::new(0, 100, 255));
+        from.set_pixel(3, 4, 10, 20, 30);
+        let mut to = test_canvas();
+        to.fill(Color::new(200, 0, 255));
+
+        let mut canvas = test_canvas();
+        canvas.crossfade(&from, &to, 0.0).unwrap();
+        assert_eq!(canvas.colors(), from.colors());
+        canvas.crossfade(&from, &to, 1.0).unwrap();
+        assert_eq!(canvas.colors(), to.colors());
+        canvas.crossfade(&from, &to, 0.5).unwrap();
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(100, 50, 255)));
+        assert_eq!(canvas.get_pixel(3, 4), Some(Color::new(105, 10, 143)));
+
+        // The blend factor is clamped.
+        canvas.crossfade(&from, &to, 2.0).unwrap();
+        assert_eq!(canvas.colors(), to.colors());
+        canvas.crossfade(&from, &to, -1.0).unwrap();
+        assert_eq!(canvas.colors(), from.colors());
+
+        let small = Canvas::new_for_test(&RGBMatrixConfig {
+            rows: 8,
+            cols: 32,
+            ..Default::default()
+        });
+        assert_eq!(
+            canvas.crossfade(&from, &small, 0.5),
+            Err(CanvasError::SizeMismatch {
+                expected: [32, 16],
+                actual: [32, 8]
+            })
+        );
+    }
+
+    #[test]
+    fn test_estimate_power() {
+        let profile = PowerProfile {
+            red_milliamps: 1.5,
+            green_milliamps: 1.0,
+            blue_milliamps: 1.25,
+            idle_milliamps: 120.0,
+        };
+        let mut canvas = test_canvas();
+        let pixels = (canvas.width() * canvas.height()) as u32;
+        assert_eq!(canvas.estimate_power_milliamps(&profile), 120);
+
+        canvas.fill(Color::WHITE);
+        assert_eq!(
+            canvas.estimate_power_milliamps(&profile),
+            120 + pixels * 15 / 4
+        );
+        canvas.fill(Color::RED);
+        assert_eq!(
+            canvas.estimate_power_milliamps(&profile),
+            120 + pixels * 3 / 2
+        );
+
+        // The current follows the gamma corrected values and the brightness.
+        canvas.fill(Color::new(128, 128, 128));
+        let gray = canvas.estimate_power_milliamps(&profile);
+        assert!(gray > 120 + pixels / 2 && gray < 120 + pixels * 15 / 8);
+        canvas.fill(Color::WHITE);
+        canvas.set_brightness(50);
+        assert_eq!(
+            canvas.estimate_power_milliamps(&profile),
+            120 + pixels * 15 / 8
+        );
+    }
+
+    #[test]
+    fn test_estimate_power_of_drawn_levels() {
+        let profile = PowerProfile {
+            red_milliamps: 1.0,
+            green_milliamps: 1.0,
+            blue_milliamps: 2.0,
+            idle_milliamps: 0.0,
+        };
+        let mut canvas = test_canvas();
+        canvas.fill(Color::WHITE);
+        canvas.apply_panel_brightness(&[50]);
+        // Half of the 2047 levels is rounded up to 1024.
+        assert_eq!(
+            canvas.estimate_power_milliamps(&profile),
+            (16 * 32 * 4 * 1024 + 1023) / 2047
+        );
+
+        // Only the visible quarter of the pixels is drawn.
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            pixelmapper: vec![NamedPixelMapperType::Scale(2)],
+            ..Default::default()
+        };
+        let mut canvas = Canvas::from_config(&config).unwrap();
+        canvas.fill(Color::WHITE);
+        assert_eq!(canvas.estimate_power_milliamps(&profile), 8 * 16 * 4);
+        let mut canvas = Canvas::from_config(&RGBMatrixConfig {
+            inverse_colors: true,
+            ..config
+        })
+        .unwrap();
+        assert_eq!(canvas.estimate_power_milliamps(&profile), 3 * 8 * 16 * 4);
+        canvas.fill(Color::WHITE);
+        assert_eq!(canvas.estimate_power_milliamps(&profile), 4 * 8 * 16 * 4);
+    }
+
+    #[test]
+    fn test_clone_is_independent() {
+        let mut canvas = test_canvas();
+        canvas.set_pixel(1, 2, 10, 20, 30);
+        let mut clone = canvas.clone();
+        assert_eq!(clone.get_pixel(1, 2), Some(Color::new(10, 20, 30)));
+
+        clone.fill(Color::WHITE);
+        clone.set_brightness(10);
+        for (_, _, color) in clone.pixels_mut() {
+            *color = Color::RED;
+        }
+        assert_eq!(canvas.get_pixel(1, 2), Some(Color::new(10, 20, 30)));
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::BLACK));
+        assert_eq!(canvas.lit_pixels(), [[1, 2]]);
+        assert_eq!(canvas.brightness(), 100);
+    }
+
+    #[test]
+    fn test_dirty_rect() {
+        let mut canvas = test_canvas();
+        assert_eq!(canvas.dirty_rect(), None);
+        canvas.set_pixel(5, 9, 255, 0, 0);
+        assert_eq!(canvas.dirty_rect(), Some([5, 9, 1, 1]));
+        canvas.set_pixel(7, 3, 0, 255, 0);
+        assert_eq!(canvas.dirty_rect(), Some([5, 3, 3, 7]));
+        // Pixels outside of the canvas are not drawn.
+        canvas.set_pixel(32, 16, 0, 0, 255);
+        assert_eq!(canvas.dirty_rect(), Some([5, 3, 3, 7]));
+
+        canvas.reset_dirty();
+        assert_eq!(canvas.dirty_rect(), None);
+        canvas.fill(Color::BLUE);
+        assert_eq!(canvas.dirty_rect(), Some([0, 0, 32, 16]));
+    }
+
+    #[test]
+    fn test_redraw_keeps_dirty_rect() {
+        let mut canvas = test_canvas();
+        canvas.set_pixel(5, 9, 255, 0, 0);
+        canvas.reset_dirty();
+        // Runtime settings draw the stored colors again, which doesn't change them.
+        canvas.apply_brightness(50);
+        canvas.apply_pwm_bits(7);
+        canvas.apply_panel_brightness(&[50]);
+        assert_eq!(canvas.dirty_rect(), None);
+        let [red, green, blue] = canvas.pixel_levels(5, 9);
+        assert!(red > 0 && red < 1024, "{red}");
+        assert_eq!([green, blue], [0, 0]);
+    }
+
+    #[test]
+    fn test_power_limited_brightness() {
+        let profile = PowerProfile {
+            red_milliamps: 1.0,
+            green_milliamps: 1.0,
+            blue_milliamps: 1.0,
+            idle_milliamps: 36.0,
+        };
+        let mut canvas = test_canvas();
+        canvas.fill(Color::WHITE);
+        assert_eq!(canvas.power_limited_brightness_for(&profile, 1600), None);
+        // The 1536 mA of the lit pixels are scaled to the 464 mA that are left of the budget.
+        let brightness = canvas.power_limited_brightness_for(&profile, 500);
+        assert_eq!(brightness, Some(30));
+        canvas.set_brightness(30);
+        assert!(canvas.estimate_power_milliamps(&profile) <= 500);
+        assert_eq!(canvas.power_limited_brightness_for(&profile, 20), Some(0));
+    }
+
+    #[test]
+    fn test_limit_power_redraws_dirty_region() {
+        let profile = PowerProfile {
+            red_milliamps: 1.0,
+            green_milliamps: 1.0,
+            blue_milliamps: 1.0,
+            idle_milliamps: 0.0,
+        };
+        let mut canvas = test_canvas();
+        canvas.fill(Color::WHITE);
+        canvas.limit_power(&profile, 500);
+        let limited = canvas.pixel_levels(1, 1);
+        assert!(limited[0] < 2047, "{limited:?}");
+        canvas.reset_dirty();
+
+        // Clear the bit planes of a pixel behind the canvas' back, which only a full redraw would restore.
+        let designator = *canvas.shared_mapper.get(0, 0).unwrap();
+        canvas.pack_pixel_levels(designator, [0; 3]);
+        // Drawing the same color keeps the limit, so only the drawn pixel is drawn again.
+        canvas.set_pixel(5, 9, 255, 255, 255);
+        assert_eq!(canvas.pixel_levels(5, 9), [2047; 3]);
+        canvas.limit_power(&profile, 500);
+        assert_eq!(canvas.pixel_levels(5, 9), limited);
+        assert_eq!(canvas.pixel_levels(0, 0), [0; 3]);
+    }
+
+    #[test]
+    fn test_get_pixel() {
+        let mut canvas = test_canvas();
+        canvas.set_brightness(50);
+        canvas.set_pixel(0, 0, 1, 2, 3);
+        canvas.set_pixel(31, 15, 255, 128, 0);
+        canvas.set_pixel(5, 9, 10, 20, 30);
+        canvas.set_pixel(5, 9, 40, 50, 60);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(1, 2, 3)));
+        assert_eq!(canvas.get_pixel(31, 15), Some(Color::new(255, 128, 0)));
+        assert_eq!(canvas.get_pixel(5, 9), Some(Color::new(40, 50, 60)));
+        assert_eq!(canvas.get_pixel(1, 0), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn test_apply_brightness() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            gamma: 1.0,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        canvas.set_pixel(3, 4, 255, 128, 0);
+        canvas.set_pixel(20, 10, 10, 200, 100);
+        assert_eq!(canvas.pixel_levels(3, 4), [2047, 1028, 0]);
+
+        canvas.apply_brightness(50);
+        assert_eq!(canvas.brightness(), 50);
+        assert_eq!(canvas.pixel_levels(3, 4), [1024, 514, 0]);
+        assert_eq!(canvas.pixel_levels(20, 10), [40, 803, 401]);
+        // The colors are kept, so the full brightness can be restored.
+        assert_eq!(canvas.get_pixel(3, 4), Some(Color::new(255, 128, 0)));
+
+        canvas.apply_brightness(0);
+        assert!(canvas.lit_pixels().is_empty());
+        assert!(canvas.bitplane_buffer.iter().all(|&word| word == 0));
+
+        canvas.apply_brightness(100);
+        assert_eq!(canvas.pixel_levels(3, 4), [2047, 1028, 0]);
+    }
+
+    #[test]
+    fn test_apply_pwm_bits() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            gamma: 1.0,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        canvas.set_pixel(3, 4, 255, 128, 0);
+        assert_eq!(canvas.pixel_levels(3, 4), [2047, 1028, 0]);
+
+        // Only the most significant bit planes are displayed.
+        canvas.apply_pwm_bits(4);
+        assert_eq!(canvas.pixel_levels(3, 4), [2047 & !0x7f, 1028 & !0x7f, 0]);
+
+        canvas.apply_pwm_bits(11);
+        assert_eq!(canvas.pixel_levels(3, 4), [2047, 1028, 0]);
+    }
+
+    #[test]
+    fn test_dithering() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            gamma: 1.0,
+            pwm_bits: 4,
+            dither_bits: 2,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        // A red value of 100 is at level 803, between the displayable levels 768 and 896.
+        canvas.set_pixel(3, 4, 100, 255, 0);
+        let red_levels = (0..4)
+            .map(|frame| canvas.pixel_levels_in_frame(3, 4, frame)[0])
+            .collect::<Vec<_>>();
+        assert_eq!(red_levels, [768, 768, 768, 896]);
+        // The average over the phases keeps two more bits than a single frame.
+        assert_eq!(red_levels.iter().sum::<u16>() / 4, 803 & !0x1f);
+        // Full levels don't overflow.
+        assert!((0..4).all(|frame| canvas.pixel_levels_in_frame(3, 4, frame)[1] == 2047 & !0x7f));
+        assert_eq!(
+            canvas.pixel_levels_in_frame(3, 4, 7),
+            [896, 2047 & !0x7f, 0]
+        );
+
+        let mut filled = Canvas::new_for_test(&config);
+        filled.fill(Color::new(100, 255, 0));
+        assert_eq!(
+            filled.pixel_levels_in_frame(3, 4, 3),
+            [896, 2047 & !0x7f, 0]
+        );
+    }
+
+    #[test]
+    fn test_set_pixel16_dithering() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            gamma: 1.0,
+            pwm_bits: 4,
+            dither_bits: 2,
+            ..Default::default()
+        };
+        let average_red = |color: Color16| {
+            let mut canvas = Canvas::new_for_test(&config);
+            canvas.set_pixel16(3, 4, color);
+            assert_eq!(canvas.get_pixel(3, 4), Some(Color::from(color)));
+            (0..4)
+                .map(|frame| canvas.pixel_levels_in_frame(3, 4, frame)[0])
+                .sum::<u16>()
+                / 4
+        };
+        // Both are a red value of 100 in 8 bits, but at the levels 799 and 801 on either side of a dithered step.
+        let darker = Color16::new(25580, 0, 0);
+        let brighter = Color16::new(25640, 0, 0);
+        assert_eq!(Color::from(darker), Color::from(brighter));
+        assert_eq!(average_red(darker), 768);
+        assert_eq!(average_red(brighter), 800);
+
+        let mut canvas = Canvas::new_for_test(&config);
+        canvas.set_pixel(3, 4, 100, 0, 0);
+        assert_eq!(canvas.pixel_levels_in_frame(3, 4, 0)[0], 768);
+        canvas.set_pixel16(3, 4, Color::new(100, 0, 0).into());
+        assert_eq!(canvas.pixel_levels_in_frame(3, 4, 0)[0], 768);
+        canvas.set_pixel16(64, 4, brighter);
+        assert_eq!(canvas.lit_pixels(), [[3, 4]]);
+    }
+
+    #[test]
+    fn test_ordered_dithering() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            pwm_bits: 4,
+            dithering: Dithering::OrderedBayer,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        // Mid-gray is at level 449, about halfway between the displayable levels 384 and 512.
+        canvas.fill(Color::new(128, 128, 128));
+        let red_levels = |canvas: &Canvas, y: usize| {
+            (0..8)
+                .map(|x| canvas.pixel_levels(x, y)[0])
+                .collect::<Vec<_>>()
+        };
+        // The upper half of the Bayer thresholds rounds up, which results in a checkerboard.
+        let even_row = [384, 512, 384, 512, 384, 512, 384, 512];
+        let odd_row = [512, 384, 512, 384, 512, 384, 512, 384];
+        assert_eq!(red_levels(&canvas, 0), even_row);
+        assert_eq!(red_levels(&canvas, 1), odd_row);
+        assert_eq!(red_levels(&canvas, 2), even_row);
+        assert_eq!(red_levels(&canvas, 3), odd_row);
+        // The pattern repeats every four pixels and is the same for set pixels.
+        assert_eq!(red_levels(&canvas, 7), red_levels(&canvas, 3));
+        let mut drawn = Canvas::new_for_test(&config);
+        for y in 0..drawn.height() {
+            for x in 0..drawn.width() {
+                drawn.set_pixel(x, y, 128, 128, 128);
+            }
+        }
+        assert_eq!(drawn.bitplane_buffer, canvas.bitplane_buffer);
+    }
+
+    #[test]
+    fn test_mixed_panels() {
+        let config = RGBMatrixConfig {
+            rows: 32,
+            chain_length: 2,
+            panels: vec![PanelSpec::new(32, 64), PanelSpec::new(32, 32)],
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        assert_eq!([canvas.width(), canvas.height()], [96, 32]);
+        // The columns of the second panel follow the 64 columns of the first one in the chain.
+        canvas.set_pixel(70, 3, 255, 255, 255);
+        canvas.set_pixel(95, 3, 255, 255, 255);
+        canvas.set_pixel(96, 3, 255, 255, 255);
+        assert_eq!(canvas.lit_pixels(), [[70, 3], [95, 3]]);
+    }
+
+    #[test]
+    fn test_row_scan_order() {
+        let mut config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            ..Default::default()
+        };
+        let canvas = Canvas::new_for_test(&config);
+        assert!(canvas.row_scan_order().eq(0..8));
+
+        config.interlaced = true;
+        let canvas = Canvas::new_for_test(&config);
+        let order = canvas.row_scan_order().collect::<Vec<_>>();
+        assert_eq!(order, [0, 2, 4, 6, 1, 3, 5, 7]);
+
+        // With an odd number of double rows, every row is still output once.
+        config.rows = 10;
+        let canvas = Canvas::new_for_test(&config);
+        let order = canvas.row_scan_order().collect::<Vec<_>>();
+        assert_eq!(order, [0, 2, 4, 1, 3]);
+    }
+
+    #[test]
+    fn test_get_pixel_out_of_bounds() {
+        let canvas = test_canvas();
+        assert_eq!(canvas.get_pixel(32, 0), None);
+        assert_eq!(canvas.get_pixel(0, 16), None);
+        assert_eq!(canvas.get_pixel(usize::MAX, usize::MAX), None);
     }
 }
 
-#[cfg(feature = "drawing")]
+/// Support for the `embedded-graphics` crate. This is enabled by the `embedded-graphics` feature, which is also
+/// enabled by the default `drawing` feature.
+#[cfg(feature = "embedded-graphics")]
 pub mod embedded_graphics_support {
-    use super::Canvas;
+    use super::{Canvas, Color};
     use embedded_graphics::{
         draw_target::DrawTarget,
         pixelcolor::Rgb888,
@@ -369,6 +2271,18 @@ pub mod embedded_graphics_support {
         Pixel,
     };
 
+    impl From<Rgb888> for Color {
+        fn from(color: Rgb888) -> Self {
+            Color::new(color.r(), color.g(), color.b())
+        }
+    }
+
+    impl From<Color> for Rgb888 {
+        fn from(color: Color) -> Self {
+            Rgb888::new(color.r, color.g, color.b)
+        }
+    }
+
     impl OriginDimensions for Canvas {
         fn size(&self) -> Size {
             Size::new(self.width() as u32, self.height() as u32)
@@ -403,8 +2317,41 @@ pub mod embedded_graphics_support {
         }
 
         fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
-            self.fill(color.r(), color.g(), color.b());
+            self.fill(color.into());
             Ok(())
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use embedded_graphics::{prelude::Point, Drawable};
+
+        use super::*;
+        use crate::RGBMatrixConfig;
+
+        #[test]
+        fn test_draw_pixel() {
+            let config = RGBMatrixConfig {
+                rows: 16,
+                cols: 32,
+                ..Default::default()
+            };
+            let mut canvas = Canvas::new_for_test(&config);
+            assert_eq!(canvas.size(), Size::new(32, 16));
+
+            Pixel(Point::new(3, 7), Rgb888::new(10, 20, 30))
+                .draw(&mut canvas)
+                .unwrap();
+            // Out of bounds pixels are discarded.
+            Pixel(Point::new(-1, 0), Rgb888::WHITE)
+                .draw(&mut canvas)
+                .unwrap();
+            Pixel(Point::new(32, 0), Rgb888::WHITE)
+                .draw(&mut canvas)
+                .unwrap();
+
+            assert_eq!(canvas.get_pixel(3, 7), Some(Color::new(10, 20, 30)));
+            assert_eq!(canvas.lit_pixels(), [[3, 7]]);
+        }
+    }
 }
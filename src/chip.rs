@@ -1,4 +1,11 @@
-use std::{error::Error, fs::read_to_string, str::FromStr};
+use alloc::{boxed::Box, format};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+#[cfg(feature = "std")]
+use std::fs::read_to_string;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PiChip {
@@ -8,6 +15,11 @@ pub enum PiChip {
     BCM2709,
     /// Model 4
     BCM2711,
+    /// Model 5, whose GPIO pins are driven by the RP1 I/O controller. The pins are written through
+    /// `/dev/gpiomem0`, and the output enable pulses are always timed by busy waiting, since the PWM peripheral
+    /// of the older models does not exist. Writes to the RP1 go over PCIe, so their latency differs from the
+    /// older models, and the default slowdown has not been tuned for many panels yet.
+    BCM2712,
 }
 
 impl FromStr for PiChip {
@@ -18,21 +30,45 @@ impl FromStr for PiChip {
             "BCM2708" | "BCM2835" => Ok(Self::BCM2708),
             "BCM2709" | "BCM2836" | "BCM2837" => Ok(Self::BCM2709),
             "BCM2711" => Ok(Self::BCM2711),
+            "BCM2712" => Ok(Self::BCM2712),
             _ => Err(format!("'{s}' is not a valid chip model.").into()),
         }
     }
 }
 
+impl Display for PiChip {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            PiChip::BCM2708 => "BCM2708",
+            PiChip::BCM2709 => "BCM2709",
+            PiChip::BCM2711 => "BCM2711",
+            PiChip::BCM2712 => "BCM2712",
+        })
+    }
+}
+
 impl PiChip {
-    /// Try to automatically determine the model.
+    /// Try to automatically determine the model, first from the revision code in `/proc/cpuinfo` and then from
+    /// the device tree.
+    #[cfg(feature = "std")]
     pub fn determine() -> Option<Self> {
+        let from_cpuinfo = read_to_string("/proc/cpuinfo")
+            .ok()
+            .and_then(|cpuinfo| Self::from_cpuinfo(&cpuinfo));
+        from_cpuinfo.or_else(|| {
+            let compatible = read_to_string("/sys/firmware/devicetree/base/compatible").ok()?;
+            Self::from_device_tree_compatible(&compatible)
+        })
+    }
+
+    /// Determine the model from the revision code in the contents of `/proc/cpuinfo`.
+    pub(crate) fn from_cpuinfo(cpuinfo: &str) -> Option<Self> {
         // https://www.raspberrypi.org/documentation/hardware/raspberrypi/revision-codes/README.md
-        let cpuinfo = read_to_string("/proc/cpuinfo").ok()?;
         let revision_str = cpuinfo
             .lines()
             .find(|line| line.starts_with("Revision"))?
-            .split(' ')
-            .last()?;
+            .split([' ', '\t'])
+            .next_back()?;
 
         let old_style = revision_str.len() == 4;
         if old_style {
@@ -52,32 +88,115 @@ impl PiChip {
             2 => Some(Self::BCM2709),
             // BCM2711
             3 => Some(Self::BCM2711),
+            // BCM2712
+            4 => Some(Self::BCM2712),
             _ => None,
         }
     }
 
+    /// Determine the model from the NUL separated entries of `/sys/firmware/devicetree/base/compatible`, e.g.
+    /// `raspberrypi,4-model-b` followed by `brcm,bcm2711`.
+    pub(crate) fn from_device_tree_compatible(compatible: &str) -> Option<Self> {
+        compatible
+            .split('\0')
+            .filter_map(|entry| entry.strip_prefix("brcm,"))
+            .find_map(|chip| chip.parse().ok())
+    }
+
     pub(crate) const fn num_cores(&self) -> usize {
         match self {
             PiChip::BCM2708 => 1,
             PiChip::BCM2709 => 4,
             PiChip::BCM2711 => 4,
+            PiChip::BCM2712 => 4,
         }
     }
 
+    /// Whether the GPIO pins are driven by the RP1 I/O controller instead of the GPIO peripheral of the chip.
+    pub(crate) const fn has_rp1(&self) -> bool {
+        matches!(self, PiChip::BCM2712)
+    }
+
     // All peripherals can be described by an offset from the Peripheral Base Address.
     pub(crate) const fn get_peripherals_base(&self) -> u64 {
         match self {
             PiChip::BCM2708 => 0x20000000,
             PiChip::BCM2709 => 0x3F000000,
             PiChip::BCM2711 => 0xFE000000,
+            PiChip::BCM2712 => 0x107C000000,
         }
     }
 
-    pub(crate) fn gpio_slowdown(&self) -> u32 {
+    /// The GPIO slowdown that works for most panels with this model. It is used unless
+    /// [`RGBMatrixConfig::slowdown`](crate::RGBMatrixConfig::slowdown) is set.
+    pub fn gpio_slowdown(&self) -> u32 {
         match self {
             PiChip::BCM2708 => 1,
             PiChip::BCM2709 => 1,
             PiChip::BCM2711 => 3,
+            PiChip::BCM2712 => 1,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    fn cpuinfo(revision: &str) -> String {
+        format!("processor\t: 0\nmodel name\t: ARMv7 Processor rev 4 (v7l)\n\nHardware\t: BCM2835\nRevision\t: {revision}\nSerial\t\t: 00000000\n")
+    }
+
+    #[test]
+    fn test_from_cpuinfo() {
+        let detect = |revision| PiChip::from_cpuinfo(&cpuinfo(revision));
+        // Old style revision codes are only used by the first models.
+        assert_eq!(detect("000e"), Some(PiChip::BCM2708));
+        assert_eq!(detect("900093"), Some(PiChip::BCM2708));
+        assert_eq!(detect("a01041"), Some(PiChip::BCM2709));
+        assert_eq!(detect("a02082"), Some(PiChip::BCM2709));
+        assert_eq!(detect("c03111"), Some(PiChip::BCM2711));
+        assert_eq!(detect("d04170"), Some(PiChip::BCM2712));
+        assert_eq!(detect("c04170"), Some(PiChip::BCM2712));
+        assert_eq!(detect("b05170"), None);
+        assert_eq!(PiChip::from_cpuinfo("processor\t: 0\n"), None);
+    }
+
+    #[test]
+    fn test_from_device_tree_compatible() {
+        let detect = PiChip::from_device_tree_compatible;
+        assert_eq!(
+            detect("raspberrypi,4-model-b\0brcm,bcm2711\0"),
+            Some(PiChip::BCM2711)
+        );
+        assert_eq!(
+            detect("raspberrypi,3-model-b\0brcm,bcm2837\0"),
+            Some(PiChip::BCM2709)
+        );
+        assert_eq!(
+            detect("raspberrypi,model-zero-w\0brcm,bcm2835\0"),
+            Some(PiChip::BCM2708)
+        );
+        assert_eq!(
+            detect("raspberrypi,5-model-b\0brcm,bcm2712\0"),
+            Some(PiChip::BCM2712)
+        );
+        assert!(PiChip::BCM2712.has_rp1());
+        assert!(!PiChip::BCM2711.has_rp1());
+        assert_eq!("bcm2712".parse::<PiChip>().unwrap(), PiChip::BCM2712);
+    }
+
+    #[test]
+    fn test_default_slowdown() {
+        let slowdown = |revision| {
+            PiChip::from_cpuinfo(&cpuinfo(revision))
+                .unwrap()
+                .gpio_slowdown()
+        };
+        assert_eq!(slowdown("900093"), 1);
+        assert_eq!(slowdown("a02082"), 1);
+        assert_eq!(slowdown("c03111"), 3);
+    }
+}
@@ -0,0 +1,269 @@
+use core::fmt::Display;
+
+use crate::{
+    flags::{
+        parse_gpio_mapping, parse_multiplexing, parse_panel_type, parse_row_address_type,
+        parse_scan_mode, parse_slowdown,
+    },
+    HardwareMapping, LedSequence, MultiplexMapperType, NamedPixelMapperType, PanelType,
+    RGBMatrixConfig, RowAddressSetterType,
+};
+
+/// The `--led-*` flags of hzeller's rpi-rgb-led-matrix library as [`clap::Args`], to be flattened into the
+/// parser of an application with `#[command(flatten)]`. The flags that are not given keep the values of the
+/// config they are [applied](ConfigArgs::apply) to, or the [default](RGBMatrixConfig::default) values when
+/// converting into a [`RGBMatrixConfig`].
+///
+/// The flags and values are the same as the ones accepted by [`RGBMatrixConfig::from_args`].
+#[derive(clap::Args, Debug, Clone, Default, PartialEq)]
+pub struct ConfigArgs {
+    /// Name of GPIO mapping used (Default: "regular").
+    #[arg(long = "led-gpio-mapping", value_name = "name", value_parser = parser(parse_gpio_mapping))]
+    pub gpio_mapping: Option<HardwareMapping>,
+
+    /// Panel rows. Typically 8, 16, 32 or 64 (Default: 32).
+    #[arg(long = "led-rows", value_name = "rows")]
+    pub rows: Option<usize>,
+
+    /// Panel columns. Typically 32 or 64 (Default: 32).
+    #[arg(long = "led-cols", value_name = "cols")]
+    pub cols: Option<usize>,
+
+    /// Number of daisy-chained panels (Default: 1).
+    #[arg(long = "led-chain", value_name = "chained")]
+    pub chain: Option<usize>,
+
+    /// Parallel chains. range=1..3 (Default: 1).
+    #[arg(long = "led-parallel", value_name = "parallel")]
+    pub parallel: Option<usize>,
+
+    // The inner option is fully qualified, otherwise clap makes the value of the flag optional.
+    /// Mux type: 0=direct, 1..18 or the name of a multiplexing mapper (Default: 0).
+    #[arg(long = "led-multiplexing", value_name = "0..18", value_parser = parser(parse_multiplexing))]
+    pub multiplexing: Option<::std::option::Option<MultiplexMapperType>>,
+
+    /// Semicolon-separated list of pixel-mappers to arrange pixels, can be repeated. Optional params after a
+    /// colon e.g. "U-mapper;Rotate:90".
+    #[arg(
+        long = "led-pixel-mapper",
+        visible_alias = "pixel-mapper",
+        value_name = "mappers",
+        value_delimiter = ';',
+        value_parser = parser(|value| value.trim().parse::<NamedPixelMapperType>())
+    )]
+    pub pixel_mappers: Vec<NamedPixelMapperType>,
+
+    /// PWM bits (Default: 11).
+    #[arg(long = "led-pwm-bits", value_name = "1..11")]
+    pub pwm_bits: Option<usize>,
+
+    /// Brightness in percent (Default: 100).
+    #[arg(long = "led-brightness", value_name = "percent")]
+    pub brightness: Option<u8>,
+
+    /// 0 = progressive; 1 = interlaced (Default: 0).
+    #[arg(long = "led-scan-mode", value_name = "0..1", value_parser = parser(parse_scan_mode))]
+    pub scan_mode: Option<bool>,
+
+    /// 0 = default; 1 = AB-addressed panels; 2 = direct row select; 3 = ABC-addressed panels; 4 = ABC Shift + DE
+    /// direct (Default: 0).
+    #[arg(long = "led-row-addr-type", value_name = "0..4", value_parser = parser(parse_row_address_type))]
+    pub row_addr_type: Option<RowAddressSetterType>,
+
+    /// Limit refresh rate to this frequency in Hz. 0 = no limit (Default: 0).
+    #[arg(long = "led-limit-refresh", value_name = "Hz")]
+    pub limit_refresh: Option<usize>,
+
+    /// Switch if your matrix has inverse colors on.
+    #[arg(long = "led-inverse", overrides_with = "no_inverse")]
+    pub inverse: bool,
+
+    /// Switch if your matrix has inverse colors off.
+    #[arg(long = "led-no-inverse", overrides_with = "inverse")]
+    pub no_inverse: bool,
+
+    /// Switch if your matrix has led colors swapped (Default: "RGB").
+    #[arg(
+        long = "led-rgb-sequence",
+        value_name = "sequence",
+        value_parser = parser(|value| value.parse::<LedSequence>())
+    )]
+    pub rgb_sequence: Option<LedSequence>,
+
+    /// PWM Nanoseconds for LSB (Default: 130).
+    #[arg(long = "led-pwm-lsb-nanoseconds", value_name = "ns")]
+    pub pwm_lsb_nanoseconds: Option<u32>,
+
+    /// Time dithering of lower bits (Default: 0).
+    #[arg(long = "led-pwm-dither-bits", value_name = "0..2")]
+    pub pwm_dither_bits: Option<usize>,
+
+    /// Needed to initialize special panels. Supported: 'FM6126A', 'FM6127'.
+    #[arg(long = "led-panel-type", value_name = "name", value_parser = parser(parse_panel_type))]
+    pub panel_type: Option<::std::option::Option<PanelType>>,
+
+    /// Slowdown GPIO. Needed for faster Pis/slower panels, -1 for no slowdown (Default: depends on the Pi).
+    #[arg(
+        long = "led-slowdown-gpio",
+        value_name = "-1..4",
+        allow_negative_numbers = true,
+        value_parser = parser(parse_slowdown)
+    )]
+    pub slowdown_gpio: Option<u32>,
+}
+
+/// Wrap a parse function whose error is not `Send` and `Sync` into a value parser.
+fn parser<T: 'static, E: Display + 'static>(
+    parse: fn(&str) -> Result<T, E>,
+) -> impl Fn(&str) -> Result<T, String> + Clone + Send + Sync + 'static {
+    move |value| parse(value).map_err(|error| error.to_string())
+}
+
+impl ConfigArgs {
+    /// Apply the given flags to the config, keeping the values of the flags that were not given.
+    pub fn apply(&self, config: &mut RGBMatrixConfig) {
+        if let Some(hardware_mapping) = self.gpio_mapping {
+            config.hardware_mapping = hardware_mapping;
+        }
+        if let Some(rows) = self.rows {
+            config.rows = rows;
+        }
+        if let Some(cols) = self.cols {
+            config.cols = cols;
+        }
+        if let Some(chain_length) = self.chain {
+            config.chain_length = chain_length;
+        }
+        if let Some(parallel) = self.parallel {
+            config.parallel = parallel;
+        }
+        if let Some(multiplexing) = self.multiplexing {
+            config.multiplexing = multiplexing;
+        }
+        if !self.pixel_mappers.is_empty() {
+            config.pixelmapper.clone_from(&self.pixel_mappers);
+        }
+        if let Some(pwm_bits) = self.pwm_bits {
+            config.pwm_bits = pwm_bits;
+        }
+        if let Some(brightness) = self.brightness {
+            config.brightness = brightness;
+        }
+        if let Some(interlaced) = self.scan_mode {
+            config.interlaced = interlaced;
+        }
+        if let Some(row_setter) = self.row_addr_type {
+            config.row_setter = row_setter;
+        }
+        if let Some(limit) = self.limit_refresh {
+            config.refresh_rate_limit = (limit > 0).then_some(limit);
+        }
+        if self.inverse {
+            config.inverse_colors = true;
+        }
+        if self.no_inverse {
+            config.inverse_colors = false;
+        }
+        if let Some(led_sequence) = self.rgb_sequence {
+            config.led_sequence = led_sequence;
+        }
+        if let Some(pwm_lsb_nanoseconds) = self.pwm_lsb_nanoseconds {
+            config.pwm_lsb_nanoseconds = pwm_lsb_nanoseconds;
+        }
+        if let Some(dither_bits) = self.pwm_dither_bits {
+            config.dither_bits = dither_bits;
+        }
+        if let Some(panel_type) = self.panel_type {
+            config.panel_type = panel_type;
+        }
+        if let Some(slowdown) = self.slowdown_gpio {
+            config.slowdown = Some(slowdown);
+        }
+    }
+}
+
+impl From<ConfigArgs> for RGBMatrixConfig {
+    fn from(args: ConfigArgs) -> Self {
+        let mut config = Self::default();
+        args.apply(&mut config);
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        led: ConfigArgs,
+        #[arg(long)]
+        image: Option<String>,
+    }
+
+    fn parse(args: &str) -> Result<RGBMatrixConfig, clap::Error> {
+        let argv = core::iter::once("demo").chain(args.split_whitespace());
+        Cli::try_parse_from(argv).map(|cli| cli.led.into())
+    }
+
+    #[test]
+    fn test_parse_argv() {
+        let args = "--led-rows=32 --led-cols 64 --led-chain=2 --led-parallel=2 --led-pwm-bits=8 \
+                    --led-brightness=50 --led-gpio-mapping=regular --led-multiplexing=1 \
+                    --led-pixel-mapper=U-mapper;Rotate:90 --led-scan-mode=1 --led-row-addr-type=4 \
+                    --led-limit-refresh=90 --led-rgb-sequence=GBR --led-pwm-lsb-nanoseconds=200 \
+                    --led-pwm-dither-bits=1 --led-panel-type=FM6126A --led-slowdown-gpio=-1 --led-inverse";
+        let flags = RGBMatrixConfig::from_args(args.split_whitespace().map(String::from)).unwrap();
+        assert_eq!(parse(args).unwrap(), flags);
+
+        let cli = Cli::try_parse_from(["demo", "--image", "logo.png", "--led-rows", "16"]).unwrap();
+        assert_eq!(cli.image.as_deref(), Some("logo.png"));
+        assert_eq!(RGBMatrixConfig::from(cli.led).rows, 16);
+
+        assert_eq!(parse("").unwrap(), RGBMatrixConfig::default());
+        let config = parse("--led-multiplexing=0 --led-limit-refresh=0 --led-panel-type=").unwrap();
+        assert_eq!(config, RGBMatrixConfig::default());
+        let config = parse("--led-inverse --led-no-inverse").unwrap();
+        assert!(!config.inverse_colors);
+    }
+
+    #[test]
+    fn test_parse_repeated_pixel_mappers() {
+        let config = parse("--pixel-mapper U-mapper --pixel-mapper Rotate:90;Mirror:H").unwrap();
+        assert_eq!(
+            config.pixelmapper,
+            vec![
+                NamedPixelMapperType::UMapper,
+                NamedPixelMapperType::Rotate(90),
+                NamedPixelMapperType::Mirror(true),
+            ]
+        );
+        let config = parse("--led-pixel-mapper=V-mapper --led-pixel-mapper=Transpose").unwrap();
+        assert_eq!(
+            config.pixelmapper,
+            vec![
+                NamedPixelMapperType::VMapper,
+                NamedPixelMapperType::Transpose
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_argv_malformed() {
+        for args in [
+            "--led-rows=many",
+            "--led-colums=64",
+            "--led-multiplexing=19",
+            "--led-scan-mode=2",
+            "--led-gpio-mapping=compute-module",
+            "--led-slowdown-gpio=-2",
+            "--led-pixel-mapper=Rotate:45",
+            "--led-daemon",
+        ] {
+            assert!(parse(args).is_err(), "{args}");
+        }
+    }
+}
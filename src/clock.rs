@@ -0,0 +1,184 @@
+use std::{
+    sync::{Condvar, Mutex},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// The time source that paces the frames of a matrix created with
+/// [`RGBMatrix::new_mock_with_clock`](crate::RGBMatrix::new_mock_with_clock) and that its framerate is measured
+/// with. Times are durations since an arbitrary fixed point.
+pub trait Clock: Send + Sync {
+    /// The current time.
+    fn now(&self) -> Duration;
+
+    /// Wait until the time reaches the deadline. It may return earlier, e.g. so that the update thread can
+    /// check whether it should stop, and is then called again.
+    fn sleep_until(&self, deadline: Duration);
+}
+
+/// The monotonic clock of the operating system, which is used by default.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep_until(&self, deadline: Duration) {
+        if let Some(remaining) = deadline.checked_sub(self.now()) {
+            sleep(remaining);
+        }
+    }
+}
+
+/// How long [`FakeClock::sleep_until`] waits for the clock to be advanced before it returns.
+const FAKE_SLEEP_POLL: Duration = Duration::from_millis(1);
+
+#[derive(Default)]
+struct FakeClockState {
+    now: Duration,
+    /// The deadline of the last call to [`FakeClock::sleep_until`].
+    sleep_deadline: Option<Duration>,
+}
+
+/// A clock that only advances when [`FakeClock::advance`] is called, to test timing dependent behavior
+/// deterministically. The update thread of a mock matrix outputs a frame, sleeps until the clock has advanced
+/// by the frame time and outputs the next one, so the number of frames follows from the advanced time.
+#[derive(Default)]
+pub struct FakeClock {
+    state: Mutex<FakeClockState>,
+    changed: Condvar,
+}
+
+impl FakeClock {
+    /// Create a clock at time zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advance the time and wake up the threads that sleep until then.
+    pub fn advance(&self, duration: Duration) {
+        self.state.lock().unwrap().now += duration;
+        self.changed.notify_all();
+    }
+
+    /// Block until a thread sleeps until a time that has not been reached yet, e.g. until the update thread has
+    /// finished the frames that are due and waits for the next one.
+    pub fn wait_for_sleeper(&self) {
+        let _state = self
+            .changed
+            .wait_while(self.state.lock().unwrap(), |state| {
+                state
+                    .sleep_deadline
+                    .is_none_or(|deadline| deadline <= state.now)
+            })
+            .unwrap();
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.state.lock().unwrap().now
+    }
+
+    /// Waits for at most a millisecond of real time, since the clock is only advanced explicitly.
+    fn sleep_until(&self, deadline: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.sleep_deadline = Some(deadline);
+        self.changed.notify_all();
+        let _state = self
+            .changed
+            .wait_timeout_while(state, FAKE_SLEEP_POLL, |state| state.now < deadline)
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread::spawn,
+    };
+
+    use super::*;
+    use crate::test_utils::{count_frames, fake_clock_matrix};
+
+    #[test]
+    fn test_fake_clock() {
+        let clock = Arc::new(FakeClock::new());
+        assert_eq!(clock.now(), Duration::ZERO);
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let sleeper = {
+            let clock = Arc::clone(&clock);
+            let ticks = Arc::clone(&ticks);
+            spawn(move || {
+                for tick in 1..=3 {
+                    let deadline = Duration::from_millis(10 * tick);
+                    while clock.now() < deadline {
+                        clock.sleep_until(deadline);
+                    }
+                    ticks.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        };
+
+        clock.wait_for_sleeper();
+        assert_eq!(ticks.load(Ordering::SeqCst), 0);
+        clock.advance(Duration::from_millis(15));
+        clock.wait_for_sleeper();
+        assert_eq!(ticks.load(Ordering::SeqCst), 1);
+        clock.advance(Duration::from_millis(15));
+        sleeper.join().unwrap();
+        assert_eq!(ticks.load(Ordering::SeqCst), 3);
+        assert_eq!(clock.now(), Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_system_clock() {
+        let clock = SystemClock::new();
+        let deadline = clock.now() + Duration::from_millis(2);
+        clock.sleep_until(deadline);
+        assert!(clock.now() >= deadline);
+    }
+
+    #[test]
+    fn test_fake_clock_frames() {
+        // The first frame is output right away, then the update thread waits for the clock.
+        let (mut matrix, _canvas, clock) = fake_clock_matrix();
+        let frames = count_frames(&mut matrix);
+        assert_eq!(frames.load(Ordering::Relaxed), 0);
+
+        // One frame is output per frame time of the refresh rate of 1000Hz.
+        for _ in 0..100 {
+            clock.advance(Duration::from_millis(1));
+            clock.wait_for_sleeper();
+        }
+        assert_eq!(frames.load(Ordering::Relaxed), 100);
+        let framerate = matrix.get_framerate();
+        assert!((framerate - 1000.0).abs() < 1.0, "{framerate}");
+
+        // Frames that were missed are not caught up on.
+        clock.advance(Duration::from_millis(5));
+        clock.wait_for_sleeper();
+        assert_eq!(frames.load(Ordering::Relaxed), 101);
+    }
+}
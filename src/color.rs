@@ -1,39 +1,596 @@
-use crate::config::K_BIT_PLANES;
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+    ops::{Add, Mul, Sub},
+    str::FromStr,
+};
 
-// Do CIE1931 luminance correction and scale to output bitplanes
-fn luminance_cie1931(c: u8, brightness: u8) -> u16 {
-    let out_factor = ((1 << K_BIT_PLANES) - 1) as f32;
-    let v = (c as f32) * (brightness as f32) / 255.0;
-    (out_factor
-        * (if v <= 8.0 {
-            v / 902.3
+use crate::{
+    config::K_BIT_PLANES,
+    math::{ln, powf, rem_euclid, round},
+};
+
+/// An RGB color with 8 bits per channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// An RGB color with 16 bits per channel, for [`Canvas::set_pixel16`](crate::Canvas::set_pixel16). It keeps
+/// gradients of high precision sources smooth, especially in dark colors, where the gamma correction spreads
+/// out the output levels of neighboring 8 bit values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Color16 {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+}
+
+impl Color16 {
+    pub const fn new(r: u16, g: u16, b: u16) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// Extend every channel from 8 to 16 bits, so that 255 becomes 65535.
+impl From<Color> for Color16 {
+    fn from(color: Color) -> Self {
+        Self::new(
+            color.r as u16 * 257,
+            color.g as u16 * 257,
+            color.b as u16 * 257,
+        )
+    }
+}
+
+/// Round every channel to the nearest 8 bit value.
+impl From<Color16> for Color {
+    fn from(color: Color16) -> Self {
+        let narrow = |c: u16| ((c as u32 + 128) / 257) as u8;
+        Self::new(narrow(color.r), narrow(color.g), narrow(color.b))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ColorParseError {
+    /// The hex string does not have 3 or 6 digits.
+    InvalidLength(usize),
+    /// The hex string contains a character that is not a hex digit.
+    InvalidCharacter(char),
+}
+
+impl Error for ColorParseError {}
+
+impl Display for ColorParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ColorParseError::InvalidLength(length) => write!(
+                f,
+                "Expected a hex color with 3 or 6 digits, but got {length} digits."
+            ),
+            ColorParseError::InvalidCharacter(character) => {
+                write!(f, "'{character}' is not a valid hex digit.")
+            }
+        }
+    }
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Parse a color from a hex string in the form `#RRGGBB`, `RRGGBB`, `#RGB` or `RGB`. In the short form,
+    /// each digit is repeated, so `#f0a` is the same as `#ff00aa`.
+    pub fn from_hex(hex: &str) -> Result<Self, ColorParseError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let nibbles = digits
+            .chars()
+            .map(|c| {
+                c.to_digit(16)
+                    .map(|d| d as u8)
+                    .ok_or(ColorParseError::InvalidCharacter(c))
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+        match nibbles[..] {
+            [r, g, b] => Ok(Self::new(r * 0x11, g * 0x11, b * 0x11)),
+            [r1, r2, g1, g2, b1, b2] => Ok(Self::new(r1 << 4 | r2, g1 << 4 | g2, b1 << 4 | b2)),
+            _ => Err(ColorParseError::InvalidLength(nibbles.len())),
+        }
+    }
+
+    /// Format the color as a lowercase hex string in the form `#rrggbb`.
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Create a color from hue (in degrees), saturation and value. The hue wraps around at 360 degrees,
+    /// saturation and value are clamped to `[0, 1]`. The resulting channels are rounded to the nearest
+    /// integer.
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let v = v.clamp(0.0, 1.0);
+        let chroma = v * s;
+        Self::from_hue_chroma(h, chroma, v - chroma)
+    }
+
+    /// Create a color from hue (in degrees), saturation and lightness. The hue wraps around at 360 degrees,
+    /// saturation and lightness are clamped to `[0, 1]`. The resulting channels are rounded to the nearest
+    /// integer.
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        Self::from_hue_chroma(h, chroma, l - chroma / 2.0)
+    }
+
+    /// Convert the color to hue (in degrees, `[0, 360)`), saturation and value (both `[0, 1]`). The hue of
+    /// gray colors is 0.
+    pub fn to_hsv(&self) -> [f32; 3] {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * rem_euclid((g - b) / chroma, 6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
         } else {
-            ((v + 16.0) / 116.0).powi(3)
-        })) as u16
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        [hue, saturation, max]
+    }
+
+    /// Multiply every channel by `factor`, rounded to the nearest integer and saturating at 0 and 255, e.g. to
+    /// fade a color out. This is the same as `color * factor`.
+    pub fn scale(&self, factor: f32) -> Self {
+        let scale = |c: u8| round(c as f32 * factor).clamp(0.0, 255.0) as u8;
+        Self::new(scale(self.r), scale(self.g), scale(self.b))
+    }
+
+    /// Linearly interpolate between `a` at `t = 0` and `b` at `t = 1`, rounded to the nearest integer. `t` is
+    /// clamped to `[0, 1]`.
+    pub fn lerp(a: Color, b: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: u8, b: u8| round(a as f32 + (b as f32 - a as f32) * t) as u8;
+        Self::new(lerp(a.r, b.r), lerp(a.g, b.g), lerp(a.b, b.b))
+    }
+
+    fn from_hue_chroma(h: f32, chroma: f32, offset: f32) -> Self {
+        let sector = rem_euclid(h, 360.0) / 60.0;
+        let x = chroma * (1.0 - (rem_euclid(sector, 2.0) - 1.0).abs());
+        let [r, g, b] = match sector as u8 {
+            0 => [chroma, x, 0.0],
+            1 => [x, chroma, 0.0],
+            2 => [0.0, chroma, x],
+            3 => [0.0, x, chroma],
+            4 => [x, 0.0, chroma],
+            _ => [chroma, 0.0, x],
+        };
+        let [r, g, b] = [r, g, b].map(|c| round((c + offset) * 255.0).clamp(0.0, 255.0) as u8);
+        Self::new(r, g, b)
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Self::new(r, g, b)
+    }
+}
+
+/// Add the channels, saturating at 255.
+impl Add for Color {
+    type Output = Color;
+
+    fn add(self, rhs: Color) -> Color {
+        Color::new(
+            self.r.saturating_add(rhs.r),
+            self.g.saturating_add(rhs.g),
+            self.b.saturating_add(rhs.b),
+        )
+    }
+}
+
+/// Subtract the channels, saturating at 0.
+impl Sub for Color {
+    type Output = Color;
+
+    fn sub(self, rhs: Color) -> Color {
+        Color::new(
+            self.r.saturating_sub(rhs.r),
+            self.g.saturating_sub(rhs.g),
+            self.b.saturating_sub(rhs.b),
+        )
+    }
+}
+
+/// Scale the channels like [`Color::scale`], saturating at 0 and 255.
+impl Mul<f32> for Color {
+    type Output = Color;
+
+    fn mul(self, factor: f32) -> Color {
+        self.scale(factor)
+    }
+}
+
+/// Modulate the channels with the ones of another color as fractions of 255, rounded to the nearest integer.
+/// Multiplying with white leaves a color unchanged, and multiplying with black gives black.
+impl Mul for Color {
+    type Output = Color;
+
+    fn mul(self, rhs: Color) -> Color {
+        let modulate = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+        Color::new(
+            modulate(self.r, rhs.r),
+            modulate(self.g, rhs.g),
+            modulate(self.b, rhs.b),
+        )
+    }
+}
+
+/// The gains of the red, green and blue channels, which correct the white point of the panels. They are applied
+/// to the color values before the gamma correction and are clamped to 0 to 1, since no channel can be brighter
+/// than at its full value. It can be parsed from the three gains separated by commas, e.g. `"1,0.9,0.75"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WhiteBalance {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+}
+
+impl WhiteBalance {
+    /// Full gain on every channel, which leaves the colors unchanged.
+    pub const NEUTRAL: Self = Self::new(1.0, 1.0, 1.0);
+
+    pub const fn new(red: f32, green: f32, blue: f32) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// The gains that shift the white of the panels to the color of a black body at the given temperature in
+    /// Kelvin, clamped to 1000 to 40000 K. Lower temperatures are warmer, e.g. 3000 K for the light of a light
+    /// bulb, and 6600 K is neutral. The gains follow the approximation of the black body colors by Tanner
+    /// Helland.
+    pub fn from_color_temperature(kelvin: u32) -> Self {
+        let temperature = kelvin.clamp(1000, 40000) as f32 / 100.0;
+        let (red, green) = if temperature <= 66.0 {
+            (255.0, 99.470_8 * ln(temperature) - 161.119_57)
+        } else {
+            (
+                329.698_73 * powf(temperature - 60.0, -0.133_204_76),
+                288.122_17 * powf(temperature - 60.0, -0.075_514_85),
+            )
+        };
+        let blue = if temperature >= 66.0 {
+            255.0
+        } else if temperature <= 19.0 {
+            0.0
+        } else {
+            138.517_73 * ln(temperature - 10.0) - 305.044_8
+        };
+        let [red, green, blue] = [red, green, blue].map(|value| value.clamp(0.0, 255.0) / 255.0);
+        Self::new(red, green, blue)
+    }
+
+    /// The gains clamped to 0 to 1. Values that are not numbers are treated as 1.
+    fn clamped(&self) -> [f32; 3] {
+        [self.red, self.green, self.blue].map(|gain| {
+            if gain.is_nan() {
+                1.0
+            } else {
+                gain.clamp(0.0, 1.0)
+            }
+        })
+    }
+}
+
+impl Default for WhiteBalance {
+    fn default() -> Self {
+        Self::NEUTRAL
+    }
+}
+
+impl FromStr for WhiteBalance {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let gains = s
+            .split(',')
+            .map(|value| value.trim().parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("Invalid white balance '{s}'. Expected three gains."))?;
+        let [red, green, blue] = gains[..] else {
+            return Err(format!(
+                "Invalid white balance '{s}'. Expected the red, green and blue gain separated by commas."
+            )
+            .into());
+        };
+        Ok(Self::new(red, green, blue))
+    }
+}
+
+impl Display for WhiteBalance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{},{},{}", self.red, self.green, self.blue)
+    }
+}
+
+/// The default gamma value, which is close to the CIE1931 luminance correction.
+pub(crate) const DEFAULT_GAMMA: f32 = 2.2;
+
+// Do gamma correction of a channel value from 0 to 1 and scale to output bitplanes
+fn luminance_gamma(value: f32, brightness: u8, gamma: f32) -> u16 {
+    let out_factor = ((1 << K_BIT_PLANES) - 1) as f32;
+    let v = powf(value, gamma) * (brightness as f32) / 100.0;
+    round(out_factor * v) as u16
 }
 
 #[derive(Clone)]
 pub(crate) struct ColorLookup {
-    per_brightness: [[u16; 256]; 100],
+    per_brightness: [[u16; 256]; 101],
+    gamma: f32,
+    /// The factors of the output levels of the red, green and blue channel, which are the gains of the white
+    /// balance after the gamma correction, or `None` if the white balance is neutral.
+    channel_factors: Option<[f32; 3]>,
 }
 
 impl ColorLookup {
-    pub(crate) fn new_cie1931() -> Self {
-        let mut per_brightness = [[0; 256]; 100];
+    /// Create the lookup for the given gamma value and white balance. Non-positive gamma values are treated as
+    /// 1.0 (no correction).
+    pub(crate) fn new(gamma: f32, white_balance: WhiteBalance) -> Self {
+        let gamma = if gamma > 0.0 { gamma } else { 1.0 };
+        let mut per_brightness = [[0; 256]; 101];
         (0..256).for_each(|c| {
-            (0..100).for_each(|b| {
-                per_brightness[b][c] = luminance_cie1931(c as u8, (b + 1) as u8);
+            (0..=100).for_each(|b| {
+                per_brightness[b][c] = luminance_gamma(c as f32 / 255.0, b as u8, gamma);
             });
         });
-        Self { per_brightness }
+        // A gain before the gamma correction is the same as its power after it.
+        let gains = white_balance.clamped();
+        let channel_factors = (gains != [1.0; 3]).then(|| gains.map(|gain| powf(gain, gamma)));
+        Self {
+            per_brightness,
+            gamma,
+            channel_factors,
+        }
     }
 
     pub(crate) fn lookup_rgb(&self, brightness: u8, r: u8, g: u8, b: u8) -> [u16; 3] {
-        let for_brightness = &self.per_brightness[brightness as usize - 1];
-        [
+        let for_brightness = &self.per_brightness[brightness as usize];
+        self.balance([
             for_brightness[r as usize],
             for_brightness[g as usize],
             for_brightness[b as usize],
-        ]
+        ])
+    }
+
+    /// The output levels of a 16 bit color, which are calculated instead of looked up.
+    pub(crate) fn levels16(&self, brightness: u8, color: Color16) -> [u16; 3] {
+        self.balance(
+            [color.r, color.g, color.b]
+                .map(|c| luminance_gamma(c as f32 / 65535.0, brightness, self.gamma)),
+        )
+    }
+
+    /// Apply the white balance to the output levels of a color.
+    fn balance(&self, levels: [u16; 3]) -> [u16; 3] {
+        match self.channel_factors {
+            Some(factors) => core::array::from_fn(|channel| {
+                round(levels[channel] as f32 * factors[channel]) as u16
+            }),
+            None => levels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        for hex in ["#ffffff", "#000000", "#808080", "#7f3a10", "#12abef"] {
+            assert_eq!(Color::from_hex(hex).unwrap().to_hex(), hex);
+        }
+        assert_eq!(Color::from_hex("#000").unwrap(), Color::new(0, 0, 0));
+        assert_eq!(
+            Color::from_hex("7F3A10").unwrap(),
+            Color::new(0x7f, 0x3a, 0x10)
+        );
+    }
+
+    #[test]
+    fn test_hex_short_form() {
+        assert_eq!(Color::from_hex("#f0a").unwrap().to_hex(), "#ff00aa");
+        assert_eq!(Color::from_hex("fff").unwrap(), Color::new(255, 255, 255));
+    }
+
+    #[test]
+    fn test_hex_invalid() {
+        assert_eq!(
+            Color::from_hex("#ffff"),
+            Err(ColorParseError::InvalidLength(4))
+        );
+        assert_eq!(Color::from_hex(""), Err(ColorParseError::InvalidLength(0)));
+        assert_eq!(
+            Color::from_hex("##fff"),
+            Err(ColorParseError::InvalidCharacter('#'))
+        );
+        assert_eq!(
+            Color::from_hex("#ggg"),
+            Err(ColorParseError::InvalidCharacter('g'))
+        );
+    }
+
+    #[test]
+    fn test_hsv_primary_hues() {
+        assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), Color::new(255, 0, 0));
+        assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::new(0, 255, 0));
+        assert_eq!(Color::from_hsv(240.0, 1.0, 1.0), Color::new(0, 0, 255));
+        assert_eq!(Color::from_hsv(360.0, 1.0, 1.0), Color::new(255, 0, 0));
+        assert_eq!(Color::from_hsv(-120.0, 1.0, 1.0), Color::new(0, 0, 255));
+        assert_eq!(Color::from_hsv(60.0, 1.0, 0.5), Color::new(128, 128, 0));
+    }
+
+    #[test]
+    fn test_hsv_grayscale() {
+        assert_eq!(Color::from_hsv(200.0, 0.0, 0.5), Color::new(128, 128, 128));
+        assert_eq!(Color::from_hsv(0.0, 0.0, 2.0), Color::new(255, 255, 255));
+        assert_eq!(Color::new(128, 128, 128).to_hsv()[..2], [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert_eq!(Color::from_hsl(0.0, 1.0, 0.5), Color::new(255, 0, 0));
+        assert_eq!(Color::from_hsl(120.0, 1.0, 0.5), Color::new(0, 255, 0));
+        assert_eq!(Color::from_hsl(240.0, 1.0, 0.5), Color::new(0, 0, 255));
+        assert_eq!(Color::from_hsl(0.0, 0.0, 1.0), Color::new(255, 255, 255));
+        assert_eq!(Color::from_hsl(0.0, 1.0, 0.0), Color::new(0, 0, 0));
+    }
+
+    #[test]
+    fn test_hsv_round_trip() {
+        for color in [
+            Color::new(255, 0, 0),
+            Color::new(0, 255, 0),
+            Color::new(0, 0, 255),
+            Color::new(12, 200, 99),
+            Color::new(250, 128, 3),
+        ] {
+            let [h, s, v] = color.to_hsv();
+            assert_eq!(Color::from_hsv(h, s, v), color);
+        }
+    }
+
+    #[test]
+    fn test_add_sub_saturate() {
+        let color = Color::new(255, 200, 10);
+        assert_eq!(color + Color::new(1, 100, 20), Color::new(255, 255, 30));
+        assert_eq!(color + Color::WHITE, Color::WHITE);
+        assert_eq!(color - Color::new(55, 201, 10), Color::new(200, 0, 0));
+        assert_eq!(Color::BLACK - color, Color::BLACK);
+    }
+
+    #[test]
+    fn test_scale() {
+        let color = Color::new(255, 100, 3);
+        assert_eq!(color.scale(0.5), Color::new(128, 50, 2));
+        assert_eq!(color * 0.5, color.scale(0.5));
+        assert_eq!(color * 2.0, Color::new(255, 200, 6));
+        assert_eq!(color * 0.0, Color::BLACK);
+        assert_eq!(color * -1.0, Color::BLACK);
+        assert_eq!(color * 1.0, color);
+    }
+
+    #[test]
+    fn test_modulate() {
+        let color = Color::new(255, 100, 3);
+        assert_eq!(color * Color::WHITE, color);
+        assert_eq!(color * Color::BLACK, Color::BLACK);
+        assert_eq!(color * Color::new(128, 255, 0), Color::new(128, 100, 0));
+    }
+
+    #[test]
+    fn test_lerp() {
+        let [a, b] = [Color::new(0, 100, 255), Color::new(255, 50, 0)];
+        assert_eq!(Color::lerp(a, b, 0.0), a);
+        assert_eq!(Color::lerp(a, b, 1.0), b);
+        assert_eq!(Color::lerp(a, b, 0.5), Color::new(128, 75, 128));
+        assert_eq!(Color::lerp(a, b, -1.0), a);
+        assert_eq!(Color::lerp(a, b, 2.0), b);
+    }
+
+    #[test]
+    fn test_gamma_lookup() {
+        let max = (1 << K_BIT_PLANES) - 1;
+        for gamma in [1.0, 1.8, DEFAULT_GAMMA, 2.8] {
+            let lookup = ColorLookup::new(gamma, WhiteBalance::NEUTRAL);
+            let table = &lookup.per_brightness[100];
+            assert_eq!(table[0], 0);
+            assert_eq!(table[255], max);
+            assert!(table.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn test_white_balance() {
+        let neutral = ColorLookup::new(DEFAULT_GAMMA, WhiteBalance::NEUTRAL);
+        let unchanged = ColorLookup::new(DEFAULT_GAMMA, WhiteBalance::new(1.0, 1.0, 1.0));
+        for c in 0..=255 {
+            assert_eq!(
+                unchanged.lookup_rgb(100, c, c, c),
+                neutral.lookup_rgb(100, c, c, c)
+            );
+        }
+
+        // Half the blue value is a quarter of the level with a gamma of 2.
+        let lookup = ColorLookup::new(2.0, WhiteBalance::new(1.0, 2.0, 0.5));
+        let max = (1 << K_BIT_PLANES) - 1;
+        assert_eq!(lookup.lookup_rgb(100, 255, 255, 255), [max, max, 512]);
+        assert_eq!(
+            lookup.levels16(100, Color16::new(65535, 65535, 65535)),
+            [max, max, 512]
+        );
+
+        let neutral = WhiteBalance::from_color_temperature(6600);
+        assert_eq!(neutral, WhiteBalance::NEUTRAL);
+        let warm = WhiteBalance::from_color_temperature(3000);
+        assert_eq!(warm.red, 1.0);
+        assert!(warm.blue < warm.green && warm.green < 1.0);
+        let cool = WhiteBalance::from_color_temperature(10000);
+        assert!(cool.red < cool.green && cool.green < 1.0);
+        assert_eq!(cool.blue, 1.0);
+
+        assert_eq!(
+            "1, 0.9,0.75".parse::<WhiteBalance>().unwrap(),
+            WhiteBalance::new(1.0, 0.9, 0.75)
+        );
+        assert!("1,0.9".parse::<WhiteBalance>().is_err());
+    }
+
+    #[test]
+    fn test_color16_conversion() {
+        for c in [0, 1, 127, 128, 254, 255] {
+            let color = Color::new(c, 255 - c, c / 2);
+            assert_eq!(Color::from(Color16::from(color)), color);
+        }
+        assert_eq!(
+            Color16::from(Color::WHITE),
+            Color16::new(65535, 65535, 65535)
+        );
+        assert_eq!(
+            Color::from(Color16::new(128, 129, 385)),
+            Color::new(0, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_levels16_match_8_bit_lookup() {
+        let lookup = ColorLookup::new(DEFAULT_GAMMA, WhiteBalance::NEUTRAL);
+        for brightness in [10, 100] {
+            for c in 0..=255 {
+                let color = Color::new(c, c, c);
+                assert_eq!(
+                    lookup.levels16(brightness, color.into()),
+                    lookup.lookup_rgb(brightness, c, c, c)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_gamma_one_is_linear() {
+        let max = ((1 << K_BIT_PLANES) - 1) as f32;
+        let lookup = ColorLookup::new(1.0, WhiteBalance::NEUTRAL);
+        (0..=255u8).for_each(|c| {
+            let expected = round(c as f32 / 255.0 * max) as u16;
+            assert_eq!(lookup.lookup_rgb(100, c, c, c), [expected; 3]);
+        });
     }
 }
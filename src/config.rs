@@ -1,8 +1,24 @@
+use alloc::{boxed::Box, format, vec, vec::Vec};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+    ops::RangeInclusive,
+    str::FromStr,
+};
+
+#[cfg(feature = "std")]
 use argh::FromArgs;
 
 use crate::{
-    canvas::LedSequence, init_sequence::PanelType, multiplex_mapper::MultiplexMapperType,
-    named_pixel_mapper::NamedPixelMapperType, row_address_setter::RowAddressSetterType,
+    canvas::{LedSequence, PowerProfile},
+    color::{WhiteBalance, DEFAULT_GAMMA},
+    config_builder::RGBMatrixConfigBuilder,
+    dithering::Dithering,
+    init_sequence::PanelType,
+    multiplex_mapper::MultiplexMapperType,
+    named_pixel_mapper::{NamedPixelMapperType, PixelMapperError},
+    pixel_mapper::CompiledPixelMapper,
+    row_address_setter::RowAddressSetterType,
     HardwareMapping, PiChip,
 };
 
@@ -15,23 +31,219 @@ pub(crate) const SUB_PANELS: usize = 2;
 /// to be able to scale down brightness more by having more bits at the bottom.
 pub(crate) const K_BIT_PLANES: usize = 11;
 
+/// The maximum number of rows of a panel that can be addressed with the five address lines A to E.
+const MAX_ROWS: usize = 64;
+
+/// The range of the base time of the PWM pulses in nanoseconds. Shorter pulses are not displayed properly by
+/// any panel, and longer ones make the refresh rate flicker visibly.
+const PWM_LSB_NANOSECONDS: RangeInclusive<u32> = 50..=3000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// The number of rows is not an even number between 8 and 64.
+    InvalidRows(usize),
+    /// The number of columns is zero.
+    InvalidCols(usize),
+    /// The chain length is zero.
+    InvalidChainLength(usize),
+    /// The number of parallel chains is zero.
+    InvalidParallel(usize),
+    /// The PWM bits are not between 1 and 11.
+    InvalidPwmBits(usize),
+    /// The base time of the PWM pulses is not between 50 and 3000 nanoseconds.
+    InvalidPwmLsbNanoseconds(u32),
+    /// The brightness is above 100 percent.
+    InvalidBrightness(u8),
+    /// The limit of the refresh rate is zero.
+    InvalidRefreshRateLimit(usize),
+    /// The gamma value is not a positive number.
+    InvalidGamma(f32),
+    /// The dither bits are not between 0 and 2.
+    InvalidDitherBits(usize),
+    /// The real-time priority is not between 1 and 99.
+    InvalidRealtimePriority(u8),
+    /// The core for the update thread does not exist on the Raspberry Pi model.
+    InvalidCpuAffinity { core: usize, cores: usize },
+    /// The hardware mapping supports fewer parallel chains. Contains the supported maximum.
+    TooManyParallelChains(usize),
+    /// The number of rows can't be split by the multiplexing mapper.
+    IncompatibleMultiplexing {
+        multiplexing: MultiplexMapperType,
+        rows: usize,
+    },
+    /// The pixel mappers can't be applied to the chain of panels.
+    PixelMapper(PixelMapperError),
+    /// A power limit is set without a power profile to estimate the current with.
+    MissingPowerProfile,
+    /// A current of the power profile is negative or not a number.
+    InvalidPowerProfile(PowerProfile),
+    /// The number of panels does not match the chain length.
+    PanelCountMismatch { panels: usize, chain_length: usize },
+    /// A panel of the chain has a different number of rows than the others or no columns.
+    InvalidPanel(PanelSpec),
+    /// The pixel mapper arranges panels, which have to be of the same size.
+    MixedPanelSizes(NamedPixelMapperType),
+    /// There is no panel with this index in the chains.
+    InvalidPanelIndex { index: usize, panels: usize },
+}
+
+impl Error for ConfigError {}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConfigError::InvalidRows(rows) => write!(
+                f,
+                "Invalid number of rows '{rows}'. It must be an even number between 8 and {MAX_ROWS}."
+            ),
+            ConfigError::InvalidCols(cols) => {
+                write!(f, "Invalid number of columns '{cols}'. It must be at least 1.")
+            }
+            ConfigError::InvalidChainLength(chain_length) => write!(
+                f,
+                "Invalid chain length '{chain_length}'. It must be at least 1."
+            ),
+            ConfigError::InvalidParallel(parallel) => write!(
+                f,
+                "Invalid number of parallel chains '{parallel}'. It must be at least 1."
+            ),
+            ConfigError::InvalidPwmBits(pwm_bits) => write!(
+                f,
+                "Invalid PWM bits '{pwm_bits}'. It must be between 1 and {K_BIT_PLANES}."
+            ),
+            ConfigError::InvalidPwmLsbNanoseconds(nanoseconds) => write!(
+                f,
+                "Invalid PWM LSB time '{nanoseconds}' ns. It must be between {} and {} ns.",
+                PWM_LSB_NANOSECONDS.start(),
+                PWM_LSB_NANOSECONDS.end()
+            ),
+            ConfigError::InvalidBrightness(brightness) => write!(
+                f,
+                "Invalid brightness '{brightness}'. It must be between 0 and 100."
+            ),
+            ConfigError::InvalidRefreshRateLimit(limit) => write!(
+                f,
+                "Invalid refresh rate limit '{limit}'. It must be at least 1."
+            ),
+            ConfigError::InvalidGamma(gamma) => {
+                write!(f, "Invalid gamma '{gamma}'. It must be a positive number.")
+            }
+            ConfigError::InvalidDitherBits(dither_bits) => {
+                write!(f, "Unsupported dither bits '{dither_bits}'.")
+            }
+            ConfigError::InvalidRealtimePriority(priority) => write!(
+                f,
+                "Invalid real-time priority '{priority}'. It must be between 1 and 99."
+            ),
+            ConfigError::InvalidCpuAffinity { core, cores } => write!(
+                f,
+                "Invalid CPU core '{core}'. The Raspberry Pi has {cores} cores, starting at 0."
+            ),
+            ConfigError::TooManyParallelChains(max) => {
+                write!(f, "GPIO mapping only supports up to {max} parallel panels.")
+            }
+            ConfigError::IncompatibleMultiplexing { multiplexing, rows } => write!(
+                f,
+                "The multiplexing mapper '{multiplexing}' does not support panels with {rows} rows."
+            ),
+            ConfigError::PixelMapper(error) => write!(f, "Pixel mapper error: {error}"),
+            ConfigError::MissingPowerProfile => write!(
+                f,
+                "A power limit requires a power profile to estimate the current of a frame."
+            ),
+            ConfigError::InvalidPowerProfile(profile) => write!(
+                f,
+                "Invalid power profile {profile:?}. All currents must be non-negative numbers."
+            ),
+            ConfigError::PanelCountMismatch {
+                panels,
+                chain_length,
+            } => write!(
+                f,
+                "The chain of {chain_length} panels is described by {panels} panel sizes."
+            ),
+            ConfigError::InvalidPanel(panel) => write!(
+                f,
+                "Invalid panel '{panel}'. All chained panels must have the same number of rows and at least one column."
+            ),
+            ConfigError::MixedPanelSizes(mapper) => write!(
+                f,
+                "The pixel mapper '{mapper}' can't arrange panels of different sizes."
+            ),
+            ConfigError::InvalidPanelIndex { index, panels } => write!(
+                f,
+                "Invalid panel index '{index}'. There are {panels} panels."
+            ),
+        }
+    }
+}
+
+/// The size of a panel in a chain of panels of different sizes, see [`RGBMatrixConfig::panels`]. It is written
+/// as `<cols>x<rows>`, e.g. `64x32` for a panel that is 64 pixels wide and 32 pixels high.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PanelSpec {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl PanelSpec {
+    pub const fn new(rows: usize, cols: usize) -> Self {
+        Self { rows, cols }
+    }
+}
+
+impl FromStr for PanelSpec {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (cols, rows) = s
+            .split_once(['x', 'X'])
+            .and_then(|(cols, rows)| Some((cols.trim().parse().ok()?, rows.trim().parse().ok()?)))
+            .ok_or_else(|| {
+                format!(
+                    "Invalid panel size '{s}'. Expected e.g. '64x32' for 64 columns and 32 rows."
+                )
+            })?;
+        Ok(Self { rows, cols })
+    }
+}
+
+impl Display for PanelSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}x{}", self.cols, self.rows)
+    }
+}
+
 /// Configuration for an RGB matrix panel controller.
-#[derive(FromArgs, Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(FromArgs))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct RGBMatrixConfig {
-    /// the display wiring e.g. "AdafruitHat" or "AdafruitHatPwm". Default: "AdafruitHatPwm"
-    #[argh(option, default = "HardwareMapping::adafruit_hat_pwm()")]
+    /// the display wiring e.g. "AdafruitHat", "AdafruitHatPwm" or "Regular", or the GPIO pins of custom wiring
+    /// e.g. "oe=18;clk=17;lat=4;addr=22,23,24,25,15;rgb=11,27,7,8,9,10". Default: "AdafruitHatPwm"
+    #[cfg_attr(
+        feature = "std",
+        argh(option, default = "HardwareMapping::adafruit_hat_pwm()")
+    )]
     pub hardware_mapping: HardwareMapping,
     /// the number of display rows. Default: 64
-    #[argh(option, default = "64")]
+    #[cfg_attr(feature = "std", argh(option, default = "64"))]
     pub rows: usize,
     /// the number of display columns. Default: 64
-    #[argh(option, default = "64")]
+    #[cfg_attr(feature = "std", argh(option, default = "64"))]
     pub cols: usize,
-    /// the display refresh rate. Default: 120
-    #[argh(option, default = "120")]
+    /// the display refresh rate. The update thread idles for the rest of each frame to not exceed it. The
+    /// achieved rate can be lower if outputting a frame takes longer. Default: 120
+    #[cfg_attr(feature = "std", argh(option, default = "120"))]
     pub refresh_rate: usize,
+    /// caps the refresh rate below `refresh_rate` by idling longer, which saves power and reduces heat at the
+    /// cost of more visible flicker. Must be at least 1. Default: no limit
+    #[cfg_attr(feature = "std", argh(option))]
+    pub refresh_rate_limit: Option<usize>,
     /// the Raspberry Pi chip model e.g. "BCM2711", Default: automatic
-    #[argh(option)]
+    #[cfg_attr(feature = "std", argh(option))]
     pub pi_chip: Option<PiChip>,
     /// the LEDs can only be switched on or off, so the shaded brightness perception is achieved via PWM
     /// (Pulse Width Modulation). In order to get a good 8 bit per color resolution (24 bit RGB), the 11 bits
@@ -41,12 +253,14 @@ pub struct RGBMatrixConfig {
     /// interested in the extremes: 1 Bit for situations that only require 8 colors (e.g. for high contrast
     /// text displays) or 11 Bit for everything else (e.g. showing images or videos). Lower number of bits use
     /// slightly less CPU and result in a higher refresh rate. Default: 11
-    #[argh(option, default = "11")]
+    #[cfg_attr(feature = "std", argh(option, default = "11"))]
     pub pwm_bits: usize,
     /// base time-unit for the on-time in the lowest significant bit in nanoseconds. Lower values will allow
     /// higher frame rate, but will also negatively impact quality in some panels. Good values for full-color
-    /// display (pwm_bits=11) are somewhere between 100 and 300. Default: 130
-    #[argh(option, default = "130")]
+    /// display (pwm_bits=11) are somewhere between 100 and 300. Each row is lit for about 2^pwm_bits times
+    /// this value per frame, so doubling it roughly halves the refresh rate. Must be between 50 and 3000.
+    /// Default: 130
+    #[cfg_attr(feature = "std", argh(option, default = "130"))]
     pub pwm_lsb_nanoseconds: u32,
     /// the Raspberry Pi starting with Pi2 are putting out data too fast for almost all LED panels. In this
     /// case, you want to slow down writing to GPIO. Zero for this parameter means 'no slowdown'. The default
@@ -54,47 +268,266 @@ pub struct RGBMatrixConfig {
     /// Raspberry Pi with a slower processor (Model A, A+, B+, Zero), then a value of 0 might work and is
     /// desirable. A Raspberry Pi3 or Pi4 might even need higher values for the panels to be happy.
     /// Default: automatic
-    #[argh(option)]
+    #[cfg_attr(feature = "std", argh(option))]
     pub slowdown: Option<u32>,
-    /// interlaced scan mode. Default: false
-    #[argh(option, default = "false")]
+    /// the CPU core that the display update thread is pinned to. Other processes can be kept off that core by
+    /// adding isolcpus=<core> to /boot/cmdline.txt, so that they can't interrupt the update and cause flicker.
+    /// Default: the last core
+    #[cfg_attr(feature = "std", argh(option))]
+    pub cpu_affinity: Option<usize>,
+    /// run the display update thread with the SCHED_FIFO real-time scheduling policy at this priority, from 1
+    /// to 99, so that it isn't preempted by normal processes. Requires root or CAP_SYS_NICE. Default: the
+    /// highest priority of the normal scheduling policy
+    #[cfg_attr(feature = "std", argh(option))]
+    pub realtime_priority: Option<u8>,
+    /// the initial brightness of the display in percent, from 0 to 100. Default: 100
+    #[cfg_attr(feature = "std", argh(option, default = "100"))]
+    pub brightness: u8,
+    /// the gamma value used to correct the perceived brightness of the color channels. 1.0 means no
+    /// correction. Default: 2.2
+    #[cfg_attr(feature = "std", argh(option, default = "DEFAULT_GAMMA"))]
+    pub gamma: f32,
+    /// the gains of the red, green and blue channel to correct the white point of the panels, from 0 to 1 and
+    /// separated by commas, e.g. "1,0.9,0.75" if white looks too blue. They are applied before the gamma
+    /// correction. Default: "1,1,1"
+    #[cfg_attr(feature = "std", argh(option, default = "WhiteBalance::NEUTRAL"))]
+    pub white_balance: WhiteBalance,
+    /// interlaced scan mode, which outputs the even rows of a frame before the odd rows. Spreading the rows
+    /// of a frame over two fields can reduce visible flicker on some panels and with many parallel chains, but
+    /// moving content can look less sharp, since the two fields of a row pair are shown at different times.
+    /// Default: false
+    #[cfg_attr(feature = "std", argh(option, default = "false"))]
     pub interlaced: bool,
-    /// the lower bits can be time dithered, i.e. their brightness contribution is achieved by only showing
-    /// them some frames (this is possible, because the PWM is implemented as binary code modulation). This
-    /// will allow higher refresh rate (or same refresh rate with increased --pwm_lsb_nanoseconds). The
-    /// disadvantage could be slightly lower brightness, in particular for longer chains, and higher CPU use.
-    /// CPU use is not of concern for Raspberry Pi 2, 3 and 4 (as we run on a dedicated core anyway) but
-    /// probably for Raspberry Pi 1 or Pi Zero. Default: 0 (no dithering)
-    #[argh(option, default = "0")]
+    /// the number of bits below the PWM bits that are kept by temporal dithering, from 0 to 2. The rounding
+    /// error of the PWM bits is distributed over 2^dither_bits successive frames, which reduces banding in
+    /// gradients at low --pwm-bits. This allows lower --pwm-bits and thus a higher refresh rate for the same
+    /// apparent color depth, at the cost of some flicker at low refresh rates and memory for the additional
+    /// frames. Default: 0 (no dithering)
+    #[cfg_attr(feature = "std", argh(option, default = "0"))]
     pub dither_bits: usize,
+    /// how the rounding error of the PWM bits is distributed: "None", "Temporal" over 2^dither_bits frames or
+    /// "OrderedBayer" over neighboring pixels. Default: "Temporal"
+    #[cfg_attr(feature = "std", argh(option, default = "Dithering::Temporal"))]
+    pub dithering: Dithering,
     /// number of daisy-chained panels. Default: 1
-    #[argh(option, default = "1")]
+    #[cfg_attr(feature = "std", argh(option, default = "1"))]
     pub chain_length: usize,
+    /// the sizes of the chained panels as <cols>x<rows>, e.g. "64x32", if they are not all --rows x --cols. One
+    /// option is given for every panel of the chain, in the order in which they appear in the matrix from left
+    /// to right. The panels share the address lines, so they must all have --rows rows, but their widths can
+    /// differ. Pixel mappers that arrange panels, like the U-mapper, need panels of the same size. Default: all
+    /// panels are --rows x --cols
+    #[cfg_attr(feature = "std", argh(option))]
+    pub panels: Vec<PanelSpec>,
     /// how many chains to run in parallel. Default: 1
-    #[argh(option, default = "1")]
+    #[cfg_attr(feature = "std", argh(option, default = "1"))]
     pub parallel: usize,
     /// typically left empty, but some panels need a particular initialization sequence. This can be e.g.
     /// "FM6126A" for that particular panel type.
-    #[argh(option)]
+    #[cfg_attr(feature = "std", argh(option))]
     pub panel_type: Option<PanelType>,
     /// the kind of multiplexing mapper.
-    #[argh(option)]
+    #[cfg_attr(feature = "std", argh(option))]
     pub multiplexing: Option<MultiplexMapperType>,
     /// the kind of pixel mapper.
-    #[argh(option)]
+    #[cfg_attr(feature = "std", argh(option))]
     pub pixelmapper: Vec<NamedPixelMapperType>,
     /// the row address setter.
-    #[argh(option, default = "RowAddressSetterType::Direct")]
+    #[cfg_attr(
+        feature = "std",
+        argh(option, default = "RowAddressSetterType::Direct")
+    )]
     pub row_setter: RowAddressSetterType,
-    /// the LED sequence, Default: "RGB"
-    #[argh(option, default = "LedSequence::Rgb")]
+    /// the order in which the LEDs are wired to the red, green and blue pins, e.g. "GRB". Default: "RGB"
+    #[cfg_attr(feature = "std", argh(option, default = "LedSequence::Rgb"))]
     pub led_sequence: LedSequence,
+    /// invert the output of the color channels for panels with common anode LEDs, on which black is shown
+    /// bright otherwise. Default: false
+    #[cfg_attr(feature = "std", argh(option, default = "false"))]
+    pub inverse_colors: bool,
+    /// whether the red channel is output. Disabled channels stay off whatever the colors that are drawn, e.g.
+    /// for monochrome signage. Default: true
+    #[cfg_attr(feature = "std", argh(option, default = "true"))]
+    pub red_enabled: bool,
+    /// whether the green channel is output. Default: true
+    #[cfg_attr(feature = "std", argh(option, default = "true"))]
+    pub green_enabled: bool,
+    /// whether the blue channel is output. Default: true
+    #[cfg_attr(feature = "std", argh(option, default = "true"))]
+    pub blue_enabled: bool,
+    /// the maximum current of the panels in milliamps, e.g. to stay within the rating of the power supply. When
+    /// the estimated current of a frame exceeds it, the whole frame is shown with a lower brightness that keeps
+    /// it within the budget. This is done for every frame that is swapped in, so only bright frames appear
+    /// dimmer. Requires --power-profile. Default: no limit
+    #[cfg_attr(feature = "std", argh(option))]
+    pub power_limit_ma: Option<u32>,
+    /// the current of the panels to estimate the current of a frame with, as the milliamps of a single red,
+    /// green and blue LED at full value and of all panels while every pixel is off, e.g. "1.5,1,1.25,120".
+    #[cfg_attr(feature = "std", argh(option))]
+    pub power_profile: Option<PowerProfile>,
 }
 
 impl RGBMatrixConfig {
+    /// Create a builder that starts from the default configuration.
+    pub fn builder() -> RGBMatrixConfigBuilder {
+        RGBMatrixConfigBuilder::default()
+    }
+
+    /// Check that the configuration is valid. This is done when creating an [`RGBMatrix`](crate::RGBMatrix), so
+    /// calling it beforehand is only needed to report errors early.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.rows < 8 || self.rows > MAX_ROWS || !self.rows.is_multiple_of(SUB_PANELS) {
+            return Err(ConfigError::InvalidRows(self.rows));
+        }
+        if self.cols == 0 {
+            return Err(ConfigError::InvalidCols(self.cols));
+        }
+        if self.chain_length == 0 {
+            return Err(ConfigError::InvalidChainLength(self.chain_length));
+        }
+        if self.parallel == 0 {
+            return Err(ConfigError::InvalidParallel(self.parallel));
+        }
+        if !(1..=K_BIT_PLANES).contains(&self.pwm_bits) {
+            return Err(ConfigError::InvalidPwmBits(self.pwm_bits));
+        }
+        if !PWM_LSB_NANOSECONDS.contains(&self.pwm_lsb_nanoseconds) {
+            return Err(ConfigError::InvalidPwmLsbNanoseconds(
+                self.pwm_lsb_nanoseconds,
+            ));
+        }
+        if self.brightness > 100 {
+            return Err(ConfigError::InvalidBrightness(self.brightness));
+        }
+        if self.refresh_rate_limit == Some(0) {
+            return Err(ConfigError::InvalidRefreshRateLimit(0));
+        }
+        Self::validate_gamma(self.gamma)?;
+        if self.dither_bits > 2 {
+            return Err(ConfigError::InvalidDitherBits(self.dither_bits));
+        }
+        if let Some(priority) = self.realtime_priority {
+            if !(1..=99).contains(&priority) {
+                return Err(ConfigError::InvalidRealtimePriority(priority));
+            }
+        }
+        if let (Some(core), Some(chip)) = (self.cpu_affinity, self.pi_chip) {
+            if core >= chip.num_cores() {
+                return Err(ConfigError::InvalidCpuAffinity {
+                    core,
+                    cores: chip.num_cores(),
+                });
+            }
+        }
+        let max_parallel = self.hardware_mapping.max_parallel_chains();
+        if self.parallel > max_parallel {
+            return Err(ConfigError::TooManyParallelChains(max_parallel));
+        }
+        if !self.panels.is_empty() && self.panels.len() != self.chain_length {
+            return Err(ConfigError::PanelCountMismatch {
+                panels: self.panels.len(),
+                chain_length: self.chain_length,
+            });
+        }
+        if let Some(&panel) = self
+            .panels
+            .iter()
+            .find(|panel| panel.rows != self.rows || panel.cols == 0)
+        {
+            return Err(ConfigError::InvalidPanel(panel));
+        }
+        if self
+            .panels
+            .iter()
+            .any(|panel| panel.cols != self.panels[0].cols)
+        {
+            if let Some(&mapper) = self.pixelmapper.iter().find(|m| m.arranges_panels()) {
+                return Err(ConfigError::MixedPanelSizes(mapper));
+            }
+        }
+        if let Some(multiplexing) = self.multiplexing {
+            // Every stretched panel still has to consist of two sub-panels.
+            let stretch_factor = multiplexing.create().panel_stretch_factor();
+            if !self.rows.is_multiple_of(stretch_factor * SUB_PANELS) {
+                return Err(ConfigError::IncompatibleMultiplexing {
+                    multiplexing,
+                    rows: self.rows,
+                });
+            }
+        }
+        CompiledPixelMapper::check(
+            &self.pixelmapper,
+            self.chain_length,
+            self.parallel,
+            self.chain_width(),
+            self.rows * self.parallel,
+        )
+        .map_err(ConfigError::PixelMapper)?;
+        if let Some(profile) = self.power_profile {
+            let currents = [
+                profile.red_milliamps,
+                profile.green_milliamps,
+                profile.blue_milliamps,
+                profile.idle_milliamps,
+            ];
+            if currents
+                .iter()
+                .any(|current| !current.is_finite() || *current < 0.0)
+            {
+                return Err(ConfigError::InvalidPowerProfile(profile));
+            }
+        } else if self.power_limit_ma.is_some() {
+            return Err(ConfigError::MissingPowerProfile);
+        }
+        Ok(())
+    }
+
     pub(crate) const fn double_rows(&self) -> usize {
         self.rows / SUB_PANELS
     }
+
+    /// The sizes of the chained panels, which are all `rows` x `cols` unless [`RGBMatrixConfig::panels`] is set.
+    pub(crate) fn chain_panels(&self) -> Vec<PanelSpec> {
+        if self.panels.is_empty() {
+            vec![PanelSpec::new(self.rows, self.cols); self.chain_length]
+        } else {
+            self.panels.clone()
+        }
+    }
+
+    /// Set the white balance to the color of a black body at the given temperature in Kelvin, see
+    /// [`WhiteBalance::from_color_temperature`].
+    pub fn set_color_temperature(&mut self, kelvin: u32) {
+        self.white_balance = WhiteBalance::from_color_temperature(kelvin);
+    }
+
+    /// Whether the red, green and blue channels are output.
+    pub(crate) fn channel_mask(&self) -> [bool; 3] {
+        [self.red_enabled, self.green_enabled, self.blue_enabled]
+    }
+
+    /// Check that the gamma value is a positive number.
+    pub(crate) fn validate_gamma(gamma: f32) -> Result<(), ConfigError> {
+        if gamma <= 0.0 || !gamma.is_finite() {
+            return Err(ConfigError::InvalidGamma(gamma));
+        }
+        Ok(())
+    }
+
+    /// The refresh rate the update thread idles to keep, which is the refresh rate capped by its limit.
+    pub(crate) fn frame_rate_target(&self) -> usize {
+        self.refresh_rate_limit
+            .map_or(self.refresh_rate, |limit| self.refresh_rate.min(limit))
+    }
+
+    /// The number of columns of a chain.
+    pub(crate) fn chain_width(&self) -> usize {
+        if self.panels.is_empty() {
+            self.cols * self.chain_length
+        } else {
+            self.panels.iter().map(|panel| panel.cols).sum()
+        }
+    }
 }
 
 impl Default for RGBMatrixConfig {
@@ -104,19 +537,224 @@ impl Default for RGBMatrixConfig {
             rows: 64,
             cols: 64,
             refresh_rate: 120,
+            refresh_rate_limit: None,
             pi_chip: None,
             pwm_bits: 11,
             pwm_lsb_nanoseconds: 130,
             slowdown: None,
+            cpu_affinity: None,
+            realtime_priority: None,
+            brightness: 100,
+            gamma: DEFAULT_GAMMA,
+            white_balance: WhiteBalance::NEUTRAL,
             interlaced: false,
             dither_bits: 0,
+            dithering: Dithering::Temporal,
             chain_length: 1,
+            panels: vec![],
             parallel: 1,
             panel_type: None,
             multiplexing: None,
             pixelmapper: vec![],
             row_setter: RowAddressSetterType::Direct,
             led_sequence: LedSequence::Rgb,
+            inverse_colors: false,
+            red_enabled: true,
+            green_enabled: true,
+            blue_enabled: true,
+            power_limit_ma: None,
+            power_profile: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_default() {
+        assert_eq!(RGBMatrixConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_invalid() {
+        let config = RGBMatrixConfig {
+            dither_bits: 3,
+            ..Default::default()
+        };
+        assert_eq!(config.validate(), Err(ConfigError::InvalidDitherBits(3)));
+
+        let config = RGBMatrixConfig {
+            refresh_rate_limit: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvalidRefreshRateLimit(0))
+        );
+
+        for pwm_lsb_nanoseconds in [0, 49, 3001] {
+            let config = RGBMatrixConfig {
+                pwm_lsb_nanoseconds,
+                ..Default::default()
+            };
+            assert_eq!(
+                config.validate(),
+                Err(ConfigError::InvalidPwmLsbNanoseconds(pwm_lsb_nanoseconds))
+            );
         }
+
+        let config = RGBMatrixConfig {
+            realtime_priority: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvalidRealtimePriority(0))
+        );
+
+        let config = RGBMatrixConfig {
+            pi_chip: Some(PiChip::BCM2708),
+            cpu_affinity: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvalidCpuAffinity { core: 1, cores: 1 })
+        );
+
+        let config = RGBMatrixConfig {
+            hardware_mapping: HardwareMapping::adafruit_hat(),
+            parallel: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::TooManyParallelChains(1))
+        );
+
+        // The stripe mapper halves the rows, which leaves an odd number of rows per panel.
+        let config = RGBMatrixConfig {
+            rows: 10,
+            multiplexing: Some(MultiplexMapperType::Stripe),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::IncompatibleMultiplexing {
+                multiplexing: MultiplexMapperType::Stripe,
+                rows: 10
+            })
+        );
+
+        let config = RGBMatrixConfig {
+            chain_length: 3,
+            pixelmapper: vec![NamedPixelMapperType::UMapper],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::PixelMapper(PixelMapperError::OddChainLength {
+                mapper: NamedPixelMapperType::UMapper,
+                chain: 3
+            }))
+        );
+
+        // Mappers are checked against the layout of the mapper before them.
+        let config = RGBMatrixConfig {
+            rows: 32,
+            pixelmapper: vec![
+                NamedPixelMapperType::Rotate(90),
+                NamedPixelMapperType::Scale(64),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::PixelMapper(
+                PixelMapperError::WidthNotDivisible {
+                    mapper: NamedPixelMapperType::Scale(64),
+                    width: 32,
+                    divisor: 64
+                }
+            ))
+        );
+
+        let config = RGBMatrixConfig {
+            power_limit_ma: Some(2000),
+            ..Default::default()
+        };
+        assert_eq!(config.validate(), Err(ConfigError::MissingPowerProfile));
+
+        let profile: PowerProfile = "1.5, 1, -1.25, 120".parse().unwrap();
+        let config = RGBMatrixConfig {
+            power_limit_ma: Some(2000),
+            power_profile: Some(profile),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvalidPowerProfile(profile))
+        );
+        assert!("1.5,1,1.25".parse::<PowerProfile>().is_err());
+    }
+
+    #[test]
+    fn test_validate_panels() {
+        let panels = vec!["64x32".parse().unwrap(), PanelSpec::new(32, 32)];
+        let config = RGBMatrixConfig {
+            rows: 32,
+            chain_length: 2,
+            panels: panels.clone(),
+            ..Default::default()
+        };
+        assert_eq!(config.validate(), Ok(()));
+        assert_eq!(config.chain_width(), 96);
+
+        let config = RGBMatrixConfig {
+            chain_length: 3,
+            ..config
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::PanelCountMismatch {
+                panels: 2,
+                chain_length: 3
+            })
+        );
+
+        let config = RGBMatrixConfig {
+            chain_length: 2,
+            panels: vec![PanelSpec::new(32, 64), PanelSpec::new(16, 32)],
+            ..config
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::InvalidPanel(PanelSpec::new(16, 32)))
+        );
+
+        let config = RGBMatrixConfig {
+            panels,
+            pixelmapper: vec![
+                NamedPixelMapperType::Mirror(true),
+                NamedPixelMapperType::UMapper,
+            ],
+            ..config
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::MixedPanelSizes(NamedPixelMapperType::UMapper))
+        );
+
+        assert_eq!(
+            "64X32".parse::<PanelSpec>().unwrap(),
+            PanelSpec::new(32, 64)
+        );
+        assert_eq!(PanelSpec::new(32, 64).to_string(), "64x32");
+        assert!("64".parse::<PanelSpec>().is_err());
+        assert!("64x".parse::<PanelSpec>().is_err());
     }
 }
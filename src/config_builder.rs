@@ -0,0 +1,284 @@
+use alloc::vec::Vec;
+
+use crate::{
+    config::ConfigError, Dithering, HardwareMapping, LedSequence, MultiplexMapperType,
+    NamedPixelMapperType, PanelSpec, PanelType, PiChip, PowerProfile, RGBMatrixConfig,
+    RowAddressSetterType, WhiteBalance,
+};
+
+/// Builder for an [`RGBMatrixConfig`] that checks the configuration when it is built. Unset options keep the
+/// values of [`RGBMatrixConfig::default`].
+///
+/// ```
+/// use rpi_led_panel::{NamedPixelMapperType, RGBMatrixConfig};
+///
+/// let config = RGBMatrixConfig::builder()
+///     .rows(32)
+///     .cols(64)
+///     .chain_length(2)
+///     .pixelmapper(NamedPixelMapperType::Rotate(90))
+///     .brightness(50)
+///     .build()
+///     .unwrap();
+/// assert_eq!(config.chain_length, 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RGBMatrixConfigBuilder {
+    config: RGBMatrixConfig,
+}
+
+impl RGBMatrixConfigBuilder {
+    /// Set the display wiring.
+    pub fn hardware_mapping(mut self, hardware_mapping: HardwareMapping) -> Self {
+        self.config.hardware_mapping = hardware_mapping;
+        self
+    }
+
+    /// Set the number of rows of a single panel.
+    pub fn rows(mut self, rows: usize) -> Self {
+        self.config.rows = rows;
+        self
+    }
+
+    /// Set the number of columns of a single panel.
+    pub fn cols(mut self, cols: usize) -> Self {
+        self.config.cols = cols;
+        self
+    }
+
+    /// Set the display refresh rate.
+    pub fn refresh_rate(mut self, refresh_rate: usize) -> Self {
+        self.config.refresh_rate = refresh_rate;
+        self
+    }
+
+    /// Cap the refresh rate, e.g. to save power.
+    pub fn refresh_rate_limit(mut self, limit: usize) -> Self {
+        self.config.refresh_rate_limit = Some(limit);
+        self
+    }
+
+    /// Set the Raspberry Pi chip model instead of determining it automatically.
+    pub fn pi_chip(mut self, pi_chip: PiChip) -> Self {
+        self.config.pi_chip = Some(pi_chip);
+        self
+    }
+
+    /// Set the number of bits used for PWM, from 1 to 11.
+    pub fn pwm_bits(mut self, pwm_bits: usize) -> Self {
+        self.config.pwm_bits = pwm_bits;
+        self
+    }
+
+    /// Set the on-time of the least significant PWM bit in nanoseconds.
+    pub fn pwm_lsb_nanoseconds(mut self, pwm_lsb_nanoseconds: u32) -> Self {
+        self.config.pwm_lsb_nanoseconds = pwm_lsb_nanoseconds;
+        self
+    }
+
+    /// Set the GPIO slowdown instead of determining it automatically.
+    pub fn slowdown(mut self, slowdown: u32) -> Self {
+        self.config.slowdown = Some(slowdown);
+        self
+    }
+
+    /// Pin the display update thread to the given CPU core instead of the last one.
+    pub fn cpu_affinity(mut self, core: usize) -> Self {
+        self.config.cpu_affinity = Some(core);
+        self
+    }
+
+    /// Run the display update thread with the real-time scheduling policy at the given priority, from 1 to 99.
+    pub fn realtime_priority(mut self, priority: u8) -> Self {
+        self.config.realtime_priority = Some(priority);
+        self
+    }
+
+    /// Set the initial brightness in percent, from 0 to 100.
+    pub fn brightness(mut self, brightness: u8) -> Self {
+        self.config.brightness = brightness;
+        self
+    }
+
+    /// Set the gamma value used to correct the perceived brightness.
+    pub fn gamma(mut self, gamma: f32) -> Self {
+        self.config.gamma = gamma;
+        self
+    }
+
+    /// Set the gains of the color channels that correct the white point of the panels.
+    pub fn white_balance(mut self, white_balance: WhiteBalance) -> Self {
+        self.config.white_balance = white_balance;
+        self
+    }
+
+    /// Set the white balance to the color of a black body at the given temperature in Kelvin.
+    pub fn color_temperature(mut self, kelvin: u32) -> Self {
+        self.config.set_color_temperature(kelvin);
+        self
+    }
+
+    /// Enable or disable the interlaced scan mode.
+    pub fn interlaced(mut self, interlaced: bool) -> Self {
+        self.config.interlaced = interlaced;
+        self
+    }
+
+    /// Set the number of bits below the PWM bits that are kept by temporal dithering.
+    pub fn dither_bits(mut self, dither_bits: usize) -> Self {
+        self.config.dither_bits = dither_bits;
+        self
+    }
+
+    /// Set how the rounding error of the PWM bits is distributed.
+    pub fn dithering(mut self, dithering: Dithering) -> Self {
+        self.config.dithering = dithering;
+        self
+    }
+
+    /// Set the number of daisy-chained panels.
+    pub fn chain_length(mut self, chain_length: usize) -> Self {
+        self.config.chain_length = chain_length;
+        self
+    }
+
+    /// Set the sizes of the chained panels if they differ, which also sets the chain length to their number.
+    pub fn panels(mut self, panels: Vec<PanelSpec>) -> Self {
+        self.config.chain_length = panels.len();
+        self.config.panels = panels;
+        self
+    }
+
+    /// Set the number of chains that run in parallel.
+    pub fn parallel(mut self, parallel: usize) -> Self {
+        self.config.parallel = parallel;
+        self
+    }
+
+    /// Set the panel type that needs a particular initialization sequence.
+    pub fn panel_type(mut self, panel_type: PanelType) -> Self {
+        self.config.panel_type = Some(panel_type);
+        self
+    }
+
+    /// Set the multiplexing mapper.
+    pub fn multiplexing(mut self, multiplexing: MultiplexMapperType) -> Self {
+        self.config.multiplexing = Some(multiplexing);
+        self
+    }
+
+    /// Add a pixel mapper. Pixel mappers are applied in the order they are added.
+    pub fn pixelmapper(mut self, pixelmapper: NamedPixelMapperType) -> Self {
+        self.config.pixelmapper.push(pixelmapper);
+        self
+    }
+
+    /// Set the row address setter.
+    pub fn row_setter(mut self, row_setter: RowAddressSetterType) -> Self {
+        self.config.row_setter = row_setter;
+        self
+    }
+
+    /// Set the LED sequence.
+    pub fn led_sequence(mut self, led_sequence: LedSequence) -> Self {
+        self.config.led_sequence = led_sequence;
+        self
+    }
+
+    /// Enable or disable inverting the color channels for common anode panels.
+    pub fn inverse_colors(mut self, inverse_colors: bool) -> Self {
+        self.config.inverse_colors = inverse_colors;
+        self
+    }
+
+    /// Enable or disable the output of the red, green and blue channels.
+    pub fn channels(mut self, red: bool, green: bool, blue: bool) -> Self {
+        self.config.red_enabled = red;
+        self.config.green_enabled = green;
+        self.config.blue_enabled = blue;
+        self
+    }
+
+    /// Limit the estimated current of the panels to a budget in milliamps, estimated with the given profile.
+    pub fn power_limit(mut self, milliamps: u32, profile: PowerProfile) -> Self {
+        self.config.power_limit_ma = Some(milliamps);
+        self.config.power_profile = Some(profile);
+        self
+    }
+
+    /// Check the configuration and return it if it is valid.
+    pub fn build(self) -> Result<RGBMatrixConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_build_valid() {
+        let config = RGBMatrixConfig::builder()
+            .hardware_mapping(HardwareMapping::regular())
+            .rows(32)
+            .cols(64)
+            .chain_length(3)
+            .parallel(2)
+            .pwm_bits(7)
+            .brightness(40)
+            .pixelmapper(NamedPixelMapperType::Mirror(true))
+            .pixelmapper(NamedPixelMapperType::Rotate(90))
+            .led_sequence(LedSequence::Bgr)
+            .build()
+            .unwrap();
+        assert_eq!(
+            config,
+            RGBMatrixConfig {
+                hardware_mapping: HardwareMapping::regular(),
+                rows: 32,
+                cols: 64,
+                chain_length: 3,
+                parallel: 2,
+                pwm_bits: 7,
+                brightness: 40,
+                pixelmapper: vec![
+                    NamedPixelMapperType::Mirror(true),
+                    NamedPixelMapperType::Rotate(90)
+                ],
+                led_sequence: LedSequence::Bgr,
+                ..Default::default()
+            }
+        );
+        assert_eq!(RGBMatrixConfig::builder().build(), Ok(Default::default()));
+    }
+
+    #[test]
+    fn test_build_invalid() {
+        let build = |builder: fn(RGBMatrixConfigBuilder) -> RGBMatrixConfigBuilder| {
+            builder(RGBMatrixConfig::builder()).build().unwrap_err()
+        };
+        assert_eq!(build(|b| b.rows(4)), ConfigError::InvalidRows(4));
+        assert_eq!(build(|b| b.rows(33)), ConfigError::InvalidRows(33));
+        assert_eq!(build(|b| b.rows(128)), ConfigError::InvalidRows(128));
+        assert_eq!(build(|b| b.cols(0)), ConfigError::InvalidCols(0));
+        assert_eq!(
+            build(|b| b.chain_length(0)),
+            ConfigError::InvalidChainLength(0)
+        );
+        assert_eq!(build(|b| b.parallel(0)), ConfigError::InvalidParallel(0));
+        assert_eq!(build(|b| b.pwm_bits(0)), ConfigError::InvalidPwmBits(0));
+        assert_eq!(build(|b| b.pwm_bits(12)), ConfigError::InvalidPwmBits(12));
+        assert_eq!(
+            build(|b| b.brightness(101)),
+            ConfigError::InvalidBrightness(101)
+        );
+        assert_eq!(build(|b| b.gamma(0.0)), ConfigError::InvalidGamma(0.0));
+        assert!(matches!(
+            build(|b| b.gamma(f32::NAN)),
+            ConfigError::InvalidGamma(gamma) if gamma.is_nan()
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+use alloc::{boxed::Box, format};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// The 4x4 Bayer threshold map, with each threshold in sixteenths.
+const BAYER_4X4: [[usize; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How the rounding error of colors that can't be displayed exactly with the PWM bits is distributed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Dithering {
+    /// Colors are rounded down to the PWM bits.
+    None,
+    /// The rounding error is distributed over successive frames, with the number of frames given by
+    /// `dither_bits`. Without dither bits, colors are rounded down like with [`Dithering::None`].
+    #[default]
+    Temporal,
+    /// The rounding error is distributed over neighboring pixels with a 4x4 Bayer threshold map, which keeps
+    /// every frame the same at the cost of a fine pattern on flat colors.
+    OrderedBayer,
+}
+
+impl FromStr for Dithering {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "temporal" => Ok(Self::Temporal),
+            "orderedbayer" => Ok(Self::OrderedBayer),
+            _ => Err(format!("'{s}' is not a valid dithering.").into()),
+        }
+    }
+}
+
+impl Display for Dithering {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Dithering::None => "None",
+            Dithering::Temporal => "Temporal",
+            Dithering::OrderedBayer => "OrderedBayer",
+        })
+    }
+}
+
+impl Dithering {
+    /// The number of frames over which the rounding error is distributed.
+    pub(crate) fn phases(&self, dither_bits: usize) -> usize {
+        match self {
+            Dithering::Temporal => 1 << dither_bits,
+            Dithering::None | Dithering::OrderedBayer => 1,
+        }
+    }
+
+    /// The threshold that is added to a level before the bits below `quantum` are dropped, for the given dither
+    /// phase and position on the matrix.
+    pub(crate) fn threshold(
+        &self,
+        quantum: usize,
+        phases: usize,
+        phase: usize,
+        [x, y]: [usize; 2],
+    ) -> u16 {
+        let (order, bits) = match self {
+            Dithering::None => return 0,
+            Dithering::Temporal => {
+                let bits = phases.trailing_zeros();
+                if bits == 0 {
+                    return 0;
+                }
+                // Alternate between low and high thresholds, so that pixels don't fade in and out over the
+                // phases.
+                (phase.reverse_bits() >> (usize::BITS - bits), bits)
+            }
+            Dithering::OrderedBayer => (BAYER_4X4[y % 4][x % 4], 4),
+        };
+        ((order * quantum) >> bits) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for dithering in [
+            Dithering::None,
+            Dithering::Temporal,
+            Dithering::OrderedBayer,
+        ] {
+            assert_eq!(
+                dithering.to_string().parse::<Dithering>().unwrap(),
+                dithering
+            );
+        }
+        assert_eq!(
+            "orderedbayer".parse::<Dithering>().unwrap(),
+            Dithering::OrderedBayer
+        );
+        assert!("bayer".parse::<Dithering>().is_err());
+    }
+
+    #[test]
+    fn test_thresholds() {
+        assert_eq!(Dithering::None.threshold(128, 1, 0, [1, 2]), 0);
+        let temporal = (0..4)
+            .map(|phase| Dithering::Temporal.threshold(128, 4, phase, [0, 0]))
+            .collect::<Vec<_>>();
+        assert_eq!(temporal, [0, 64, 32, 96]);
+        // Every threshold of the Bayer map is used once in each 4x4 block.
+        let mut bayer = (0..16)
+            .map(|i| Dithering::OrderedBayer.threshold(16, 1, 0, [4 + i % 4, 8 + i / 4]))
+            .collect::<Vec<_>>();
+        bayer.sort();
+        assert_eq!(bayer, (0..16).collect::<Vec<_>>());
+    }
+}
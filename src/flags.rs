@@ -0,0 +1,374 @@
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use crate::{
+    HardwareMapping, LedSequence, MultiplexMapperType, NamedPixelMapperType, PanelType,
+    RGBMatrixConfig, RowAddressSetterType,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlagError {
+    /// The argument is not one of the `--led-*` flags of the original library.
+    UnknownFlag(String),
+    /// The flag of the original library has no equivalent in this crate.
+    UnsupportedFlag(String),
+    /// The flag was the last argument, without a value.
+    MissingValue(String),
+    /// The value of the flag could not be parsed.
+    InvalidValue {
+        flag: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl Error for FlagError {}
+
+impl Display for FlagError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FlagError::UnknownFlag(flag) => write!(f, "Unknown flag '{flag}'."),
+            FlagError::UnsupportedFlag(flag) => write!(f, "The flag '{flag}' is not supported."),
+            FlagError::MissingValue(flag) => write!(f, "The flag '{flag}' requires a value."),
+            FlagError::InvalidValue {
+                flag,
+                value,
+                reason,
+            } => write!(f, "Invalid value '{value}' for '{flag}': {reason}"),
+        }
+    }
+}
+
+type FlagSetter = fn(&mut RGBMatrixConfig, &str) -> Result<(), Box<dyn Error>>;
+
+/// The flags of the original library that take a value, and how they change the config.
+const FLAGS: [(&str, FlagSetter); 17] = [
+    ("--led-gpio-mapping", |config, value| {
+        config.hardware_mapping = parse_gpio_mapping(value)?;
+        Ok(())
+    }),
+    ("--led-rows", |config, value| {
+        config.rows = value.parse()?;
+        Ok(())
+    }),
+    ("--led-cols", |config, value| {
+        config.cols = value.parse()?;
+        Ok(())
+    }),
+    ("--led-chain", |config, value| {
+        config.chain_length = value.parse()?;
+        Ok(())
+    }),
+    ("--led-parallel", |config, value| {
+        config.parallel = value.parse()?;
+        Ok(())
+    }),
+    ("--led-multiplexing", |config, value| {
+        config.multiplexing = parse_multiplexing(value)?;
+        Ok(())
+    }),
+    ("--led-pixel-mapper", |config, value| {
+        config.pixelmapper = NamedPixelMapperType::parse_chain(value)?;
+        Ok(())
+    }),
+    ("--led-pwm-bits", |config, value| {
+        config.pwm_bits = value.parse()?;
+        Ok(())
+    }),
+    ("--led-brightness", |config, value| {
+        config.brightness = value.parse()?;
+        Ok(())
+    }),
+    ("--led-scan-mode", |config, value| {
+        config.interlaced = parse_scan_mode(value)?;
+        Ok(())
+    }),
+    ("--led-row-addr-type", |config, value| {
+        config.row_setter = parse_row_address_type(value)?;
+        Ok(())
+    }),
+    ("--led-limit-refresh", |config, value| {
+        // Like in the original library, 0 doesn't limit the refresh rate.
+        config.refresh_rate_limit = match value.parse()? {
+            0 => None,
+            limit => Some(limit),
+        };
+        Ok(())
+    }),
+    ("--led-rgb-sequence", |config, value| {
+        config.led_sequence = value.parse::<LedSequence>()?;
+        Ok(())
+    }),
+    ("--led-pwm-lsb-nanoseconds", |config, value| {
+        config.pwm_lsb_nanoseconds = value.parse()?;
+        Ok(())
+    }),
+    ("--led-pwm-dither-bits", |config, value| {
+        config.dither_bits = value.parse()?;
+        Ok(())
+    }),
+    ("--led-panel-type", |config, value| {
+        config.panel_type = parse_panel_type(value)?;
+        Ok(())
+    }),
+    ("--led-slowdown-gpio", |config, value| {
+        config.slowdown = Some(parse_slowdown(value)?);
+        Ok(())
+    }),
+];
+
+type SwitchSetter = fn(&mut RGBMatrixConfig);
+
+/// The flags of the original library that don't take a value.
+const SWITCHES: [(&str, SwitchSetter); 2] = [
+    ("--led-inverse", |config| config.inverse_colors = true),
+    ("--led-no-inverse", |config| config.inverse_colors = false),
+];
+
+/// Flags of the original library without an equivalent, e.g. because this crate is not a daemon and does not
+/// drop privileges itself.
+const UNSUPPORTED_FLAGS: [&str; 10] = [
+    "--led-show-refresh",
+    "--led-no-show-refresh",
+    "--led-hardware-pulse",
+    "--led-no-hardware-pulse",
+    "--led-daemon",
+    "--led-no-daemon",
+    "--led-drop-privs",
+    "--led-no-drop-privs",
+    "--led-drop-priv-user",
+    "--led-drop-priv-group",
+];
+
+/// The names of the `--led-gpio-mapping` option of the original library.
+const GPIO_MAPPING_NAMES: [(&str, HardwareMapping); 6] = [
+    ("regular", HardwareMapping::REGULAR),
+    ("adafruit-hat", HardwareMapping::ADAFRUIT_HAT),
+    ("adafruit-hat-pwm", HardwareMapping::ADAFRUIT_HAT_PWM),
+    ("regular-pi1", HardwareMapping::REGULAR_PI1),
+    ("classic", HardwareMapping::CLASSIC),
+    ("classic-pi1", HardwareMapping::CLASSIC_PI1),
+];
+
+/// The multiplexing mappers in the order of the `--led-multiplexing` numbers of the original library, starting
+/// at 1. 0 is a direct mapping.
+const MULTIPLEXING_NUMBERS: [MultiplexMapperType; 18] = [
+    MultiplexMapperType::Stripe,
+    MultiplexMapperType::Checkered,
+    MultiplexMapperType::Spiral,
+    MultiplexMapperType::ZStripe08,
+    MultiplexMapperType::ZStripe44,
+    MultiplexMapperType::Coreman,
+    MultiplexMapperType::Kaler2Scan,
+    MultiplexMapperType::ZStripe80,
+    MultiplexMapperType::P10Z,
+    MultiplexMapperType::QiangLiQ8,
+    MultiplexMapperType::InversedZStripe,
+    MultiplexMapperType::P10Outdoor1R1G1B1,
+    MultiplexMapperType::P10Outdoor1R1G1B2,
+    MultiplexMapperType::P10Outdoor1R1G1B3,
+    MultiplexMapperType::P10Coreman,
+    MultiplexMapperType::P8Outdoor1R1G1B,
+    MultiplexMapperType::FlippedStripe,
+    MultiplexMapperType::P10Outdoor32x16HalfScan,
+];
+
+pub(crate) fn parse_gpio_mapping(value: &str) -> Result<HardwareMapping, Box<dyn Error>> {
+    match GPIO_MAPPING_NAMES.iter().find(|(name, _)| *name == value) {
+        Some((_, mapping)) => Ok(*mapping),
+        None => value.parse(),
+    }
+}
+
+pub(crate) fn parse_multiplexing(
+    value: &str,
+) -> Result<Option<MultiplexMapperType>, Box<dyn Error>> {
+    match value.parse::<usize>() {
+        Ok(0) => Ok(None),
+        Ok(number) => MULTIPLEXING_NUMBERS
+            .get(number - 1)
+            .copied()
+            .map(Some)
+            .ok_or_else(|| {
+                let max = MULTIPLEXING_NUMBERS.len();
+                format!("The multiplexing must be between 0 and {max}.").into()
+            }),
+        Err(_) => Ok(Some(value.parse()?)),
+    }
+}
+
+pub(crate) fn parse_scan_mode(value: &str) -> Result<bool, Box<dyn Error>> {
+    match value {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err("The scan mode is 0 for progressive or 1 for interlaced.".into()),
+    }
+}
+
+pub(crate) fn parse_row_address_type(value: &str) -> Result<RowAddressSetterType, Box<dyn Error>> {
+    match value {
+        "0" => Ok(RowAddressSetterType::Direct),
+        "1" => Ok(RowAddressSetterType::ShiftRegister),
+        "2" => Ok(RowAddressSetterType::DirectABCDLine),
+        "3" => Ok(RowAddressSetterType::ABCShiftRegister),
+        "4" => Ok(RowAddressSetterType::SM5266),
+        _ => value.parse(),
+    }
+}
+
+pub(crate) fn parse_panel_type(value: &str) -> Result<Option<PanelType>, Box<dyn Error>> {
+    match value {
+        "" => Ok(None),
+        value => Ok(Some(value.parse()?)),
+    }
+}
+
+pub(crate) fn parse_slowdown(value: &str) -> Result<u32, Box<dyn Error>> {
+    // The original library uses -1 for no slowdown on all models.
+    match value.parse::<i32>()? {
+        -1 => Ok(0),
+        slowdown => Ok(u32::try_from(slowdown)?),
+    }
+}
+
+impl RGBMatrixConfig {
+    /// Create a config from the `--led-*` flags of hzeller's rpi-rgb-led-matrix library, e.g.
+    /// `--led-rows=32 --led-chain 2 --led-gpio-mapping=adafruit-hat`. Values can follow the flag after a `=` or
+    /// as the next argument. Options that are not given keep their [default](RGBMatrixConfig::default) values.
+    ///
+    /// The arguments must not include the program name, e.g. pass `std::env::args().skip(1)`. Every argument
+    /// must be one of the flags, and flags of the original library without an equivalent in this crate, like
+    /// `--led-daemon`, are rejected. The config is not validated, which is done by
+    /// [`RGBMatrixConfig::validate`] and when creating the matrix.
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<Self, FlagError> {
+        let mut config = Self::default();
+        while let Some(arg) = args.next() {
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            if UNSUPPORTED_FLAGS.contains(&flag.as_str()) {
+                return Err(FlagError::UnsupportedFlag(flag));
+            }
+            if let (Some((_, setter)), None) =
+                (SWITCHES.iter().find(|(name, _)| *name == flag), &value)
+            {
+                setter(&mut config);
+                continue;
+            }
+            let Some((_, setter)) = FLAGS.iter().find(|(name, _)| *name == flag) else {
+                return Err(FlagError::UnknownFlag(flag));
+            };
+            let value = match value.or_else(|| args.next()) {
+                Some(value) => value,
+                None => return Err(FlagError::MissingValue(flag)),
+            };
+            setter(&mut config, &value).map_err(|error| FlagError::InvalidValue {
+                flag,
+                value,
+                reason: error.to_string(),
+            })?;
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn from_args(args: &str) -> Result<RGBMatrixConfig, FlagError> {
+        RGBMatrixConfig::from_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_from_args() {
+        let config = from_args(
+            "--led-rows=32 --led-cols 64 --led-chain=2 --led-parallel=2 --led-pwm-bits=8 \
+             --led-brightness=50 --led-gpio-mapping=regular --led-multiplexing=1 \
+             --led-pixel-mapper=U-mapper;Rotate:90 --led-scan-mode=1 --led-row-addr-type=4 \
+             --led-limit-refresh=90 --led-rgb-sequence=GBR --led-pwm-lsb-nanoseconds=200 \
+             --led-pwm-dither-bits=1 --led-panel-type=FM6126A --led-slowdown-gpio=-1 --led-inverse",
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            RGBMatrixConfig {
+                hardware_mapping: HardwareMapping::REGULAR,
+                rows: 32,
+                cols: 64,
+                refresh_rate_limit: Some(90),
+                pwm_bits: 8,
+                pwm_lsb_nanoseconds: 200,
+                slowdown: Some(0),
+                brightness: 50,
+                interlaced: true,
+                dither_bits: 1,
+                chain_length: 2,
+                parallel: 2,
+                panel_type: Some(PanelType::FM6126),
+                multiplexing: Some(MultiplexMapperType::Stripe),
+                pixelmapper: vec![
+                    NamedPixelMapperType::UMapper,
+                    NamedPixelMapperType::Rotate(90)
+                ],
+                row_setter: RowAddressSetterType::SM5266,
+                led_sequence: LedSequence::Gbr,
+                inverse_colors: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(config.validate(), Ok(()));
+
+        assert_eq!(from_args("").unwrap(), RGBMatrixConfig::default());
+        let config = from_args("--led-multiplexing=8 --led-limit-refresh=0").unwrap();
+        assert_eq!(config.multiplexing, Some(MultiplexMapperType::ZStripe80));
+        assert_eq!(config.refresh_rate_limit, None);
+    }
+
+    #[test]
+    fn test_from_args_malformed() {
+        assert_eq!(
+            from_args("--led-rows=32 --led-colums=64"),
+            Err(FlagError::UnknownFlag("--led-colums".into()))
+        );
+        assert_eq!(
+            from_args("image.ppm"),
+            Err(FlagError::UnknownFlag("image.ppm".into()))
+        );
+        assert_eq!(
+            from_args("--led-no-hardware-pulse"),
+            Err(FlagError::UnsupportedFlag("--led-no-hardware-pulse".into()))
+        );
+        assert_eq!(
+            from_args("--led-rows"),
+            Err(FlagError::MissingValue("--led-rows".into()))
+        );
+        for args in [
+            "--led-rows=many",
+            "--led-multiplexing=19",
+            "--led-scan-mode=2",
+            "--led-gpio-mapping=compute-module",
+            "--led-slowdown-gpio=-2",
+        ] {
+            let error = from_args(args).unwrap_err();
+            assert!(
+                matches!(error, FlagError::InvalidValue { .. }),
+                "{args}: {error}"
+            );
+        }
+        assert_eq!(
+            from_args("--led-scan-mode 2").unwrap_err().to_string(),
+            "Invalid value '2' for '--led-scan-mode': The scan mode is 0 for progressive or 1 for interlaced."
+        );
+    }
+}
@@ -0,0 +1,533 @@
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::{Canvas, Color};
+
+/// Codepoint of the glyph that is drawn in place of characters that are missing from a font.
+const REPLACEMENT_CHARACTER: u32 = 0xFFFD;
+
+/// The digits 0 to 9 in 3x5 pixels, as rows from top to bottom with the leftmost pixel in bit 2.
+const DIGITS_3X5: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// The horizontal distance between the digits of [`DIGITS_3X5`], including a column of spacing.
+const DIGIT_ADVANCE: i32 = 4;
+
+#[derive(Debug)]
+pub enum FontError {
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    InvalidUtf8,
+    InvalidLine {
+        line: usize,
+        content: String,
+    },
+    MissingBoundingBox,
+}
+
+impl Error for FontError {}
+
+impl Display for FontError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            FontError::Io(error) => write!(f, "Failed to read the font: {error}."),
+            FontError::InvalidUtf8 => write!(f, "The font is not valid UTF-8."),
+            FontError::InvalidLine { line, content } => {
+                write!(f, "Invalid line {line} in font: '{content}'.")
+            }
+            FontError::MissingBoundingBox => {
+                write!(f, "The font does not have a FONTBOUNDINGBOX.")
+            }
+        }
+    }
+}
+
+/// A single character of a [`Font`].
+#[derive(Debug, Clone)]
+pub struct Glyph {
+    /// Horizontal distance to the origin of the next glyph.
+    advance: i32,
+    /// Bounding box as `[width, height, x_offset, y_offset]`, with the offsets relative to the origin on the
+    /// baseline.
+    bounding_box: [i32; 4],
+    /// Rows of the bitmap from top to bottom, with the leftmost pixel in the most significant bit of the first
+    /// byte.
+    bitmap: Vec<Vec<u8>>,
+}
+
+impl Glyph {
+    /// Horizontal distance from the origin of this glyph to the origin of the next one.
+    pub fn advance(&self) -> i32 {
+        self.advance
+    }
+
+    /// Bounding box of the bitmap as `[width, height, x_offset, y_offset]`. The offsets locate the bottom left
+    /// corner of the bitmap relative to the glyph origin on the baseline, with positive `y_offset` going up.
+    pub fn bounding_box(&self) -> [i32; 4] {
+        self.bounding_box
+    }
+
+    /// Whether the bitmap pixel at (`x`, `y`) is set, counted from the top left corner of the bounding box.
+    /// Pixels outside of the bounding box are not set.
+    pub fn is_pixel_set(&self, x: usize, y: usize) -> bool {
+        self.bitmap
+            .get(y)
+            .and_then(|row| row.get(x / 8))
+            .is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0)
+    }
+}
+
+/// A bitmap font in the Glyph Bitmap Distribution Format (BDF), as used by the fonts that come with hzeller's
+/// rpi-rgb-led-matrix library.
+#[derive(Debug, Clone)]
+pub struct Font {
+    glyphs: BTreeMap<u32, Glyph>,
+    /// Font bounding box as `[width, height, x_offset, y_offset]`.
+    bounding_box: [i32; 4],
+}
+
+impl Font {
+    /// Load a font from a BDF file.
+    #[cfg(feature = "std")]
+    pub fn from_bdf_file(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        let bytes = std::fs::read(path).map_err(FontError::Io)?;
+        Self::from_bdf_bytes(&bytes)
+    }
+
+    /// Parse a font from the contents of a BDF file.
+    pub fn from_bdf_bytes(bytes: &[u8]) -> Result<Self, FontError> {
+        let text = core::str::from_utf8(bytes).map_err(|_| FontError::InvalidUtf8)?;
+
+        let mut bounding_box = None;
+        let mut glyphs = BTreeMap::new();
+        let mut encoding = None;
+        let mut glyph = None;
+        let mut in_bitmap = false;
+
+        for (index, line) in text.lines().enumerate() {
+            let invalid_line = || FontError::InvalidLine {
+                line: index + 1,
+                content: line.to_string(),
+            };
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+
+            if in_bitmap {
+                if keyword == "ENDCHAR" {
+                    in_bitmap = false;
+                    if let (Some(encoding), Some(glyph)) = (encoding.take(), glyph.take()) {
+                        glyphs.insert(encoding, glyph);
+                    }
+                } else {
+                    let glyph: &mut Glyph = glyph.as_mut().ok_or_else(invalid_line)?;
+                    glyph
+                        .bitmap
+                        .push(parse_bitmap_row(keyword).ok_or_else(invalid_line)?);
+                }
+                continue;
+            }
+
+            match keyword {
+                "FONTBOUNDINGBOX" => {
+                    bounding_box = Some(parse_numbers(words).ok_or_else(invalid_line)?);
+                }
+                "STARTCHAR" => {
+                    let bounding_box = bounding_box.ok_or(FontError::MissingBoundingBox)?;
+                    encoding = None;
+                    glyph = Some(Glyph {
+                        advance: bounding_box[0],
+                        bounding_box,
+                        bitmap: Vec::new(),
+                    });
+                }
+                "ENCODING" => {
+                    // Glyphs that are not in the standard encoding have an encoding of -1 and are skipped.
+                    let [value] = parse_numbers(words).ok_or_else(invalid_line)?;
+                    encoding = u32::try_from(value).ok();
+                }
+                "DWIDTH" => {
+                    let [advance, _] = parse_numbers(words).ok_or_else(invalid_line)?;
+                    glyph.as_mut().ok_or_else(invalid_line)?.advance = advance;
+                }
+                "BBX" => {
+                    let glyph = glyph.as_mut().ok_or_else(invalid_line)?;
+                    glyph.bounding_box = parse_numbers(words).ok_or_else(invalid_line)?;
+                    if glyph.bounding_box[0] < 0 || glyph.bounding_box[1] < 0 {
+                        return Err(invalid_line());
+                    }
+                }
+                "BITMAP" => {
+                    glyph.as_ref().ok_or_else(invalid_line)?;
+                    in_bitmap = true;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            glyphs,
+            bounding_box: bounding_box.ok_or(FontError::MissingBoundingBox)?,
+        })
+    }
+
+    /// Height of the font bounding box in pixels.
+    pub fn height(&self) -> i32 {
+        self.bounding_box[1]
+    }
+
+    /// Distance from the baseline to the top of the font bounding box in pixels.
+    pub fn baseline(&self) -> i32 {
+        self.bounding_box[1] + self.bounding_box[3]
+    }
+
+    /// The glyph for a character, or `None` if the font doesn't contain it.
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs.get(&(character as u32))
+    }
+
+    /// The glyph for a character, or the replacement character glyph if the font doesn't contain it.
+    fn glyph_or_replacement(&self, character: char) -> Option<&Glyph> {
+        self.glyph(character)
+            .or_else(|| self.glyphs.get(&REPLACEMENT_CHARACTER))
+    }
+}
+
+fn parse_numbers<'a, const N: usize>(mut words: impl Iterator<Item = &'a str>) -> Option<[i32; N]> {
+    let mut numbers = [0; N];
+    for number in numbers.iter_mut() {
+        *number = words.next()?.parse().ok()?;
+    }
+    Some(numbers)
+}
+
+fn parse_bitmap_row(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+impl Canvas {
+    /// Draw a string with its first glyph's origin at (`x`, `y`), where `y` is the baseline of the text.
+    /// Characters that are missing from the font are drawn as the replacement character if the font has one and
+    /// are skipped otherwise. Parts of the text that are outside of the canvas are discarded.
+    ///
+    /// Returns the horizontal advance of the whole string, so scrolling text can be animated by drawing it at a
+    /// decreasing `x` every frame until `x` is smaller than the negative advance.
+    pub fn draw_text(&mut self, font: &Font, x: i32, y: i32, color: Color, text: &str) -> i32 {
+        let [x, y] = [x, y].map(i64::from);
+        let mut advance = 0;
+        for glyph in text.chars().filter_map(|c| font.glyph_or_replacement(c)) {
+            let [width, height, x_offset, y_offset] = glyph.bounding_box.map(i64::from);
+            let left = x + advance + x_offset;
+            let top = y - y_offset - height + 1;
+            // Skip glyphs that are completely outside of the canvas.
+            if left + width > 0 && left < self.width() as i64 {
+                for row in 0..height {
+                    for column in 0..width {
+                        if glyph.is_pixel_set(column as usize, row as usize) {
+                            self.set_pixel_clipped(left + column, top + row, color);
+                        }
+                    }
+                }
+            }
+            advance += i64::from(glyph.advance);
+        }
+        advance as i32
+    }
+
+    /// Draw a number in decimal with a built-in 3x5 pixel font, with the top left corner of the first digit at
+    /// (`x`, `y`). This needs no [`Font`], e.g. to show a counter while debugging. Parts of the number that are
+    /// outside of the canvas are discarded.
+    ///
+    /// Returns the width of the number in pixels.
+    pub fn draw_number(&mut self, x: i32, y: i32, value: u32, color: Color) -> i32 {
+        let digits = value.checked_ilog10().unwrap_or(0) + 1;
+        let [x, y] = [x, y].map(i64::from);
+        for position in 0..digits {
+            let digit = value / 10u32.pow(digits - 1 - position) % 10;
+            let left = x + i64::from(DIGIT_ADVANCE) * i64::from(position);
+            for (row, bits) in DIGITS_3X5[digit as usize].iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) != 0 {
+                        self.set_pixel_clipped(left + column, y + row as i64, color);
+                    }
+                }
+            }
+        }
+        DIGIT_ADVANCE * digits as i32 - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RGBMatrixConfig;
+
+    const TEST_FONT: &str = "\
+STARTFONT 2.1
+FONT -Misc-Fixed-Medium-R-Normal--7-70-75-75-C-50-ISO10646-1
+SIZE 7 75 75
+FONTBOUNDINGBOX 5 7 0 -1
+STARTPROPERTIES 2
+FONT_ASCENT 6
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 4
+STARTCHAR space
+ENCODING 32
+SWIDTH 686 0
+DWIDTH 5 0
+BBX 5 7 0 -1
+BITMAP
+00
+00
+00
+00
+00
+00
+00
+ENDCHAR
+STARTCHAR A
+ENCODING 65
+SWIDTH 686 0
+DWIDTH 5 0
+BBX 5 7 0 -1
+BITMAP
+20
+50
+88
+88
+F8
+88
+00
+ENDCHAR
+STARTCHAR i
+ENCODING 105
+SWIDTH 411 0
+DWIDTH 3 0
+BBX 1 6 1 0
+BITMAP
+80
+00
+80
+80
+80
+80
+ENDCHAR
+STARTCHAR uni0000
+ENCODING -1
+DWIDTH 5 0
+BBX 5 7 0 -1
+BITMAP
+F8
+F8
+F8
+F8
+F8
+F8
+F8
+ENDCHAR
+ENDFONT
+";
+
+    fn test_font() -> Font {
+        Font::from_bdf_bytes(TEST_FONT.as_bytes()).unwrap()
+    }
+
+    fn test_canvas() -> Canvas {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            ..Default::default()
+        };
+        Canvas::new_for_test(&config)
+    }
+
+    #[test]
+    fn test_parse_font() {
+        let font = test_font();
+        assert_eq!(font.height(), 7);
+        assert_eq!(font.baseline(), 6);
+        // The unencoded glyph is skipped.
+        assert_eq!(font.glyphs.len(), 3);
+    }
+
+    #[test]
+    fn test_draw_text_advance() {
+        let font = test_font();
+        let mut canvas = test_canvas();
+        let advance = canvas.draw_text(&font, 0, 6, Color::WHITE, "Ai A");
+        assert_eq!(advance, 5 + 3 + 5 + 5);
+        // Missing glyphs without a replacement glyph are skipped.
+        let advance = canvas.draw_text(&font, 0, 6, Color::WHITE, "AxA");
+        assert_eq!(advance, 5 + 5);
+    }
+
+    #[test]
+    fn test_draw_text() {
+        let font = test_font();
+        let mut canvas = test_canvas();
+        canvas.draw_text(&font, 1, 7, Color::WHITE, "i");
+        // The glyph starts one pixel to the right of the origin and sits on the baseline.
+        assert_eq!(
+            canvas.lit_pixels(),
+            [[2, 2], [2, 4], [2, 5], [2, 6], [2, 7]]
+        );
+    }
+
+    #[test]
+    fn test_draw_text_clipped() {
+        let font = test_font();
+        let mut canvas = test_canvas();
+        let advance = canvas.draw_text(&font, -2, 6, Color::WHITE, "A");
+        assert_eq!(advance, 5);
+        assert_eq!(
+            canvas.lit_pixels(),
+            [
+                [0, 1],
+                [1, 2],
+                [2, 3],
+                [2, 4],
+                [0, 5],
+                [1, 5],
+                [2, 5],
+                [2, 6]
+            ]
+        );
+
+        let mut canvas = test_canvas();
+        canvas.draw_text(&font, 29, 6, Color::WHITE, "AA");
+        assert_eq!(
+            canvas.lit_pixels(),
+            [
+                [31, 1],
+                [30, 2],
+                [29, 3],
+                [29, 4],
+                [29, 5],
+                [30, 5],
+                [31, 5],
+                [29, 6]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_glyph_bitmap() {
+        let font = test_font();
+        let glyph = font.glyph('A').unwrap();
+        assert_eq!(glyph.advance(), 5);
+        assert_eq!(glyph.bounding_box(), [5, 7, 0, -1]);
+        assert_eq!(
+            glyph.bitmap,
+            [[0x20], [0x50], [0x88], [0x88], [0xF8], [0x88], [0x00]]
+        );
+        let rows: Vec<String> = (0..7)
+            .map(|y| {
+                (0..5)
+                    .map(|x| if glyph.is_pixel_set(x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            ["..#..", ".#.#.", "#...#", "#...#", "#####", "#...#", "....."]
+        );
+        assert!(!glyph.is_pixel_set(5, 0));
+        assert!(!glyph.is_pixel_set(0, 7));
+
+        let glyph = font.glyph('i').unwrap();
+        assert_eq!(glyph.advance(), 3);
+        assert_eq!(glyph.bounding_box(), [1, 6, 1, 0]);
+        assert_eq!(
+            glyph.bitmap,
+            [[0x80], [0x00], [0x80], [0x80], [0x80], [0x80]]
+        );
+    }
+
+    #[test]
+    fn test_missing_glyph() {
+        let font = test_font();
+        assert!(font.glyph('x').is_none());
+        assert!(font.glyph('\u{FFFD}').is_none());
+    }
+
+    #[test]
+    fn test_draw_number() {
+        let mut canvas = test_canvas();
+        let width = canvas.draw_number(1, 2, 42, Color::WHITE);
+        assert_eq!(width, 7);
+        #[rustfmt::skip]
+        let expected = [
+            [1, 2], [3, 2], [5, 2], [6, 2], [7, 2],
+            [1, 3], [3, 3], [7, 3],
+            [1, 4], [2, 4], [3, 4], [5, 4], [6, 4], [7, 4],
+            [3, 5], [5, 5],
+            [3, 6], [5, 6], [6, 6], [7, 6],
+        ];
+        assert_eq!(canvas.lit_pixels(), expected);
+
+        let mut canvas = test_canvas();
+        assert_eq!(canvas.draw_number(30, 0, 0, Color::WHITE), 3);
+        assert_eq!(canvas.lit_pixels().len(), 7);
+        assert_eq!(canvas.draw_number(0, 0, u32::MAX, Color::WHITE), 39);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_bdf_file() {
+        let path =
+            std::env::temp_dir().join(format!("rpi_led_panel_test_{}.bdf", std::process::id()));
+        std::fs::write(&path, TEST_FONT).unwrap();
+        let font = Font::from_bdf_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(font.unwrap().glyph('A').unwrap().advance(), 5);
+
+        assert!(matches!(
+            Font::from_bdf_file("/nonexistent/font.bdf"),
+            Err(FontError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_font() {
+        assert!(matches!(
+            Font::from_bdf_bytes(b"STARTFONT 2.1\nSTARTCHAR A\n"),
+            Err(FontError::MissingBoundingBox)
+        ));
+        assert!(matches!(
+            Font::from_bdf_bytes(b"FONTBOUNDINGBOX 5 7 0 -1\nSTARTCHAR A\nBBX 5 x 0 0\n"),
+            Err(FontError::InvalidLine { line: 3, content }) if content == "BBX 5 x 0 0"
+        ));
+        assert!(matches!(
+            Font::from_bdf_bytes(&[0xff, 0xfe]),
+            Err(FontError::InvalidUtf8)
+        ));
+    }
+}
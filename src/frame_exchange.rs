@@ -0,0 +1,333 @@
+use std::{
+    mem::replace,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, TryRecvError},
+        Arc, Condvar, Mutex,
+    },
+};
+
+use crate::{utils::FrameRateMonitor, Canvas, Clock, Color, FrameStats};
+
+/// A callback that is run by the update thread after every frame.
+pub(crate) type FrameCallback = Box<dyn FnMut() + Send>;
+
+#[derive(Default)]
+struct ExchangeState {
+    /// The canvas that is displayed from the start of the next frame.
+    pending: Option<Box<Canvas>>,
+    /// Canvases that are no longer displayed and can be handed out for drawing. They are kept in the boxes they
+    /// are handed out in.
+    #[allow(clippy::vec_box)]
+    released: Vec<Box<Canvas>>,
+    /// Whether the update thread has stopped.
+    closed: bool,
+}
+
+/// Hands canvases between the user and the update thread. The user queues a canvas, which the update thread
+/// picks up at the start of its next frame, releasing the canvas it displayed before.
+pub(crate) struct FrameExchange {
+    state: Mutex<ExchangeState>,
+    /// Notified whenever the update thread picked up a canvas or stopped.
+    picked_up: Condvar,
+    /// The canvas that is output by the update thread. It is locked separately, so that queueing a canvas does
+    /// not wait for the output of a frame.
+    displayed: Mutex<Box<Canvas>>,
+    callbacks: Mutex<Vec<FrameCallback>>,
+    /// Measures the rate at which frames are output.
+    frame_rate_monitor: Mutex<FrameRateMonitor>,
+    /// The clock that the frame rate is measured with.
+    clock: Arc<dyn Clock>,
+    /// The statistics of the last frame.
+    frame_stats: Mutex<FrameStats>,
+    /// Whether the displayed canvas is output. Otherwise the display is blank.
+    enabled: AtomicBool,
+    /// The ticks that queued canvases are picked up on, instead of at the start of every frame.
+    external_vsync: Mutex<Option<Receiver<()>>>,
+}
+
+impl FrameExchange {
+    pub(crate) fn new(displayed: Box<Canvas>, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            state: Mutex::default(),
+            picked_up: Condvar::new(),
+            displayed: Mutex::new(displayed),
+            callbacks: Mutex::default(),
+            frame_rate_monitor: Mutex::new(FrameRateMonitor::new()),
+            clock,
+            frame_stats: Mutex::default(),
+            enabled: AtomicBool::new(true),
+            external_vsync: Mutex::default(),
+        }
+    }
+
+    /// Queue a canvas without waiting for the update thread. Returns a canvas that is not displayed, which is
+    /// either a queued canvas that was never displayed, a released canvas or `None` if there is no free canvas.
+    pub(crate) fn swap(&self, canvas: Box<Canvas>) -> Option<Box<Canvas>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(skipped) = state.pending.replace(canvas) {
+            return Some(skipped);
+        }
+        state.released.pop()
+    }
+
+    /// Queue a canvas without waiting for the update thread. A previously queued canvas that was not picked up
+    /// yet is released without being displayed, which is returned as `true`.
+    pub(crate) fn submit(&self, canvas: Box<Canvas>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(skipped) = state.pending.replace(canvas) else {
+            return false;
+        };
+        state.released.push(skipped);
+        true
+    }
+
+    /// Take a canvas that is no longer displayed, if there is one.
+    pub(crate) fn take_released(&self) -> Option<Box<Canvas>> {
+        self.state.lock().unwrap().released.pop()
+    }
+
+    /// Queue a canvas and wait until the update thread picked it up. Returns the canvas that was displayed
+    /// before, or the queued canvas itself if the update thread has stopped.
+    pub(crate) fn swap_blocking(&self, canvas: Box<Canvas>) -> Box<Canvas> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(skipped) = state.pending.replace(canvas) {
+            state.released.push(skipped);
+        }
+        // Canvases that were released earlier are kept for `swap`.
+        let released_before = state.released.len();
+        loop {
+            if state.closed {
+                return state.pending.take().unwrap_or_else(|| {
+                    state
+                        .released
+                        .pop()
+                        .expect("Queued canvases are kept until they are released.")
+                });
+            }
+            if state.pending.is_none() && state.released.len() > released_before {
+                return state.released.pop().unwrap();
+            }
+            state = self.picked_up.wait(state).unwrap();
+        }
+    }
+
+    /// Register a callback that is run after every frame.
+    pub(crate) fn add_callback(&self, callback: FrameCallback) {
+        self.callbacks.lock().unwrap().push(callback);
+    }
+
+    /// The colors of the displayed canvas, row by row, which are all black while the display is blank.
+    pub(crate) fn displayed_colors(&self) -> Vec<Color> {
+        let colors = self.displayed.lock().unwrap().colors();
+        if self.is_enabled() {
+            colors
+        } else {
+            vec![Color::BLACK; colors.len()]
+        }
+    }
+
+    /// Blank the display or output the displayed canvas again.
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether the displayed canvas is output.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Only pick up queued canvases after a tick was received from the receiver.
+    pub(crate) fn set_external_vsync(&self, receiver: Receiver<()>) {
+        *self.external_vsync.lock().unwrap() = Some(receiver);
+    }
+
+    /// Whether a queued canvas can be picked up in this frame, which is always the case without an external
+    /// vsync. All ticks received since the last frame are consumed and count as one. Once the sender is gone,
+    /// the external vsync is removed.
+    fn vsync_ticked(&self) -> bool {
+        let mut external_vsync = self.external_vsync.lock().unwrap();
+        let Some(receiver) = external_vsync.as_ref() else {
+            return true;
+        };
+        let mut ticked = false;
+        loop {
+            match receiver.try_recv() {
+                Ok(()) => ticked = true,
+                Err(TryRecvError::Empty) => return ticked,
+                Err(TryRecvError::Disconnected) => {
+                    *external_vsync = None;
+                    return true;
+                }
+            }
+        }
+    }
+
+    /// Called by the update thread at the start of a frame. Replaces the displayed canvas if a new one was
+    /// queued and the external vsync allows it, and returns whether it did.
+    pub(crate) fn pick_up(&self) -> bool {
+        if !self.vsync_ticked() {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap();
+        let Some(canvas) = state.pending.take() else {
+            return false;
+        };
+        let previous = replace(&mut *self.displayed.lock().unwrap(), canvas);
+        state.released.push(previous);
+        self.picked_up.notify_all();
+        true
+    }
+
+    /// Called by the update thread to output the displayed canvas.
+    pub(crate) fn with_displayed<R>(&self, f: impl FnOnce(&mut Canvas) -> R) -> R {
+        f(&mut self.displayed.lock().unwrap())
+    }
+
+    /// The average rate at which frames were output recently, in frames per second.
+    pub(crate) fn frame_rate(&self) -> f32 {
+        self.frame_rate_monitor.lock().unwrap().get_fps()
+    }
+
+    /// The statistics of the last frame that was output.
+    pub(crate) fn frame_stats(&self) -> FrameStats {
+        *self.frame_stats.lock().unwrap()
+    }
+
+    /// Called by the update thread after a frame was output.
+    pub(crate) fn frame_finished(&self, stats: FrameStats) {
+        self.frame_rate_monitor
+            .lock()
+            .unwrap()
+            .update(self.clock.now());
+        *self.frame_stats.lock().unwrap() = stats;
+        self.callbacks
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .for_each(|callback| callback());
+    }
+
+    /// Called by the update thread when it stops.
+    pub(crate) fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.picked_up.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread::{sleep, spawn},
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::{Color, RGBMatrixConfig, SystemClock};
+
+    fn test_canvas(color: Color) -> Box<Canvas> {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            ..Default::default()
+        };
+        let mut canvas = Box::new(Canvas::new_for_test(&config));
+        canvas.fill(color);
+        canvas
+    }
+
+    fn color(canvas: &Canvas) -> Option<Color> {
+        canvas.get_pixel(0, 0)
+    }
+
+    #[test]
+    fn test_swap_without_update_thread() {
+        let exchange = FrameExchange::new(test_canvas(Color::BLACK), Arc::new(SystemClock::new()));
+
+        // Nothing was released yet, so there is no free canvas.
+        assert!(exchange.swap(test_canvas(Color::RED)).is_none());
+        // A queued canvas that was not picked up yet is handed back.
+        let skipped = exchange.swap(test_canvas(Color::GREEN)).unwrap();
+        assert_eq!(color(&skipped), Some(Color::RED));
+
+        assert!(exchange.pick_up());
+        assert!(!exchange.pick_up());
+        assert_eq!(
+            exchange.with_displayed(|canvas| color(canvas)),
+            Some(Color::GREEN)
+        );
+        let released = exchange.swap(test_canvas(Color::BLUE)).unwrap();
+        assert_eq!(color(&released), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn test_swap_blocking() {
+        let exchange = Arc::new(FrameExchange::new(
+            test_canvas(Color::BLACK),
+            Arc::new(SystemClock::new()),
+        ));
+        let thread_exchange = Arc::clone(&exchange);
+        let handle = spawn(move || {
+            let mut picked_up = 0;
+            while picked_up < 2 {
+                sleep(Duration::from_millis(1));
+                picked_up += thread_exchange.pick_up() as usize;
+            }
+            thread_exchange.close();
+        });
+
+        let returned = exchange.swap_blocking(test_canvas(Color::RED));
+        assert_eq!(color(&returned), Some(Color::BLACK));
+        let returned = exchange.swap_blocking(returned);
+        assert_eq!(color(&returned), Some(Color::RED));
+        handle.join().unwrap();
+        assert_eq!(
+            exchange.with_displayed(|canvas| color(canvas)),
+            Some(Color::BLACK)
+        );
+
+        // Once the update thread stopped, the canvas is handed back right away.
+        let mut canvas = test_canvas(Color::WHITE);
+        canvas.set_pixel(1, 1, 1, 2, 3);
+        let returned = exchange.swap_blocking(canvas);
+        assert_eq!(returned.get_pixel(1, 1), Some(Color::new(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_external_vsync() {
+        let exchange = FrameExchange::new(test_canvas(Color::BLACK), Arc::new(SystemClock::new()));
+        let (sender, receiver) = std::sync::mpsc::channel();
+        exchange.set_external_vsync(receiver);
+
+        assert!(exchange.swap(test_canvas(Color::RED)).is_none());
+        assert!(!exchange.pick_up());
+        // Ticks without a queued canvas are not saved up.
+        sender.send(()).unwrap();
+        sender.send(()).unwrap();
+        assert!(exchange.pick_up());
+        exchange.swap(test_canvas(Color::GREEN));
+        assert!(!exchange.pick_up());
+        // Without a sender, canvases are picked up in every frame again.
+        drop(sender);
+        assert!(exchange.pick_up());
+        exchange.swap(test_canvas(Color::BLUE));
+        assert!(exchange.pick_up());
+    }
+
+    #[test]
+    fn test_callbacks() {
+        let exchange = FrameExchange::new(test_canvas(Color::BLACK), Arc::new(SystemClock::new()));
+        let frames = Arc::new(AtomicUsize::new(0));
+        let callback_frames = Arc::clone(&frames);
+        exchange.add_callback(Box::new(move || {
+            callback_frames.fetch_add(1, Ordering::Relaxed);
+        }));
+        exchange.frame_finished(FrameStats::default());
+        exchange.frame_finished(FrameStats::default());
+        assert_eq!(frames.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use gif::{Encoder, EncodingError, Frame, Repeat};
+
+use crate::RGBMatrix;
+
+/// The NeuQuant sampling speed for frames with more than 256 colors, from 1 (best quality) to 30 (fastest).
+const QUANTIZATION_SPEED: i32 = 10;
+
+#[derive(Debug)]
+pub enum RecordingError {
+    /// Writing the GIF failed.
+    Encoding(EncodingError),
+    /// The canvas size changed during the recording, e.g. because a pixel mapper was applied.
+    FrameSizeChanged {
+        expected: [usize; 2],
+        actual: [usize; 2],
+    },
+    /// The canvas is too large for a GIF, which is limited to 65535 pixels in each direction.
+    FrameTooLarge([usize; 2]),
+    /// The recording was finished without capturing a frame.
+    NoFrames,
+}
+
+impl Error for RecordingError {}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::Encoding(error) => write!(f, "Failed to write the GIF: {error}"),
+            RecordingError::FrameSizeChanged { expected, actual } => write!(
+                f,
+                "The frame size changed from {}x{} to {}x{} during the recording.",
+                expected[0], expected[1], actual[0], actual[1]
+            ),
+            RecordingError::FrameTooLarge([width, height]) => {
+                write!(
+                    f,
+                    "A frame of {width}x{height} pixels is too large for a GIF."
+                )
+            }
+            RecordingError::NoFrames => f.write_str("No frames were captured."),
+        }
+    }
+}
+
+impl From<EncodingError> for RecordingError {
+    fn from(error: EncodingError) -> Self {
+        Self::Encoding(error)
+    }
+}
+
+/// Records the frames displayed by an [`RGBMatrix`] into an animated GIF, e.g. to share a demo of an animation.
+///
+/// Call [`FrameRecorder::capture`] after every [`RGBMatrix::update_on_vsync`] and [`FrameRecorder::finish`] at
+/// the end. The GIF has the size of the canvas. Frames with up to 256 colors, which covers most content drawn
+/// for LED panels, keep their exact colors. Frames with more colors are reduced to 256 colors with NeuQuant.
+pub struct FrameRecorder {
+    /// The encoder, created with the size of the first captured frame.
+    encoder: Option<Encoder<BufWriter<File>>>,
+    /// The file until the first frame is captured.
+    file: Option<BufWriter<File>>,
+    /// The frame size in pixels.
+    size: [usize; 2],
+    /// The delay between frames in hundredths of a second.
+    delay: u16,
+}
+
+impl FrameRecorder {
+    /// Create the GIF file at `path` and start a recording that is played back with `fps` frames per second. GIF
+    /// frame delays are multiples of 10 ms, so the frame rate is rounded to the nearest possible one.
+    pub fn start(path: impl AsRef<Path>, fps: u32) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let delay = (100.0 / fps.max(1) as f64)
+            .round()
+            .clamp(1.0, u16::MAX as f64) as u16;
+        Ok(Self {
+            encoder: None,
+            file: Some(file),
+            size: [0, 0],
+            delay,
+        })
+    }
+
+    /// Add the frame that is currently displayed by the matrix to the recording.
+    pub fn capture(&mut self, matrix: &RGBMatrix) -> Result<(), RecordingError> {
+        let (width, height, colors) = matrix.displayed_frame();
+        let [gif_width, gif_height] = Self::gif_size(width, height)?;
+        if let Some(file) = self.file.take() {
+            let mut encoder = Encoder::new(file, gif_width, gif_height, &[])?;
+            encoder.set_repeat(Repeat::Infinite)?;
+            self.encoder = Some(encoder);
+            self.size = [width, height];
+        } else if self.size != [width, height] {
+            return Err(RecordingError::FrameSizeChanged {
+                expected: self.size,
+                actual: [width, height],
+            });
+        }
+        // The encoder is missing if creating it failed before.
+        let encoder = self
+            .encoder
+            .as_mut()
+            .ok_or(RecordingError::Encoding(EncodingError::WriterNotFound))?;
+
+        let pixels = colors
+            .iter()
+            .flat_map(|color| [color.r, color.g, color.b])
+            .collect::<Vec<u8>>();
+        let mut frame = Frame::from_rgb_speed(gif_width, gif_height, &pixels, QUANTIZATION_SPEED);
+        frame.delay = self.delay;
+        encoder.write_frame(&frame)?;
+        Ok(())
+    }
+
+    /// Finish the recording and write the end of the GIF.
+    pub fn finish(self) -> Result<(), RecordingError> {
+        let encoder = self.encoder.ok_or(RecordingError::NoFrames)?;
+        encoder
+            .into_inner()?
+            .into_inner()
+            .map_err(|error| RecordingError::Encoding(EncodingError::Io(error.into_error())))?;
+        Ok(())
+    }
+
+    fn gif_size(width: usize, height: usize) -> Result<[u16; 2], RecordingError> {
+        match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(width), Ok(height)) => Ok([width, height]),
+            _ => Err(RecordingError::FrameTooLarge([width, height])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gif::{ColorOutput, DecodeOptions};
+
+    use super::*;
+    use crate::{Color, RGBMatrixConfig};
+
+    #[test]
+    fn test_record_frames() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            refresh_rate: 1000,
+            ..Default::default()
+        };
+        let (mut matrix, mut canvas) = RGBMatrix::new_mock(config).unwrap();
+        let path = std::env::temp_dir().join(format!("rpi_led_panel_{}.gif", std::process::id()));
+
+        let mut recorder = FrameRecorder::start(&path, 20).unwrap();
+        for color in [Color::RED, Color::GREEN, Color::BLUE] {
+            canvas.fill(color);
+            canvas = matrix.update_on_vsync(canvas);
+            recorder.capture(&matrix).unwrap();
+        }
+        recorder.finish().unwrap();
+
+        let mut options = DecodeOptions::new();
+        options.set_color_output(ColorOutput::RGBA);
+        let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+        assert_eq!([decoder.width(), decoder.height()], [32, 16]);
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!([frame.width, frame.height], [32, 16]);
+            assert_eq!(frame.delay, 5);
+            frames.push(frame.buffer[..4].to_vec());
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            frames,
+            [[255, 0, 0, 255], [0, 128, 0, 255], [0, 0, 255, 255]]
+        );
+    }
+
+    #[test]
+    fn test_finish_without_frames() {
+        let path =
+            std::env::temp_dir().join(format!("rpi_led_panel_empty_{}.gif", std::process::id()));
+        let recorder = FrameRecorder::start(&path, 20).unwrap();
+        assert!(matches!(recorder.finish(), Err(RecordingError::NoFrames)));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
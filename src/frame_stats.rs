@@ -0,0 +1,19 @@
+use core::time::Duration;
+
+/// Where the time of outputting a frame went, as measured by the update thread. The bit planes themselves are
+/// generated while drawing on the canvas, so all of the time is spent on scanning them out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// The time to output the frame, without the idle time that limits the refresh rate.
+    pub scan_time: Duration,
+    /// The part of the scan time spent clocking the bit planes into the panels. It grows with
+    /// [`RGBMatrixConfig::slowdown`](crate::RGBMatrixConfig::slowdown), the number of columns and the chain
+    /// length.
+    pub clock_in_time: Duration,
+    /// The rest of the scan time, which is mostly spent waiting for the output enable pulses of the bit planes
+    /// to finish. It grows with
+    /// [`RGBMatrixConfig::pwm_lsb_nanoseconds`](crate::RGBMatrixConfig::pwm_lsb_nanoseconds).
+    pub pulse_time: Duration,
+    /// The number of bit planes output for every row.
+    pub bitplanes: usize,
+}
@@ -1,14 +1,18 @@
 use std::{
     error::Error,
     fmt::{Display, Formatter},
+    io,
+    time::Instant,
 };
 
 use crate::{
     chip::PiChip,
-    config::K_BIT_PLANES,
     gpio_bits,
-    pin_pulser::PinPulser,
-    registers::{ClkRegisters, GPIOFunction, GPIORegisters, PWMRegisters, TimeRegisters},
+    gpio_output::{bitplane_timings_ns, GpioOutput, ScanOutput},
+    pin_pulser::{PinPulser, PulseRegisters},
+    registers::{
+        ClkRegisters, GPIOFunction, GPIORegisters, PWMRegisters, PeripheralMemory, TimeRegisters,
+    },
     row_address_setter::RowAddressSetter,
     utils::linux_has_module_loaded,
     RGBMatrixConfig,
@@ -18,6 +22,8 @@ use crate::{
 pub enum GpioInitializationError {
     OneWireProtocolEnabled,
     SoundModuleLoaded,
+    /// The GPIO pins of the RP1 of the Raspberry Pi 5 could not be mapped through `/dev/gpiomem0`.
+    Rp1MemoryAccessError(io::Error),
 }
 
 impl Error for GpioInitializationError {}
@@ -37,6 +43,11 @@ impl Display for GpioInitializationError {
                 `/etc/modprobe.d/alsa-blacklist.conf`\n\
                 Finally, reboot the system and try again.",
             ),
+            GpioInitializationError::Rp1MemoryAccessError(error) => write!(
+                f,
+                "Failed to access the GPIO pins through '/dev/gpiomem0': {error}. Run as root, or add the user \
+                to the `gpio` group."
+            ),
         }
     }
 }
@@ -44,29 +55,43 @@ impl Display for GpioInitializationError {
 pub(crate) struct Gpio {
     gpio_registers: GPIORegisters,
     time_registers: TimeRegisters,
-    pwm_registers: PWMRegisters,
+    /// The PWM peripheral, which the RP1 of the Raspberry Pi 5 does not provide at the same address, and which
+    /// can't be mapped through `/dev/gpiomem`.
+    pwm_registers: Option<PWMRegisters>,
+    clk_registers: Option<ClkRegisters>,
     pin_pulser: PinPulser,
     input_bits: u32,
     output_bits: u32,
     reserved_bits: u32,
     gpio_slowdown: u32,
+    /// The reference for [`ScanOutput::time_ns`].
+    start_time: Instant,
 }
 
 impl Gpio {
-    /// Initialize GPIO and loads all registers. Needs root privileges.
+    /// Initialize GPIO and loads all registers from the opened memory device.
     pub(crate) fn new(
         chip: PiChip,
+        memory: Option<PeripheralMemory>,
         config: &RGBMatrixConfig,
         address_setter: &dyn RowAddressSetter,
     ) -> Result<Self, GpioInitializationError> {
-        if linux_has_module_loaded("snd_bcm2835") {
+        // The sound of the Raspberry Pi 5 does not use the PWM peripheral.
+        if !chip.has_rp1() && linux_has_module_loaded("snd_bcm2835") {
             return Err(GpioInitializationError::SoundModuleLoaded);
         }
 
-        let mut gpio_registers = GPIORegisters::new(chip);
-        let time_registers = TimeRegisters::new(chip);
-        let mut pwm_registers = PWMRegisters::new(chip);
-        let mut clk_registers = ClkRegisters::new(chip);
+        let mut gpio_registers = GPIORegisters::new(chip, memory.as_ref())
+            .map_err(GpioInitializationError::Rp1MemoryAccessError)?;
+        let mut time_registers = TimeRegisters::new(chip, memory.as_ref());
+        let (mut pwm_registers, mut clk_registers) =
+            match memory.filter(PeripheralMemory::has_all_peripherals) {
+                Some(memory) => (
+                    Some(PWMRegisters::new(chip, &memory)),
+                    Some(ClkRegisters::new(chip, &memory)),
+                ),
+                None => (None, None),
+            };
         // TODO: We can drop privileges here.
 
         // Tell GPIO about all bits we intend to use.
@@ -113,21 +138,20 @@ impl Gpio {
         }
         assert!(output_bits == all_used_bits);
 
-        let mut bitplane_timings = Vec::new();
-        let mut timing_ns = config.pwm_lsb_nanoseconds;
-        (0..K_BIT_PLANES).for_each(|b| {
-            bitplane_timings.push(timing_ns);
-            if b >= config.dither_bits {
-                timing_ns *= 2;
-            };
-        });
+        let bitplane_timings = bitplane_timings_ns(config);
 
+        // Pulses are timed by the PWM peripheral if the output enable is on a PWM pin, e.g. with the PWM
+        // modification of the Adafruit HAT, and by busy waiting otherwise.
         let pin_pulser = PinPulser::new(
             config.hardware_mapping.output_enable,
             &bitplane_timings,
-            &mut pwm_registers,
-            &mut gpio_registers,
-            &mut clk_registers,
+            pwm_registers.is_some(),
+            &mut PulseRegisters {
+                gpio_registers: &mut gpio_registers,
+                time_registers: &mut time_registers,
+                pwm_registers: pwm_registers.as_mut(),
+                clk_registers: clk_registers.as_mut(),
+            },
         );
 
         let gpio_slowdown = config.slowdown.unwrap_or_else(|| chip.gpio_slowdown());
@@ -136,11 +160,13 @@ impl Gpio {
             gpio_registers,
             time_registers,
             pwm_registers,
+            clk_registers,
             pin_pulser,
             input_bits,
             output_bits,
             reserved_bits,
             gpio_slowdown,
+            start_time: Instant::now(),
         })
     }
 
@@ -169,22 +195,39 @@ impl Gpio {
 
     pub(crate) fn send_pulse(&mut self, bitplane: usize) {
         let Gpio {
+            gpio_registers,
             time_registers,
             pwm_registers,
+            clk_registers,
             pin_pulser,
             ..
         } = self;
-        pin_pulser.send_pulse(bitplane, pwm_registers, time_registers);
+        pin_pulser.send_pulse(
+            bitplane,
+            &mut PulseRegisters {
+                gpio_registers,
+                time_registers,
+                pwm_registers: pwm_registers.as_mut(),
+                clk_registers: clk_registers.as_mut(),
+            },
+        );
     }
 
     pub(crate) fn wait_pulse_finished(&mut self) {
         let Gpio {
+            gpio_registers,
             time_registers,
             pwm_registers,
+            clk_registers,
             pin_pulser,
             ..
         } = self;
-        pin_pulser.wait_pulse_finished(time_registers, pwm_registers);
+        pin_pulser.wait_pulse_finished(&mut PulseRegisters {
+            gpio_registers,
+            time_registers,
+            pwm_registers: pwm_registers.as_mut(),
+            clk_registers: clk_registers.as_mut(),
+        });
     }
 
     pub(crate) fn request_enabled_inputs(&mut self, mut enabled_bits: u32) -> u32 {
@@ -215,3 +258,31 @@ impl Gpio {
         self.time_registers.sleep(duration_us);
     }
 }
+
+impl ScanOutput for Gpio {
+    fn send_pulse(&mut self, bitplane: usize) {
+        Gpio::send_pulse(self, bitplane);
+    }
+
+    fn wait_pulse_finished(&mut self) {
+        Gpio::wait_pulse_finished(self);
+    }
+
+    fn time_ns(&self) -> u64 {
+        self.start_time.elapsed().as_nanos() as u64
+    }
+}
+
+impl GpioOutput for Gpio {
+    fn set_bits(&mut self, value: u32) {
+        Gpio::set_bits(self, value);
+    }
+
+    fn clear_bits(&mut self, value: u32) {
+        Gpio::clear_bits(self, value);
+    }
+
+    fn write_masked_bits(&mut self, value: u32, mask: u32) {
+        Gpio::write_masked_bits(self, value, mask);
+    }
+}
@@ -0,0 +1,73 @@
+use alloc::vec::Vec;
+
+use crate::{config::K_BIT_PLANES, RGBMatrixConfig};
+
+/// The GPIO output operations, which are all that is needed to set the row address.
+pub(crate) trait GpioOutput {
+    fn set_bits(&mut self, value: u32);
+    fn clear_bits(&mut self, value: u32);
+
+    fn write_masked_bits(&mut self, value: u32, mask: u32) {
+        self.clear_bits(!value & mask);
+        self.set_bits(value & mask);
+    }
+}
+
+/// The operations needed to scan out a frame.
+pub(crate) trait ScanOutput: GpioOutput {
+    /// Switch the output on for the duration of the bit plane.
+    fn send_pulse(&mut self, bitplane: usize);
+    /// Wait until the previous pulse is over.
+    fn wait_pulse_finished(&mut self);
+    /// A monotonic time instant in nanoseconds.
+    fn time_ns(&self) -> u64;
+}
+
+/// The duration of the output enable pulse of each bit plane, which doubles from plane to plane.
+pub(crate) fn bitplane_timings_ns(config: &RGBMatrixConfig) -> Vec<u32> {
+    (0..K_BIT_PLANES)
+        .map(|plane| config.pwm_lsb_nanoseconds << plane)
+        .collect()
+}
+
+/// Records the state of the output bits after every operation.
+#[derive(Default)]
+pub(crate) struct RecordingGpio {
+    pub(crate) bits: u32,
+    pub(crate) states: Vec<u32>,
+}
+
+#[cfg(test)]
+impl RecordingGpio {
+    /// The states at every rising edge of the given clock bits.
+    pub(crate) fn clocked_states(&self, clock: u32) -> Vec<u32> {
+        self.states
+            .windows(2)
+            .filter(|w| w[0] & clock == 0 && w[1] & clock != 0)
+            .map(|w| w[1])
+            .collect()
+    }
+}
+
+impl GpioOutput for RecordingGpio {
+    fn set_bits(&mut self, value: u32) {
+        self.bits |= value;
+        self.states.push(self.bits);
+    }
+
+    fn clear_bits(&mut self, value: u32) {
+        self.bits &= !value;
+        self.states.push(self.bits);
+    }
+}
+
+/// Pulses are not recorded, and the time is the number of operations.
+impl ScanOutput for RecordingGpio {
+    fn send_pulse(&mut self, _bitplane: usize) {}
+
+    fn wait_pulse_finished(&mut self) {}
+
+    fn time_ns(&self) -> u64 {
+        self.states.len() as u64
+    }
+}
@@ -1,4 +1,10 @@
-use std::{error::Error, ops::BitOr, str::FromStr};
+use alloc::{boxed::Box, format, string::ToString, vec, vec::Vec};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+    ops::BitOr,
+    str::FromStr,
+};
 
 use crate::gpio_bits;
 
@@ -73,6 +79,9 @@ impl Panels {
     }
 }
 
+/// The GPIO pins that the panel signals are connected to, which depend on the adapter board. The built-in
+/// mappings can be selected by name, e.g. `--hardware-mapping AdafruitHat`, and other wiring can be described
+/// with [`HardwareMapping::custom`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct HardwareMapping {
     pub(crate) output_enable: u32,
@@ -88,23 +97,259 @@ pub struct HardwareMapping {
     pub(crate) panels: Panels,
 }
 
+/// The highest GPIO pin on the header of the Raspberry Pi.
+const MAX_GPIO_PIN: u8 = 27;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardwareMappingError {
+    /// The pin is not a GPIO pin on the header of the Raspberry Pi.
+    InvalidPin(u8),
+    /// The pin is assigned to more than one signal.
+    DuplicatePin(u8),
+    /// There are more than 5 address lines.
+    TooManyAddressLines(usize),
+    /// The number of parallel chains is not between 1 and 6.
+    InvalidChainCount(usize),
+}
+
+impl Error for HardwareMappingError {}
+
+impl Display for HardwareMappingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HardwareMappingError::InvalidPin(pin) => write!(
+                f,
+                "Invalid pin '{pin}'. It must be a GPIO pin between 0 and {MAX_GPIO_PIN}."
+            ),
+            HardwareMappingError::DuplicatePin(pin) => {
+                write!(f, "The pin '{pin}' is used more than once.")
+            }
+            HardwareMappingError::TooManyAddressLines(lines) => write!(
+                f,
+                "Invalid number of address lines '{lines}'. There can be at most 5."
+            ),
+            HardwareMappingError::InvalidChainCount(chains) => write!(
+                f,
+                "Invalid number of parallel chains '{chains}'. It must be between 1 and 6."
+            ),
+        }
+    }
+}
+
+/// The GPIO pins of the color inputs of one parallel chain, for the upper (1) and lower (2) sub-panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChainPins {
+    pub r1: u8,
+    pub g1: u8,
+    pub b1: u8,
+    pub r2: u8,
+    pub g2: u8,
+    pub b2: u8,
+}
+
+impl ChainPins {
+    const fn pins(&self) -> [u8; 6] {
+        [self.r1, self.g1, self.b1, self.r2, self.g2, self.b2]
+    }
+}
+
+/// Parses the name of a built-in mapping or a custom mapping in the format that custom mappings are displayed
+/// in, e.g. `oe=18;clk=17;lat=4;addr=22,23,24,25,15;rgb=11,27,7,8,9,10`. The pins of each `rgb` chain are given
+/// in the order R1, G1, B1, R2, G2, B2.
 impl FromStr for HardwareMapping {
     type Err = Box<dyn Error>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "AdafruitHat" => Ok(Self::adafruit_hat()),
-            "AdafruitHatPwm" => Ok(Self::adafruit_hat_pwm()),
-            "Regular" => Ok(Self::regular()),
-            "RegularPi1" => Ok(Self::regular_pi1()),
-            "Classic" => Ok(Self::classic()),
-            "ClassicPi1" => Ok(Self::classic_pi1()),
-            _ => Err(format!("'{s}' is not a valid GPIO mapping.").into()),
+        if let Some((_, mapping)) = NAMED_HARDWARE_MAPPINGS.iter().find(|(name, _)| *name == s) {
+            return Ok(*mapping);
+        }
+        if !s.contains('=') {
+            return Err(format!("'{s}' is not a valid GPIO mapping.").into());
+        }
+
+        let parse_pins = |value: &str| -> Result<Vec<u8>, Box<dyn Error>> {
+            value
+                .split(',')
+                .filter(|pin| !pin.is_empty())
+                .map(|pin| {
+                    pin.trim()
+                        .parse()
+                        .map_err(|_| format!("'{pin}' is not a valid pin.").into())
+                })
+                .collect()
+        };
+        let parse_pin = |value: &str| -> Result<u8, Box<dyn Error>> {
+            match parse_pins(value)?.as_slice() {
+                [pin] => Ok(*pin),
+                _ => Err(format!("'{value}' is not a single pin.").into()),
+            }
+        };
+
+        let (mut output_enable, mut clock, mut strobe) = (None, None, None);
+        let mut address = vec![];
+        let mut chains = vec![];
+        for entry in s.split(';') {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("'{entry}' is not a key=value pair."))?;
+            match key.trim() {
+                "oe" => output_enable = Some(parse_pin(value)?),
+                "clk" => clock = Some(parse_pin(value)?),
+                "lat" => strobe = Some(parse_pin(value)?),
+                "addr" => address = parse_pins(value)?,
+                "rgb" => match parse_pins(value)?.as_slice() {
+                    &[r1, g1, b1, r2, g2, b2] => chains.push(ChainPins {
+                        r1,
+                        g1,
+                        b1,
+                        r2,
+                        g2,
+                        b2,
+                    }),
+                    _ => return Err(format!("'{value}' are not the 6 pins of a chain.").into()),
+                },
+                key => return Err(format!("'{key}' is not a GPIO mapping signal.").into()),
+            }
+        }
+        let missing = |signal: &str| format!("The GPIO mapping is missing the '{signal}' pin.");
+        Ok(Self::custom(
+            output_enable.ok_or_else(|| missing("oe"))?,
+            clock.ok_or_else(|| missing("clk"))?,
+            strobe.ok_or_else(|| missing("lat"))?,
+            &address,
+            &chains,
+        )?)
+    }
+}
+
+/// Displays the name of a built-in mapping, or the pins of a custom mapping in the format accepted by
+/// [`HardwareMapping::from_str`].
+impl Display for HardwareMapping {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if let Some((name, _)) = NAMED_HARDWARE_MAPPINGS
+            .iter()
+            .find(|(_, mapping)| mapping == self)
+        {
+            return f.write_str(name);
+        }
+        let pins = |bits: u32| {
+            (0..u32::BITS)
+                .filter(|pin| bits & (1 << pin) != 0)
+                .map(|pin| pin.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        write!(
+            f,
+            "oe={};clk={};lat={};addr={}",
+            pins(self.output_enable),
+            pins(self.clock),
+            pins(self.strobe),
+            [self.a, self.b, self.c, self.d, self.e]
+                .into_iter()
+                .filter(|&bits| bits != 0)
+                .map(pins)
+                .collect::<Vec<_>>()
+                .join(",")
+        )?;
+        for chain in self.panels.color_bits.iter().filter(|c| c.used_bits() != 0) {
+            write!(
+                f,
+                ";rgb={}",
+                [chain.r1, chain.g1, chain.b1, chain.r2, chain.g2, chain.b2]
+                    .map(pins)
+                    .join(",")
+            )?;
         }
+        Ok(())
     }
 }
 
+/// The hardware mappings that can be selected by name.
+const NAMED_HARDWARE_MAPPINGS: [(&str, HardwareMapping); 6] = [
+    ("AdafruitHat", HardwareMapping::ADAFRUIT_HAT),
+    ("AdafruitHatPwm", HardwareMapping::ADAFRUIT_HAT_PWM),
+    ("Regular", HardwareMapping::REGULAR),
+    ("RegularPi1", HardwareMapping::REGULAR_PI1),
+    ("Classic", HardwareMapping::CLASSIC),
+    ("ClassicPi1", HardwareMapping::CLASSIC_PI1),
+];
+
 impl HardwareMapping {
+    /// See [`HardwareMapping::regular`].
+    pub const REGULAR: Self = Self::regular();
+    /// See [`HardwareMapping::adafruit_hat`].
+    pub const ADAFRUIT_HAT: Self = Self::adafruit_hat();
+    /// See [`HardwareMapping::adafruit_hat_pwm`].
+    pub const ADAFRUIT_HAT_PWM: Self = Self::adafruit_hat_pwm();
+    /// See [`HardwareMapping::regular_pi1`].
+    pub const REGULAR_PI1: Self = Self::regular_pi1();
+    /// See [`HardwareMapping::classic`].
+    pub const CLASSIC: Self = Self::classic();
+    /// See [`HardwareMapping::classic_pi1`].
+    pub const CLASSIC_PI1: Self = Self::classic_pi1();
+
+    /// Create a mapping for other wiring from GPIO pin numbers. `address` holds the pins of the row address
+    /// lines, starting with A, and `chains` the color pins of up to 6 parallel chains.
+    pub fn custom(
+        output_enable: u8,
+        clock: u8,
+        strobe: u8,
+        address: &[u8],
+        chains: &[ChainPins],
+    ) -> Result<Self, HardwareMappingError> {
+        if address.len() > 5 {
+            return Err(HardwareMappingError::TooManyAddressLines(address.len()));
+        }
+        if chains.is_empty() || chains.len() > 6 {
+            return Err(HardwareMappingError::InvalidChainCount(chains.len()));
+        }
+        let mut used_bits = 0u32;
+        let mut bit = |pin: u8| {
+            if pin > MAX_GPIO_PIN {
+                return Err(HardwareMappingError::InvalidPin(pin));
+            }
+            if used_bits & gpio_bits!(pin) != 0 {
+                return Err(HardwareMappingError::DuplicatePin(pin));
+            }
+            used_bits |= gpio_bits!(pin);
+            Ok(gpio_bits!(pin))
+        };
+
+        let mut address_bits = [0; 5];
+        let mut color_bits = [ColorBits::unused(); 6];
+        let output_enable = bit(output_enable)?;
+        let clock = bit(clock)?;
+        let strobe = bit(strobe)?;
+        for (bits, &pin) in address_bits.iter_mut().zip(address) {
+            *bits = bit(pin)?;
+        }
+        for (bits, chain) in color_bits.iter_mut().zip(chains) {
+            let [r1, g1, b1, r2, g2, b2] = chain.pins();
+            *bits = ColorBits {
+                r1: bit(r1)?,
+                g1: bit(g1)?,
+                b1: bit(b1)?,
+                r2: bit(r2)?,
+                g2: bit(g2)?,
+                b2: bit(b2)?,
+            };
+        }
+        let [a, b, c, d, e] = address_bits;
+        Ok(Self {
+            output_enable,
+            clock,
+            strobe,
+            a,
+            b,
+            c,
+            d,
+            e,
+            panels: Panels { color_bits },
+        })
+    }
+
+    /// All bits of the output enable, clock, strobe and color pins, without the address lines.
     pub(crate) fn used_bits(&self) -> u32 {
         self.output_enable | self.clock | self.strobe | self.panels.used_bits()
     }
@@ -182,7 +427,7 @@ impl HardwareMapping {
         }
     }
 
-    // An unmodified Adafruit HAT
+    /// An unmodified Adafruit HAT or Bonnet, with a single chain.
     pub const fn adafruit_hat() -> Self {
         Self {
             output_enable: gpio_bits!(4),
@@ -215,7 +460,8 @@ impl HardwareMapping {
         }
     }
 
-    // An Adafruit HAT with the PWM modification
+    /// An Adafruit HAT or Bonnet with the PWM modification, which connects GPIO 4 to GPIO 18 so that the output
+    /// enable can be driven by the hardware PWM.
     pub const fn adafruit_hat_pwm() -> Self {
         Self {
             output_enable: gpio_bits!(18),
@@ -344,3 +590,89 @@ impl HardwareMapping {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address_bits(mapping: &HardwareMapping) -> u32 {
+        mapping.a | mapping.b | mapping.c | mapping.d | mapping.e
+    }
+
+    #[test]
+    fn test_named_mapping_bits() {
+        let regular = "Regular".parse::<HardwareMapping>().unwrap();
+        assert_eq!(regular, HardwareMapping::REGULAR);
+        assert_eq!(
+            regular.used_bits(),
+            gpio_bits!(18, 17, 4, 11, 27, 7, 8, 9, 10, 12, 5, 6, 19, 13, 20, 14, 2, 3, 26, 16, 21)
+        );
+        assert_eq!(address_bits(&regular), gpio_bits!(22, 23, 24, 25, 15));
+        assert_eq!(regular.max_parallel_chains(), 3);
+
+        let adafruit_hat = "AdafruitHat".parse::<HardwareMapping>().unwrap();
+        assert_eq!(adafruit_hat, HardwareMapping::ADAFRUIT_HAT);
+        assert_eq!(
+            adafruit_hat.used_bits(),
+            gpio_bits!(4, 17, 21, 5, 13, 6, 12, 16, 23)
+        );
+        assert_eq!(address_bits(&adafruit_hat), gpio_bits!(22, 26, 27, 20, 24));
+        assert_eq!(adafruit_hat.max_parallel_chains(), 1);
+
+        // The PWM modification only moves the output enable.
+        let adafruit_hat_pwm = "AdafruitHatPwm".parse::<HardwareMapping>().unwrap();
+        assert_eq!(adafruit_hat_pwm, HardwareMapping::ADAFRUIT_HAT_PWM);
+        assert_eq!(
+            adafruit_hat_pwm.used_bits(),
+            gpio_bits!(18, 17, 21, 5, 13, 6, 12, 16, 23)
+        );
+        assert_eq!(address_bits(&adafruit_hat_pwm), address_bits(&adafruit_hat));
+
+        for (name, mapping) in NAMED_HARDWARE_MAPPINGS {
+            assert_eq!(mapping.to_string(), name);
+        }
+    }
+
+    #[test]
+    fn test_custom_mapping() {
+        let chain = ChainPins {
+            r1: 5,
+            g1: 13,
+            b1: 6,
+            r2: 12,
+            g2: 16,
+            b2: 23,
+        };
+        // A custom mapping with the same pins as a built-in one is the built-in one.
+        let mapping = HardwareMapping::custom(4, 17, 21, &[22, 26, 27, 20, 24], &[chain]).unwrap();
+        assert_eq!(mapping, HardwareMapping::ADAFRUIT_HAT);
+
+        let mapping = HardwareMapping::custom(18, 17, 21, &[22, 26, 27, 20], &[chain]).unwrap();
+        assert_eq!(
+            mapping.used_bits(),
+            gpio_bits!(18, 17, 21, 5, 13, 6, 12, 16, 23)
+        );
+        assert_eq!(mapping.e, 0);
+        let displayed = mapping.to_string();
+        assert_eq!(
+            displayed,
+            "oe=18;clk=17;lat=21;addr=22,26,27,20;rgb=5,13,6,12,16,23"
+        );
+        assert_eq!(displayed.parse::<HardwareMapping>().unwrap(), mapping);
+
+        assert_eq!(
+            HardwareMapping::custom(4, 17, 28, &[], &[chain]),
+            Err(HardwareMappingError::InvalidPin(28))
+        );
+        assert_eq!(
+            HardwareMapping::custom(4, 17, 5, &[], &[chain]),
+            Err(HardwareMappingError::DuplicatePin(5))
+        );
+        assert_eq!(
+            HardwareMapping::custom(4, 17, 21, &[], &[]),
+            Err(HardwareMappingError::InvalidChainCount(0))
+        );
+        assert!("oe=18;clk=17;addr=22".parse::<HardwareMapping>().is_err());
+        assert!("Bonnet".parse::<HardwareMapping>().is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use image::{Rgb, RgbImage, RgbaImage};
+
+use crate::{Canvas, Color, Sprite};
+
+impl Sprite {
+    /// Create a sprite with the colors of an image, without a transparent color.
+    pub fn from_image(img: &RgbImage) -> Self {
+        Self::from_fn(img.width() as usize, img.height() as usize, |x, y| {
+            let [r, g, b] = img.get_pixel(x as u32, y as u32).0;
+            Color::new(r, g, b)
+        })
+    }
+}
+
+impl Canvas {
+    /// Copy an image onto the canvas with its top left corner at (`dst_x`, `dst_y`). Parts of the image that
+    /// are outside of the canvas are discarded.
+    pub fn blit_image(&mut self, img: &RgbImage, dst_x: i32, dst_y: i32) {
+        self.blit_with(
+            img.width(),
+            img.height(),
+            dst_x,
+            dst_y,
+            |canvas, x, y, [sx, sy]| {
+                let [r, g, b] = img.get_pixel(sx, sy).0;
+                canvas.set_pixel(x, y, r, g, b);
+            },
+        );
+    }
+
+    /// Copy an image with an alpha channel onto the canvas with its top left corner at (`dst_x`, `dst_y`).
+    /// Fully transparent pixels are skipped and partially transparent pixels are blended with the canvas.
+    /// Parts of the image that are outside of the canvas are discarded.
+    pub fn blit_image_rgba(&mut self, img: &RgbaImage, dst_x: i32, dst_y: i32) {
+        self.blit_with(
+            img.width(),
+            img.height(),
+            dst_x,
+            dst_y,
+            |canvas, x, y, [sx, sy]| {
+                let [r, g, b, alpha] = img.get_pixel(sx, sy).0;
+                canvas.blend_pixel(x, y, Color::new(r, g, b), alpha);
+            },
+        );
+    }
+
+    /// Create an image with the colors of the canvas.
+    pub(crate) fn to_rgb_image(&self) -> RgbImage {
+        RgbImage::from_fn(self.width() as u32, self.height() as u32, |x, y| {
+            let color = self.get_pixel(x as usize, y as usize).unwrap_or_default();
+            Rgb([color.r, color.g, color.b])
+        })
+    }
+
+    /// Call `f` with the canvas coordinates and the source coordinates of every pixel of an image that is
+    /// placed at (`dst_x`, `dst_y`) and lies within the canvas.
+    fn blit_with(
+        &mut self,
+        width: u32,
+        height: u32,
+        dst_x: i32,
+        dst_y: i32,
+        mut f: impl FnMut(&mut Self, usize, usize, [u32; 2]),
+    ) {
+        let [dst_x, dst_y] = [dst_x, dst_y].map(i64::from);
+        let x_range = dst_x.max(0)..(dst_x + width as i64).min(self.width() as i64);
+        let y_range = dst_y.max(0)..(dst_y + height as i64).min(self.height() as i64);
+        for y in y_range {
+            for x in x_range.clone() {
+                let source = [(x - dst_x) as u32, (y - dst_y) as u32];
+                f(self, x as usize, y as usize, source);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+    use crate::{RGBMatrix, RGBMatrixConfig};
+
+    fn test_canvas() -> Canvas {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            ..Default::default()
+        };
+        Canvas::new_for_test(&config)
+    }
+
+    #[test]
+    fn test_blit_image() {
+        let mut canvas = test_canvas();
+        let img = RgbImage::from_fn(2, 2, |x, y| Rgb([x as u8 + 1, y as u8 + 1, 7]));
+        canvas.blit_image(&img, 30, 3);
+        assert_eq!(canvas.get_pixel(30, 3), Some(Color::new(1, 1, 7)));
+        assert_eq!(canvas.get_pixel(31, 4), Some(Color::new(2, 2, 7)));
+        assert_eq!(canvas.lit_pixels(), [[30, 3], [31, 3], [30, 4], [31, 4]]);
+    }
+
+    #[test]
+    fn test_blit_image_negative_offset() {
+        let mut canvas = test_canvas();
+        let img = RgbImage::from_fn(2, 2, |x, y| Rgb([x as u8 + 1, y as u8 + 1, 7]));
+        canvas.blit_image(&img, -1, -1);
+        assert_eq!(canvas.lit_pixels(), [[0, 0]]);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(2, 2, 7)));
+    }
+
+    #[test]
+    fn test_blit_image_rgba() {
+        let mut canvas = test_canvas();
+        canvas.fill(Color::new(0, 0, 200));
+        let img = RgbaImage::from_fn(3, 1, |x, _| Rgba([255, 0, 0, [0, 255, 128][x as usize]]));
+        canvas.blit_image_rgba(&img, 0, 0);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::new(0, 0, 200)));
+        assert_eq!(canvas.get_pixel(1, 0), Some(Color::new(255, 0, 0)));
+        assert_eq!(canvas.get_pixel(2, 0), Some(Color::new(128, 0, 100)));
+    }
+
+    #[test]
+    fn test_sprite_from_image() {
+        let img = RgbImage::from_fn(3, 2, |x, y| Rgb([x as u8 * 100, y as u8 * 100, 0]));
+        let sprite = Sprite::from_image(&img).with_transparent(Color::BLACK);
+        assert_eq!([sprite.width(), sprite.height()], [3, 2]);
+        assert_eq!(sprite.get_pixel(2, 1), Some(Color::new(200, 100, 0)));
+
+        let mut canvas = test_canvas();
+        canvas.fill(Color::BLUE);
+        canvas.draw_sprite(&sprite, 30, 15);
+        assert_eq!(canvas.get_pixel(30, 15), Some(Color::BLUE));
+        assert_eq!(canvas.get_pixel(31, 15), Some(Color::new(100, 0, 0)));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            pixelmapper: vec!["U-mapper".parse().unwrap()],
+            chain_length: 2,
+            ..Default::default()
+        };
+        let (mut matrix, mut canvas) = RGBMatrix::new_mock(config).unwrap();
+        assert_eq!([canvas.width(), canvas.height()], [32, 32]);
+        let pattern = RgbImage::from_fn(32, 32, |x, y| Rgb([x as u8 * 8, y as u8 * 8, 255]));
+        canvas.blit_image(&pattern, 0, 0);
+        let _canvas = matrix.update_on_vsync(canvas);
+
+        assert_eq!(matrix.snapshot(), pattern);
+    }
+}
@@ -1,10 +1,19 @@
-use std::{error::Error, str::FromStr};
+use alloc::{boxed::Box, format};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
-use crate::{gpio::Gpio, gpio_bits, RGBMatrixConfig};
+use crate::{gpio_bits, gpio_output::GpioOutput, RGBMatrixConfig};
 
+/// Panels with driver chips that need their registers to be initialized before they display anything. The
+/// initialization is run once when the [`RGBMatrix`](crate::RGBMatrix) is created.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PanelType {
+    /// Panels with FM6126 or FM6126A drivers. Also accepts `FM6126A` on the command line.
     FM6126,
+    /// Panels with FM6127 drivers.
     FM6127,
 }
 
@@ -13,24 +22,34 @@ impl FromStr for PanelType {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_uppercase().as_str() {
-            "FM6126" => Ok(Self::FM6126),
+            "FM6126" | "FM6126A" => Ok(Self::FM6126),
             "FM6127" => Ok(Self::FM6127),
             _ => Err(format!("'{s}' is not a valid panel type.").into()),
         }
     }
 }
 
+impl Display for PanelType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            PanelType::FM6126 => "FM6126",
+            PanelType::FM6127 => "FM6127",
+        })
+    }
+}
+
 impl PanelType {
-    pub(crate) fn run_init_sequence(&self, gpio: &mut Gpio, config: &RGBMatrixConfig) {
+    pub(crate) fn run_init_sequence(&self, gpio: &mut dyn GpioOutput, config: &RGBMatrixConfig) {
         match self {
             Self::FM6126 => Self::init_fm6126(gpio, config),
             Self::FM6127 => Self::init_fm6127(gpio, config),
         }
     }
 
-    fn init_fm6126(gpio: &mut Gpio, config: &RGBMatrixConfig) {
+    fn init_fm6126(gpio: &mut dyn GpioOutput, config: &RGBMatrixConfig) {
         let hm = &config.hardware_mapping;
-        let columns = config.cols;
+        // The registers of all chained panels are written at once.
+        let columns = config.chain_width();
         let bits_on = hm.panels.used_bits() | hm.a;
         let bits_off = hm.a;
         let mask = bits_on | hm.strobe;
@@ -46,7 +65,7 @@ impl PanelType {
             } else {
                 bits_on
             };
-            if c > columns - 12 {
+            if c + 12 > columns {
                 value |= hm.strobe
             };
             gpio.write_masked_bits(value, mask);
@@ -61,7 +80,7 @@ impl PanelType {
             } else {
                 bits_on
             };
-            if c > columns - 13 {
+            if c + 13 > columns {
                 value |= hm.strobe
             };
             gpio.write_masked_bits(value, mask);
@@ -73,9 +92,10 @@ impl PanelType {
 
     /// The FM6217 is very similar to the FM6216. FM6217 adds Register 3 to allow for automatic bad pixel
     /// suppression.
-    fn init_fm6127(gpio: &mut Gpio, config: &RGBMatrixConfig) {
+    fn init_fm6127(gpio: &mut dyn GpioOutput, config: &RGBMatrixConfig) {
         let hm = &config.hardware_mapping;
-        let columns = config.cols;
+        // The registers of all chained panels are written at once.
+        let columns = config.chain_width();
         let bits_on = hm.panels.color_bits[0].used_bits() | hm.a;
         let bits_off = 0;
         let mask = bits_on | hm.strobe;
@@ -92,7 +112,7 @@ impl PanelType {
             } else {
                 bits_on
             };
-            if c > columns - 12 {
+            if c + 12 > columns {
                 value |= hm.strobe
             };
             gpio.write_masked_bits(value, mask);
@@ -107,7 +127,7 @@ impl PanelType {
             } else {
                 bits_on
             };
-            if c > columns - 13 {
+            if c + 13 > columns {
                 value |= hm.strobe
             };
             gpio.write_masked_bits(value, mask);
@@ -122,7 +142,7 @@ impl PanelType {
             } else {
                 bits_on
             };
-            if c > columns - 11 {
+            if c + 11 > columns {
                 value |= hm.strobe
             };
             gpio.write_masked_bits(value, mask);
@@ -132,3 +152,76 @@ impl PanelType {
         gpio.clear_bits(hm.strobe);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::*;
+    use crate::gpio_output::RecordingGpio;
+
+    /// The register values that are clocked in, split where the strobe is released.
+    fn written_registers(gpio: &RecordingGpio, config: &RGBMatrixConfig) -> Vec<Vec<bool>> {
+        let hm = &config.hardware_mapping;
+        let data = hm.panels.color_bits[0].r1;
+        let mut registers = vec![vec![]];
+        let mut strobe = false;
+        for state in gpio.clocked_states(hm.clock) {
+            // The register is latched when the strobe is released after being held for the last columns.
+            if strobe && state & hm.strobe == 0 {
+                registers.push(vec![]);
+            }
+            strobe = state & hm.strobe != 0;
+            registers.last_mut().unwrap().push(state & data != 0);
+        }
+        registers
+    }
+
+    #[test]
+    fn test_fm6126_init_sequence() {
+        let config = RGBMatrixConfig {
+            rows: 32,
+            cols: 32,
+            chain_length: 2,
+            panel_type: Some(PanelType::FM6126),
+            ..Default::default()
+        };
+        let mut gpio = RecordingGpio::default();
+        PanelType::FM6126.run_init_sequence(&mut gpio, &config);
+
+        let registers = written_registers(&gpio, &config);
+        // Two registers are written to all columns of the chain.
+        assert_eq!(registers.len(), 2);
+        assert!(registers.iter().all(|register| register.len() == 64));
+        let expected = |value: u16| {
+            (0..64)
+                .map(|c| value & (1 << (c % 16)) != 0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(registers[0], expected(0b0111111111111111));
+        assert_eq!(registers[1], expected(0b0000000001000000));
+        // The strobe is released at the end.
+        assert_eq!(gpio.bits & config.hardware_mapping.strobe, 0);
+    }
+
+    #[test]
+    fn test_fm6127_init_sequence() {
+        let config = RGBMatrixConfig {
+            rows: 32,
+            cols: 64,
+            ..Default::default()
+        };
+        let mut gpio = RecordingGpio::default();
+        PanelType::FM6127.run_init_sequence(&mut gpio, &config);
+
+        let registers = written_registers(&gpio, &config);
+        assert_eq!(registers.len(), 3);
+        assert!(registers.iter().all(|register| register.len() == 64));
+    }
+
+    #[test]
+    fn test_parse_fm6126a() {
+        assert_eq!("FM6126A".parse::<PanelType>().unwrap(), PanelType::FM6126);
+        assert_eq!("fm6127".parse::<PanelType>().unwrap(), PanelType::FM6127);
+    }
+}
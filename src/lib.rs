@@ -1,24 +1,98 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+// Without the standard library, the code that scans the bit planes out to the GPIO pins is not compiled.
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
+
+extern crate alloc;
+
+mod bitplane_packing;
 mod canvas;
 mod chip;
+#[cfg(feature = "clap")]
+mod clap_args;
+#[cfg(feature = "std")]
+mod clock;
 mod color;
 mod config;
+mod config_builder;
+mod dithering;
+mod flags;
+mod font;
+#[cfg(feature = "std")]
+mod frame_exchange;
+#[cfg(feature = "gif")]
+mod frame_recorder;
+mod frame_stats;
+#[cfg(feature = "std")]
 mod gpio;
+mod gpio_output;
 mod hardware_mapping;
+#[cfg(feature = "image")]
+mod image_support;
 mod init_sequence;
+mod macros;
+mod math;
+#[cfg(feature = "std")]
+mod mock_backend;
 mod multiplex_mapper;
+mod named_colors;
 mod named_pixel_mapper;
+#[cfg(feature = "serde")]
+mod panel_layout;
+#[cfg(feature = "std")]
+mod panic_guard;
+#[cfg(feature = "std")]
 mod pin_pulser;
 mod pixel_mapper;
+#[cfg(feature = "preview")]
+mod preview;
+mod primitives;
+#[cfg(feature = "std")]
 mod registers;
+#[cfg(feature = "std")]
 mod rgb_matrix;
 mod row_address_setter;
+mod self_test;
+#[cfg(feature = "serde")]
+mod serde_support;
+mod sprite;
+#[cfg(test)]
+mod test_utils;
+#[cfg(feature = "std")]
+mod threaded_matrix;
+#[cfg(feature = "std")]
 mod utils;
 
-pub use canvas::{Canvas, LedSequence};
+pub use canvas::{Canvas, CanvasError, LedSequence, PowerProfile};
 pub use chip::PiChip;
-pub use config::RGBMatrixConfig;
-pub use hardware_mapping::HardwareMapping;
+#[cfg(feature = "clap")]
+pub use clap_args::ConfigArgs;
+#[cfg(feature = "std")]
+pub use clock::{Clock, FakeClock, SystemClock};
+pub use color::{Color, Color16, ColorParseError, WhiteBalance};
+pub use config::{ConfigError, PanelSpec, RGBMatrixConfig};
+pub use config_builder::RGBMatrixConfigBuilder;
+pub use dithering::Dithering;
+pub use flags::FlagError;
+pub use font::{Font, FontError, Glyph};
+#[cfg(feature = "gif")]
+pub use frame_recorder::{FrameRecorder, RecordingError};
+pub use frame_stats::FrameStats;
+pub use hardware_mapping::{ChainPins, HardwareMapping, HardwareMappingError};
 pub use init_sequence::PanelType;
 pub use multiplex_mapper::MultiplexMapperType;
+pub use named_pixel_mapper::{
+    GridLayout, NamedPixelMapper, NamedPixelMapperType, PixelMapperError, PixelMapperParseError,
+};
+#[cfg(feature = "serde")]
+pub use panel_layout::{PanelLayout, PanelLayoutError, PanelPlacement};
+#[cfg(feature = "std")]
+pub use panic_guard::PanicGuard;
+#[cfg(feature = "preview")]
+pub use preview::{PreviewError, PreviewMatrix};
+#[cfg(feature = "std")]
 pub use rgb_matrix::RGBMatrix;
 pub use row_address_setter::RowAddressSetterType;
+pub use self_test::SelfTestPattern;
+pub use sprite::{Sprite, SpriteError};
+#[cfg(feature = "std")]
+pub use threaded_matrix::ThreadedMatrix;
@@ -0,0 +1,20 @@
+/// Sets the bits that are passed as arguments.
+#[macro_export]
+macro_rules! gpio_bits {
+    ( $( $x:expr ),* ) => {
+        {
+            0
+            $(
+                | (1 << $x)
+            )*
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_gpio_bits() {
+        assert_eq!(gpio_bits!(1, 4, 5), 1 << 1 | 1 << 4 | 1 << 5);
+    }
+}
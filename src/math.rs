@@ -0,0 +1,204 @@
+/// Round half away from zero, like [`f32::round`].
+pub(crate) fn round(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.round()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        fallback::round(x)
+    }
+}
+
+/// The least non-negative remainder, like [`f32::rem_euclid`].
+pub(crate) fn rem_euclid(x: f32, divisor: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.rem_euclid(divisor)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        fallback::rem_euclid(x, divisor)
+    }
+}
+
+/// Raise a non-negative base to a power, like [`f32::powf`].
+pub(crate) fn powf(base: f32, exponent: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        base.powf(exponent)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        fallback::powf(base, exponent)
+    }
+}
+
+/// The natural logarithm, like [`f32::ln`].
+pub(crate) fn ln(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.ln()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        fallback::ln_f32(x)
+    }
+}
+
+/// Implementations for targets without the standard library, where `core` lacks these functions. They are
+/// computed in double precision, which makes them exact or accurate to the last bit of the `f32` result for the
+/// values used by this crate.
+#[cfg(any(not(feature = "std"), test))]
+mod fallback {
+    use core::f64::consts::LN_2;
+
+    /// Every `f32` of at least this magnitude is an integer.
+    const MIN_INTEGER: f32 = 8_388_608.0;
+
+    pub(super) fn round(x: f32) -> f32 {
+        if x.is_nan() || x.abs() >= MIN_INTEGER {
+            return x;
+        }
+        let rounded = (x.abs() as f64 + 0.5) as u32 as f32;
+        if x < 0.0 {
+            -rounded
+        } else {
+            rounded
+        }
+    }
+
+    pub(super) fn rem_euclid(x: f32, divisor: f32) -> f32 {
+        let remainder = x % divisor;
+        if remainder < 0.0 {
+            remainder + divisor.abs()
+        } else {
+            remainder
+        }
+    }
+
+    pub(super) fn powf(base: f32, exponent: f32) -> f32 {
+        if exponent == 0.0 || base == 1.0 {
+            return 1.0;
+        }
+        if base == 0.0 {
+            return if exponent > 0.0 { 0.0 } else { f32::INFINITY };
+        }
+        if base < 0.0 || !base.is_finite() || !exponent.is_finite() {
+            return f32::NAN;
+        }
+        exp(exponent as f64 * ln(base as f64)) as f32
+    }
+
+    pub(super) fn ln_f32(x: f32) -> f32 {
+        if x == 0.0 {
+            return f32::NEG_INFINITY;
+        }
+        if x < 0.0 || x.is_nan() {
+            return f32::NAN;
+        }
+        if x == f32::INFINITY {
+            return x;
+        }
+        ln(x as f64) as f32
+    }
+
+    /// The natural logarithm of a positive, finite number.
+    fn ln(x: f64) -> f64 {
+        // Split x into a mantissa in [1, 2) and a power of two, which is exact for normal numbers.
+        let bits = x.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+        let mantissa = f64::from_bits((bits & !(0x7ff << 52)) | (1023 << 52));
+        // ln(m) = 2 atanh(s) with s = (m - 1) / (m + 1) in [0, 1/3), whose series converges quickly.
+        let s = (mantissa - 1.0) / (mantissa + 1.0);
+        let s2 = s * s;
+        let mut term = s;
+        let mut sum = 0.0;
+        for k in 0..20 {
+            sum += term / (2 * k + 1) as f64;
+            term *= s2;
+        }
+        2.0 * sum + exponent as f64 * LN_2
+    }
+
+    /// The exponential function for arguments whose result is within the range of `f32`.
+    fn exp(x: f64) -> f64 {
+        if x < -200.0 {
+            return 0.0;
+        }
+        if x > 200.0 {
+            return f64::INFINITY;
+        }
+        // e^x = 2^k e^r with |r| <= ln(2) / 2, where the Taylor series of e^r converges quickly.
+        let k = (x / LN_2 + if x < 0.0 { -0.5 } else { 0.5 }) as i64;
+        let r = x - k as f64 * LN_2;
+        let mut term = 1.0;
+        let mut sum = 1.0;
+        for n in 1..20 {
+            term *= r / n as f64;
+            sum += term;
+        }
+        sum * f64::from_bits(((k + 1023) as u64) << 52)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_round() {
+        for x in [
+            0.0, 0.4, 0.5, 0.49999997, 1.5, 2.5, -0.5, -1.4, -2.5, 254.5, 1e9, -1e9,
+        ] {
+            assert_eq!(fallback::round(x), x.round(), "{x}");
+        }
+    }
+
+    #[test]
+    fn test_fallback_rem_euclid() {
+        for x in [0.0, 1.5, 59.0, 360.0, 725.0, -30.0, -360.0, -1e-3] {
+            assert_eq!(fallback::rem_euclid(x, 360.0), x.rem_euclid(360.0), "{x}");
+        }
+        assert_eq!(fallback::rem_euclid(-7.0, 6.0), 5.0);
+    }
+
+    #[test]
+    fn test_fallback_powf() {
+        for gamma in [0.5, 1.0, 1.8, 2.2, 2.8, 4.0] {
+            for c in 0..=255 {
+                let base = c as f32 / 255.0;
+                let expected = base.powf(gamma);
+                let actual = fallback::powf(base, gamma);
+                assert!(
+                    (actual - expected).abs() <= expected * 1e-6,
+                    "{base} {gamma}"
+                );
+            }
+        }
+        assert_eq!(fallback::powf(0.0, 2.2), 0.0);
+        assert_eq!(fallback::powf(2.0, 10.0), 1024.0);
+        assert!(fallback::powf(-1.0, 0.5).is_nan());
+    }
+
+    #[test]
+    fn test_fallback_ln() {
+        for x in [1e-3f32, 0.5, 1.0, 2.0, 10.0, 66.0, 400.0, 1e6] {
+            let expected = x.ln();
+            assert!(
+                (fallback::ln_f32(x) - expected).abs() <= expected.abs() * 1e-6,
+                "{x}"
+            );
+        }
+        assert_eq!(fallback::ln_f32(0.0), f32::NEG_INFINITY);
+        assert!(fallback::ln_f32(-1.0).is_nan());
+    }
+
+    #[test]
+    fn test_std_functions() {
+        assert_eq!(round(2.5), 3.0);
+        assert_eq!(rem_euclid(-1.0, 6.0), 5.0);
+        assert_eq!(powf(4.0, 0.5), 2.0);
+        assert_eq!(ln(1.0), 0.0);
+    }
+}
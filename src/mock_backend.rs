@@ -0,0 +1,389 @@
+use std::{
+    sync::mpsc::{Receiver, TryRecvError},
+    time::Duration,
+};
+
+use crate::{
+    frame_exchange::FrameExchange,
+    gpio_output::{bitplane_timings_ns, GpioOutput, ScanOutput},
+    utils::{interrupt_received, set_thread_affinity},
+    Clock, FrameStats, RGBMatrixConfig,
+};
+
+/// A rough duration of a GPIO register write, as on a Raspberry Pi 3 without slowdown.
+const SIMULATED_WRITE_NS: u64 = 15;
+
+/// Scans out frames to nowhere, advancing a simulated clock by the time that the GPIO writes and output enable
+/// pulses would take on the hardware. Pulses are timed like by the PWM peripheral, so the next bit plane is
+/// clocked in while the previous pulse is still running.
+pub(crate) struct SimulatedOutput {
+    /// The simulated time in nanoseconds.
+    time_ns: u64,
+    /// The duration of a GPIO write, including the slowdown.
+    write_ns: u64,
+    /// The end of the current pulse.
+    pulse_end_ns: u64,
+    bitplane_timings_ns: Vec<u32>,
+}
+
+impl SimulatedOutput {
+    pub(crate) fn new(config: &RGBMatrixConfig) -> Self {
+        let slowdown = config
+            .slowdown
+            .or(config.pi_chip.map(|chip| chip.gpio_slowdown()))
+            .unwrap_or(1);
+        Self {
+            time_ns: 0,
+            write_ns: SIMULATED_WRITE_NS * (slowdown as u64 + 1),
+            pulse_end_ns: 0,
+            bitplane_timings_ns: bitplane_timings_ns(config),
+        }
+    }
+}
+
+impl GpioOutput for SimulatedOutput {
+    fn set_bits(&mut self, _value: u32) {
+        self.time_ns += self.write_ns;
+    }
+
+    fn clear_bits(&mut self, _value: u32) {
+        self.time_ns += self.write_ns;
+    }
+}
+
+impl ScanOutput for SimulatedOutput {
+    fn send_pulse(&mut self, bitplane: usize) {
+        self.pulse_end_ns = self.time_ns + self.bitplane_timings_ns[bitplane] as u64;
+    }
+
+    fn wait_pulse_finished(&mut self) {
+        self.time_ns = self.time_ns.max(self.pulse_end_ns);
+    }
+
+    fn time_ns(&self) -> u64 {
+        self.time_ns
+    }
+}
+
+/// The update loop of a matrix created with [`crate::RGBMatrix::new_mock`]. It picks up queued canvases at the
+/// refresh rate like the hardware update thread, but keeps the displayed canvas in memory instead of
+/// outputting it. The frames are scanned out to a [`SimulatedOutput`], so the frame statistics show the time
+/// the output would take. The thread is only pinned to a core if one is configured and keeps the default
+/// priority. Frames are paced with the given clock.
+pub(crate) fn run_mock_update_thread(
+    config: &RGBMatrixConfig,
+    frame_exchange: &FrameExchange,
+    shutdown_receiver: &Receiver<()>,
+    clock: &dyn Clock,
+) {
+    if let Some(core_id) = config.cpu_affinity {
+        if !set_thread_affinity(core_id) {
+            eprintln!("Could not pin the update thread to core {core_id}.");
+        }
+    }
+    let frame_time_target = Duration::from_secs_f64(1.0 / config.frame_rate_target() as f64);
+
+    let mut output = SimulatedOutput::new(config);
+    let mut address_setter = config.row_setter.create(config);
+    let color_clk_mask = config
+        .hardware_mapping
+        .get_color_clock_mask(config.parallel);
+    // Selects the dither phase of the canvas.
+    let mut frame = 0usize;
+
+    let shutdown_requested =
+        || shutdown_receiver.try_recv() != Err(TryRecvError::Empty) || interrupt_received();
+
+    'frames: loop {
+        let start_time = clock.now();
+        // Try to receive a shutdown request.
+        if shutdown_requested() {
+            break;
+        }
+        frame_exchange.pick_up();
+        let stats = if frame_exchange.is_enabled() {
+            let stats = frame_exchange.with_displayed(|canvas| {
+                canvas.dump_to_matrix(
+                    &mut output,
+                    &config.hardware_mapping,
+                    address_setter.as_mut(),
+                    frame,
+                    color_clk_mask,
+                )
+            });
+            frame = frame.wrapping_add(1);
+            stats
+        } else {
+            FrameStats::default()
+        };
+        frame_exchange.frame_finished(stats);
+
+        // Sleep for the rest of the frame. The clock may wake up early, e.g. when it is not advanced, so a
+        // shutdown request is not missed.
+        let deadline = start_time + frame_time_target;
+        while clock.now() < deadline {
+            if shutdown_requested() {
+                break 'frames;
+            }
+            clock.sleep_until(deadline);
+        }
+    }
+
+    // Turn it off like the hardware update thread.
+    let stats = frame_exchange.with_displayed(|canvas| {
+        canvas.clear();
+        canvas.dump_to_matrix(
+            &mut output,
+            &config.hardware_mapping,
+            address_setter.as_mut(),
+            0,
+            color_clk_mask,
+        )
+    });
+    frame_exchange.frame_finished(stats);
+    frame_exchange.close();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::atomic::Ordering, sync::Arc, time::Duration};
+
+    use crate::{
+        test_utils::{count_frames, mock_matrix, test_config, wait_until},
+        Clock, Color, FakeClock, RGBMatrix, RGBMatrixConfig,
+    };
+
+    #[test]
+    fn test_update_on_vsync() {
+        let (mut matrix, mut canvas) = mock_matrix();
+        assert!(matrix
+            .get_frame()
+            .iter()
+            .all(|&color| color == Color::BLACK));
+
+        canvas.set_pixel(0, 0, 255, 0, 0);
+        canvas.set_pixel(31, 15, 0, 0, 255);
+        let canvas = matrix.update_on_vsync(canvas);
+
+        let frame = matrix.get_frame();
+        assert_eq!(frame.len(), 32 * 16);
+        assert_eq!(frame[0], Color::RED);
+        assert_eq!(frame[32 * 16 - 1], Color::BLUE);
+        assert_eq!(
+            frame.iter().filter(|&&color| color != Color::BLACK).count(),
+            2
+        );
+
+        // The returned canvas is the one that was displayed before.
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn test_swap_and_frame_callbacks() {
+        let (mut matrix, mut canvas) = mock_matrix();
+        let frames = count_frames(&mut matrix);
+
+        canvas.fill(Color::GREEN);
+        let mut canvas = matrix.swap(canvas);
+        canvas.fill(Color::WHITE);
+        // Waiting for the next frame makes sure the swapped canvas was picked up.
+        let _canvas = matrix.update_on_vsync(canvas);
+
+        assert!(matrix
+            .get_frame()
+            .iter()
+            .all(|&color| color == Color::WHITE));
+        // The frame callbacks run after the canvas was picked up.
+        wait_until(|| frames.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn test_mapped_frame() {
+        let config = RGBMatrixConfig {
+            pixelmapper: vec!["Rotate:90".parse().unwrap()],
+            ..test_config()
+        };
+        let (mut matrix, mut canvas) = RGBMatrix::new_mock(config).unwrap();
+        assert_eq!([canvas.width(), canvas.height()], [16, 32]);
+
+        canvas.set_pixel(15, 31, 255, 255, 255);
+        let _canvas = matrix.update_on_vsync(canvas);
+
+        // The frame is in the visible coordinates of the canvas.
+        let frame = matrix.get_frame();
+        assert_eq!(frame.len(), 16 * 32);
+        assert_eq!(frame[16 * 32 - 1], Color::WHITE);
+    }
+
+    #[test]
+    fn test_dithering_reduces_banding() {
+        let config = RGBMatrixConfig {
+            pwm_bits: 3,
+            dither_bits: 2,
+            gamma: 1.0,
+            ..test_config()
+        };
+        let (mut matrix, mut canvas) = RGBMatrix::new_mock(config).unwrap();
+        canvas.fill_gradient_horizontal(Color::BLACK, Color::new(128, 0, 0));
+        let _canvas = matrix.update_on_vsync(canvas);
+
+        let distinct_levels = |levels: &[u16]| {
+            let mut levels = levels.to_vec();
+            levels.dedup();
+            levels.len()
+        };
+        let red_levels = |frame| -> Vec<u16> {
+            matrix.output_levels(frame)[..32]
+                .iter()
+                .map(|[red, _, _]| *red)
+                .collect()
+        };
+        let single_frame = red_levels(0);
+        let averaged = (0..4).map(red_levels).fold(vec![0; 32], |sum, levels| {
+            sum.iter().zip(levels).map(|(a, b)| a + b).collect()
+        });
+        assert!(distinct_levels(&averaged) > distinct_levels(&single_frame));
+        assert!(averaged.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_frame_stats() {
+        let config = RGBMatrixConfig {
+            pwm_lsb_nanoseconds: 200,
+            slowdown: Some(2),
+            ..test_config()
+        };
+        let (mut matrix, canvas) = RGBMatrix::new_mock(config).unwrap();
+        let canvas = matrix.update_on_vsync(canvas);
+        let _canvas = matrix.update_on_vsync(canvas);
+        let stats = matrix.frame_stats();
+
+        assert_eq!(stats.bitplanes, 11);
+        assert_eq!(stats.scan_time, stats.clock_in_time + stats.pulse_time);
+        // Every column of the 8 double rows and 11 bit planes takes 3 writes.
+        let writes_ns = 8 * 11 * (32 * 3 + 1) * super::SIMULATED_WRITE_NS * 3;
+        assert_eq!(stats.clock_in_time.as_nanos() as u64, writes_ns);
+        // The pulses of all bit planes of each row have to finish, except the last one of the frame.
+        let row_pulses_ns: u64 = (0..11).map(|plane| 200 << plane).sum();
+        let pulses_ns = 8 * row_pulses_ns;
+        let scan_ns = stats.scan_time.as_nanos() as u64;
+        assert!(scan_ns >= pulses_ns - (200 << 10), "{scan_ns}");
+        assert!(scan_ns <= pulses_ns + writes_ns * 2, "{scan_ns}");
+    }
+
+    #[test]
+    fn test_pwm_lsb_nanoseconds() {
+        // The highest refresh rate that the simulated scan time allows.
+        let framerate = |pwm_lsb_nanoseconds| {
+            let config = RGBMatrixConfig {
+                pwm_lsb_nanoseconds,
+                ..test_config()
+            };
+            let (mut matrix, canvas) = RGBMatrix::new_mock(config).unwrap();
+            let canvas = matrix.update_on_vsync(canvas);
+            let _canvas = matrix.update_on_vsync(canvas);
+            1.0 / matrix.frame_stats().scan_time.as_secs_f64()
+        };
+        let fast = framerate(100);
+        let slow = framerate(400);
+        assert!(slow < fast, "{slow} {fast}");
+        // The pulses take most of the time, so the refresh rate drops almost in proportion.
+        assert!(slow < fast / 3.0, "{slow} {fast}");
+    }
+
+    #[test]
+    fn test_set_enabled() {
+        let (mut matrix, mut canvas) = mock_matrix();
+        canvas.fill(Color::RED);
+        let mut canvas = matrix.update_on_vsync(canvas);
+
+        matrix.set_enabled(false);
+        assert!(!matrix.is_enabled());
+        // Canvases are still picked up while the display is off.
+        for _ in 0..2 {
+            canvas.fill(Color::BLUE);
+            canvas = matrix.update_on_vsync(canvas);
+        }
+        assert!(matrix
+            .get_frame()
+            .iter()
+            .all(|&color| color == Color::BLACK));
+        let stats = matrix.frame_stats();
+        assert_eq!(stats.bitplanes, 0);
+        assert_eq!(stats.scan_time, Duration::ZERO);
+
+        matrix.set_enabled(true);
+        assert!(matrix.get_frame().iter().all(|&color| color == Color::BLUE));
+        wait_until(|| matrix.frame_stats().bitplanes == 11);
+    }
+
+    #[test]
+    fn test_drop_blanks_display() {
+        let (mut matrix, mut canvas) = mock_matrix();
+        canvas.fill(Color::WHITE);
+        let _canvas = matrix.update_on_vsync(canvas);
+        let frame_exchange = matrix.frame_exchange();
+        let frames = count_frames(&mut matrix);
+        let frames_before_drop = frames.load(Ordering::Relaxed);
+
+        drop(matrix);
+        // The update thread has finished with a black frame that was output in full.
+        assert!(frames.load(Ordering::Relaxed) > frames_before_drop);
+        assert!(frame_exchange
+            .displayed_colors()
+            .iter()
+            .all(|&color| color == Color::BLACK));
+        assert_eq!(frame_exchange.frame_stats().bitplanes, 11);
+        assert!(frame_exchange
+            .with_displayed(|canvas| canvas.lit_pixels())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_measured_refresh_rate() {
+        for (refresh_rate_limit, expected) in
+            [(None, 200.0), (Some(500), 200.0), (Some(100), 100.0)]
+        {
+            let config = RGBMatrixConfig {
+                refresh_rate: 200,
+                refresh_rate_limit,
+                ..test_config()
+            };
+            let clock = Arc::new(FakeClock::new());
+            let (matrix, _canvas) =
+                RGBMatrix::new_mock_with_clock(config, Arc::clone(&clock) as Arc<dyn Clock>)
+                    .unwrap();
+            clock.wait_for_sleeper();
+            // Advance the clock in steps shorter than the frame time for more frames than are averaged. The
+            // update thread idles between the frames to not exceed the refresh rate and its limit.
+            for _ in 0..1000 {
+                clock.advance(Duration::from_millis(1));
+                clock.wait_for_sleeper();
+            }
+            let framerate = matrix.get_framerate();
+            assert!((framerate - expected).abs() < 0.5, "{framerate}");
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cpu_affinity() {
+        let config = RGBMatrixConfig {
+            cpu_affinity: Some(0),
+            ..test_config()
+        };
+        let (mut matrix, _canvas) = RGBMatrix::new_mock(config).unwrap();
+        // Frame callbacks run on the update thread, so they can read its affinity mask.
+        let (sender, receiver) = std::sync::mpsc::channel();
+        matrix.on_frame(move || {
+            let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+            let size = std::mem::size_of::<libc::cpu_set_t>();
+            assert_eq!(unsafe { libc::sched_getaffinity(0, size, &mut set) }, 0);
+            let cores = (0..libc::CPU_SETSIZE as usize)
+                .filter(|&core| unsafe { libc::CPU_ISSET(core, &set) })
+                .collect::<Vec<_>>();
+            let _ = sender.send(cores);
+        });
+        assert_eq!(receiver.recv().unwrap(), [0]);
+    }
+}
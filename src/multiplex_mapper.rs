@@ -1,7 +1,24 @@
-use std::{error::Error, str::FromStr};
-
+use alloc::{boxed::Box, format};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// The multiplexing of panels whose pixels are not wired in the order in which the rows are scanned, selected
+/// with [`RGBMatrixConfig::multiplexing`](crate::RGBMatrixConfig::multiplexing). Panels with a direct 1:N scan,
+/// where N is half the number of rows, don't need a multiplexing mapper.
+///
+/// The names and mappings are the same as the `--led-multiplexing` options of the original library.
+///
+/// Panels that light more rows per scan line than the upper and lower half of a direct scan show the image split
+/// into blocks. E.g. a 64x64 outdoor panel with only the address lines A to D has 16 scan lines for 64 rows, so
+/// every scan line lights four rows, two in each half. Those panels usually need [`MultiplexMapperType::Stripe`]
+/// or [`MultiplexMapperType::FlippedStripe`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MultiplexMapperType {
+    /// Each scan line lights two rows in each half of the panel, one in the upper and one in the lower quarter of
+    /// the half. The shift registers hold the row of the upper quarter after the one of the lower quarter.
     Stripe,
     Checkered,
     Spiral,
@@ -18,6 +35,7 @@ pub enum MultiplexMapperType {
     P10Outdoor1R1G1B3,
     P10Coreman,
     P8Outdoor1R1G1B,
+    /// Like [`MultiplexMapperType::Stripe`], but with the row of the upper quarter first in the shift registers.
     FlippedStripe,
     P10Outdoor32x16HalfScan,
 }
@@ -50,6 +68,31 @@ impl FromStr for MultiplexMapperType {
     }
 }
 
+impl Display for MultiplexMapperType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            MultiplexMapperType::Stripe => "Stripe",
+            MultiplexMapperType::Checkered => "Checkered",
+            MultiplexMapperType::Spiral => "Spiral",
+            MultiplexMapperType::ZStripe08 => "ZStripe08",
+            MultiplexMapperType::ZStripe44 => "ZStripe44",
+            MultiplexMapperType::ZStripe80 => "ZStripe80",
+            MultiplexMapperType::Coreman => "Coreman",
+            MultiplexMapperType::Kaler2Scan => "Kaler2Scan",
+            MultiplexMapperType::P10Z => "P10Z",
+            MultiplexMapperType::QiangLiQ8 => "QiangLiQ8",
+            MultiplexMapperType::InversedZStripe => "InversedZStripe",
+            MultiplexMapperType::P10Outdoor1R1G1B1 => "P10Outdoor1R1G1B1",
+            MultiplexMapperType::P10Outdoor1R1G1B2 => "P10Outdoor1R1G1B2",
+            MultiplexMapperType::P10Outdoor1R1G1B3 => "P10Outdoor1R1G1B3",
+            MultiplexMapperType::P10Coreman => "P10Coreman",
+            MultiplexMapperType::P8Outdoor1R1G1B => "P8Outdoor1R1G1B",
+            MultiplexMapperType::FlippedStripe => "FlippedStripe",
+            MultiplexMapperType::P10Outdoor32x16HalfScan => "P10Outdoor32x16HalfScan",
+        })
+    }
+}
+
 impl MultiplexMapperType {
     pub(crate) fn create(&self) -> Box<dyn MultiplexMapper> {
         match self {
@@ -660,7 +703,7 @@ impl MultiplexMapper for InversedZStripe {
         let tile_width = 8;
         let tile_height = 4;
 
-        let vert_block_is_even = (y / tile_height) % 2 == 0;
+        let vert_block_is_even = (y / tile_height).is_multiple_of(2);
         let even_offset: [usize; 8] = [15, 13, 11, 9, 7, 5, 3, 1];
 
         let matrix_x = x
@@ -725,7 +768,7 @@ impl MultiplexMapper for P10Outdoor1R1G1BMultiplexMapper1 {
     }
 
     fn map_single_panel(&self, x: usize, y: usize) -> [usize; 2] {
-        let vblock_is_even = (y / P10_TILE_HEIGHT) % 2 == 0;
+        let vblock_is_even = (y / P10_TILE_HEIGHT).is_multiple_of(2);
 
         let matrix_x = P10_TILE_WIDTH * (1 + vblock_is_even as usize + 2 * (x / P10_TILE_WIDTH))
             - (x % P10_TILE_WIDTH)
@@ -774,7 +817,7 @@ impl MultiplexMapper for P10Outdoor1R1G1BMultiplexMapper2 {
     }
 
     fn map_single_panel(&self, x: usize, y: usize) -> [usize; 2] {
-        let vblock_is_even = (y / P10_TILE_HEIGHT) % 2 == 0;
+        let vblock_is_even = (y / P10_TILE_HEIGHT).is_multiple_of(2);
         let even_vblock_shift = vblock_is_even as usize * P10_EVEN_VBLOCK_OFFSET;
         let odd_vblock_shift = (!vblock_is_even) as usize * P10_ODD_VBLOCK_OFFSET;
 
@@ -827,7 +870,7 @@ impl MultiplexMapper for P10Outdoor1R1G1BMultiplexMapper3 {
     }
 
     fn map_single_panel(&self, x: usize, y: usize) -> [usize; 2] {
-        let vblock_is_even = (y / P10_TILE_HEIGHT) % 2 == 0;
+        let vblock_is_even = (y / P10_TILE_HEIGHT).is_multiple_of(2);
         let even_vblock_shift = vblock_is_even as usize * P10_EVEN_VBLOCK_OFFSET;
         let odd_vblock_shift = (!vblock_is_even) as usize * P10_ODD_VBLOCK_OFFSET;
 
@@ -937,12 +980,12 @@ impl MultiplexMapper for P10Outdoor32x16HalfScanMapper {
         let dx = x % 8;
 
         let matrix_y = if y / 8 == 0 {
-            if y % 2 == 0 {
+            if y.is_multiple_of(2) {
                 0
             } else {
                 1
             }
-        } else if y % 2 == 0 {
+        } else if y.is_multiple_of(2) {
             2
         } else {
             3
@@ -1002,7 +1045,7 @@ impl MultiplexMapper for P8Outdoor1R1G1BMultiplexMapper {
     }
 
     fn map_single_panel(&self, x: usize, y: usize) -> [usize; 2] {
-        let vblock_is_even = (y / P10_TILE_HEIGHT) % 2 == 0;
+        let vblock_is_even = (y / P10_TILE_HEIGHT).is_multiple_of(2);
         let matrix_x = if vblock_is_even {
             P8_TILE_WIDTH * (1 + P8_TILE_WIDTH - 2 * (x / P8_TILE_WIDTH)) + P8_TILE_WIDTH
                 - (x % P8_TILE_WIDTH)
@@ -1018,3 +1061,82 @@ impl MultiplexMapper for P8Outdoor1R1G1BMultiplexMapper {
         [matrix_x, matrix_y]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    /// Create a mapper for a chain of two 32x16 panels, returning it with the matrix size.
+    fn create_mapper(mapper_type: MultiplexMapperType) -> (Box<dyn MultiplexMapper>, [usize; 2]) {
+        let mut mapper = mapper_type.create();
+        let [mut rows, mut cols] = [16, 32];
+        mapper.edit_rows_cols(&mut rows, &mut cols);
+        (mapper, [2 * cols, rows])
+    }
+
+    #[test]
+    fn test_stripe() {
+        let (mapper, [width, height]) = create_mapper(MultiplexMapperType::Stripe);
+        // The panel is scanned as half as many rows of twice the width.
+        assert_eq!([width, height], [128, 8]);
+        assert_eq!(mapper.get_size_mapping(width, height), [64, 16]);
+
+        let map = |x, y| mapper.map_visible_to_matrix(width, height, x, y);
+        // The upper quarter of each half is shifted right by a panel width.
+        assert_eq!(map(3, 2), [35, 2]);
+        assert_eq!(map(3, 5), [3, 1]);
+        assert_eq!(map(3, 9), [35, 5]);
+        assert_eq!(map(3, 13), [3, 5]);
+        // Chained panels are placed next to each other in the stretched matrix.
+        assert_eq!(map(35, 2), [99, 2]);
+    }
+
+    #[test]
+    fn test_stripe_64x64_with_16_scan_lines() {
+        let mut mapper = MultiplexMapperType::Stripe.create();
+        let [mut rows, mut cols] = [64, 64];
+        mapper.edit_rows_cols(&mut rows, &mut cols);
+        // 16 scan lines, each outputting a row of the upper and lower half, with two rows of the panel each.
+        assert_eq!([rows / 2, cols], [16, 128]);
+        assert_eq!(mapper.get_size_mapping(cols, rows), [64, 64]);
+
+        // The scan line, whether the pixel is output on the lower half (R2, G2, B2), and its position in the
+        // shift registers.
+        let scan = |x, y| {
+            let [matrix_x, matrix_y] = mapper.map_visible_to_matrix(cols, rows, x, y);
+            (matrix_y % 16, matrix_y >= 16, matrix_x)
+        };
+        assert_eq!(scan(0, 0), (0, false, 64));
+        assert_eq!(scan(10, 5), (5, false, 74));
+        assert_eq!(scan(10, 21), (5, false, 10));
+        assert_eq!(scan(10, 37), (5, true, 74));
+        assert_eq!(scan(10, 53), (5, true, 10));
+        assert_eq!(scan(63, 63), (15, true, 63));
+
+        // Every pixel of the panel has its own position.
+        let mut positions: Vec<_> = (0..64)
+            .flat_map(|y| (0..64).map(move |x| [x, y]))
+            .map(|[x, y]| mapper.map_visible_to_matrix(cols, rows, x, y))
+            .collect();
+        positions.sort();
+        positions.dedup();
+        assert_eq!(positions.len(), 64 * 64);
+    }
+
+    #[test]
+    fn test_checkered() {
+        let (mapper, [width, height]) = create_mapper(MultiplexMapperType::Checkered);
+        assert_eq!([width, height], [128, 8]);
+
+        let map = |x, y| mapper.map_visible_to_matrix(width, height, x, y);
+        // Top checks are shifted right by half a panel, the right checks by another half.
+        assert_eq!(map(3, 2), [19, 2]);
+        assert_eq!(map(20, 2), [52, 2]);
+        assert_eq!(map(3, 5), [3, 1]);
+        assert_eq!(map(20, 5), [36, 1]);
+        assert_eq!(map(20, 12), [36, 4]);
+        assert_eq!(map(52, 5), [100, 1]);
+    }
+}
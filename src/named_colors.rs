@@ -0,0 +1,342 @@
+use crate::Color;
+
+/// The named colors from the CSS Color Module Level 4 specification.
+impl Color {
+    pub const ALICE_BLUE: Color = Color::new(0xf0, 0xf8, 0xff);
+    pub const ANTIQUE_WHITE: Color = Color::new(0xfa, 0xeb, 0xd7);
+    pub const AQUA: Color = Color::new(0x00, 0xff, 0xff);
+    pub const AQUAMARINE: Color = Color::new(0x7f, 0xff, 0xd4);
+    pub const AZURE: Color = Color::new(0xf0, 0xff, 0xff);
+    pub const BEIGE: Color = Color::new(0xf5, 0xf5, 0xdc);
+    pub const BISQUE: Color = Color::new(0xff, 0xe4, 0xc4);
+    pub const BLACK: Color = Color::new(0x00, 0x00, 0x00);
+    pub const BLANCHED_ALMOND: Color = Color::new(0xff, 0xeb, 0xcd);
+    pub const BLUE: Color = Color::new(0x00, 0x00, 0xff);
+    pub const BLUE_VIOLET: Color = Color::new(0x8a, 0x2b, 0xe2);
+    pub const BROWN: Color = Color::new(0xa5, 0x2a, 0x2a);
+    pub const BURLY_WOOD: Color = Color::new(0xde, 0xb8, 0x87);
+    pub const CADET_BLUE: Color = Color::new(0x5f, 0x9e, 0xa0);
+    pub const CHARTREUSE: Color = Color::new(0x7f, 0xff, 0x00);
+    pub const CHOCOLATE: Color = Color::new(0xd2, 0x69, 0x1e);
+    pub const CORAL: Color = Color::new(0xff, 0x7f, 0x50);
+    pub const CORNFLOWER_BLUE: Color = Color::new(0x64, 0x95, 0xed);
+    pub const CORNSILK: Color = Color::new(0xff, 0xf8, 0xdc);
+    pub const CRIMSON: Color = Color::new(0xdc, 0x14, 0x3c);
+    pub const CYAN: Color = Color::new(0x00, 0xff, 0xff);
+    pub const DARK_BLUE: Color = Color::new(0x00, 0x00, 0x8b);
+    pub const DARK_CYAN: Color = Color::new(0x00, 0x8b, 0x8b);
+    pub const DARK_GOLDENROD: Color = Color::new(0xb8, 0x86, 0x0b);
+    pub const DARK_GRAY: Color = Color::new(0xa9, 0xa9, 0xa9);
+    pub const DARK_GREEN: Color = Color::new(0x00, 0x64, 0x00);
+    pub const DARK_GREY: Color = Color::new(0xa9, 0xa9, 0xa9);
+    pub const DARK_KHAKI: Color = Color::new(0xbd, 0xb7, 0x6b);
+    pub const DARK_MAGENTA: Color = Color::new(0x8b, 0x00, 0x8b);
+    pub const DARK_OLIVE_GREEN: Color = Color::new(0x55, 0x6b, 0x2f);
+    pub const DARK_ORANGE: Color = Color::new(0xff, 0x8c, 0x00);
+    pub const DARK_ORCHID: Color = Color::new(0x99, 0x32, 0xcc);
+    pub const DARK_RED: Color = Color::new(0x8b, 0x00, 0x00);
+    pub const DARK_SALMON: Color = Color::new(0xe9, 0x96, 0x7a);
+    pub const DARK_SEA_GREEN: Color = Color::new(0x8f, 0xbc, 0x8f);
+    pub const DARK_SLATE_BLUE: Color = Color::new(0x48, 0x3d, 0x8b);
+    pub const DARK_SLATE_GRAY: Color = Color::new(0x2f, 0x4f, 0x4f);
+    pub const DARK_SLATE_GREY: Color = Color::new(0x2f, 0x4f, 0x4f);
+    pub const DARK_TURQUOISE: Color = Color::new(0x00, 0xce, 0xd1);
+    pub const DARK_VIOLET: Color = Color::new(0x94, 0x00, 0xd3);
+    pub const DEEP_PINK: Color = Color::new(0xff, 0x14, 0x93);
+    pub const DEEP_SKY_BLUE: Color = Color::new(0x00, 0xbf, 0xff);
+    pub const DIM_GRAY: Color = Color::new(0x69, 0x69, 0x69);
+    pub const DIM_GREY: Color = Color::new(0x69, 0x69, 0x69);
+    pub const DODGER_BLUE: Color = Color::new(0x1e, 0x90, 0xff);
+    pub const FIRE_BRICK: Color = Color::new(0xb2, 0x22, 0x22);
+    pub const FLORAL_WHITE: Color = Color::new(0xff, 0xfa, 0xf0);
+    pub const FOREST_GREEN: Color = Color::new(0x22, 0x8b, 0x22);
+    pub const FUCHSIA: Color = Color::new(0xff, 0x00, 0xff);
+    pub const GAINSBORO: Color = Color::new(0xdc, 0xdc, 0xdc);
+    pub const GHOST_WHITE: Color = Color::new(0xf8, 0xf8, 0xff);
+    pub const GOLD: Color = Color::new(0xff, 0xd7, 0x00);
+    pub const GOLDENROD: Color = Color::new(0xda, 0xa5, 0x20);
+    pub const GRAY: Color = Color::new(0x80, 0x80, 0x80);
+    pub const GREEN: Color = Color::new(0x00, 0x80, 0x00);
+    pub const GREEN_YELLOW: Color = Color::new(0xad, 0xff, 0x2f);
+    pub const GREY: Color = Color::new(0x80, 0x80, 0x80);
+    pub const HONEYDEW: Color = Color::new(0xf0, 0xff, 0xf0);
+    pub const HOT_PINK: Color = Color::new(0xff, 0x69, 0xb4);
+    pub const INDIAN_RED: Color = Color::new(0xcd, 0x5c, 0x5c);
+    pub const INDIGO: Color = Color::new(0x4b, 0x00, 0x82);
+    pub const IVORY: Color = Color::new(0xff, 0xff, 0xf0);
+    pub const KHAKI: Color = Color::new(0xf0, 0xe6, 0x8c);
+    pub const LAVENDER: Color = Color::new(0xe6, 0xe6, 0xfa);
+    pub const LAVENDER_BLUSH: Color = Color::new(0xff, 0xf0, 0xf5);
+    pub const LAWN_GREEN: Color = Color::new(0x7c, 0xfc, 0x00);
+    pub const LEMON_CHIFFON: Color = Color::new(0xff, 0xfa, 0xcd);
+    pub const LIGHT_BLUE: Color = Color::new(0xad, 0xd8, 0xe6);
+    pub const LIGHT_CORAL: Color = Color::new(0xf0, 0x80, 0x80);
+    pub const LIGHT_CYAN: Color = Color::new(0xe0, 0xff, 0xff);
+    pub const LIGHT_GOLDENROD_YELLOW: Color = Color::new(0xfa, 0xfa, 0xd2);
+    pub const LIGHT_GRAY: Color = Color::new(0xd3, 0xd3, 0xd3);
+    pub const LIGHT_GREEN: Color = Color::new(0x90, 0xee, 0x90);
+    pub const LIGHT_GREY: Color = Color::new(0xd3, 0xd3, 0xd3);
+    pub const LIGHT_PINK: Color = Color::new(0xff, 0xb6, 0xc1);
+    pub const LIGHT_SALMON: Color = Color::new(0xff, 0xa0, 0x7a);
+    pub const LIGHT_SEA_GREEN: Color = Color::new(0x20, 0xb2, 0xaa);
+    pub const LIGHT_SKY_BLUE: Color = Color::new(0x87, 0xce, 0xfa);
+    pub const LIGHT_SLATE_GRAY: Color = Color::new(0x77, 0x88, 0x99);
+    pub const LIGHT_SLATE_GREY: Color = Color::new(0x77, 0x88, 0x99);
+    pub const LIGHT_STEEL_BLUE: Color = Color::new(0xb0, 0xc4, 0xde);
+    pub const LIGHT_YELLOW: Color = Color::new(0xff, 0xff, 0xe0);
+    pub const LIME: Color = Color::new(0x00, 0xff, 0x00);
+    pub const LIME_GREEN: Color = Color::new(0x32, 0xcd, 0x32);
+    pub const LINEN: Color = Color::new(0xfa, 0xf0, 0xe6);
+    pub const MAGENTA: Color = Color::new(0xff, 0x00, 0xff);
+    pub const MAROON: Color = Color::new(0x80, 0x00, 0x00);
+    pub const MEDIUM_AQUAMARINE: Color = Color::new(0x66, 0xcd, 0xaa);
+    pub const MEDIUM_BLUE: Color = Color::new(0x00, 0x00, 0xcd);
+    pub const MEDIUM_ORCHID: Color = Color::new(0xba, 0x55, 0xd3);
+    pub const MEDIUM_PURPLE: Color = Color::new(0x93, 0x70, 0xdb);
+    pub const MEDIUM_SEA_GREEN: Color = Color::new(0x3c, 0xb3, 0x71);
+    pub const MEDIUM_SLATE_BLUE: Color = Color::new(0x7b, 0x68, 0xee);
+    pub const MEDIUM_SPRING_GREEN: Color = Color::new(0x00, 0xfa, 0x9a);
+    pub const MEDIUM_TURQUOISE: Color = Color::new(0x48, 0xd1, 0xcc);
+    pub const MEDIUM_VIOLET_RED: Color = Color::new(0xc7, 0x15, 0x85);
+    pub const MIDNIGHT_BLUE: Color = Color::new(0x19, 0x19, 0x70);
+    pub const MINT_CREAM: Color = Color::new(0xf5, 0xff, 0xfa);
+    pub const MISTY_ROSE: Color = Color::new(0xff, 0xe4, 0xe1);
+    pub const MOCCASIN: Color = Color::new(0xff, 0xe4, 0xb5);
+    pub const NAVAJO_WHITE: Color = Color::new(0xff, 0xde, 0xad);
+    pub const NAVY: Color = Color::new(0x00, 0x00, 0x80);
+    pub const OLD_LACE: Color = Color::new(0xfd, 0xf5, 0xe6);
+    pub const OLIVE: Color = Color::new(0x80, 0x80, 0x00);
+    pub const OLIVE_DRAB: Color = Color::new(0x6b, 0x8e, 0x23);
+    pub const ORANGE: Color = Color::new(0xff, 0xa5, 0x00);
+    pub const ORANGE_RED: Color = Color::new(0xff, 0x45, 0x00);
+    pub const ORCHID: Color = Color::new(0xda, 0x70, 0xd6);
+    pub const PALE_GOLDENROD: Color = Color::new(0xee, 0xe8, 0xaa);
+    pub const PALE_GREEN: Color = Color::new(0x98, 0xfb, 0x98);
+    pub const PALE_TURQUOISE: Color = Color::new(0xaf, 0xee, 0xee);
+    pub const PALE_VIOLET_RED: Color = Color::new(0xdb, 0x70, 0x93);
+    pub const PAPAYA_WHIP: Color = Color::new(0xff, 0xef, 0xd5);
+    pub const PEACH_PUFF: Color = Color::new(0xff, 0xda, 0xb9);
+    pub const PERU: Color = Color::new(0xcd, 0x85, 0x3f);
+    pub const PINK: Color = Color::new(0xff, 0xc0, 0xcb);
+    pub const PLUM: Color = Color::new(0xdd, 0xa0, 0xdd);
+    pub const POWDER_BLUE: Color = Color::new(0xb0, 0xe0, 0xe6);
+    pub const PURPLE: Color = Color::new(0x80, 0x00, 0x80);
+    pub const REBECCA_PURPLE: Color = Color::new(0x66, 0x33, 0x99);
+    pub const RED: Color = Color::new(0xff, 0x00, 0x00);
+    pub const ROSY_BROWN: Color = Color::new(0xbc, 0x8f, 0x8f);
+    pub const ROYAL_BLUE: Color = Color::new(0x41, 0x69, 0xe1);
+    pub const SADDLE_BROWN: Color = Color::new(0x8b, 0x45, 0x13);
+    pub const SALMON: Color = Color::new(0xfa, 0x80, 0x72);
+    pub const SANDY_BROWN: Color = Color::new(0xf4, 0xa4, 0x60);
+    pub const SEA_GREEN: Color = Color::new(0x2e, 0x8b, 0x57);
+    pub const SEASHELL: Color = Color::new(0xff, 0xf5, 0xee);
+    pub const SIENNA: Color = Color::new(0xa0, 0x52, 0x2d);
+    pub const SILVER: Color = Color::new(0xc0, 0xc0, 0xc0);
+    pub const SKY_BLUE: Color = Color::new(0x87, 0xce, 0xeb);
+    pub const SLATE_BLUE: Color = Color::new(0x6a, 0x5a, 0xcd);
+    pub const SLATE_GRAY: Color = Color::new(0x70, 0x80, 0x90);
+    pub const SLATE_GREY: Color = Color::new(0x70, 0x80, 0x90);
+    pub const SNOW: Color = Color::new(0xff, 0xfa, 0xfa);
+    pub const SPRING_GREEN: Color = Color::new(0x00, 0xff, 0x7f);
+    pub const STEEL_BLUE: Color = Color::new(0x46, 0x82, 0xb4);
+    pub const TAN: Color = Color::new(0xd2, 0xb4, 0x8c);
+    pub const TEAL: Color = Color::new(0x00, 0x80, 0x80);
+    pub const THISTLE: Color = Color::new(0xd8, 0xbf, 0xd8);
+    pub const TOMATO: Color = Color::new(0xff, 0x63, 0x47);
+    pub const TURQUOISE: Color = Color::new(0x40, 0xe0, 0xd0);
+    pub const VIOLET: Color = Color::new(0xee, 0x82, 0xee);
+    pub const WHEAT: Color = Color::new(0xf5, 0xde, 0xb3);
+    pub const WHITE: Color = Color::new(0xff, 0xff, 0xff);
+    pub const WHITE_SMOKE: Color = Color::new(0xf5, 0xf5, 0xf5);
+    pub const YELLOW: Color = Color::new(0xff, 0xff, 0x00);
+    pub const YELLOW_GREEN: Color = Color::new(0x9a, 0xcd, 0x32);
+}
+
+/// The named colors with their lowercase CSS names.
+pub(crate) const NAMED_COLORS: [(&str, Color); 148] = [
+    ("aliceblue", Color::ALICE_BLUE),
+    ("antiquewhite", Color::ANTIQUE_WHITE),
+    ("aqua", Color::AQUA),
+    ("aquamarine", Color::AQUAMARINE),
+    ("azure", Color::AZURE),
+    ("beige", Color::BEIGE),
+    ("bisque", Color::BISQUE),
+    ("black", Color::BLACK),
+    ("blanchedalmond", Color::BLANCHED_ALMOND),
+    ("blue", Color::BLUE),
+    ("blueviolet", Color::BLUE_VIOLET),
+    ("brown", Color::BROWN),
+    ("burlywood", Color::BURLY_WOOD),
+    ("cadetblue", Color::CADET_BLUE),
+    ("chartreuse", Color::CHARTREUSE),
+    ("chocolate", Color::CHOCOLATE),
+    ("coral", Color::CORAL),
+    ("cornflowerblue", Color::CORNFLOWER_BLUE),
+    ("cornsilk", Color::CORNSILK),
+    ("crimson", Color::CRIMSON),
+    ("cyan", Color::CYAN),
+    ("darkblue", Color::DARK_BLUE),
+    ("darkcyan", Color::DARK_CYAN),
+    ("darkgoldenrod", Color::DARK_GOLDENROD),
+    ("darkgray", Color::DARK_GRAY),
+    ("darkgreen", Color::DARK_GREEN),
+    ("darkgrey", Color::DARK_GREY),
+    ("darkkhaki", Color::DARK_KHAKI),
+    ("darkmagenta", Color::DARK_MAGENTA),
+    ("darkolivegreen", Color::DARK_OLIVE_GREEN),
+    ("darkorange", Color::DARK_ORANGE),
+    ("darkorchid", Color::DARK_ORCHID),
+    ("darkred", Color::DARK_RED),
+    ("darksalmon", Color::DARK_SALMON),
+    ("darkseagreen", Color::DARK_SEA_GREEN),
+    ("darkslateblue", Color::DARK_SLATE_BLUE),
+    ("darkslategray", Color::DARK_SLATE_GRAY),
+    ("darkslategrey", Color::DARK_SLATE_GREY),
+    ("darkturquoise", Color::DARK_TURQUOISE),
+    ("darkviolet", Color::DARK_VIOLET),
+    ("deeppink", Color::DEEP_PINK),
+    ("deepskyblue", Color::DEEP_SKY_BLUE),
+    ("dimgray", Color::DIM_GRAY),
+    ("dimgrey", Color::DIM_GREY),
+    ("dodgerblue", Color::DODGER_BLUE),
+    ("firebrick", Color::FIRE_BRICK),
+    ("floralwhite", Color::FLORAL_WHITE),
+    ("forestgreen", Color::FOREST_GREEN),
+    ("fuchsia", Color::FUCHSIA),
+    ("gainsboro", Color::GAINSBORO),
+    ("ghostwhite", Color::GHOST_WHITE),
+    ("gold", Color::GOLD),
+    ("goldenrod", Color::GOLDENROD),
+    ("gray", Color::GRAY),
+    ("green", Color::GREEN),
+    ("greenyellow", Color::GREEN_YELLOW),
+    ("grey", Color::GREY),
+    ("honeydew", Color::HONEYDEW),
+    ("hotpink", Color::HOT_PINK),
+    ("indianred", Color::INDIAN_RED),
+    ("indigo", Color::INDIGO),
+    ("ivory", Color::IVORY),
+    ("khaki", Color::KHAKI),
+    ("lavender", Color::LAVENDER),
+    ("lavenderblush", Color::LAVENDER_BLUSH),
+    ("lawngreen", Color::LAWN_GREEN),
+    ("lemonchiffon", Color::LEMON_CHIFFON),
+    ("lightblue", Color::LIGHT_BLUE),
+    ("lightcoral", Color::LIGHT_CORAL),
+    ("lightcyan", Color::LIGHT_CYAN),
+    ("lightgoldenrodyellow", Color::LIGHT_GOLDENROD_YELLOW),
+    ("lightgray", Color::LIGHT_GRAY),
+    ("lightgreen", Color::LIGHT_GREEN),
+    ("lightgrey", Color::LIGHT_GREY),
+    ("lightpink", Color::LIGHT_PINK),
+    ("lightsalmon", Color::LIGHT_SALMON),
+    ("lightseagreen", Color::LIGHT_SEA_GREEN),
+    ("lightskyblue", Color::LIGHT_SKY_BLUE),
+    ("lightslategray", Color::LIGHT_SLATE_GRAY),
+    ("lightslategrey", Color::LIGHT_SLATE_GREY),
+    ("lightsteelblue", Color::LIGHT_STEEL_BLUE),
+    ("lightyellow", Color::LIGHT_YELLOW),
+    ("lime", Color::LIME),
+    ("limegreen", Color::LIME_GREEN),
+    ("linen", Color::LINEN),
+    ("magenta", Color::MAGENTA),
+    ("maroon", Color::MAROON),
+    ("mediumaquamarine", Color::MEDIUM_AQUAMARINE),
+    ("mediumblue", Color::MEDIUM_BLUE),
+    ("mediumorchid", Color::MEDIUM_ORCHID),
+    ("mediumpurple", Color::MEDIUM_PURPLE),
+    ("mediumseagreen", Color::MEDIUM_SEA_GREEN),
+    ("mediumslateblue", Color::MEDIUM_SLATE_BLUE),
+    ("mediumspringgreen", Color::MEDIUM_SPRING_GREEN),
+    ("mediumturquoise", Color::MEDIUM_TURQUOISE),
+    ("mediumvioletred", Color::MEDIUM_VIOLET_RED),
+    ("midnightblue", Color::MIDNIGHT_BLUE),
+    ("mintcream", Color::MINT_CREAM),
+    ("mistyrose", Color::MISTY_ROSE),
+    ("moccasin", Color::MOCCASIN),
+    ("navajowhite", Color::NAVAJO_WHITE),
+    ("navy", Color::NAVY),
+    ("oldlace", Color::OLD_LACE),
+    ("olive", Color::OLIVE),
+    ("olivedrab", Color::OLIVE_DRAB),
+    ("orange", Color::ORANGE),
+    ("orangered", Color::ORANGE_RED),
+    ("orchid", Color::ORCHID),
+    ("palegoldenrod", Color::PALE_GOLDENROD),
+    ("palegreen", Color::PALE_GREEN),
+    ("paleturquoise", Color::PALE_TURQUOISE),
+    ("palevioletred", Color::PALE_VIOLET_RED),
+    ("papayawhip", Color::PAPAYA_WHIP),
+    ("peachpuff", Color::PEACH_PUFF),
+    ("peru", Color::PERU),
+    ("pink", Color::PINK),
+    ("plum", Color::PLUM),
+    ("powderblue", Color::POWDER_BLUE),
+    ("purple", Color::PURPLE),
+    ("rebeccapurple", Color::REBECCA_PURPLE),
+    ("red", Color::RED),
+    ("rosybrown", Color::ROSY_BROWN),
+    ("royalblue", Color::ROYAL_BLUE),
+    ("saddlebrown", Color::SADDLE_BROWN),
+    ("salmon", Color::SALMON),
+    ("sandybrown", Color::SANDY_BROWN),
+    ("seagreen", Color::SEA_GREEN),
+    ("seashell", Color::SEASHELL),
+    ("sienna", Color::SIENNA),
+    ("silver", Color::SILVER),
+    ("skyblue", Color::SKY_BLUE),
+    ("slateblue", Color::SLATE_BLUE),
+    ("slategray", Color::SLATE_GRAY),
+    ("slategrey", Color::SLATE_GREY),
+    ("snow", Color::SNOW),
+    ("springgreen", Color::SPRING_GREEN),
+    ("steelblue", Color::STEEL_BLUE),
+    ("tan", Color::TAN),
+    ("teal", Color::TEAL),
+    ("thistle", Color::THISTLE),
+    ("tomato", Color::TOMATO),
+    ("turquoise", Color::TURQUOISE),
+    ("violet", Color::VIOLET),
+    ("wheat", Color::WHEAT),
+    ("white", Color::WHITE),
+    ("whitesmoke", Color::WHITE_SMOKE),
+    ("yellow", Color::YELLOW),
+    ("yellowgreen", Color::YELLOW_GREEN),
+];
+
+impl Color {
+    /// Look up a named CSS color, e.g. `"CornflowerBlue"`. The lookup ignores case.
+    pub fn from_name(name: &str) -> Option<Color> {
+        NAMED_COLORS
+            .iter()
+            .find(|(color_name, _)| color_name.eq_ignore_ascii_case(name))
+            .map(|(_, color)| *color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Color::from_name("red"), Some(Color::new(255, 0, 0)));
+        assert_eq!(
+            Color::from_name("CornflowerBlue"),
+            Some(Color::new(100, 149, 237))
+        );
+        assert_eq!(
+            Color::from_name("REBECCAPURPLE"),
+            Some(Color::new(102, 51, 153))
+        );
+        assert_eq!(Color::from_name("green"), Some(Color::GREEN));
+        assert_eq!(Color::GREEN, Color::new(0, 128, 0));
+        assert_eq!(Color::LIGHT_GOLDENROD_YELLOW.to_hex(), "#fafad2");
+    }
+
+    #[test]
+    fn test_from_name_unknown() {
+        assert_eq!(Color::from_name("not a color"), None);
+        assert_eq!(Color::from_name(""), None);
+    }
+}
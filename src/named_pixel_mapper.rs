@@ -1,4 +1,13 @@
-use std::{error::Error, str::FromStr};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    str::FromStr,
+};
 
 /// Enum representing different pixel mapping options for mapping the logical layout of your boards
 /// to your physical arrangement. These options allow you to customize the mapping to match your unique setup.
@@ -8,6 +17,8 @@ use std::{error::Error, str::FromStr};
 /// You can apply multiple mappers in your configuration, and they will be applied in the order you specify.
 /// For example, to first mirror the panels horizontally and then rotate the resulting screen,
 /// You can use `--pixelmapper Mirror:H --pixelmapper Rotate:90`
+/// The same chain can be stored as a single string and parsed with [`NamedPixelMapperType::parse_chain`],
+/// e.g. `Mirror:H;Rotate:90`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum NamedPixelMapperType {
     /// The "Mirror" mapper allows you to mirror the output either horizontally or vertically.
@@ -17,7 +28,22 @@ pub enum NamedPixelMapperType {
     /// The "Rotate" mapper allows you to rotate your screen by a specified angle in degrees.
     /// Specify the desired angle as a parameter after a colon.
     /// Example: `--pixelmapper Rotate:90` for a 90-degree rotation.
+    /// Negative angles and angles beyond 360 degrees are normalized, so `Rotate:-90` is the same as
+    /// `Rotate:270` and `Rotate:450` is the same as `Rotate:90`.
     Rotate(usize),
+    /// The "Transpose" mapper swaps the X and Y coordinates. Unlike a rotation, it does not reverse any axis.
+    /// Example: `--pixelmapper Transpose`
+    Transpose,
+    /// The "Scale" mapper presents a canvas that is smaller than the matrix by the given factor in both
+    /// dimensions. Each visible pixel is mapped to the top-left physical pixel of its `factor` x `factor`
+    /// block. The matrix dimensions need to be divisible by the factor.
+    /// Example: `--pixelmapper Scale:2`
+    Scale(usize),
+    /// The "Offset" mapper shifts the visible content by the given horizontal and vertical offset. Pixels that
+    /// are shifted past an edge of the matrix wrap around to the opposite edge, so offsetting by the full
+    /// width or height leaves the layout unchanged. Offsets may be negative.
+    /// Example: `--pixelmapper Offset:8,4`
+    Offset(isize, isize),
     /// The `UMapper` represents a pixel mapping strategy where a long chain of display panels
     /// is arranged in a U-shape configuration. This arrangement allows for a single chain display
     /// with panels of double height but still utilizing only one data chain.
@@ -43,65 +69,576 @@ pub enum NamedPixelMapperType {
     ///   [<][<][<][<]  }--- Pi connector #2
     ///   [>][>][>][>]
     UMapper,
+    /// The `VMapper` is the vertical counterpart of the [`NamedPixelMapperType::UMapper`]. The chain is
+    /// folded in the same place, but hangs downwards with the panels turned by 90 degrees, so the two halves
+    /// become two columns instead of two rows. This doubles the visible width instead of the height.
+    ///
+    /// `VMapper` takes no parameters.
+    /// `--pixelmapper V-mapper`
+    ///
+    /// For example, a single chain display with four 32x32 panels like this:
+    ///    [<][<][<][<] }- Raspberry Pi connector
+    ///
+    /// can be arranged in this 64x64 V-shaped display, where the chain goes down the left column from the
+    /// connector at its top and back up the right one:
+    ///    [v][^]
+    ///    [v][^]
+    ///
+    /// With multiple chains, the columns of each chain are placed next to each other, from left to right.
+    VMapper,
+    /// The `Serpentine` mapper is meant for panel grids that are wired back and forth, where the chain runs
+    /// along a row of panels and comes back along the next one, so every other row of panels is mounted upside
+    /// down (rotated by 180 degrees). The mapper reverses the panel order and the pixels within each panel on
+    /// every odd row so that the visible image appears upright.
+    ///
+    /// The panels of a chain are split into as many rows as possible without making the grid taller than wide,
+    /// e.g. a chain of 4 panels into 2x2 and a chain of 6 panels into 3x2 panels. A chain with a prime number
+    /// of panels stays a single row. The grids of parallel chains are placed below each other, and the rows of
+    /// panels are flipped by their position in the whole display, so two parallel chains of two panels each
+    /// also form a serpentine.
+    ///
+    /// `Serpentine` takes no parameters.
+    /// `--pixelmapper Serpentine`
+    ///
+    /// For example, a chain of four panels is arranged in this 2x2 grid:
+    ///   [>][>]-.
+    ///   [<][<]-'
+    ///
+    /// And two chains with two panels each:
+    ///   [<][<]  }--- Pi connector #1
+    ///   [>][>]  }--- Pi connector #2
+    Serpentine,
+    /// The `Grid` mapper places the panels in an arbitrary grid, each one rotated by a multiple of 90 degrees,
+    /// which generalizes the `U-mapper`, `V-mapper` and `Serpentine` mappers. The panels are numbered in the
+    /// order in which they appear in the matrix, i.e. along the first chain and then along the next parallel
+    /// chains. The layout lists the panel and its rotation for every cell of the grid as `panel/angle`, with
+    /// the cells of a row separated by commas and the rows separated by `|`, see [`GridLayout`]. Rotations by
+    /// 90 and 270 degrees need square panels.
+    ///
+    /// Example: `--pixelmapper Grid:2/0,3/0|1/180,0/180` arranges a chain of four panels like the `U-mapper`:
+    ///    [2][3]
+    ///    [1][0] (upside down)
+    Grid(GridLayout),
 }
 
 impl FromStr for NamedPixelMapperType {
-    type Err = Box<dyn Error>;
+    type Err = PixelMapperParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((command, param)) = s.split_once(':') {
-            match command {
-                "Mirror" => match param {
-                    "H" | "h" => Ok(Self::Mirror(true)),
-                    "V" | "v" => Ok(Self::Mirror(false)),
-                    other => Err(format!(
-                        "'{}' is not valid. Mirror parameter should be either 'V' or 'H'",
-                        other
-                    )
-                    .into()),
-                },
-                "Rotate" => {
-                    if let Ok(angle) = param.parse::<usize>() {
-                        if angle % 90 != 0 {
-                            return Err(format!(
-                                "'{}' is not valid. Rotation needs to be a multiple of 90 degrees",
-                                angle
-                            )
-                            .into());
-                        }
-                        return Ok(Self::Rotate((angle + 360) % 360));
-                    }
-                    Err("Rotation angle is missing or invalid".into())
+        let (mapper, param) = match s.split_once(':') {
+            Some((mapper, param)) => (mapper, Some(param)),
+            None => (s, None),
+        };
+        let invalid_parameter = |param: &str| PixelMapperParseError::InvalidParameter {
+            mapper: mapper.to_string(),
+            parameter: param.to_string(),
+        };
+        let parse = |parse: fn(&str) -> Option<Self>| match param {
+            None | Some("") => Err(PixelMapperParseError::MissingParameter {
+                mapper: mapper.to_string(),
+            }),
+            Some(param) => parse(param).ok_or_else(|| invalid_parameter(param)),
+        };
+        let without_parameter = |mapper: Self| match param {
+            None => Ok(mapper),
+            Some(param) => Err(invalid_parameter(param)),
+        };
+        match mapper {
+            "Mirror" => parse(|param| match param {
+                "H" | "h" => Some(Self::Mirror(true)),
+                "V" | "v" => Some(Self::Mirror(false)),
+                _ => None,
+            }),
+            "Rotate" => parse(|param| {
+                let angle = param.parse::<i64>().ok()?;
+                // Normalize into [0, 360), so that e.g. -90 becomes 270 and 450 becomes 90.
+                (angle % 90 == 0).then_some(Self::Rotate(angle.rem_euclid(360) as usize))
+            }),
+            "Scale" => parse(|param| match param.parse::<usize>() {
+                Ok(factor) if factor > 0 => Some(Self::Scale(factor)),
+                _ => None,
+            }),
+            "Offset" => parse(|param| {
+                let (dx, dy) = param.split_once(',')?;
+                Some(Self::Offset(dx.parse().ok()?, dy.parse().ok()?))
+            }),
+            "Grid" => parse(|param| param.parse().ok().map(Self::Grid)),
+            "U-mapper" => without_parameter(Self::UMapper),
+            "V-mapper" => without_parameter(Self::VMapper),
+            "Serpentine" => without_parameter(Self::Serpentine),
+            "Transpose" => without_parameter(Self::Transpose),
+            _ => Err(PixelMapperParseError::UnknownMapper(mapper.to_string())),
+        }
+    }
+}
+
+impl Display for NamedPixelMapperType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NamedPixelMapperType::Mirror(true) => f.write_str("Mirror:H"),
+            NamedPixelMapperType::Mirror(false) => f.write_str("Mirror:V"),
+            NamedPixelMapperType::Rotate(angle) => write!(f, "Rotate:{angle}"),
+            NamedPixelMapperType::Transpose => f.write_str("Transpose"),
+            NamedPixelMapperType::Scale(factor) => write!(f, "Scale:{factor}"),
+            NamedPixelMapperType::Offset(dx, dy) => write!(f, "Offset:{dx},{dy}"),
+            NamedPixelMapperType::UMapper => f.write_str("U-mapper"),
+            NamedPixelMapperType::VMapper => f.write_str("V-mapper"),
+            NamedPixelMapperType::Serpentine => f.write_str("Serpentine"),
+            NamedPixelMapperType::Grid(layout) => write!(f, "Grid:{layout}"),
+        }
+    }
+}
+
+/// The reason why a pixel mapper could not be parsed, with the part of the input that is wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PixelMapperParseError {
+    /// The name before the colon is not one of the mappers, e.g. `Rotatte` in `Rotatte:90`.
+    UnknownMapper(String),
+    /// The mapper needs a parameter after a colon, e.g. `Rotate` instead of `Rotate:90`.
+    MissingParameter { mapper: String },
+    /// The parameter is not valid for the mapper, e.g. `45` in `Rotate:45`, or the mapper doesn't take one.
+    InvalidParameter { mapper: String, parameter: String },
+}
+
+impl Error for PixelMapperParseError {}
+
+impl Display for PixelMapperParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PixelMapperParseError::UnknownMapper(mapper) => {
+                write!(f, "'{mapper}' is not a valid pixel mapper.")
+            }
+            PixelMapperParseError::MissingParameter { mapper } => {
+                write!(f, "The pixel mapper '{mapper}' needs a parameter")?;
+                match mapper.as_str() {
+                    "Mirror" => write!(f, ", e.g. 'Mirror:H'."),
+                    "Rotate" => write!(f, ", e.g. 'Rotate:90'."),
+                    "Scale" => write!(f, ", e.g. 'Scale:2'."),
+                    "Offset" => write!(f, ", e.g. 'Offset:8,4'."),
+                    "Grid" => write!(f, ", e.g. 'Grid:0/0,1/0|3/180,2/180'."),
+                    _ => write!(f, "."),
+                }
+            }
+            PixelMapperParseError::InvalidParameter { mapper, parameter } => {
+                write!(f, "'{parameter}' is not a valid parameter of '{mapper}'. ")?;
+                match mapper.as_str() {
+                    "Mirror" => write!(f, "It should be either 'H' or 'V'."),
+                    "Rotate" => write!(f, "Rotation needs to be a multiple of 90 degrees."),
+                    "Scale" => write!(f, "The scale factor needs to be at least 1."),
+                    "Offset" => write!(f, "It should be two integers like '8,4'."),
+                    "Grid" => write!(
+                        f,
+                        "It should be rows of equal length separated by '|' of at most {} panels with a \
+                        rotation, like '0/0,1/0|3/180,2/180'.",
+                        GridLayout::MAX_PANELS
+                    ),
+                    _ => write!(f, "The mapper takes no parameter."),
                 }
-                other => Err(format!("'{}' is not a valid Pixel mapping.", other).into()),
             }
-        } else if s == "U-mapper" {
-            Ok(Self::UMapper)
-        } else {
-            Err(format!("'{}' is not a valid Pixel mapping.", s).into())
         }
     }
 }
 
-impl NamedPixelMapperType {
-    pub(crate) fn create(self, chain: usize, parallel: usize) -> Box<dyn NamedPixelMapper> {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PixelMapperError {
+    /// The mapper needs a longer chain of panels.
+    ChainTooShort {
+        mapper: NamedPixelMapperType,
+        chain: usize,
+        minimum: usize,
+    },
+    /// The mapper needs an even number of chained panels.
+    OddChainLength {
+        mapper: NamedPixelMapperType,
+        chain: usize,
+    },
+    /// The mapper needs at least one parallel chain.
+    NoParallelChains { mapper: NamedPixelMapperType },
+    /// The width of the layout the mapper is applied to is not divisible as required.
+    WidthNotDivisible {
+        mapper: NamedPixelMapperType,
+        width: usize,
+        divisor: usize,
+    },
+    /// The height of the layout the mapper is applied to is not divisible as required.
+    HeightNotDivisible {
+        mapper: NamedPixelMapperType,
+        height: usize,
+        divisor: usize,
+    },
+    /// A panel of the grid layout is not one of the chained panels.
+    PanelOutOfRange {
+        mapper: NamedPixelMapperType,
+        panel: usize,
+        panels: usize,
+    },
+    /// A panel of the grid layout is rotated by 90 or 270 degrees, but the panels are not square.
+    NonSquarePanels {
+        mapper: NamedPixelMapperType,
+        width: usize,
+        height: usize,
+    },
+    /// A custom mapper maps the matrix to a visible layout without pixels, or with more pixels than can be
+    /// addressed.
+    InvalidVisibleSize { width: usize, height: usize },
+    /// A custom mapper maps a visible pixel outside of the matrix it is applied to.
+    PositionOutOfRange {
+        visible: [usize; 2],
+        matrix: [usize; 2],
+        width: usize,
+        height: usize,
+    },
+}
+
+impl Error for PixelMapperError {}
+
+impl Display for PixelMapperError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
+            PixelMapperError::ChainTooShort {
+                mapper,
+                chain,
+                minimum,
+            } => write!(
+                f,
+                "{mapper}: need at least '--chain-length {minimum}', but the chain length is {chain}."
+            ),
+            PixelMapperError::OddChainLength { mapper, chain } => write!(
+                f,
+                "{mapper}: the chain length (--chain-length) needs to be divisible by two, but is {chain}."
+            ),
+            PixelMapperError::NoParallelChains { mapper } => {
+                write!(f, "{mapper}: need at least one parallel chain.")
+            }
+            PixelMapperError::WidthNotDivisible {
+                mapper,
+                width,
+                divisor,
+            } => write!(
+                f,
+                "{mapper}: expected the width {width} to be divisible by {divisor}."
+            ),
+            PixelMapperError::HeightNotDivisible {
+                mapper,
+                height,
+                divisor,
+            } => write!(
+                f,
+                "{mapper}: expected the height {height} to be divisible by {divisor}."
+            ),
+            PixelMapperError::PanelOutOfRange {
+                mapper,
+                panel,
+                panels,
+            } => write!(
+                f,
+                "{mapper}: there is no panel {panel}, the chains only have {panels} panels."
+            ),
+            PixelMapperError::NonSquarePanels {
+                mapper,
+                width,
+                height,
+            } => write!(
+                f,
+                "{mapper}: panels can only be rotated by 90 or 270 degrees if they are square, but they are \
+                {width}x{height}."
+            ),
+            PixelMapperError::InvalidVisibleSize { width, height } => write!(
+                f,
+                "Custom pixel mapper: the visible size {width}x{height} is invalid."
+            ),
+            PixelMapperError::PositionOutOfRange {
+                visible: [visible_x, visible_y],
+                matrix: [matrix_x, matrix_y],
+                width,
+                height,
+            } => write!(
+                f,
+                "Custom pixel mapper: the visible pixel ({visible_x}, {visible_y}) is mapped to ({matrix_x}, \
+                {matrix_y}), which is outside of the {width}x{height} matrix."
+            ),
+        }
+    }
+}
+
+impl NamedPixelMapperType {
+    /// Parse a chain of mappers separated by semicolons, e.g. `Mirror:H;Rotate:90;U-mapper`, in the order in
+    /// which they are applied. Whitespace around the mappers and empty entries are ignored, so an empty string
+    /// is an empty chain. This is the format of `--led-pixel-mapper` in the original library.
+    pub fn parse_chain(chain: &str) -> Result<Vec<Self>, PixelMapperParseError> {
+        chain
+            .split(';')
+            .map(str::trim)
+            .filter(|mapper| !mapper.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    pub(crate) fn create(
+        self,
+        chain: usize,
+        parallel: usize,
+    ) -> Result<Box<dyn NamedPixelMapper>, PixelMapperError> {
+        let mapper: Box<dyn NamedPixelMapper> = match self {
             NamedPixelMapperType::Mirror(horizontal) => Box::new(MirrorPixelMapper { horizontal }),
             NamedPixelMapperType::Rotate(angle) => Box::new(RotatePixelMapper { angle }),
+            NamedPixelMapperType::Transpose => Box::new(TransposePixelMapper),
+            NamedPixelMapperType::Scale(factor) => Box::new(ScalePixelMapper { factor }),
+            NamedPixelMapperType::Offset(dx, dy) => Box::new(OffsetPixelMapper { dx, dy }),
             NamedPixelMapperType::UMapper => {
-                Box::new(UArrangeMapper::new_with_parameters(chain, parallel))
+                Box::new(UArrangeMapper::new_with_parameters(chain, parallel)?)
+            }
+            NamedPixelMapperType::VMapper => {
+                Box::new(VArrangeMapper::new_with_parameters(chain, parallel)?)
             }
+            NamedPixelMapperType::Serpentine => {
+                Box::new(SerpentineMapper::new_with_parameters(chain, parallel)?)
+            }
+            NamedPixelMapperType::Grid(layout) => {
+                Box::new(GridMapper::new_with_parameters(layout, chain, parallel)?)
+            }
+        };
+        Ok(mapper)
+    }
+
+    /// Whether the mapper moves whole panels, which requires all panels to be of the same size.
+    pub(crate) fn arranges_panels(&self) -> bool {
+        matches!(
+            self,
+            NamedPixelMapperType::UMapper
+                | NamedPixelMapperType::VMapper
+                | NamedPixelMapperType::Serpentine
+                | NamedPixelMapperType::Grid(_)
+        )
+    }
+
+    /// Check that the mapper can be applied to a layout of the given size.
+    pub(crate) fn check_size(
+        self,
+        chain: usize,
+        parallel: usize,
+        matrix_width: usize,
+        matrix_height: usize,
+    ) -> Result<(), PixelMapperError> {
+        let [width_divisor, height_divisor] = match self {
+            NamedPixelMapperType::Scale(factor) => [factor, factor],
+            NamedPixelMapperType::UMapper | NamedPixelMapperType::VMapper => [1, parallel],
+            NamedPixelMapperType::Serpentine => [SerpentineMapper::panel_rows(chain), parallel],
+            NamedPixelMapperType::Grid(_) => [chain, parallel],
+            _ => [1, 1],
+        };
+        if !matrix_width.is_multiple_of(width_divisor) {
+            return Err(PixelMapperError::WidthNotDivisible {
+                mapper: self,
+                width: matrix_width,
+                divisor: width_divisor,
+            });
+        }
+        if !matrix_height.is_multiple_of(height_divisor) {
+            return Err(PixelMapperError::HeightNotDivisible {
+                mapper: self,
+                height: matrix_height,
+                divisor: height_divisor,
+            });
+        }
+        if let NamedPixelMapperType::Grid(layout) = self {
+            let [width, height] = [matrix_width / chain, matrix_height / parallel];
+            let rotated = layout.cells().any(|(_, angle)| angle % 180 != 0);
+            if rotated && width != height {
+                return Err(PixelMapperError::NonSquarePanels {
+                    mapper: self,
+                    width,
+                    height,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The arrangement of the panels of the [`NamedPixelMapperType::Grid`] mapper: the panel and its clockwise
+/// rotation in degrees for every cell of a grid of at most [`GridLayout::MAX_PANELS`] cells and panels. It is
+/// parsed from and displayed as the rows of the grid separated by `|`, each a list of `panel/angle` cells
+/// separated by commas, e.g. `0/0,1/0|3/180,2/180`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridLayout {
+    columns: usize,
+    rows: usize,
+    /// The panel shifted left by two bits and the number of quarter turns of every cell, row by row. The
+    /// entries after the cells of the grid are unused.
+    cells: [u8; GridLayout::MAX_PANELS],
+}
+
+impl GridLayout {
+    /// The largest number of cells of a grid and of panels, which is fixed so that the layout can be copied.
+    pub const MAX_PANELS: usize = 64;
+
+    /// Create a layout from the rows of the grid, given as the panel and its rotation in degrees for every cell.
+    /// Returns `None` if the rows are empty or of different lengths, the grid has more than
+    /// [`GridLayout::MAX_PANELS`] cells, a panel is not below it or an angle is not a multiple of 90 degrees.
+    /// Angles are normalized like the ones of [`NamedPixelMapperType::Rotate`].
+    pub fn new(rows: &[&[(usize, i64)]]) -> Option<Self> {
+        let columns = rows.first()?.len();
+        if columns == 0
+            || columns * rows.len() > Self::MAX_PANELS
+            || rows.iter().any(|row| row.len() != columns)
+        {
+            return None;
+        }
+        let mut cells = [0; Self::MAX_PANELS];
+        for (cell, &(panel, angle)) in cells.iter_mut().zip(rows.iter().copied().flatten()) {
+            if panel >= Self::MAX_PANELS || angle % 90 != 0 {
+                return None;
+            }
+            *cell = (panel << 2) as u8 | (angle.rem_euclid(360) / 90) as u8;
         }
+        Some(Self {
+            columns,
+            rows: rows.len(),
+            cells,
+        })
+    }
+
+    /// Get the number of cells in every row of the grid.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Get the number of rows of the grid.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the panel and its rotation in degrees at a cell, or `None` if it is outside of the grid.
+    pub fn cell(&self, column: usize, row: usize) -> Option<(usize, usize)> {
+        if column >= self.columns || row >= self.rows {
+            return None;
+        }
+        Some(Self::decode(self.cells[row * self.columns + column]))
+    }
+
+    /// The panel and angle of every cell, row by row.
+    fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells[..self.columns * self.rows]
+            .iter()
+            .map(|&cell| Self::decode(cell))
+    }
+
+    /// Split a cell into the panel and its angle.
+    fn decode(cell: u8) -> (usize, usize) {
+        ((cell >> 2) as usize, (cell & 0b11) as usize * 90)
+    }
+}
+
+impl FromStr for GridLayout {
+    type Err = PixelMapperParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_cell = |cell: &str| {
+            let (panel, angle) = cell.split_once('/')?;
+            Some((panel.trim().parse().ok()?, angle.trim().parse().ok()?))
+        };
+        let rows: Option<Vec<Vec<(usize, i64)>>> = s
+            .split('|')
+            .map(|row| row.split(',').map(parse_cell).collect())
+            .collect();
+        let rows = rows.unwrap_or_default();
+        let rows: Vec<&[(usize, i64)]> = rows.iter().map(Vec::as_slice).collect();
+        Self::new(&rows).ok_or_else(|| PixelMapperParseError::InvalidParameter {
+            mapper: "Grid".to_string(),
+            parameter: s.to_string(),
+        })
+    }
+}
+
+impl Display for GridLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for (i, (panel, angle)) in self.cells().enumerate() {
+            if i > 0 {
+                f.write_str(if i % self.columns == 0 { "|" } else { "," })?;
+            }
+            write!(f, "{panel}/{angle}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for GridLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "GridLayout(\"{self}\")")
     }
 }
 
 /// A pixel mapper is a way for you to map pixels of LED matrixes to a different
-/// layout. If you have an implementation of a PixelMapper, you can give it
-/// to the RGBMatrix::apply_pixel_mapper(), which then presents you a canvas
-/// that has the new "visible_width", "visible_height".
-pub(crate) trait NamedPixelMapper {
+/// layout. If you have an implementation of a `NamedPixelMapper`, you can give it
+/// to [`RGBMatrix::apply_named_pixel_mapper`](crate::RGBMatrix::apply_named_pixel_mapper), which then
+/// presents you a canvas that has the new "visible_width", "visible_height".
+///
+/// Mappers are applied in the order they are registered. The "matrix" a mapper sees is the visible layout
+/// produced by the mapper applied before it, or the raw chain of panels (`cols * chain_length` by
+/// `rows * parallel`) if it is the first one.
+///
+/// # Example
+///
+/// An identity mapper that leaves the layout unchanged, and a mapper that maps pixels outside of the matrix,
+/// which is rejected:
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// use rpi_led_panel::{NamedPixelMapper, PixelMapperError, RGBMatrix, RGBMatrixConfig};
+///
+/// struct IdentityMapper;
+///
+/// impl NamedPixelMapper for IdentityMapper {
+///     fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
+///         [matrix_width, matrix_height]
+///     }
+///
+///     fn map_visible_to_matrix(
+///         &self,
+///         _matrix_width: usize,
+///         _matrix_height: usize,
+///         visible_x: usize,
+///         visible_y: usize,
+///     ) -> [usize; 2] {
+///         [visible_x, visible_y]
+///     }
+/// }
+///
+/// struct WideMapper;
+///
+/// impl NamedPixelMapper for WideMapper {
+///     fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
+///         [matrix_width * 2, matrix_height]
+///     }
+///
+///     fn map_visible_to_matrix(
+///         &self,
+///         _matrix_width: usize,
+///         _matrix_height: usize,
+///         visible_x: usize,
+///         visible_y: usize,
+///     ) -> [usize; 2] {
+///         [visible_x, visible_y]
+///     }
+/// }
+///
+/// let (mut matrix, canvas) = RGBMatrix::new_mock(RGBMatrixConfig::default()).unwrap();
+/// matrix.apply_named_pixel_mapper(Box::new(IdentityMapper)).unwrap();
+/// assert!(matches!(
+///     matrix.apply_named_pixel_mapper(Box::new(WideMapper)),
+///     Err(PixelMapperError::PositionOutOfRange { .. })
+/// ));
+/// let canvas = matrix.update_on_vsync(canvas);
+/// ```
+pub trait NamedPixelMapper {
+    /// Given the underlying matrix (width, height), returns the visible (width, height) after the mapping.
+    /// E.g. a 90 degree rotation might map matrix=(64, 32) -> visible=(32, 64).
     fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2];
 
+    /// Map where a visible pixel (x, y) is mapped to the underlying matrix (x, y). This is called for every
+    /// visible pixel within the size returned by [`NamedPixelMapper::get_size_mapping`]. The returned
+    /// coordinates must lie within (`matrix_width`, `matrix_height`), otherwise applying the mapper fails.
     fn map_visible_to_matrix(
         &self,
         matrix_width: usize,
@@ -141,7 +678,7 @@ struct RotatePixelMapper {
 
 impl NamedPixelMapper for RotatePixelMapper {
     fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
-        if self.angle % 180 == 0 {
+        if self.angle.is_multiple_of(180) {
             [matrix_width, matrix_height]
         } else {
             [matrix_height, matrix_width]
@@ -165,33 +702,112 @@ impl NamedPixelMapper for RotatePixelMapper {
     }
 }
 
+struct TransposePixelMapper;
+
+impl NamedPixelMapper for TransposePixelMapper {
+    fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
+        [matrix_height, matrix_width]
+    }
+
+    fn map_visible_to_matrix(
+        &self,
+        _matrix_width: usize,
+        _matrix_height: usize,
+        x: usize,
+        y: usize,
+    ) -> [usize; 2] {
+        [y, x]
+    }
+}
+
+struct ScalePixelMapper {
+    factor: usize,
+}
+
+impl NamedPixelMapper for ScalePixelMapper {
+    fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
+        [matrix_width / self.factor, matrix_height / self.factor]
+    }
+
+    fn map_visible_to_matrix(
+        &self,
+        _matrix_width: usize,
+        _matrix_height: usize,
+        x: usize,
+        y: usize,
+    ) -> [usize; 2] {
+        [x * self.factor, y * self.factor]
+    }
+}
+
+struct OffsetPixelMapper {
+    dx: isize,
+    dy: isize,
+}
+
+impl NamedPixelMapper for OffsetPixelMapper {
+    fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
+        [matrix_width, matrix_height]
+    }
+
+    fn map_visible_to_matrix(
+        &self,
+        matrix_width: usize,
+        matrix_height: usize,
+        x: usize,
+        y: usize,
+    ) -> [usize; 2] {
+        // The mapper is created without knowing the size of the matrix, so the offsets are reduced to within the
+        // matrix here, before adding them can overflow.
+        let dx = self.dx.rem_euclid(matrix_width as isize) as usize;
+        let dy = self.dy.rem_euclid(matrix_height as isize) as usize;
+        [(x + dx) % matrix_width, (y + dy) % matrix_height]
+    }
+}
+
 struct UArrangeMapper {
     parallel: usize,
 }
 
 impl UArrangeMapper {
-    fn new_with_parameters(chain: usize, parallel: usize) -> Self {
+    fn new_with_parameters(chain: usize, parallel: usize) -> Result<Self, PixelMapperError> {
+        Self::check_chain(NamedPixelMapperType::UMapper, chain, parallel)?;
+        Ok(Self { parallel })
+    }
+
+    /// Check that the chains can be folded in half, for the U-mapper and the V-mapper.
+    fn check_chain(
+        mapper: NamedPixelMapperType,
+        chain: usize,
+        parallel: usize,
+    ) -> Result<(), PixelMapperError> {
         if chain < 2 {
             // technically, a chain of 2 would work, but somewhat pointless
-            panic!("U-mapper: need at least '--chain_length 4' for useful folding");
+            return Err(PixelMapperError::ChainTooShort {
+                mapper,
+                chain,
+                minimum: 2,
+            });
         }
-        if chain % 2 != 0 {
-            panic!("U-mapper: Chain (--chain_length) needs to be divisible by two");
+        if !chain.is_multiple_of(2) {
+            return Err(PixelMapperError::OddChainLength { mapper, chain });
         }
-        Self { parallel }
+        if parallel == 0 {
+            return Err(PixelMapperError::NoParallelChains { mapper });
+        }
+        Ok(())
+    }
+
+    /// The width of half of a chain, where it is folded.
+    fn fold_width(matrix_width: usize) -> usize {
+        (matrix_width / 64) * 32 // Div at 32px boundary
     }
 }
 
 impl NamedPixelMapper for UArrangeMapper {
     fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
-        let visible_width = (matrix_width / 64) * 32; // Div at 32px boundary
+        let visible_width = Self::fold_width(matrix_width);
         let visible_height = 2 * matrix_height;
-        if matrix_height % self.parallel != 0 {
-            eprintln!(
-                "For parallel={} we would expect the height={matrix_height} to be divisible by {}.",
-                self.parallel, self.parallel
-            );
-        }
         [visible_width, visible_height]
     }
 
@@ -203,7 +819,7 @@ impl NamedPixelMapper for UArrangeMapper {
         y: usize,
     ) -> [usize; 2] {
         let panel_height = matrix_height / self.parallel;
-        let visible_width = (matrix_width / 64) * 32;
+        let visible_width = Self::fold_width(matrix_width);
         let slab_height = 2 * panel_height; // one folded u-shape
         let base_y = (y / slab_height) * panel_height;
 
@@ -214,9 +830,763 @@ impl NamedPixelMapper for UArrangeMapper {
             matrix_x += matrix_width / 2;
         } else {
             matrix_x = visible_width - x - 1;
-            matrix_y = slab_height - y - 1;
+            matrix_y = slab_height - matrix_y - 1;
         }
 
         [matrix_x, base_y + matrix_y]
     }
 }
+
+/// The U-mapper turned counterclockwise by 90 degrees.
+struct VArrangeMapper {
+    folded: UArrangeMapper,
+}
+
+impl VArrangeMapper {
+    fn new_with_parameters(chain: usize, parallel: usize) -> Result<Self, PixelMapperError> {
+        UArrangeMapper::check_chain(NamedPixelMapperType::VMapper, chain, parallel)?;
+        Ok(Self {
+            folded: UArrangeMapper { parallel },
+        })
+    }
+}
+
+impl NamedPixelMapper for VArrangeMapper {
+    fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
+        let [folded_width, folded_height] =
+            self.folded.get_size_mapping(matrix_width, matrix_height);
+        [folded_height, folded_width]
+    }
+
+    fn map_visible_to_matrix(
+        &self,
+        matrix_width: usize,
+        matrix_height: usize,
+        x: usize,
+        y: usize,
+    ) -> [usize; 2] {
+        // The top of the U-shape is the left of the V-shape.
+        let folded_width = UArrangeMapper::fold_width(matrix_width);
+        self.folded
+            .map_visible_to_matrix(matrix_width, matrix_height, folded_width - y - 1, x)
+    }
+}
+
+struct SerpentineMapper {
+    parallel: usize,
+    /// The number of rows of panels of every chain.
+    rows: usize,
+}
+
+impl SerpentineMapper {
+    fn new_with_parameters(chain: usize, parallel: usize) -> Result<Self, PixelMapperError> {
+        let mapper = NamedPixelMapperType::Serpentine;
+        if chain == 0 {
+            return Err(PixelMapperError::ChainTooShort {
+                mapper,
+                chain,
+                minimum: 1,
+            });
+        }
+        if parallel == 0 {
+            return Err(PixelMapperError::NoParallelChains { mapper });
+        }
+        Ok(Self {
+            parallel,
+            rows: Self::panel_rows(chain),
+        })
+    }
+
+    /// The number of rows that a chain of panels is split into, which is the largest divisor of the chain
+    /// length that does not exceed the number of panels per row.
+    fn panel_rows(chain: usize) -> usize {
+        (1..=chain)
+            .take_while(|rows| rows * rows <= chain)
+            .filter(|rows| chain.is_multiple_of(*rows))
+            .last()
+            .unwrap_or(1)
+    }
+}
+
+impl NamedPixelMapper for SerpentineMapper {
+    fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
+        [matrix_width / self.rows, matrix_height * self.rows]
+    }
+
+    fn map_visible_to_matrix(
+        &self,
+        matrix_width: usize,
+        matrix_height: usize,
+        x: usize,
+        y: usize,
+    ) -> [usize; 2] {
+        // Only the height of the panels is needed, turning a whole row of panels reverses the panel order and
+        // the pixels within each panel, whatever the width of the panels.
+        let row_width = matrix_width / self.rows;
+        let panel_height = matrix_height / self.parallel;
+        let panel_row = y / panel_height;
+        let within_panel_y = y % panel_height;
+        // The rows of a chain follow each other in the chain, and the chains are the rows of the matrix.
+        let matrix_left = (panel_row % self.rows) * row_width;
+        let matrix_top = (panel_row / self.rows) * panel_height;
+
+        if panel_row.is_multiple_of(2) {
+            [matrix_left + x, matrix_top + within_panel_y]
+        } else {
+            [
+                matrix_left + row_width - x - 1,
+                matrix_top + panel_height - within_panel_y - 1,
+            ]
+        }
+    }
+}
+
+struct GridMapper {
+    layout: GridLayout,
+    chain: usize,
+    parallel: usize,
+}
+
+impl GridMapper {
+    fn new_with_parameters(
+        layout: GridLayout,
+        chain: usize,
+        parallel: usize,
+    ) -> Result<Self, PixelMapperError> {
+        let mapper = NamedPixelMapperType::Grid(layout);
+        if chain == 0 {
+            return Err(PixelMapperError::ChainTooShort {
+                mapper,
+                chain,
+                minimum: 1,
+            });
+        }
+        if parallel == 0 {
+            return Err(PixelMapperError::NoParallelChains { mapper });
+        }
+        let panels = chain * parallel;
+        if let Some((panel, _)) = layout.cells().find(|&(panel, _)| panel >= panels) {
+            return Err(PixelMapperError::PanelOutOfRange {
+                mapper,
+                panel,
+                panels,
+            });
+        }
+        Ok(Self {
+            layout,
+            chain,
+            parallel,
+        })
+    }
+}
+
+impl NamedPixelMapper for GridMapper {
+    fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
+        let panel_width = matrix_width / self.chain;
+        let panel_height = matrix_height / self.parallel;
+        [
+            self.layout.columns() * panel_width,
+            self.layout.rows() * panel_height,
+        ]
+    }
+
+    fn map_visible_to_matrix(
+        &self,
+        matrix_width: usize,
+        matrix_height: usize,
+        x: usize,
+        y: usize,
+    ) -> [usize; 2] {
+        let panel_width = matrix_width / self.chain;
+        let panel_height = matrix_height / self.parallel;
+        let [within_x, within_y] = [x % panel_width, y % panel_height];
+        let Some((panel, angle)) = self.layout.cell(x / panel_width, y / panel_height) else {
+            return [matrix_width, matrix_height];
+        };
+        // Panels rotated by 90 or 270 degrees are square.
+        let [panel_x, panel_y] = match angle {
+            0 => [within_x, within_y],
+            90 => [panel_width - within_y - 1, within_x],
+            180 => [panel_width - within_x - 1, panel_height - within_y - 1],
+            270 => [within_y, panel_height - within_x - 1],
+            _ => unreachable!(),
+        };
+        [
+            (panel % self.chain) * panel_width + panel_x,
+            (panel / self.chain) * panel_height + panel_y,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec};
+
+    use super::*;
+
+    /// Map a visible pixel through a chain of mappers, which are applied in the given order.
+    fn map_chain(
+        mappers: &[NamedPixelMapperType],
+        matrix_width: usize,
+        matrix_height: usize,
+        x: usize,
+        y: usize,
+    ) -> [usize; 2] {
+        let mappers: Vec<_> = mappers.iter().map(|m| m.create(1, 1).unwrap()).collect();
+        let mut sizes = vec![[matrix_width, matrix_height]];
+        for mapper in &mappers {
+            let [width, height] = *sizes.last().unwrap();
+            sizes.push(mapper.get_size_mapping(width, height));
+        }
+        mappers
+            .iter()
+            .zip(sizes.iter())
+            .rev()
+            .fold([x, y], |[x, y], (mapper, [width, height])| {
+                mapper.map_visible_to_matrix(*width, *height, x, y)
+            })
+    }
+
+    #[test]
+    fn test_parse_serpentine() {
+        assert_eq!(
+            "Serpentine".parse::<NamedPixelMapperType>().unwrap(),
+            NamedPixelMapperType::Serpentine
+        );
+    }
+
+    #[test]
+    fn test_serpentine_2x2() {
+        // Two chains of two 32x32 panels each.
+        let mapper = NamedPixelMapperType::Serpentine.create(2, 2).unwrap();
+        assert_eq!(mapper.get_size_mapping(64, 64), [64, 64]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 64, 0, 0), [0, 0]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 64, 63, 31), [63, 31]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 64, 0, 32), [63, 63]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 64, 63, 63), [0, 32]);
+    }
+
+    /// Check that the corners of the visible 32x32 panels, given row by row as the index of the panel in the
+    /// chain, map to the corners of that panel, turned by 180 degrees on odd rows.
+    fn assert_serpentine_panels(chain: usize, panels: &[&[usize]]) {
+        let mapper = NamedPixelMapperType::Serpentine.create(chain, 1).unwrap();
+        let [width, height] = [32 * chain, 32];
+        assert!(NamedPixelMapperType::Serpentine
+            .check_size(chain, 1, width, height)
+            .is_ok());
+        assert_eq!(
+            mapper.get_size_mapping(width, height),
+            [32 * panels[0].len(), 32 * panels.len()]
+        );
+        for (row, panels) in panels.iter().enumerate() {
+            for (column, panel) in panels.iter().enumerate() {
+                for [dx, dy] in [[0, 0], [31, 0], [0, 31], [31, 31]] {
+                    let [x, y] = [32 * column + dx, 32 * row + dy];
+                    let expected = if row % 2 == 0 {
+                        [32 * panel + dx, dy]
+                    } else {
+                        [32 * panel + 31 - dx, 31 - dy]
+                    };
+                    assert_eq!(
+                        mapper.map_visible_to_matrix(width, height, x, y),
+                        expected,
+                        "({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_serpentine_single_chain() {
+        assert_serpentine_panels(4, &[&[0, 1], &[3, 2]]);
+        assert_serpentine_panels(6, &[&[0, 1, 2], &[5, 4, 3]]);
+        assert_serpentine_panels(9, &[&[0, 1, 2], &[5, 4, 3], &[6, 7, 8]]);
+        // A prime number of panels can't be split into rows.
+        assert_serpentine_panels(3, &[&[0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_serpentine_narrow_panels() {
+        // Two chains of three 32x16 panels each, also when configured as a single panel with 96 columns.
+        for chain in [3, 1] {
+            let mapper = NamedPixelMapperType::Serpentine.create(chain, 2).unwrap();
+            assert!(NamedPixelMapperType::Serpentine
+                .check_size(chain, 2, 96, 32)
+                .is_ok());
+            assert_eq!(mapper.map_visible_to_matrix(96, 32, 0, 0), [0, 0]);
+            assert_eq!(mapper.map_visible_to_matrix(96, 32, 0, 16), [95, 31]);
+            assert_eq!(mapper.map_visible_to_matrix(96, 32, 31, 16), [64, 31]);
+            assert_eq!(mapper.map_visible_to_matrix(96, 32, 95, 31), [0, 16]);
+        }
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mapper = NamedPixelMapperType::Transpose.create(1, 1).unwrap();
+        assert_eq!(mapper.get_size_mapping(64, 32), [32, 64]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 3, 50), [50, 3]);
+    }
+
+    #[test]
+    fn test_transpose_twice_is_identity() {
+        let mappers = [
+            NamedPixelMapperType::Transpose,
+            NamedPixelMapperType::Transpose,
+        ];
+        for y in 0..32 {
+            for x in 0..64 {
+                assert_eq!(map_chain(&mappers, 64, 32, x, y), [x, y]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transpose_after_mirror() {
+        let mappers = [
+            NamedPixelMapperType::Mirror(true),
+            NamedPixelMapperType::Transpose,
+        ];
+        // The visible size is 32x64, visible (x, y) is transposed to (y, x) and then mirrored horizontally.
+        assert_eq!(map_chain(&mappers, 64, 32, 0, 0), [63, 0]);
+        assert_eq!(map_chain(&mappers, 64, 32, 5, 10), [53, 5]);
+        assert_eq!(map_chain(&mappers, 64, 32, 31, 63), [0, 31]);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let mappers = [
+            NamedPixelMapperType::Mirror(true),
+            NamedPixelMapperType::Mirror(false),
+            NamedPixelMapperType::Rotate(0),
+            NamedPixelMapperType::Rotate(90),
+            NamedPixelMapperType::Rotate(180),
+            NamedPixelMapperType::Rotate(270),
+            NamedPixelMapperType::Transpose,
+            NamedPixelMapperType::Scale(1),
+            NamedPixelMapperType::Scale(3),
+            NamedPixelMapperType::Offset(8, 4),
+            NamedPixelMapperType::Offset(-3, 0),
+            NamedPixelMapperType::UMapper,
+            NamedPixelMapperType::VMapper,
+            NamedPixelMapperType::Serpentine,
+            "Grid:2/0,3/90|1/180,0/270".parse().unwrap(),
+        ];
+        for mapper in mappers {
+            assert_eq!(
+                mapper.to_string().parse::<NamedPixelMapperType>().unwrap(),
+                mapper
+            );
+        }
+        assert_eq!(NamedPixelMapperType::Mirror(true).to_string(), "Mirror:H");
+        assert_eq!(NamedPixelMapperType::Mirror(false).to_string(), "Mirror:V");
+        assert_eq!(NamedPixelMapperType::Rotate(90).to_string(), "Rotate:90");
+        assert_eq!(NamedPixelMapperType::UMapper.to_string(), "U-mapper");
+    }
+
+    #[test]
+    fn test_parse_chain() {
+        assert_eq!(
+            NamedPixelMapperType::parse_chain("Mirror:H;Rotate:90;U-mapper").unwrap(),
+            [
+                NamedPixelMapperType::Mirror(true),
+                NamedPixelMapperType::Rotate(90),
+                NamedPixelMapperType::UMapper,
+            ]
+        );
+        assert_eq!(
+            NamedPixelMapperType::parse_chain(" Rotate:90 ; Mirror:V;").unwrap(),
+            [
+                NamedPixelMapperType::Rotate(90),
+                NamedPixelMapperType::Mirror(false),
+            ]
+        );
+        assert_eq!(NamedPixelMapperType::parse_chain("").unwrap(), []);
+
+        let error = NamedPixelMapperType::parse_chain("Mirror:H;Rotatte:90;U-mapper").unwrap_err();
+        assert_eq!(
+            error,
+            PixelMapperParseError::UnknownMapper("Rotatte".to_string())
+        );
+        assert_eq!(error.to_string(), "'Rotatte' is not a valid pixel mapper.");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parse = |s: &str| s.parse::<NamedPixelMapperType>().unwrap_err();
+        for mapper in ["Rotatte:90", "Unknown", "u-mapper", ":90"] {
+            let name = mapper.split(':').next().unwrap().to_string();
+            assert_eq!(parse(mapper), PixelMapperParseError::UnknownMapper(name));
+        }
+        for mapper in ["Mirror", "Rotate:", "Scale", "Offset:"] {
+            let name = mapper.trim_end_matches(':').to_string();
+            assert_eq!(
+                parse(mapper),
+                PixelMapperParseError::MissingParameter { mapper: name }
+            );
+        }
+        for (mapper, parameter) in [
+            ("Mirror", "X"),
+            ("Rotate", "45"),
+            ("Rotate", "ninety"),
+            ("Scale", "0"),
+            ("Offset", "8"),
+            ("Grid", "0/0,1/0|2/0"),
+            ("Grid", "0/45"),
+            ("Grid", "0,1"),
+            ("Grid", "64/0"),
+            ("U-mapper", "Z"),
+        ] {
+            assert_eq!(
+                parse(&format!("{mapper}:{parameter}")),
+                PixelMapperParseError::InvalidParameter {
+                    mapper: mapper.to_string(),
+                    parameter: parameter.to_string(),
+                }
+            );
+        }
+        assert_eq!(
+            parse("Rotate").to_string(),
+            "The pixel mapper 'Rotate' needs a parameter, e.g. 'Rotate:90'."
+        );
+        assert_eq!(
+            parse("Rotate:45").to_string(),
+            "'45' is not a valid parameter of 'Rotate'. Rotation needs to be a multiple of 90 degrees."
+        );
+        assert_eq!(
+            parse("U-mapper:Z").to_string(),
+            "'Z' is not a valid parameter of 'U-mapper'. The mapper takes no parameter."
+        );
+    }
+
+    #[test]
+    fn test_parse_rotation_normalization() {
+        let parse = |s: &str| s.parse::<NamedPixelMapperType>().unwrap();
+        assert_eq!(parse("Rotate:0"), NamedPixelMapperType::Rotate(0));
+        assert_eq!(parse("Rotate:-90"), NamedPixelMapperType::Rotate(270));
+        assert_eq!(parse("Rotate:-180"), NamedPixelMapperType::Rotate(180));
+        assert_eq!(parse("Rotate:450"), NamedPixelMapperType::Rotate(90));
+        assert_eq!(parse("Rotate:720"), NamedPixelMapperType::Rotate(0));
+        assert!("Rotate:-45".parse::<NamedPixelMapperType>().is_err());
+        assert!("Rotate:".parse::<NamedPixelMapperType>().is_err());
+    }
+
+    #[test]
+    fn test_scale() {
+        for factor in [2, 3] {
+            let mapper = NamedPixelMapperType::Scale(factor).create(1, 1).unwrap();
+            let size = 64 / factor * factor;
+            let [width, height] = mapper.get_size_mapping(size, size);
+            assert_eq!([width, height], [64 / factor, 64 / factor]);
+            assert_eq!(mapper.map_visible_to_matrix(size, size, 0, 0), [0, 0]);
+            assert_eq!(
+                mapper.map_visible_to_matrix(size, size, 1, 2),
+                [factor, 2 * factor]
+            );
+            // The last visible pixel maps to the top-left pixel of the last block within the matrix.
+            let [x, y] = mapper.map_visible_to_matrix(size, size, width - 1, height - 1);
+            assert_eq!([x, y], [size - factor, size - factor]);
+        }
+    }
+
+    #[test]
+    fn test_scale_not_divisible() {
+        let error = NamedPixelMapperType::Scale(3)
+            .check_size(1, 1, 64, 64)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            PixelMapperError::WidthNotDivisible { divisor: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_scale() {
+        assert_eq!(
+            "Scale:2".parse::<NamedPixelMapperType>().unwrap(),
+            NamedPixelMapperType::Scale(2)
+        );
+        assert!("Scale:0".parse::<NamedPixelMapperType>().is_err());
+        assert!("Scale:x".parse::<NamedPixelMapperType>().is_err());
+    }
+
+    #[test]
+    fn test_offset() {
+        let mapper = NamedPixelMapperType::Offset(8, 4).create(1, 1).unwrap();
+        assert_eq!(mapper.get_size_mapping(64, 32), [64, 32]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 0, 0), [8, 4]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 60, 30), [4, 2]);
+    }
+
+    #[test]
+    fn test_offset_full_size_is_identity() {
+        let mapper = NamedPixelMapperType::Offset(64, -32).create(1, 1).unwrap();
+        for y in 0..32 {
+            for x in 0..64 {
+                assert_eq!(mapper.map_visible_to_matrix(64, 32, x, y), [x, y]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_offset_negative_wraps() {
+        let mapper = NamedPixelMapperType::Offset(-1, -2).create(1, 1).unwrap();
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 0, 0), [63, 30]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 1, 2), [0, 0]);
+        let mapper = NamedPixelMapperType::Offset(-65, 0).create(1, 1).unwrap();
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 0, 0), [63, 0]);
+    }
+
+    #[test]
+    fn test_offset_extreme() {
+        let mapper = NamedPixelMapperType::Offset(isize::MAX, isize::MIN)
+            .create(1, 1)
+            .unwrap();
+        // `isize::MAX` is one less than a multiple of 64, and `isize::MIN` is a multiple of 32.
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 0, 0), [63, 0]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 63, 31), [62, 31]);
+        let mapper = NamedPixelMapperType::Offset(isize::MIN, isize::MAX)
+            .create(1, 1)
+            .unwrap();
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 0, 0), [0, 31]);
+        assert_eq!(mapper.map_visible_to_matrix(64, 32, 63, 31), [63, 30]);
+        assert_eq!(mapper.map_visible_to_matrix(3, 5, 2, 4), [0, 1]);
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(
+            "Offset:8,4".parse::<NamedPixelMapperType>().unwrap(),
+            NamedPixelMapperType::Offset(8, 4)
+        );
+        assert_eq!(
+            "Offset:-8,-4".parse::<NamedPixelMapperType>().unwrap(),
+            NamedPixelMapperType::Offset(-8, -4)
+        );
+        assert!("Offset:8".parse::<NamedPixelMapperType>().is_err());
+    }
+
+    #[test]
+    fn test_umapper_odd_chain() {
+        let error = NamedPixelMapperType::UMapper.create(3, 1).err().unwrap();
+        assert!(matches!(
+            error,
+            PixelMapperError::OddChainLength { chain: 3, .. }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "U-mapper: the chain length (--chain-length) needs to be divisible by two, but is 3."
+        );
+    }
+
+    #[test]
+    fn test_umapper_short_chain() {
+        let error = NamedPixelMapperType::UMapper.create(1, 1).err().unwrap();
+        assert!(matches!(
+            error,
+            PixelMapperError::ChainTooShort {
+                chain: 1,
+                minimum: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_umapper_parallel() {
+        let mapper = NamedPixelMapperType::UMapper.create(4, 2).unwrap();
+        assert_eq!(mapper.get_size_mapping(128, 64), [64, 128]);
+        // The second chain is folded into the lower half of the visible area.
+        assert_eq!(mapper.map_visible_to_matrix(128, 64, 0, 64), [64, 32]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 64, 0, 127), [63, 32]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 64, 63, 96), [0, 63]);
+    }
+
+    #[test]
+    fn test_umapper_parallel_not_dividing_height() {
+        let mapper = NamedPixelMapperType::UMapper;
+        assert!(mapper.create(4, 3).is_ok());
+        // Three parallel chains of 32 rows each work.
+        assert!(mapper.check_size(4, 3, 128, 96).is_ok());
+        // After rotating the same matrix, the height can no longer be divided into three chains.
+        let error = mapper.check_size(4, 3, 96, 128).unwrap_err();
+        assert!(matches!(
+            error,
+            PixelMapperError::HeightNotDivisible {
+                height: 128,
+                divisor: 3,
+                ..
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "U-mapper: expected the height 128 to be divisible by 3."
+        );
+    }
+
+    #[test]
+    fn test_vmapper_4_panel_chain() {
+        // A chain of four 32x32 panels folded into a 2x2 arrangement.
+        let mapper = NamedPixelMapperType::VMapper.create(4, 1).unwrap();
+        assert_eq!(mapper.get_size_mapping(128, 32), [64, 64]);
+        // The first panel in the chain is in the top left, turned by 90 degrees.
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 0, 0), [127, 0]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 31, 0), [127, 31]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 0, 63), [64, 0]);
+        // The right column goes back up, turned the other way.
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 32, 0), [0, 31]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 63, 0), [0, 0]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 63, 63), [63, 0]);
+    }
+
+    #[test]
+    fn test_vmapper_parallel() {
+        // Two chains of four 32x32 panels each.
+        let mapper = NamedPixelMapperType::VMapper.create(4, 2).unwrap();
+        assert_eq!(mapper.get_size_mapping(128, 64), [128, 64]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 64, 0, 0), [127, 0]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 64, 64, 0), [127, 32]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 64, 127, 63), [63, 32]);
+    }
+
+    #[test]
+    fn test_vmapper_chain_of_wide_panels() {
+        // Four 32x32 panels configured as a chain of two panels with 64 columns fold the same way.
+        let configured = NamedPixelMapperType::VMapper.create(2, 1).unwrap();
+        let physical = NamedPixelMapperType::VMapper.create(4, 1).unwrap();
+        assert!(NamedPixelMapperType::VMapper
+            .check_size(2, 1, 128, 32)
+            .is_ok());
+        assert_eq!(configured.get_size_mapping(128, 32), [64, 64]);
+        for y in 0..64 {
+            for x in 0..64 {
+                assert_eq!(
+                    configured.map_visible_to_matrix(128, 32, x, y),
+                    physical.map_visible_to_matrix(128, 32, x, y)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_vmapper_covers_matrix() {
+        let mapper = NamedPixelMapperType::VMapper.create(4, 2).unwrap();
+        let [width, height] = mapper.get_size_mapping(128, 64);
+        let mut mapped: Vec<_> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| [x, y]))
+            .map(|[x, y]| mapper.map_visible_to_matrix(128, 64, x, y))
+            .collect();
+        mapped.sort();
+        mapped.dedup();
+        assert_eq!(mapped.len(), 128 * 64);
+    }
+
+    #[test]
+    fn test_vmapper_odd_chain() {
+        let error = NamedPixelMapperType::VMapper.create(3, 1).err().unwrap();
+        assert!(matches!(
+            error,
+            PixelMapperError::OddChainLength { chain: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_grid_2x2_rotated_panel() {
+        // A chain of four 32x32 panels in a 2x2 grid, where the third panel is upside down.
+        let layout = "0/0,1/0|2/180,3/0".parse().unwrap();
+        let mapper = NamedPixelMapperType::Grid(layout).create(4, 1).unwrap();
+        assert_eq!(mapper.get_size_mapping(128, 32), [64, 64]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 0, 0), [0, 0]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 63, 31), [63, 31]);
+        // The corners of the rotated panel are swapped diagonally.
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 0, 32), [95, 31]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 31, 63), [64, 0]);
+        assert_eq!(mapper.map_visible_to_matrix(128, 32, 32, 32), [96, 0]);
+    }
+
+    #[test]
+    fn test_grid_generalizes_umapper() {
+        let grid = "2/0,3/0|1/180,0/180".parse().unwrap();
+        let grid = NamedPixelMapperType::Grid(grid).create(4, 1).unwrap();
+        let umapper = NamedPixelMapperType::UMapper.create(4, 1).unwrap();
+        assert_eq!(
+            grid.get_size_mapping(128, 32),
+            umapper.get_size_mapping(128, 32)
+        );
+        for y in 0..64 {
+            for x in 0..64 {
+                assert_eq!(
+                    grid.map_visible_to_matrix(128, 32, x, y),
+                    umapper.map_visible_to_matrix(128, 32, x, y)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_grid_quarter_turns() {
+        // Two chains of one 32x32 panel each, side by side, with the second panel rotated.
+        let layout = "0/0,1/90".parse().unwrap();
+        let mapper = NamedPixelMapperType::Grid(layout);
+        assert!(mapper.check_size(1, 2, 32, 64).is_ok());
+        let mapper = mapper.create(1, 2).unwrap();
+        assert_eq!(mapper.get_size_mapping(32, 64), [64, 32]);
+        assert_eq!(mapper.map_visible_to_matrix(32, 64, 32, 0), [31, 32]);
+        assert_eq!(mapper.map_visible_to_matrix(32, 64, 63, 0), [31, 63]);
+        assert_eq!(mapper.map_visible_to_matrix(32, 64, 32, 31), [0, 32]);
+    }
+
+    #[test]
+    fn test_grid_errors() {
+        let layout: GridLayout = "0/0,1/0|2/0,3/0".parse().unwrap();
+        let mapper = NamedPixelMapperType::Grid(layout);
+        let error = mapper.create(3, 1).err().unwrap();
+        assert_eq!(
+            error,
+            PixelMapperError::PanelOutOfRange {
+                mapper,
+                panel: 3,
+                panels: 3
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Grid:0/0,1/0|2/0,3/0: there is no panel 3, the chains only have 3 panels."
+        );
+
+        let mapper = NamedPixelMapperType::Grid("0/270".parse().unwrap());
+        assert_eq!(
+            mapper.check_size(1, 1, 64, 32),
+            Err(PixelMapperError::NonSquarePanels {
+                mapper,
+                width: 64,
+                height: 32
+            })
+        );
+        assert_eq!(
+            "Grid:1/0,0/0|2/0".parse::<NamedPixelMapperType>().unwrap_err().to_string(),
+            "'1/0,0/0|2/0' is not a valid parameter of 'Grid'. It should be rows of equal length separated by '|' \
+            of at most 64 panels with a rotation, like '0/0,1/0|3/180,2/180'."
+        );
+    }
+
+    #[test]
+    fn test_grid_layout() {
+        let layout = GridLayout::new(&[&[(0, 0), (1, -90)], &[(2, 450), (3, 180)]]).unwrap();
+        assert_eq!([layout.columns(), layout.rows()], [2, 2]);
+        assert_eq!(layout.cell(1, 0), Some((1, 270)));
+        assert_eq!(layout.cell(0, 1), Some((2, 90)));
+        assert_eq!(layout.cell(2, 0), None);
+        assert_eq!(layout.to_string(), "0/0,1/270|2/90,3/180");
+        assert_eq!(
+            format!("{layout:?}"),
+            r#"GridLayout("0/0,1/270|2/90,3/180")"#
+        );
+        assert_eq!(" 0 / 0 , 1/270|2/90,3/180".parse(), Ok(layout));
+
+        assert!(GridLayout::new(&[]).is_none());
+        assert!(GridLayout::new(&[&[]]).is_none());
+        assert!(GridLayout::new(&[&[(0, 0); 65]]).is_none());
+        assert!(GridLayout::new(&[&[(0, 0); 64]]).is_some());
+    }
+}
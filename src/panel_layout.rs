@@ -0,0 +1,333 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GridLayout, NamedPixelMapper, NamedPixelMapperType, PixelMapperError};
+
+/// The arrangement of the panels of a video wall, as an alternative to writing the layout string of the `Grid`
+/// pixel mapper by hand. It is usually read from a JSON file like this one, which places a chain of six panels
+/// in two rows of three, with the second row upside down:
+///
+/// ```json
+/// {
+///     "columns": 3,
+///     "rows": 2,
+///     "panels": [
+///         { "chain_index": 0, "column": 0, "row": 0 },
+///         { "chain_index": 1, "column": 1, "row": 0 },
+///         { "chain_index": 2, "column": 2, "row": 0 },
+///         { "chain_index": 3, "column": 2, "row": 1, "rotation": 180 },
+///         { "chain_index": 4, "column": 1, "row": 1, "rotation": 180 },
+///         { "chain_index": 5, "column": 0, "row": 1, "rotation": 180 }
+///     ]
+/// }
+/// ```
+///
+/// Every cell of the grid needs exactly one panel, so that every visible pixel is shown by exactly one pixel of
+/// the panels.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PanelLayout {
+    /// The number of panels in every row of the grid.
+    pub columns: usize,
+    /// The number of rows of the grid.
+    pub rows: usize,
+    pub panels: Vec<PanelPlacement>,
+}
+
+/// Where a panel is placed in a [`PanelLayout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PanelPlacement {
+    /// The position of the panel in the matrix, counting along the first chain and then along the next parallel
+    /// chains.
+    pub chain_index: usize,
+    /// The column of the grid cell, from the left.
+    pub column: usize,
+    /// The row of the grid cell, from the top.
+    pub row: usize,
+    /// The clockwise rotation of the panel in degrees, a multiple of 90. Default: 0
+    #[serde(default)]
+    pub rotation: i64,
+}
+
+#[derive(Debug)]
+pub enum PanelLayoutError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// The grid has no cells or more than [`GridLayout::MAX_PANELS`].
+    InvalidGridSize {
+        columns: usize,
+        rows: usize,
+    },
+    /// The panel is placed outside of the grid.
+    OutsideGrid(PanelPlacement),
+    /// The rotation of the panel is not a multiple of 90 degrees.
+    InvalidRotation(PanelPlacement),
+    /// The chain index of the panel is not below [`GridLayout::MAX_PANELS`].
+    InvalidChainIndex(PanelPlacement),
+    /// No panel is placed in the cell.
+    Gap {
+        column: usize,
+        row: usize,
+    },
+    /// More than one panel is placed in the cell.
+    Overlap {
+        column: usize,
+        row: usize,
+    },
+    /// The panel is placed in more than one cell, so it would have to show both.
+    DuplicatePanel(usize),
+    /// The layout does not fit the chained panels.
+    PixelMapper(PixelMapperError),
+}
+
+impl Error for PanelLayoutError {}
+
+impl Display for PanelLayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PanelLayoutError::Io(error) => write!(f, "Failed to read the panel layout: {error}."),
+            PanelLayoutError::Json(error) => write!(f, "Invalid panel layout: {error}."),
+            PanelLayoutError::InvalidGridSize { columns, rows } => write!(
+                f,
+                "Invalid grid of {columns}x{rows} panels. It must have between 1 and {} cells.",
+                GridLayout::MAX_PANELS
+            ),
+            PanelLayoutError::OutsideGrid(panel) => write!(
+                f,
+                "Panel {} is placed in column {} and row {}, which is outside of the grid.",
+                panel.chain_index, panel.column, panel.row
+            ),
+            PanelLayoutError::InvalidRotation(panel) => write!(
+                f,
+                "Panel {} is rotated by {} degrees, but it must be a multiple of 90 degrees.",
+                panel.chain_index, panel.rotation
+            ),
+            PanelLayoutError::InvalidChainIndex(panel) => write!(
+                f,
+                "Invalid chain index {}. It must be below {}.",
+                panel.chain_index,
+                GridLayout::MAX_PANELS
+            ),
+            PanelLayoutError::Gap { column, row } => {
+                write!(f, "There is no panel in column {column} and row {row}.")
+            }
+            PanelLayoutError::Overlap { column, row } => {
+                write!(
+                    f,
+                    "There is more than one panel in column {column} and row {row}."
+                )
+            }
+            PanelLayoutError::DuplicatePanel(chain_index) => {
+                write!(f, "Panel {chain_index} is placed more than once.")
+            }
+            PanelLayoutError::PixelMapper(error) => write!(f, "Pixel mapper error: {error}"),
+        }
+    }
+}
+
+impl PanelLayout {
+    /// Read a layout from a JSON file.
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self, PanelLayoutError> {
+        let json = std::fs::read_to_string(path).map_err(PanelLayoutError::Io)?;
+        Self::from_json(&json)
+    }
+
+    /// Parse a layout from JSON.
+    pub fn from_json(json: &str) -> Result<Self, PanelLayoutError> {
+        serde_json::from_str(json).map_err(PanelLayoutError::Json)
+    }
+
+    /// Check that every cell has exactly one panel and convert the layout to the equivalent `Grid` pixel
+    /// mapper, which can be added to [`RGBMatrixConfig::pixelmapper`](crate::RGBMatrixConfig::pixelmapper).
+    pub fn to_mapper_type(&self) -> Result<NamedPixelMapperType, PanelLayoutError> {
+        let [columns, rows] = [self.columns, self.rows];
+        if columns == 0
+            || rows == 0
+            || columns
+                .checked_mul(rows)
+                .is_none_or(|panels| panels > GridLayout::MAX_PANELS)
+        {
+            return Err(PanelLayoutError::InvalidGridSize { columns, rows });
+        }
+        let mut cells: Vec<Option<(usize, i64)>> = vec![None; columns * rows];
+        let mut placed = [false; GridLayout::MAX_PANELS];
+        for &panel in &self.panels {
+            if panel.column >= columns || panel.row >= rows {
+                return Err(PanelLayoutError::OutsideGrid(panel));
+            }
+            if panel.rotation % 90 != 0 {
+                return Err(PanelLayoutError::InvalidRotation(panel));
+            }
+            let Some(placed) = placed.get_mut(panel.chain_index) else {
+                return Err(PanelLayoutError::InvalidChainIndex(panel));
+            };
+            if *placed {
+                return Err(PanelLayoutError::DuplicatePanel(panel.chain_index));
+            }
+            *placed = true;
+            let cell = &mut cells[panel.row * columns + panel.column];
+            if cell.is_some() {
+                return Err(PanelLayoutError::Overlap {
+                    column: panel.column,
+                    row: panel.row,
+                });
+            }
+            *cell = Some((panel.chain_index, panel.rotation));
+        }
+        let cells = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                cell.ok_or(PanelLayoutError::Gap {
+                    column: i % columns,
+                    row: i / columns,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let rows: Vec<&[(usize, i64)]> = cells.chunks(columns).collect();
+        let layout = GridLayout::new(&rows).expect("The layout was checked.");
+        Ok(NamedPixelMapperType::Grid(layout))
+    }
+
+    /// Create the pixel mapper of the layout for the given number of chained panels and parallel chains, e.g. to
+    /// register it with [`RGBMatrix::apply_named_pixel_mapper`](crate::RGBMatrix::apply_named_pixel_mapper).
+    pub fn create_mapper(
+        &self,
+        chain: usize,
+        parallel: usize,
+    ) -> Result<Box<dyn NamedPixelMapper>, PanelLayoutError> {
+        self.to_mapper_type()?
+            .create(chain, parallel)
+            .map_err(PanelLayoutError::PixelMapper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT_2X3: &str = r#"{
+        "columns": 3,
+        "rows": 2,
+        "panels": [
+            { "chain_index": 0, "column": 0, "row": 0 },
+            { "chain_index": 1, "column": 1, "row": 0 },
+            { "chain_index": 2, "column": 2, "row": 0 },
+            { "chain_index": 3, "column": 2, "row": 1, "rotation": 180 },
+            { "chain_index": 4, "column": 1, "row": 1, "rotation": -180 },
+            { "chain_index": 5, "column": 0, "row": 1, "rotation": 180 }
+        ]
+    }"#;
+
+    #[test]
+    fn test_layout_2x3() {
+        let layout = PanelLayout::from_json(LAYOUT_2X3).unwrap();
+        assert_eq!(
+            layout.to_mapper_type().unwrap().to_string(),
+            "Grid:0/0,1/0,2/0|5/180,4/180,3/180"
+        );
+
+        // A chain of six 32x16 panels.
+        let mapper = layout.create_mapper(6, 1).unwrap();
+        assert_eq!(mapper.get_size_mapping(192, 16), [96, 32]);
+        assert_eq!(mapper.map_visible_to_matrix(192, 16, 95, 0), [95, 0]);
+        assert_eq!(mapper.map_visible_to_matrix(192, 16, 0, 16), [191, 15]);
+        assert_eq!(mapper.map_visible_to_matrix(192, 16, 95, 31), [96, 0]);
+
+        // Every visible pixel is shown by a different pixel of the panels.
+        let mut mapped: Vec<_> = (0..32)
+            .flat_map(|y| (0..96).map(move |x| [x, y]))
+            .map(|[x, y]| mapper.map_visible_to_matrix(192, 16, x, y))
+            .collect();
+        mapped.sort();
+        mapped.dedup();
+        assert_eq!(mapped.len(), 192 * 16);
+        assert!(mapped.iter().all(|&[x, y]| x < 192 && y < 16));
+    }
+
+    #[test]
+    fn test_layout_invalid() {
+        let overlapping = LAYOUT_2X3.replace(
+            r#""chain_index": 5, "column": 0, "row": 1"#,
+            r#""chain_index": 5, "column": 1, "row": 1"#,
+        );
+        let layout = PanelLayout::from_json(&overlapping).unwrap();
+        let error = layout.to_mapper_type().unwrap_err();
+        assert!(matches!(
+            error,
+            PanelLayoutError::Overlap { column: 1, row: 1 }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "There is more than one panel in column 1 and row 1."
+        );
+
+        let mut layout = PanelLayout::from_json(LAYOUT_2X3).unwrap();
+        layout.panels.pop();
+        assert!(matches!(
+            layout.to_mapper_type(),
+            Err(PanelLayoutError::Gap { column: 0, row: 1 })
+        ));
+        layout.panels.push(PanelPlacement {
+            chain_index: 2,
+            column: 0,
+            row: 1,
+            rotation: 0,
+        });
+        assert!(matches!(
+            layout.to_mapper_type(),
+            Err(PanelLayoutError::DuplicatePanel(2))
+        ));
+        layout.panels[5].chain_index = 5;
+        layout.panels[5].rotation = 45;
+        assert!(matches!(
+            layout.to_mapper_type(),
+            Err(PanelLayoutError::InvalidRotation(_))
+        ));
+
+        // The layout needs six panels.
+        let layout = PanelLayout::from_json(LAYOUT_2X3).unwrap();
+        assert!(matches!(
+            layout.create_mapper(3, 1),
+            Err(PanelLayoutError::PixelMapper(
+                PixelMapperError::PanelOutOfRange { panels: 3, .. }
+            ))
+        ));
+        assert!(matches!(
+            PanelLayout::from_json(r#"{ "columns": 3 }"#),
+            Err(PanelLayoutError::Json(_))
+        ));
+
+        // The number of cells overflows.
+        for [columns, rows] in [[usize::MAX, 2], [usize::MAX, usize::MAX]] {
+            let layout = PanelLayout {
+                columns,
+                rows,
+                panels: Vec::new(),
+            };
+            assert!(matches!(
+                layout.to_mapper_type(),
+                Err(PanelLayoutError::InvalidGridSize { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_from_json_file() {
+        let path =
+            std::env::temp_dir().join(format!("rpi_led_panel_layout_{}.json", std::process::id()));
+        std::fs::write(&path, LAYOUT_2X3).unwrap();
+        let layout = PanelLayout::from_json_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(layout.unwrap().panels.len(), 6);
+
+        assert!(matches!(
+            PanelLayout::from_json_file("/nonexistent/layout.json"),
+            Err(PanelLayoutError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+use std::{sync::Arc, thread::panicking};
+
+use crate::frame_exchange::FrameExchange;
+
+/// Blanks the display if it is dropped while its thread panics, created with [`RGBMatrix::panic_guard`].
+///
+/// Dropping the [`RGBMatrix`] itself also blanks the display, but a panic on a render thread that does not own
+/// the matrix would leave the last frame lit indefinitely. Holding a guard on that thread makes the unwinding
+/// turn the display off, like [`RGBMatrix::set_enabled`] with `false`. It stays off until it is enabled again,
+/// e.g. after the panic was caught with [`std::panic::catch_unwind`]:
+///
+/// ```
+/// use std::panic::{catch_unwind, AssertUnwindSafe};
+///
+/// use rpi_led_panel::{RGBMatrix, RGBMatrixConfig};
+///
+/// let (mut matrix, mut canvas) = RGBMatrix::new_mock(RGBMatrixConfig::default()).unwrap();
+/// let guard = matrix.panic_guard();
+/// let result = catch_unwind(AssertUnwindSafe(move || {
+///     let _guard = guard;
+///     canvas.set_pixel(0, 0, 255, 255, 255);
+///     panic!("Rendering failed.");
+/// }));
+/// assert!(result.is_err());
+/// assert!(!matrix.is_enabled());
+/// ```
+///
+/// [`RGBMatrix`]: crate::RGBMatrix
+/// [`RGBMatrix::panic_guard`]: crate::RGBMatrix::panic_guard
+/// [`RGBMatrix::set_enabled`]: crate::RGBMatrix::set_enabled
+pub struct PanicGuard {
+    frame_exchange: Arc<FrameExchange>,
+}
+
+impl PanicGuard {
+    pub(crate) fn new(frame_exchange: Arc<FrameExchange>) -> Self {
+        Self { frame_exchange }
+    }
+}
+
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        if panicking() {
+            self.frame_exchange.set_enabled(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_utils::{mock_matrix, wait_until},
+        Color,
+    };
+
+    #[test]
+    fn test_panic_guard() {
+        let (mut matrix, mut canvas) = mock_matrix();
+        canvas.fill(Color::WHITE);
+        let canvas = matrix.update_on_vsync(canvas);
+
+        // The guard does nothing when it is dropped normally.
+        drop(matrix.panic_guard());
+        assert!(matrix.is_enabled());
+
+        let guard = matrix.panic_guard();
+        let render_thread = std::thread::spawn(move || {
+            let _guard = guard;
+            let mut canvas = canvas;
+            canvas.set_pixel(0, 0, 255, 0, 0);
+            panic!("Rendering failed.");
+        });
+        assert!(render_thread.join().is_err());
+
+        assert!(!matrix.is_enabled());
+        assert!(matrix
+            .get_frame()
+            .iter()
+            .all(|&color| color == Color::BLACK));
+        // No frame is scanned out anymore.
+        wait_until(|| matrix.frame_stats().bitplanes == 0);
+    }
+}
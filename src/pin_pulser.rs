@@ -1,16 +1,180 @@
+use std::time::{Duration, Instant};
+
 use crate::{
     gpio_bits,
-    registers::{ClkRegisters, GPIOFunction, GPIORegisters, PWMRegisters, TimeRegisters},
+    registers::{
+        ClkRegisters, GPIOFunction, GPIORegisters, PWMRegisters, TimeRegisters, PWM_CTL_CLRF1,
+        PWM_CTL_POLA1, PWM_CTL_PWEN1, PWM_CTL_USEF1,
+    },
 };
 
 const PWM_BASE_TIME_NS: u32 = 2;
 
+/// The register operations that are used to pulse the output enable pins.
+pub(crate) trait PulseOutput {
+    fn select_function(&mut self, pin: u8, function: GPIOFunction);
+    fn set_bits(&mut self, value: u32);
+    fn clear_bits(&mut self, value: u32);
+    fn init_pwm_divider(&mut self, divider: u32);
+    fn set_pwm_ctl(&mut self, value: u32);
+    fn set_pwm_pulse_period(&mut self, value: u32);
+    fn push_fifo(&mut self, value: u32);
+    fn fifo_empty(&self) -> bool;
+    /// Time instant in microseconds.
+    fn get_time(&self) -> u64;
+    fn sleep_at_most(&mut self, duration_us: u64);
+    /// Wait for exactly this many nanoseconds.
+    fn wait_ns(&mut self, duration_ns: u32);
+
+    /// Channel 1: Use FIFO | Polarity (1=low, 0=high) | Enable Channel
+    fn enable_pwm(&mut self) {
+        self.set_pwm_ctl(PWM_CTL_USEF1 | PWM_CTL_POLA1 | PWM_CTL_PWEN1);
+    }
+
+    /// Channel 1: Use FIFO | Polarity (1=low, 0=high) | Clear FIFO
+    fn reset_pwm(&mut self) {
+        self.set_pwm_ctl(PWM_CTL_USEF1 | PWM_CTL_POLA1 | PWM_CTL_CLRF1);
+    }
+}
+
+/// The peripheral registers of the Raspberry Pi that are used to pulse the output enable pins.
+pub(crate) struct PulseRegisters<'a> {
+    pub(crate) gpio_registers: &'a mut GPIORegisters,
+    pub(crate) time_registers: &'a mut TimeRegisters,
+    /// The PWM peripheral, if the chip has one. It is only used by the [`HardwarePinPulser`].
+    pub(crate) pwm_registers: Option<&'a mut PWMRegisters>,
+    pub(crate) clk_registers: Option<&'a mut ClkRegisters>,
+}
+
+impl PulseRegisters<'_> {
+    fn pwm_registers(&mut self) -> &mut PWMRegisters {
+        self.pwm_registers
+            .as_deref_mut()
+            .expect("The hardware pin pulser needs the PWM peripheral")
+    }
+}
+
+impl PulseOutput for PulseRegisters<'_> {
+    fn select_function(&mut self, pin: u8, function: GPIOFunction) {
+        self.gpio_registers.select_function(pin, function);
+    }
+
+    fn set_bits(&mut self, value: u32) {
+        self.gpio_registers.write_set_bits(value);
+    }
+
+    fn clear_bits(&mut self, value: u32) {
+        self.gpio_registers.write_clr_bits(value);
+    }
+
+    fn init_pwm_divider(&mut self, divider: u32) {
+        self.clk_registers
+            .as_deref_mut()
+            .expect("The hardware pin pulser needs the PWM clock")
+            .init_pwm_divider(divider);
+    }
+
+    fn set_pwm_ctl(&mut self, value: u32) {
+        self.pwm_registers().set_pwm_ctl(value);
+    }
+
+    fn set_pwm_pulse_period(&mut self, value: u32) {
+        self.pwm_registers().set_pwm_pulse_period(value);
+    }
+
+    fn push_fifo(&mut self, value: u32) {
+        self.pwm_registers().push_fifo(value);
+    }
+
+    fn fifo_empty(&self) -> bool {
+        self.pwm_registers
+            .as_deref()
+            .expect("The hardware pin pulser needs the PWM peripheral")
+            .fifo_empty()
+    }
+
+    fn get_time(&self) -> u64 {
+        self.time_registers.get_time()
+    }
+
+    fn sleep_at_most(&mut self, duration_us: u64) {
+        self.time_registers.sleep_at_most(duration_us);
+    }
+
+    fn wait_ns(&mut self, duration_ns: u32) {
+        let end_time = Instant::now() + Duration::from_nanos(duration_ns as u64);
+        self.time_registers.sleep_at_most(duration_ns as u64 / 1000);
+        while Instant::now() < end_time {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+/// The GPIO pin and the function that routes the PWM peripheral to it, if the output enable is on a pin that
+/// can be driven by the hardware PWM.
+fn pwm_pin_function(pins: u32) -> Option<(u8, GPIOFunction)> {
+    if pins == gpio_bits!(18) {
+        // GPIO 18 in PWM0 mode
+        Some((18, GPIOFunction::Alt5))
+    } else if pins == gpio_bits!(12) {
+        // GPIO 12 in PWM0 mode
+        Some((12, GPIOFunction::Alt0))
+    } else {
+        None
+    }
+}
+
+/// Switches the output enable on for the duration of a bit plane.
+pub(crate) enum PinPulser {
+    /// The pulses are timed by the PWM peripheral, which is used when the output enable is connected to a PWM
+    /// pin, e.g. with [`HardwareMapping::ADAFRUIT_HAT_PWM`](crate::HardwareMapping::ADAFRUIT_HAT_PWM).
+    Hardware(HardwarePinPulser),
+    /// The pulses are timed by busy waiting, which is more susceptible to jitter.
+    Timer(TimerPinPulser),
+}
+
+impl PinPulser {
+    /// The pulses are timed by the PWM peripheral if `hardware_pwm` is available and the output enable is on a
+    /// PWM pin.
+    pub(crate) fn new(
+        pins: u32,
+        bitplane_timings_ns: &[u32],
+        hardware_pwm: bool,
+        output: &mut dyn PulseOutput,
+    ) -> Self {
+        match pwm_pin_function(pins).filter(|_| hardware_pwm) {
+            Some((pin, function)) => Self::Hardware(HardwarePinPulser::new(
+                pin,
+                function,
+                bitplane_timings_ns,
+                output,
+            )),
+            None => Self::Timer(TimerPinPulser::new(pins, bitplane_timings_ns, output)),
+        }
+    }
+
+    pub(crate) fn send_pulse(&mut self, bitplane: usize, output: &mut dyn PulseOutput) {
+        match self {
+            Self::Hardware(pulser) => pulser.send_pulse(bitplane, output),
+            Self::Timer(pulser) => pulser.send_pulse(bitplane, output),
+        }
+    }
+
+    pub(crate) fn wait_pulse_finished(&mut self, output: &mut dyn PulseOutput) {
+        match self {
+            Self::Hardware(pulser) => pulser.wait_pulse_finished(output),
+            // The pulse is already over when `send_pulse` returns.
+            Self::Timer(_) => {}
+        }
+    }
+}
+
 struct Pulse {
     start_time: u64,
     sleep_hint_us: u32,
 }
 
-pub(crate) struct PinPulser {
+pub(crate) struct HardwarePinPulser {
     /// Hints how long to sleep.
     sleep_hints_us: Vec<u32>,
     /// Pulse period for each bit plane.
@@ -19,30 +183,21 @@ pub(crate) struct PinPulser {
     current_pulse: Option<Pulse>,
 }
 
-impl PinPulser {
-    pub(crate) fn new(
-        pins: u32,
+impl HardwarePinPulser {
+    fn new(
+        pin: u8,
+        function: GPIOFunction,
         bitplane_timings_ns: &[u32],
-        pwm_registers: &mut PWMRegisters,
-        gpio_registers: &mut GPIORegisters,
-        clk_registers: &mut ClkRegisters,
+        output: &mut dyn PulseOutput,
     ) -> Self {
         let sleep_hints_us = bitplane_timings_ns.iter().map(|t| t / 1000).collect();
 
         let time_base = bitplane_timings_ns[0];
 
-        if pins == gpio_bits!(18) {
-            // Set GPIO 18 to PWM0 mode
-            gpio_registers.select_function(18, GPIOFunction::Alt5);
-        } else if pins == gpio_bits!(12) {
-            // Set GPIO 12 to PWM0 mode
-            gpio_registers.select_function(12, GPIOFunction::Alt0);
-        } else {
-            unreachable!()
-        }
+        output.select_function(pin, function);
 
-        pwm_registers.reset_pwm();
-        clk_registers.init_pwm_divider((time_base / 2) / PWM_BASE_TIME_NS);
+        output.reset_pwm();
+        output.init_pwm_divider((time_base / 2) / PWM_BASE_TIME_NS);
         let pulse_periods = bitplane_timings_ns
             .iter()
             .map(|timing| 2 * timing / time_base)
@@ -55,48 +210,39 @@ impl PinPulser {
         }
     }
 
-    pub(crate) fn send_pulse(
-        &mut self,
-        bitplane: usize,
-        pwm_registers: &mut PWMRegisters,
-        time_registers: &mut TimeRegisters,
-    ) {
+    fn send_pulse(&mut self, bitplane: usize, output: &mut dyn PulseOutput) {
         if self.pulse_periods[bitplane] < 16 {
-            pwm_registers.set_pwm_pulse_period(self.pulse_periods[bitplane]);
-            pwm_registers.push_fifo(self.pulse_periods[bitplane]);
+            output.set_pwm_pulse_period(self.pulse_periods[bitplane]);
+            output.push_fifo(self.pulse_periods[bitplane]);
         } else {
             // Keep the actual range as short as possible, as we have to wait for one full period of these in
             // the zero phase. The hardware can't deal with values < 2, so only do this when we have have
             // enough of these.
             let period_fraction = self.pulse_periods[bitplane] / 8;
-            pwm_registers.set_pwm_pulse_period(period_fraction);
+            output.set_pwm_pulse_period(period_fraction);
             for _ in 0..8 {
-                pwm_registers.push_fifo(period_fraction);
+                output.push_fifo(period_fraction);
             }
         }
 
         // We need one sentinel value at the end to have it go back to default state (otherwise it just
         // repeats the last value, so will be constantly 'on').
-        pwm_registers.push_fifo(0);
+        output.push_fifo(0);
 
         // For some reason, we need a second empty sentinel in the FIFO, otherwise our way to detect the end
         // of the pulse, which relies on "is the queue empty" does not work. It is not entirely clear why that
         // is from the data sheet, but probably there is some buffering register in which data elements are
         // kept after the FIFO is emptied.
-        pwm_registers.push_fifo(0);
+        output.push_fifo(0);
 
         self.current_pulse = Some(Pulse {
-            start_time: time_registers.get_time(),
+            start_time: output.get_time(),
             sleep_hint_us: self.sleep_hints_us[bitplane],
         });
-        pwm_registers.enable_pwm();
+        output.enable_pwm();
     }
 
-    pub(crate) fn wait_pulse_finished(
-        &mut self,
-        time_registers: &mut TimeRegisters,
-        pwm_registers: &mut PWMRegisters,
-    ) {
+    fn wait_pulse_finished(&mut self, output: &mut dyn PulseOutput) {
         let pulse = match self.current_pulse.take() {
             Some(t) => t,
             None => {
@@ -104,15 +250,191 @@ impl PinPulser {
             }
         };
 
-        let already_elapsed_us = time_registers.get_time() - pulse.start_time;
+        let already_elapsed_us = output.get_time() - pulse.start_time;
         let remaining_time_us = (pulse.sleep_hint_us as u64).saturating_sub(already_elapsed_us);
-        time_registers.sleep_at_most(remaining_time_us);
+        output.sleep_at_most(remaining_time_us);
 
-        while !pwm_registers.fifo_empty() {
+        while !output.fifo_empty() {
             // busy wait until done.
             std::thread::yield_now();
         }
 
-        pwm_registers.reset_pwm();
+        output.reset_pwm();
+    }
+}
+
+pub(crate) struct TimerPinPulser {
+    /// The output enable bits, which are active low.
+    pins: u32,
+    /// Pulse duration for each bit plane.
+    bitplane_timings_ns: Vec<u32>,
+}
+
+impl TimerPinPulser {
+    fn new(pins: u32, bitplane_timings_ns: &[u32], output: &mut dyn PulseOutput) -> Self {
+        // Start with the output disabled.
+        output.set_bits(pins);
+        Self {
+            pins,
+            bitplane_timings_ns: bitplane_timings_ns.to_vec(),
+        }
+    }
+
+    fn send_pulse(&mut self, bitplane: usize, output: &mut dyn PulseOutput) {
+        output.clear_bits(self.pins);
+        output.wait_ns(self.bitplane_timings_ns[bitplane]);
+        output.set_bits(self.pins);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::K_BIT_PLANES, HardwareMapping};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Write {
+        Function(u8, GPIOFunction),
+        SetBits(u32),
+        ClearBits(u32),
+        PwmDivider(u32),
+        PwmCtl(u32),
+        PwmRange(u32),
+        Fifo(u32),
+        Wait(u32),
+    }
+
+    #[derive(Default)]
+    struct RecordingOutput {
+        writes: Vec<Write>,
+    }
+
+    impl PulseOutput for RecordingOutput {
+        fn select_function(&mut self, pin: u8, function: GPIOFunction) {
+            self.writes.push(Write::Function(pin, function));
+        }
+
+        fn set_bits(&mut self, value: u32) {
+            self.writes.push(Write::SetBits(value));
+        }
+
+        fn clear_bits(&mut self, value: u32) {
+            self.writes.push(Write::ClearBits(value));
+        }
+
+        fn init_pwm_divider(&mut self, divider: u32) {
+            self.writes.push(Write::PwmDivider(divider));
+        }
+
+        fn set_pwm_ctl(&mut self, value: u32) {
+            self.writes.push(Write::PwmCtl(value));
+        }
+
+        fn set_pwm_pulse_period(&mut self, value: u32) {
+            self.writes.push(Write::PwmRange(value));
+        }
+
+        fn push_fifo(&mut self, value: u32) {
+            self.writes.push(Write::Fifo(value));
+        }
+
+        fn fifo_empty(&self) -> bool {
+            true
+        }
+
+        fn get_time(&self) -> u64 {
+            0
+        }
+
+        fn sleep_at_most(&mut self, _duration_us: u64) {}
+
+        fn wait_ns(&mut self, duration_ns: u32) {
+            self.writes.push(Write::Wait(duration_ns));
+        }
+    }
+
+    const RESET: u32 = PWM_CTL_USEF1 | PWM_CTL_POLA1 | PWM_CTL_CLRF1;
+    const ENABLE: u32 = PWM_CTL_USEF1 | PWM_CTL_POLA1 | PWM_CTL_PWEN1;
+
+    fn bitplane_timings_ns() -> Vec<u32> {
+        (0..K_BIT_PLANES).map(|plane| 130 << plane).collect()
+    }
+
+    #[test]
+    fn test_hardware_pulses() {
+        let mut output = RecordingOutput::default();
+        let mut pulser = PinPulser::new(
+            HardwareMapping::ADAFRUIT_HAT_PWM.output_enable,
+            &bitplane_timings_ns(),
+            true,
+            &mut output,
+        );
+        assert!(matches!(pulser, PinPulser::Hardware(_)));
+        // The output enable is routed to the PWM peripheral, whose clock ticks twice per PWM_BASE_TIME_NS.
+        assert_eq!(
+            output.writes,
+            [
+                Write::Function(18, GPIOFunction::Alt5),
+                Write::PwmCtl(RESET),
+                Write::PwmDivider(32),
+            ]
+        );
+
+        output.writes.clear();
+        pulser.send_pulse(0, &mut output);
+        pulser.wait_pulse_finished(&mut output);
+        assert_eq!(
+            output.writes,
+            [
+                Write::PwmRange(2),
+                Write::Fifo(2),
+                Write::Fifo(0),
+                Write::Fifo(0),
+                Write::PwmCtl(ENABLE),
+                Write::PwmCtl(RESET),
+            ]
+        );
+
+        // Long pulses are split into 8 shorter periods.
+        output.writes.clear();
+        pulser.send_pulse(4, &mut output);
+        assert_eq!(output.writes[0], Write::PwmRange(4));
+        assert_eq!(output.writes[1..9], [Write::Fifo(4); 8]);
+        // No GPIO is written directly.
+        assert!(!output.writes.iter().any(|write| matches!(
+            write,
+            Write::SetBits(_) | Write::ClearBits(_) | Write::Wait(_)
+        )));
+    }
+
+    #[test]
+    fn test_timer_pulses() {
+        let output_enable = HardwareMapping::ADAFRUIT_HAT.output_enable;
+        let mut output = RecordingOutput::default();
+        let mut pulser = PinPulser::new(output_enable, &bitplane_timings_ns(), true, &mut output);
+        assert!(matches!(pulser, PinPulser::Timer(_)));
+        assert_eq!(output.writes, [Write::SetBits(output_enable)]);
+
+        output.writes.clear();
+        pulser.send_pulse(3, &mut output);
+        pulser.wait_pulse_finished(&mut output);
+        assert_eq!(
+            output.writes,
+            [
+                Write::ClearBits(output_enable),
+                Write::Wait(1040),
+                Write::SetBits(output_enable),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timer_pulses_without_hardware_pwm() {
+        // The Raspberry Pi 5 has no PWM peripheral at the usual address, so even a PWM pin is pulsed by the timer.
+        let output_enable = HardwareMapping::ADAFRUIT_HAT_PWM.output_enable;
+        let mut output = RecordingOutput::default();
+        let pulser = PinPulser::new(output_enable, &bitplane_timings_ns(), false, &mut output);
+        assert!(matches!(pulser, PinPulser::Timer(_)));
+        assert_eq!(output.writes, [Write::SetBits(output_enable)]);
     }
 }
@@ -1,4 +1,12 @@
-use crate::{multiplex_mapper::MultiplexMapper, named_pixel_mapper::NamedPixelMapper};
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+
+use crate::{
+    canvas::{PixelDesignator, PixelDesignatorMap},
+    config::PanelSpec,
+    multiplex_mapper::{MultiplexMapper, MultiplexMapperType},
+    named_pixel_mapper::{NamedPixelMapper, NamedPixelMapperType, PixelMapperError},
+    ConfigError, RGBMatrixConfig,
+};
 
 /// A pixel mapper is a way for you to map pixels of LED matrixes to a different
 /// layout. If you have an implementation of a PixelMapper, you can give it
@@ -21,12 +29,38 @@ pub(crate) trait PixelMapper {
     ) -> [usize; 2];
 }
 
-pub(crate) struct MultiplexMapperWrapper(pub(crate) Box<dyn MultiplexMapper>);
+/// Applies the multiplexing to every panel of the chain, which can have different widths.
+pub(crate) struct PanelMultiplexMapper {
+    /// The first visible column of every panel, along with the first matrix column and the multiplexing of the
+    /// panel.
+    panels: Vec<(usize, usize, Box<dyn MultiplexMapper>)>,
+}
+
+impl PanelMultiplexMapper {
+    pub(crate) fn new(multiplexing: MultiplexMapperType, panels: &[PanelSpec]) -> Self {
+        let [mut visible_start, mut matrix_start] = [0, 0];
+        let panels = panels
+            .iter()
+            .map(|panel| {
+                let mut mapper = multiplexing.create();
+                let [mut rows, mut cols] = [panel.rows, panel.cols];
+                mapper.edit_rows_cols(&mut rows, &mut cols);
+                let starts = (visible_start, matrix_start);
+                visible_start += panel.cols;
+                matrix_start += cols;
+                (starts.0, starts.1, mapper)
+            })
+            .collect();
+        Self { panels }
+    }
+}
 
-impl PixelMapper for MultiplexMapperWrapper {
+impl PixelMapper for PanelMultiplexMapper {
     fn get_size_mapping(&self, matrix_width: usize, matrix_height: usize) -> [usize; 2] {
-        // Delegate the call to the underlying MultiplexMapper
-        self.0.get_size_mapping(matrix_width, matrix_height)
+        // All panels are stretched by the same factor.
+        self.panels[0]
+            .2
+            .get_size_mapping(matrix_width, matrix_height)
     }
 
     fn map_visible_to_matrix(
@@ -36,28 +70,438 @@ impl PixelMapper for MultiplexMapperWrapper {
         visible_x: usize,
         visible_y: usize,
     ) -> [usize; 2] {
-        // Delegate the call to the underlying MultiplexMapper
-        self.0
-            .map_visible_to_matrix(matrix_width, matrix_height, visible_x, visible_y)
+        let (visible_start, matrix_start, mapper) = self
+            .panels
+            .iter()
+            .rev()
+            .find(|(visible_start, _, _)| *visible_start <= visible_x)
+            .expect("The first panel starts at column 0.");
+        let [x, y] = mapper.map_visible_to_matrix(
+            matrix_width,
+            matrix_height,
+            visible_x - visible_start,
+            visible_y,
+        );
+        [matrix_start + x, y]
     }
 }
 
-pub(crate) struct NamedPixelMapperWrapper(pub(crate) Box<dyn NamedPixelMapper>);
+/// The size of the layout each mapper of a chain maps into, followed by the visible size.
+type LayoutSizes = Vec<[usize; 2]>;
+
+/// Several named pixel mappers, composed into a single pass that builds the designator map of the visible
+/// pixels. The mappers are only evaluated once per visible pixel when compiling, instead of building a map for
+/// every mapper of the chain, and each visible pixel needs a single lookup afterwards.
+pub(crate) struct CompiledPixelMapper {
+    mappers: Vec<Box<dyn NamedPixelMapper>>,
+    sizes: LayoutSizes,
+    /// The angle of a single rotation by 90 or 270 degrees, which is compiled without evaluating the mapper.
+    rotation: Option<usize>,
+}
 
-impl PixelMapper for NamedPixelMapperWrapper {
-    fn get_size_mapping(&self, width: usize, height: usize) -> [usize; 2] {
-        // Delegate the call to the underlying NamedPixelMapper
-        self.0.get_size_mapping(width, height)
+impl CompiledPixelMapper {
+    /// Compose the mappers, which are applied in the given order, on top of a matrix of the given size.
+    pub(crate) fn new(
+        mapper_types: &[NamedPixelMapperType],
+        chain: usize,
+        parallel: usize,
+        matrix_width: usize,
+        matrix_height: usize,
+    ) -> Result<Self, PixelMapperError> {
+        let (mappers, sizes) =
+            Self::create_mappers(mapper_types, chain, parallel, matrix_width, matrix_height)?;
+        let rotation = match mapper_types {
+            [NamedPixelMapperType::Rotate(angle @ (90 | 270))] => Some(*angle),
+            _ => None,
+        };
+        Ok(Self {
+            mappers,
+            sizes,
+            rotation,
+        })
     }
 
-    fn map_visible_to_matrix(
+    /// Compile a user provided mapper on top of a matrix of the given size. Unlike the named mappers, which
+    /// leave pixels beyond the matrix dark, the visible size and every mapped position are checked, so that a
+    /// faulty mapper is rejected instead of hiding parts of the canvas.
+    pub(crate) fn new_custom(
+        mapper: Box<dyn NamedPixelMapper>,
+        matrix_width: usize,
+        matrix_height: usize,
+    ) -> Result<Self, PixelMapperError> {
+        let [width, height] = mapper.get_size_mapping(matrix_width, matrix_height);
+        if width == 0 || height == 0 || width.checked_mul(height).is_none() {
+            return Err(PixelMapperError::InvalidVisibleSize { width, height });
+        }
+        for y in 0..height {
+            for x in 0..width {
+                let [matrix_x, matrix_y] =
+                    mapper.map_visible_to_matrix(matrix_width, matrix_height, x, y);
+                if matrix_x >= matrix_width || matrix_y >= matrix_height {
+                    return Err(PixelMapperError::PositionOutOfRange {
+                        visible: [x, y],
+                        matrix: [matrix_x, matrix_y],
+                        width: matrix_width,
+                        height: matrix_height,
+                    });
+                }
+            }
+        }
+        Ok(Self {
+            mappers: vec![mapper],
+            sizes: vec![[matrix_width, matrix_height], [width, height]],
+            rotation: None,
+        })
+    }
+
+    /// Build the designator map of the visible pixels from the map of the matrix the mappers were composed
+    /// on. Visible pixels that are mapped outside of the matrix are not drawn.
+    pub(crate) fn apply(
         &self,
-        old_width: usize,
-        old_height: usize,
-        x: usize,
-        y: usize,
-    ) -> [usize; 2] {
-        // Delegate the call to the underlying NamedPixelMapper
-        self.0.map_visible_to_matrix(old_width, old_height, x, y)
+        shared_mapper: &PixelDesignatorMap,
+        pixel_designator: PixelDesignator,
+    ) -> PixelDesignatorMap {
+        let [visible_width, visible_height] = self.sizes[self.sizes.len() - 1];
+        let mut designators = Vec::with_capacity(visible_width * visible_height);
+        match self.rotation {
+            Some(angle) => Self::rotate(angle, shared_mapper, &mut designators),
+            None => self.chain(shared_mapper, pixel_designator, &mut designators),
+        }
+        PixelDesignatorMap::from_designators(
+            pixel_designator,
+            visible_width,
+            visible_height,
+            designators,
+        )
+    }
+
+    /// Evaluate the chained mappers for every visible pixel.
+    fn chain(
+        &self,
+        shared_mapper: &PixelDesignatorMap,
+        pixel_designator: PixelDesignator,
+        designators: &mut Vec<PixelDesignator>,
+    ) {
+        let [visible_width, visible_height] = self.sizes[self.sizes.len() - 1];
+        for y in 0..visible_height {
+            for x in 0..visible_width {
+                let mut position = Some([x, y]);
+                for (mapper, &[width, height]) in self.mappers.iter().zip(self.sizes.iter()).rev() {
+                    position = position
+                        .map(|[x, y]| mapper.map_visible_to_matrix(width, height, x, y))
+                        .filter(|&[x, y]| x < width && y < height);
+                }
+                designators.push(match position {
+                    Some([x, y]) => *shared_mapper.get(x, y).unwrap(),
+                    None => pixel_designator,
+                });
+            }
+        }
+    }
+
+    /// A single rotation by 90 or 270 degrees transposes the matrix. Every visible row is a whole column of
+    /// the matrix, so it is copied in one go instead of evaluating the mapper per pixel.
+    fn rotate(
+        angle: usize,
+        shared_mapper: &PixelDesignatorMap,
+        designators: &mut Vec<PixelDesignator>,
+    ) {
+        let [matrix_width, matrix_height] = [shared_mapper.width(), shared_mapper.height()];
+        for y in 0..matrix_width {
+            // Rotating clockwise reads the columns from right to left and top to bottom, rotating counter
+            // clockwise from left to right and bottom to top.
+            let column = if angle == 90 { matrix_width - y - 1 } else { y };
+            let column = (0..matrix_height).map(|row| *shared_mapper.get(column, row).unwrap());
+            if angle == 90 {
+                designators.extend(column);
+            } else {
+                designators.extend(column.rev());
+            }
+        }
+    }
+
+    /// Check that the mappers can be composed on top of a matrix of the given size, without compiling them.
+    pub(crate) fn check(
+        mapper_types: &[NamedPixelMapperType],
+        chain: usize,
+        parallel: usize,
+        matrix_width: usize,
+        matrix_height: usize,
+    ) -> Result<(), PixelMapperError> {
+        Self::create_mappers(mapper_types, chain, parallel, matrix_width, matrix_height).map(|_| ())
+    }
+
+    /// Create the mappers along with the size of the layout each mapper maps into, followed by the visible
+    /// size.
+    fn create_mappers(
+        mapper_types: &[NamedPixelMapperType],
+        chain: usize,
+        parallel: usize,
+        matrix_width: usize,
+        matrix_height: usize,
+    ) -> Result<(Vec<Box<dyn NamedPixelMapper>>, LayoutSizes), PixelMapperError> {
+        let mut sizes = vec![[matrix_width, matrix_height]];
+        let mut mappers = Vec::with_capacity(mapper_types.len());
+        for mapper_type in mapper_types {
+            let [width, height] = sizes[sizes.len() - 1];
+            mapper_type.check_size(chain, parallel, width, height)?;
+            let mapper = mapper_type.create(chain, parallel)?;
+            sizes.push(mapper.get_size_mapping(width, height));
+            mappers.push(mapper);
+        }
+        Ok((mappers, sizes))
+    }
+}
+
+/// Build the mapping from visible pixels to the matrix, including the multiplexing and the configured pixel
+/// mappers. The rows and columns of the config are adjusted to the multiplexing.
+pub(crate) fn create_shared_mapper(
+    config: &mut RGBMatrixConfig,
+) -> Result<(PixelDesignator, Arc<PixelDesignatorMap>), ConfigError> {
+    let pixel_designator = PixelDesignator::new(&config.hardware_mapping, config.led_sequence);
+    let width = config.chain_width();
+    let height = config.rows * config.parallel;
+    let mut shared_mapper = PixelDesignatorMap::new(pixel_designator, width, height, config);
+
+    // Apply the mapping for the panels first.
+    if let Some(multiplexing) = config.multiplexing {
+        let mapper = PanelMultiplexMapper::new(multiplexing, &config.chain_panels());
+        multiplexing
+            .create()
+            .edit_rows_cols(&mut config.rows, &mut config.cols);
+        for panel in &mut config.panels {
+            multiplexing
+                .create()
+                .edit_rows_cols(&mut panel.rows, &mut panel.cols);
+        }
+        shared_mapper = apply_pixel_mapper(&shared_mapper, mapper, pixel_designator);
+    }
+
+    // Apply higher level mappers that might arrange panels. They are compiled into a single pass, so that the
+    // mappers only need to be evaluated once per pixel.
+    if !config.pixelmapper.is_empty() {
+        let mapper = CompiledPixelMapper::new(
+            &config.pixelmapper,
+            config.chain_length,
+            config.parallel,
+            shared_mapper.width(),
+            shared_mapper.height(),
+        )
+        .map_err(ConfigError::PixelMapper)?;
+        shared_mapper = mapper.apply(&shared_mapper, pixel_designator);
+    }
+
+    Ok((pixel_designator, Arc::new(shared_mapper)))
+}
+
+pub(crate) fn apply_pixel_mapper(
+    shared_mapper: &PixelDesignatorMap,
+    mapper: impl PixelMapper,
+    pixel_designator: PixelDesignator,
+) -> PixelDesignatorMap {
+    let old_width = shared_mapper.width();
+    let old_height = shared_mapper.height();
+    let [new_width, new_height] = mapper.get_size_mapping(old_width, old_height);
+    let mut designators = Vec::with_capacity(new_width * new_height);
+    for y in 0..new_height {
+        for x in 0..new_width {
+            let [orig_x, orig_y] = mapper.map_visible_to_matrix(old_width, old_height, x, y);
+            // Pixels beyond the matrix are not drawn, like with the named mappers.
+            if orig_x >= old_width || orig_y >= old_height {
+                designators.push(pixel_designator);
+                continue;
+            }
+            designators.push(*shared_mapper.get(orig_x, orig_y).unwrap());
+        }
+    }
+    PixelDesignatorMap::from_designators(pixel_designator, new_width, new_height, designators)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A map of a matrix of the given size, whose designators tell the pixels apart.
+    fn test_matrix(width: usize, height: usize) -> (PixelDesignatorMap, PixelDesignator) {
+        let config = RGBMatrixConfig {
+            rows: height,
+            cols: width,
+            ..Default::default()
+        };
+        let pixel_designator = PixelDesignator::new(&config.hardware_mapping, config.led_sequence);
+        let matrix = PixelDesignatorMap::new(pixel_designator, width, height, &config);
+        (matrix, pixel_designator)
+    }
+
+    #[test]
+    fn test_compiled_matches_chained_mappers() {
+        // Four chained 64x64 panels.
+        let [chain, parallel] = [4, 1];
+        let [matrix_width, matrix_height] = [64 * chain, 64 * parallel];
+        let (matrix, pixel_designator) = test_matrix(matrix_width, matrix_height);
+        let mapper_types = [
+            NamedPixelMapperType::Mirror(true),
+            NamedPixelMapperType::Rotate(90),
+            NamedPixelMapperType::UMapper,
+        ];
+        let mappers: Vec<_> = mapper_types
+            .iter()
+            .map(|m| m.create(chain, parallel).unwrap())
+            .collect();
+        let compiled =
+            CompiledPixelMapper::new(&mapper_types, chain, parallel, matrix_width, matrix_height)
+                .unwrap()
+                .apply(&matrix, pixel_designator);
+
+        let mirrored_size = mappers[0].get_size_mapping(matrix_width, matrix_height);
+        let rotated_size = mappers[1].get_size_mapping(mirrored_size[0], mirrored_size[1]);
+        let visible_size = mappers[2].get_size_mapping(rotated_size[0], rotated_size[1]);
+        assert_eq!([compiled.width(), compiled.height()], visible_size);
+
+        for y in 0..visible_size[1] {
+            for x in 0..visible_size[0] {
+                let [x1, y1] =
+                    mappers[2].map_visible_to_matrix(rotated_size[0], rotated_size[1], x, y);
+                let [x2, y2] =
+                    mappers[1].map_visible_to_matrix(mirrored_size[0], mirrored_size[1], x1, y1);
+                let [x3, y3] =
+                    mappers[0].map_visible_to_matrix(matrix_width, matrix_height, x2, y2);
+                assert_eq!(compiled.get(x, y), matrix.get(x3, y3));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotation_fast_path_matches_chained_mappers() {
+        let [matrix_width, matrix_height] = [64, 32];
+        let (matrix, pixel_designator) = test_matrix(matrix_width, matrix_height);
+        for angle in [90, 270] {
+            let mapper_types = [NamedPixelMapperType::Rotate(angle)];
+            let compiled =
+                CompiledPixelMapper::new(&mapper_types, 1, 1, matrix_width, matrix_height).unwrap();
+            assert_eq!(compiled.rotation, Some(angle));
+            let rotated = compiled.apply(&matrix, pixel_designator);
+            let chained = CompiledPixelMapper {
+                rotation: None,
+                ..compiled
+            }
+            .apply(&matrix, pixel_designator);
+            assert_eq!([rotated.width(), rotated.height()], [32, 64]);
+            for y in 0..64 {
+                for x in 0..32 {
+                    assert_eq!(rotated.get(x, y), chained.get(x, y), "{angle}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed_panel_multiplexing() {
+        let [wide, narrow] = [PanelSpec::new(32, 64), PanelSpec::new(32, 32)];
+        let chain = PanelMultiplexMapper::new(MultiplexMapperType::Stripe, &[wide, narrow]);
+        let wide_alone = PanelMultiplexMapper::new(MultiplexMapperType::Stripe, &[wide]);
+        let narrow_alone = PanelMultiplexMapper::new(MultiplexMapperType::Stripe, &[narrow]);
+        // The stripe multiplexing doubles the columns of each panel and halves its rows.
+        let [matrix_width, matrix_height] = [2 * 96, 16];
+        assert_eq!(
+            chain.get_size_mapping(matrix_width, matrix_height),
+            [96, 32]
+        );
+        for y in 0..32 {
+            for x in 0..64 {
+                assert_eq!(
+                    chain.map_visible_to_matrix(matrix_width, matrix_height, x, y),
+                    wide_alone.map_visible_to_matrix(2 * 64, 16, x, y)
+                );
+            }
+            for x in 0..32 {
+                let [narrow_x, narrow_y] = narrow_alone.map_visible_to_matrix(2 * 32, 16, x, y);
+                assert_eq!(
+                    chain.map_visible_to_matrix(matrix_width, matrix_height, 64 + x, y),
+                    [2 * 64 + narrow_x, narrow_y]
+                );
+            }
+        }
+    }
+
+    /// A custom mapper with a fixed visible size, which shifts the pixels to the right.
+    struct ShiftMapper {
+        size: [usize; 2],
+        shift: usize,
+    }
+
+    impl NamedPixelMapper for ShiftMapper {
+        fn get_size_mapping(&self, _matrix_width: usize, _matrix_height: usize) -> [usize; 2] {
+            self.size
+        }
+
+        fn map_visible_to_matrix(
+            &self,
+            _matrix_width: usize,
+            _matrix_height: usize,
+            visible_x: usize,
+            visible_y: usize,
+        ) -> [usize; 2] {
+            [visible_x + self.shift, visible_y]
+        }
+    }
+
+    #[test]
+    fn test_custom_mapper() {
+        let (matrix, pixel_designator) = test_matrix(64, 32);
+        let mapper = Box::new(ShiftMapper {
+            size: [32, 32],
+            shift: 32,
+        });
+        let compiled = CompiledPixelMapper::new_custom(mapper, 64, 32)
+            .unwrap()
+            .apply(&matrix, pixel_designator);
+        assert_eq!([compiled.width(), compiled.height()], [32, 32]);
+        assert_eq!(compiled.get(0, 5), matrix.get(32, 5));
+
+        for (size, shift, error) in [
+            (
+                [32, 32],
+                33,
+                PixelMapperError::PositionOutOfRange {
+                    visible: [31, 0],
+                    matrix: [64, 0],
+                    width: 64,
+                    height: 32,
+                },
+            ),
+            (
+                [32, 33],
+                0,
+                PixelMapperError::PositionOutOfRange {
+                    visible: [0, 32],
+                    matrix: [0, 32],
+                    width: 64,
+                    height: 32,
+                },
+            ),
+            (
+                [0, 32],
+                0,
+                PixelMapperError::InvalidVisibleSize {
+                    width: 0,
+                    height: 32,
+                },
+            ),
+            (
+                [usize::MAX, 2],
+                0,
+                PixelMapperError::InvalidVisibleSize {
+                    width: usize::MAX,
+                    height: 2,
+                },
+            ),
+        ] {
+            let mapper = Box::new(ShiftMapper { size, shift });
+            assert_eq!(
+                CompiledPixelMapper::new_custom(mapper, 64, 32).err(),
+                Some(error)
+            );
+        }
     }
 }
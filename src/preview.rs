@@ -0,0 +1,103 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use minifb::{Key, Window, WindowOptions};
+
+use crate::{rgb_matrix::MatrixCreationError, Canvas, RGBMatrix, RGBMatrixConfig};
+
+#[derive(Debug)]
+pub enum PreviewError {
+    /// Creating the mock matrix failed, e.g. because the config is invalid.
+    Matrix(MatrixCreationError),
+    /// Opening the window failed, e.g. because there is no display.
+    Window(minifb::Error),
+}
+
+impl Error for PreviewError {}
+
+impl Display for PreviewError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreviewError::Matrix(error) => write!(f, "Failed to create the matrix: {error}"),
+            PreviewError::Window(error) => write!(f, "Failed to open the preview window: {error}"),
+        }
+    }
+}
+
+impl From<MatrixCreationError> for PreviewError {
+    fn from(error: MatrixCreationError) -> Self {
+        Self::Matrix(error)
+    }
+}
+
+/// Shows the frames of a matrix in a desktop window instead of on the panels, to develop animations without a
+/// Raspberry Pi. Frames are swapped with [`PreviewMatrix::update_on_vsync`] like with
+/// [`RGBMatrix::update_on_vsync`], on a matrix created with [`RGBMatrix::new_mock`], and every displayed frame
+/// is drawn into the window with square LEDs and a dark gap between them like [`RGBMatrix::preview_frame`].
+///
+/// The window is only redrawn when a frame is swapped, so it must be created and updated on the thread that
+/// draws the frames, which has to be the main thread on some platforms.
+pub struct PreviewMatrix {
+    matrix: RGBMatrix,
+    window: Window,
+    led_size: usize,
+    gap: usize,
+}
+
+impl PreviewMatrix {
+    /// Create a mock matrix for the config and open a window that shows its frames, with every LED drawn as a
+    /// square of `led_size` window pixels and `gap` window pixels between the LEDs.
+    pub fn new(
+        config: RGBMatrixConfig,
+        led_size: usize,
+        gap: usize,
+    ) -> Result<(Self, Box<Canvas>), PreviewError> {
+        let led_size = led_size.max(1);
+        let (matrix, canvas) = RGBMatrix::new_mock(config)?;
+        let (width, height, _) = matrix.preview_frame(led_size, gap);
+        let mut window = Window::new(
+            "rpi-led-panel preview",
+            width,
+            height,
+            WindowOptions::default(),
+        )
+        .map_err(PreviewError::Window)?;
+        // The matrix already paces the frames at its refresh rate.
+        window.set_target_fps(0);
+        let preview = Self {
+            matrix,
+            window,
+            led_size,
+            gap,
+        };
+        Ok((preview, canvas))
+    }
+
+    /// Display the canvas like [`RGBMatrix::update_on_vsync`], draw it into the window and return the canvas
+    /// that was displayed before.
+    pub fn update_on_vsync(&mut self, canvas: Box<Canvas>) -> Box<Canvas> {
+        let canvas = self.matrix.update_on_vsync(canvas);
+        let (width, height, pixels) = self.matrix.preview_frame(self.led_size, self.gap);
+        if let Err(error) = self.window.update_with_buffer(&pixels, width, height) {
+            eprintln!("Failed to draw the preview window: {error}");
+        }
+        canvas
+    }
+
+    /// Whether the window is still open. It is closed by the user, or by pressing Escape.
+    pub fn is_open(&self) -> bool {
+        self.window.is_open() && !self.window.is_key_down(Key::Escape)
+    }
+
+    /// Get the matrix, e.g. to read its framerate.
+    pub fn matrix(&self) -> &RGBMatrix {
+        &self.matrix
+    }
+
+    /// Get the matrix to change its runtime settings, e.g. the brightness.
+    pub fn matrix_mut(&mut self) -> &mut RGBMatrix {
+        &mut self.matrix
+    }
+}
@@ -0,0 +1,684 @@
+use alloc::vec::Vec;
+
+use crate::{Canvas, Color};
+
+impl Canvas {
+    /// Draw a line from (`x0`, `y0`) to (`x1`, `y1`), including both end points, using Bresenham's
+    /// algorithm. Parts of the line that are outside of the canvas are discarded.
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        let [x0, y0, x1, y1] = [x0, y0, x1, y1].map(i64::from);
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let step_x = if x0 < x1 { 1 } else { -1 };
+        let step_y = if y0 < y1 { 1 } else { -1 };
+
+        let [mut x, mut y] = [x0, y0];
+        let mut error = dx + dy;
+        loop {
+            self.set_pixel_clipped(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled_error = 2 * error;
+            if doubled_error >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled_error <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Draw an anti-aliased line from (`x0`, `y0`) to (`x1`, `y1`) using Xiaolin Wu's algorithm. Along the
+    /// line, the two pixels closest to it are blended over the existing pixels like [`Canvas::blend_pixel`], in
+    /// proportion to their distance from the line. The blending is done on the drawn colors, before the gamma
+    /// correction, so the perceived brightness of the line only approximately stays the same along it.
+    /// Horizontal, vertical and 45 degree lines have no partially covered pixels and look like the ones of
+    /// [`Canvas::draw_line`]. Parts of the line that are outside of the canvas are discarded.
+    pub fn draw_line_aa(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        let [mut x0, mut y0, mut x1, mut y1] = [x0, y0, x1, y1].map(i64::from);
+        // Step along the longer axis, which is x after swapping the axes of steep lines.
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            (x0, y0, x1, y1) = (y0, x0, y1, x1);
+        }
+        if x0 > x1 {
+            (x0, y0, x1, y1) = (x1, y1, x0, y0);
+        }
+        let [dx, dy] = [x1 - x0, y1 - y0].map(i128::from);
+        // Only the columns within the canvas are stepped through.
+        let columns = if steep { self.height() } else { self.width() } as i64;
+        let mut plot = |x: i64, y: i64, alpha: i128| {
+            let [x, y] = if steep { [y, x] } else { [x, y] };
+            if x >= 0 && y >= 0 && alpha > 0 {
+                self.blend_pixel(x as usize, y as usize, color, alpha as u8);
+            }
+        };
+        for x in x0.max(0)..=x1.min(columns - 1) {
+            // The line crosses this column at y + remainder / dx. The offset exceeds i64 for distant end points.
+            let offset = i128::from(x - x0) * dy;
+            let (y, remainder) = match dx {
+                0 => (y0, 0),
+                _ => (y0 + offset.div_euclid(dx) as i64, offset.rem_euclid(dx)),
+            };
+            let coverage = match dx {
+                0 => 0,
+                _ => (remainder * 255 + dx / 2) / dx,
+            };
+            plot(x, y, 255 - coverage);
+            plot(x, y + 1, coverage);
+        }
+    }
+
+    /// Draw lines that connect the consecutive `points`, given as (x, y). Parts of the lines that are outside of
+    /// the canvas are discarded. Nothing is drawn for fewer than two points.
+    pub fn draw_polyline(&mut self, points: &[(i32, i32)], color: Color) {
+        for window in points.windows(2) {
+            let [(x0, y0), (x1, y1)] = [window[0], window[1]];
+            self.draw_line(x0, y0, x1, y1, color);
+        }
+    }
+
+    /// Draw the outline of a polygon, which connects the consecutive `points` like
+    /// [`Canvas::draw_polyline`] and the last point back to the first. Nothing is drawn for fewer than two
+    /// points.
+    pub fn draw_polygon(&mut self, points: &[(i32, i32)], color: Color) {
+        if points.len() < 2 {
+            return;
+        }
+        self.draw_polyline(points, color);
+        let [(x0, y0), (x1, y1)] = [points[points.len() - 1], points[0]];
+        self.draw_line(x0, y0, x1, y1, color);
+    }
+
+    /// Fill a polygon with the corners `points`, given as (x, y), including its outline as drawn by
+    /// [`Canvas::draw_polygon`]. The interior is determined with the even-odd rule, so concave polygons are
+    /// filled correctly and areas enclosed by self-intersecting edges an even number of times stay empty. Parts
+    /// of the polygon that are outside of the canvas are discarded. Nothing is drawn for fewer than two points.
+    pub fn fill_polygon(&mut self, points: &[(i32, i32)], color: Color) {
+        if points.len() < 2 {
+            return;
+        }
+        // The products of the coordinates exceed i64 for distant corners.
+        let corners: Vec<[i128; 2]> = points
+            .iter()
+            .map(|&(x, y)| [x, y].map(i128::from))
+            .collect();
+        let y_min = corners.iter().map(|[_, y]| *y).min().unwrap().max(0);
+        let y_max = corners
+            .iter()
+            .map(|[_, y]| *y)
+            .max()
+            .unwrap()
+            .min(self.height() as i128 - 1);
+        // The x coordinates at which the edges cross the row, as fractions with a positive denominator.
+        let mut crossings: Vec<(i128, i128)> = Vec::new();
+        for y in y_min..=y_max {
+            crossings.clear();
+            for (i, &[x0, y0]) in corners.iter().enumerate() {
+                let [x1, y1] = corners[(i + 1) % corners.len()];
+                // Every edge includes its start row but not its end row, so that a corner between two edges
+                // is crossed once, or not at all where both edges are on the same side of the row.
+                if (y0 <= y && y < y1) || (y1 <= y && y < y0) {
+                    let (numerator, denominator) = (x0 * (y1 - y0) + (y - y0) * (x1 - x0), y1 - y0);
+                    crossings.push(if denominator < 0 {
+                        (-numerator, -denominator)
+                    } else {
+                        (numerator, denominator)
+                    });
+                }
+            }
+            crossings.sort_by(|(n0, d0), (n1, d1)| (n0 * d1).cmp(&(n1 * d0)));
+            for span in crossings.chunks_exact(2) {
+                let [(start_n, start_d), (end_n, end_d)] = [span[0], span[1]];
+                let x_start = -(-start_n).div_euclid(start_d);
+                let x_end = end_n.div_euclid(end_d);
+                // The crossings are between the corners, so they are within i64.
+                let [x_start, x_end, y] = [x_start, x_end, y].map(|value| value as i64);
+                self.fill_rect_clipped(x_start, y, x_end - x_start + 1, 1, color);
+            }
+        }
+        self.draw_polygon(points, color);
+    }
+
+    /// Draw the outline of a rectangle with its top left corner at (`x`, `y`). Parts of the rectangle that are
+    /// outside of the canvas are discarded. Nothing is drawn if the width or height is not positive.
+    pub fn draw_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        let [x, y, width, height] = [x, y, width, height].map(i64::from);
+        self.fill_rect_clipped(x, y, width, 1, color);
+        self.fill_rect_clipped(x, y + height - 1, width, 1, color);
+        self.fill_rect_clipped(x, y, 1, height, color);
+        self.fill_rect_clipped(x + width - 1, y, 1, height, color);
+    }
+
+    /// Fill a rectangle with its top left corner at (`x`, `y`). Parts of the rectangle that are outside of the
+    /// canvas are discarded. Nothing is drawn if the width or height is not positive.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        let [x, y, width, height] = [x, y, width, height].map(i64::from);
+        self.fill_rect_clipped(x, y, width, height, color);
+    }
+
+    /// Draw the outline of a circle around (`cx`, `cy`) using the midpoint circle algorithm. A radius of 0 draws
+    /// a single pixel, nothing is drawn for a negative radius. Parts of the circle that are outside of the
+    /// canvas are discarded.
+    pub fn draw_circle(&mut self, cx: i32, cy: i32, radius: i32, color: Color) {
+        let [cx, cy] = [cx, cy].map(i64::from);
+        Self::for_each_circle_octant_point(radius, |x, y| {
+            [
+                [x, y],
+                [y, x],
+                [-y, x],
+                [-x, y],
+                [-x, -y],
+                [-y, -x],
+                [y, -x],
+                [x, -y],
+            ]
+            .iter()
+            .for_each(|[dx, dy]| self.set_pixel_clipped(cx + dx, cy + dy, color));
+        });
+    }
+
+    /// Fill a circle around (`cx`, `cy`). A radius of 0 draws a single pixel, nothing is drawn for a negative
+    /// radius. Parts of the circle that are outside of the canvas are discarded.
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, radius: i32, color: Color) {
+        let [cx, cy] = [cx, cy].map(i64::from);
+        Self::for_each_circle_octant_point(radius, |x, y| {
+            // Fill the horizontal spans between the mirrored points.
+            self.fill_rect_clipped(cx - x, cy + y, 2 * x + 1, 1, color);
+            self.fill_rect_clipped(cx - x, cy - y, 2 * x + 1, 1, color);
+            self.fill_rect_clipped(cx - y, cy + x, 2 * y + 1, 1, color);
+            self.fill_rect_clipped(cx - y, cy - x, 2 * y + 1, 1, color);
+        });
+    }
+
+    /// Fill the canvas with a gradient that goes from `top` in the first row to `bottom` in the last row.
+    pub fn fill_gradient_vertical(&mut self, top: Color, bottom: Color) {
+        let [width, height] = [self.width(), self.height()];
+        for y in 0..height {
+            let color = Self::interpolate_color(top, bottom, y, height);
+            self.fill_rect_clipped(0, y as i64, width as i64, 1, color);
+        }
+    }
+
+    /// Fill the canvas with a gradient that goes from `left` in the first column to `right` in the last
+    /// column.
+    pub fn fill_gradient_horizontal(&mut self, left: Color, right: Color) {
+        let [width, height] = [self.width(), self.height()];
+        for x in 0..width {
+            let color = Self::interpolate_color(left, right, x, width);
+            self.fill_rect_clipped(x as i64, 0, 1, height as i64, color);
+        }
+    }
+
+    /// Linearly interpolate between `from` and `to` for line `index` of a gradient that is `length` lines long.
+    fn interpolate_color(from: Color, to: Color, index: usize, length: usize) -> Color {
+        let steps = length.saturating_sub(1).max(1) as u32;
+        let index = index as u32;
+        let channel = |from: u8, to: u8| {
+            ((from as u32 * (steps - index) + to as u32 * index + steps / 2) / steps) as u8
+        };
+        Color::new(
+            channel(from.r, to.r),
+            channel(from.g, to.g),
+            channel(from.b, to.b),
+        )
+    }
+
+    /// Call `f` with the points (x, y) of the octant of a circle around the origin where x >= y >= 0.
+    fn for_each_circle_octant_point(radius: i32, mut f: impl FnMut(i64, i64)) {
+        if radius < 0 {
+            return;
+        }
+        let mut x = i64::from(radius);
+        let mut y = 0;
+        let mut error = 1 - x;
+        while x >= y {
+            f(x, y);
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    fn fill_rect_clipped(&mut self, x: i64, y: i64, width: i64, height: i64, color: Color) {
+        let x_start = x.max(0);
+        let y_start = y.max(0);
+        let x_end = (x + width).min(self.width() as i64);
+        let y_end = (y + height).min(self.height() as i64);
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                self.set_pixel(x as usize, y as usize, color.r, color.g, color.b);
+            }
+        }
+    }
+
+    /// Set a pixel given in signed coordinates, discarding it if it is outside of the canvas.
+    pub(crate) fn set_pixel_clipped(&mut self, x: i64, y: i64, color: Color) {
+        if x < 0 || y < 0 {
+            return;
+        }
+        self.set_pixel(x as usize, y as usize, color.r, color.g, color.b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{Canvas, Color, RGBMatrixConfig};
+
+    fn test_canvas() -> Canvas {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            ..Default::default()
+        };
+        Canvas::new_for_test(&config)
+    }
+
+    #[test]
+    fn test_draw_line_diagonal() {
+        let mut canvas = test_canvas();
+        canvas.draw_line(0, 0, 3, 3, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[0, 0], [1, 1], [2, 2], [3, 3]]);
+    }
+
+    #[test]
+    fn test_draw_line_horizontal() {
+        let mut canvas = test_canvas();
+        canvas.draw_line(5, 2, 2, 2, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[2, 2], [3, 2], [4, 2], [5, 2]]);
+    }
+
+    #[test]
+    fn test_draw_line_vertical() {
+        let mut canvas = test_canvas();
+        canvas.draw_line(1, 1, 1, 3, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[1, 1], [1, 2], [1, 3]]);
+    }
+
+    #[test]
+    fn test_draw_line_steep_and_shallow() {
+        let mut canvas = test_canvas();
+        canvas.draw_line(0, 0, 1, 3, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[0, 0], [0, 1], [1, 2], [1, 3]]);
+
+        let mut canvas = test_canvas();
+        canvas.draw_line(0, 0, 3, 1, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[0, 0], [1, 0], [2, 1], [3, 1]]);
+    }
+
+    #[test]
+    fn test_draw_line_clipped() {
+        let mut canvas = test_canvas();
+        canvas.draw_line(-2, -2, 1, 1, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[0, 0], [1, 1]]);
+
+        let mut canvas = test_canvas();
+        canvas.draw_line(30, 14, 33, 17, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[30, 14], [31, 15]]);
+    }
+
+    #[test]
+    fn test_draw_line_aa_diagonal() {
+        let mut canvas = test_canvas();
+        canvas.draw_line_aa(1, 1, 6, 6, Color::WHITE);
+        assert_eq!(
+            canvas.lit_pixels(),
+            [[1, 1], [2, 2], [3, 3], [4, 4], [5, 5], [6, 6]]
+        );
+        assert!((1..=6).all(|i| canvas.get_pixel(i, i) == Some(Color::WHITE)));
+    }
+
+    #[test]
+    fn test_draw_line_aa_coverage() {
+        let mut canvas = test_canvas();
+        canvas.draw_line_aa(0, 0, 4, 2, Color::WHITE);
+        let value = |x, y| canvas.get_pixel(x, y).unwrap().r;
+        // The line passes through the end points and the middle of the pixels at (2, 1).
+        assert_eq!([value(0, 0), value(2, 1), value(4, 2)], [255, 255, 255]);
+        // In the other columns, it passes halfway between two pixels, which share its intensity.
+        assert_eq!([value(1, 0), value(1, 1)], [127, 128]);
+        assert_eq!([value(3, 1), value(3, 2)], [127, 128]);
+        assert_eq!(value(2, 0), 0);
+        assert_eq!(value(2, 2), 0);
+
+        // Steep lines distribute the intensity between horizontal neighbors.
+        let mut canvas = test_canvas();
+        canvas.draw_line_aa(10, 12, 9, 9, Color::RED);
+        let value = |x, y| canvas.get_pixel(x, y).unwrap().r;
+        assert_eq!([value(9, 9), value(10, 12)], [255, 255]);
+        assert_eq!([value(9, 10), value(10, 10)], [170, 85]);
+        assert_eq!([value(9, 11), value(10, 11)], [85, 170]);
+    }
+
+    #[test]
+    fn test_draw_line_aa_clipped() {
+        let mut canvas = test_canvas();
+        canvas.draw_line_aa(-3, -1, 2, 1, Color::WHITE);
+        assert!(canvas.lit_pixels().iter().all(|&[x, y]| x <= 2 && y <= 1));
+        assert_eq!(canvas.get_pixel(2, 1), Some(Color::WHITE));
+    }
+
+    #[test]
+    fn test_draw_line_aa_distant_end_points() {
+        let mut canvas = test_canvas();
+        canvas.draw_line_aa(i32::MIN, i32::MIN, i32::MAX, i32::MAX, Color::WHITE);
+        assert_eq!(
+            canvas.lit_pixels(),
+            (0..16).map(|i| [i, i]).collect::<Vec<_>>()
+        );
+        let mut canvas = test_canvas();
+        canvas.draw_line_aa(3, i32::MAX, 3, i32::MIN, Color::WHITE);
+        assert_eq!(
+            canvas.lit_pixels(),
+            (0..16).map(|y| [3, y]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_draw_polyline() {
+        let mut canvas = test_canvas();
+        canvas.draw_polyline(&[(0, 0), (2, 0), (2, 2)], Color::WHITE);
+        assert_eq!(
+            canvas.lit_pixels(),
+            [[0, 0], [1, 0], [2, 0], [2, 1], [2, 2]]
+        );
+
+        canvas.draw_polyline(&[], Color::WHITE);
+        canvas.draw_polyline(&[(5, 5)], Color::WHITE);
+        canvas.draw_polygon(&[(5, 5)], Color::WHITE);
+        assert_eq!(canvas.lit_pixels().len(), 5);
+    }
+
+    #[test]
+    fn test_draw_polygon_triangle() {
+        let mut canvas = test_canvas();
+        canvas.draw_polygon(&[(2, 2), (10, 2), (2, 10)], Color::WHITE);
+
+        // The outline consists of exactly the pixels of the three edges.
+        let mut edges = test_canvas();
+        edges.draw_line(2, 2, 10, 2, Color::WHITE);
+        edges.draw_line(10, 2, 2, 10, Color::WHITE);
+        edges.draw_line(2, 10, 2, 2, Color::WHITE);
+        let lit = canvas.lit_pixels();
+        assert_eq!(lit, edges.lit_pixels());
+        assert!([[6, 2], [6, 6], [2, 6]]
+            .iter()
+            .all(|pixel| lit.contains(pixel)));
+        assert!(!lit.contains(&[4, 4]));
+    }
+
+    #[test]
+    fn test_draw_polygon_clipped() {
+        let mut canvas = test_canvas();
+        canvas.draw_polygon(&[(-2, 0), (1, 0), (1, -3)], Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[0, 0], [1, 0]]);
+    }
+
+    #[test]
+    fn test_fill_polygon_convex() {
+        let points = [(2, 1), (9, 3), (8, 10), (1, 8)];
+        let mut canvas = test_canvas();
+        canvas.fill_polygon(&points, Color::WHITE);
+        let lit = canvas.lit_pixels();
+        for pixel in [[5, 5], [2, 7], [8, 9], [7, 4], [4, 3]] {
+            assert!(lit.contains(&pixel), "{pixel:?}");
+        }
+        for pixel in [[0, 0], [9, 1], [1, 1], [10, 10], [1, 10], [10, 5]] {
+            assert!(!lit.contains(&pixel), "{pixel:?}");
+        }
+
+        // The fill covers the outline and every row between the corners without gaps.
+        let mut outline = test_canvas();
+        outline.draw_polygon(&points, Color::WHITE);
+        assert!(outline.lit_pixels().iter().all(|pixel| lit.contains(pixel)));
+        for y in 1..=10 {
+            let row: Vec<usize> = lit
+                .iter()
+                .filter(|[_, py]| *py == y)
+                .map(|[x, _]| *x)
+                .collect();
+            assert_eq!(row.len(), row.last().unwrap() - row[0] + 1, "row {y}");
+        }
+    }
+
+    #[test]
+    fn test_fill_polygon_concave() {
+        // An arrow pointing right, with a notch at its tail.
+        let points = [
+            (0, 4),
+            (8, 4),
+            (8, 0),
+            (14, 7),
+            (8, 14),
+            (8, 10),
+            (0, 10),
+            (3, 7),
+        ];
+        let mut canvas = test_canvas();
+        canvas.fill_polygon(&points, Color::WHITE);
+        let lit = canvas.lit_pixels();
+        for pixel in [
+            [5, 7],
+            [8, 7],
+            [12, 7],
+            [9, 2],
+            [9, 12],
+            [1, 5],
+            [1, 9],
+            [6, 4],
+        ] {
+            assert!(lit.contains(&pixel), "{pixel:?}");
+        }
+        // The notch and the corners next to the shaft and the tip are not filled.
+        for pixel in [
+            [0, 7],
+            [1, 7],
+            [2, 7],
+            [5, 2],
+            [5, 12],
+            [12, 2],
+            [12, 12],
+            [15, 7],
+        ] {
+            assert!(!lit.contains(&pixel), "{pixel:?}");
+        }
+    }
+
+    #[test]
+    fn test_fill_polygon_clipped() {
+        let mut canvas = test_canvas();
+        canvas.fill_polygon(&[(-5, -5), (40, -5), (40, 30), (-5, 30)], Color::WHITE);
+        assert_eq!(canvas.lit_pixels().len(), 32 * 16);
+
+        let mut canvas = test_canvas();
+        canvas.fill_polygon(&[(-4, -4), (6, -4), (-4, 6)], Color::WHITE);
+        let lit = canvas.lit_pixels();
+        assert_eq!(lit.len(), 6);
+        assert!(lit.iter().all(|[x, y]| x + y <= 2));
+        canvas.fill_polygon(&[(3, 3)], Color::RED);
+        assert_eq!(canvas.get_pixel(3, 3), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn test_fill_polygon_distant_corners() {
+        let mut canvas = test_canvas();
+        canvas.fill_polygon(
+            &[
+                (-3_000_000, -3_000_000),
+                (3_000_000, -2_900_000),
+                (0, 3_000_000),
+            ],
+            Color::WHITE,
+        );
+        assert_eq!(canvas.lit_pixels().len(), 32 * 16);
+    }
+
+    #[test]
+    fn test_draw_rect() {
+        let mut canvas = test_canvas();
+        canvas.draw_rect(1, 1, 3, 3, Color::WHITE);
+        assert_eq!(
+            canvas.lit_pixels(),
+            [
+                [1, 1],
+                [2, 1],
+                [3, 1],
+                [1, 2],
+                [3, 2],
+                [1, 3],
+                [2, 3],
+                [3, 3]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut canvas = test_canvas();
+        canvas.fill_rect(2, 3, 2, 2, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[2, 3], [3, 3], [2, 4], [3, 4]]);
+    }
+
+    #[test]
+    fn test_rect_clipped_top_left() {
+        let mut canvas = test_canvas();
+        canvas.fill_rect(-1, -2, 3, 4, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[0, 0], [1, 0], [0, 1], [1, 1]]);
+
+        let mut canvas = test_canvas();
+        canvas.draw_rect(-1, -1, 3, 3, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[1, 0], [0, 1], [1, 1]]);
+    }
+
+    #[test]
+    fn test_rect_empty() {
+        let mut canvas = test_canvas();
+        canvas.fill_rect(2, 2, 0, 5, Color::WHITE);
+        canvas.fill_rect(2, 2, 5, -1, Color::WHITE);
+        canvas.draw_rect(2, 2, 0, 0, Color::WHITE);
+        canvas.draw_rect(2, 2, -3, 3, Color::WHITE);
+        assert!(canvas.lit_pixels().is_empty());
+    }
+
+    #[test]
+    fn test_circle_radius_zero() {
+        let mut canvas = test_canvas();
+        canvas.draw_circle(4, 5, 0, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[4, 5]]);
+
+        let mut canvas = test_canvas();
+        canvas.fill_circle(4, 5, 0, Color::WHITE);
+        assert_eq!(canvas.lit_pixels(), [[4, 5]]);
+    }
+
+    #[test]
+    fn test_circle_symmetry() {
+        let [cx, cy] = [15, 7];
+        for radius in 1..7 {
+            let mut canvas = test_canvas();
+            canvas.draw_circle(cx as i32, cy as i32, radius, Color::WHITE);
+            let lit = canvas.lit_pixels();
+            assert!(!lit.is_empty());
+            for &[x, y] in &lit {
+                let [dx, dy] = [x as i64 - cx, y as i64 - cy];
+                for [mx, my] in [[dx, dy], [-dx, dy], [dx, -dy], [-dx, -dy], [dy, dx]] {
+                    let mirrored = [(cx + mx) as usize, (cy + my) as usize];
+                    assert!(
+                        lit.contains(&mirrored),
+                        "{mirrored:?} missing for r={radius}"
+                    );
+                }
+            }
+            // All points are within half a pixel of the radius.
+            for &[x, y] in &lit {
+                let distance =
+                    ((x as f64 - cx as f64).powi(2) + (y as f64 - cy as f64).powi(2)).sqrt();
+                assert!((distance - radius as f64).abs() < 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_circle() {
+        let mut canvas = test_canvas();
+        canvas.fill_circle(3, 3, 1, Color::WHITE);
+        assert_eq!(
+            canvas.lit_pixels(),
+            [[3, 2], [2, 3], [3, 3], [4, 3], [3, 4]]
+        );
+        let mut canvas = test_canvas();
+        canvas.fill_circle(7, 7, 3, Color::WHITE);
+        let lit = canvas.lit_pixels();
+        let mut outline = test_canvas();
+        outline.draw_circle(7, 7, 3, Color::WHITE);
+        assert!(outline.lit_pixels().iter().all(|p| lit.contains(p)));
+        assert!(lit.contains(&[7, 7]));
+    }
+
+    #[test]
+    fn test_circle_clipped_top() {
+        let mut canvas = test_canvas();
+        canvas.draw_circle(10, 1, 4, Color::WHITE);
+        let lit = canvas.lit_pixels();
+        assert!(lit.contains(&[10, 5]));
+        assert!(lit.contains(&[14, 1]));
+        assert!(lit.iter().all(|&[x, y]| x < 32 && y < 16));
+
+        let mut canvas = test_canvas();
+        canvas.fill_circle(10, -2, 3, Color::WHITE);
+        assert_eq!(
+            canvas.lit_pixels(),
+            [
+                [8, 0],
+                [9, 0],
+                [10, 0],
+                [11, 0],
+                [12, 0],
+                [9, 1],
+                [10, 1],
+                [11, 1]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fill_gradient_vertical() {
+        let mut canvas = test_canvas();
+        canvas.fill_gradient_vertical(Color::BLACK, Color::WHITE);
+        for x in 0..canvas.width() {
+            assert_eq!(canvas.get_pixel(x, 0), Some(Color::BLACK));
+            assert_eq!(canvas.get_pixel(x, 15), Some(Color::WHITE));
+            // With an even number of rows the midpoint lies between the two middle rows.
+            assert_eq!(canvas.get_pixel(x, 7), Some(Color::new(119, 119, 119)));
+            assert_eq!(canvas.get_pixel(x, 8), Some(Color::new(136, 136, 136)));
+        }
+    }
+
+    #[test]
+    fn test_fill_gradient_horizontal() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 33,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new_for_test(&config);
+        canvas.fill_gradient_horizontal(Color::BLACK, Color::WHITE);
+        for y in 0..canvas.height() {
+            assert_eq!(canvas.get_pixel(0, y), Some(Color::BLACK));
+            assert_eq!(canvas.get_pixel(16, y), Some(Color::new(128, 128, 128)));
+            assert_eq!(canvas.get_pixel(32, y), Some(Color::WHITE));
+        }
+    }
+}
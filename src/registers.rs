@@ -1,8 +1,9 @@
 use std::{
-    fs::OpenOptions,
+    fs::{File, OpenOptions},
+    io,
     rc::Rc,
     thread::{sleep, yield_now},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use memmap2::{MmapMut, MmapOptions};
@@ -73,6 +74,7 @@ impl GPIOFunctionSelectRegisters {
 }
 
 #[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum GPIOFunction {
     Input,
     Output,
@@ -99,59 +101,206 @@ impl GPIOFunction {
     }
 }
 
-pub fn mmap_bcm_register(chip: PiChip, offset: u64, size_bytes: usize) -> Rc<MmapMut> {
-    let file = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open("/dev/mem")
-        .expect("Failed to open '/dev/mem'");
-    let base = chip.get_peripherals_base();
-    let map = unsafe {
-        MmapOptions::new()
-            .offset(base + offset)
-            .len(size_bytes)
-            .map_mut(&file)
-            .unwrap()
-    };
-    Rc::new(map)
+/// A character device through which the peripheral registers are mapped, in the order in which they are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MemoryDevice {
+    /// All of the physical memory, which needs root privileges.
+    Mem,
+    /// Only the GPIO registers, which is accessible to the members of the `gpio` group. The system timer and
+    /// the PWM peripheral are not available through it.
+    GpioMem,
 }
 
-pub(crate) struct GPIORegisters {
-    clr0_reg: MmapPtr<u32>,
-    set0_reg: MmapPtr<u32>,
-    lvl0_reg: MmapPtr<u32>,
-    function_select_registers: GPIOFunctionSelectRegisters,
+impl MemoryDevice {
+    pub(crate) fn path(self) -> &'static str {
+        match self {
+            Self::Mem => "/dev/mem",
+            Self::GpioMem => "/dev/gpiomem",
+        }
+    }
+
+    /// Open `/dev/mem`, or `/dev/gpiomem` if that fails. Returns the error of `/dev/gpiomem` if neither can be
+    /// opened.
+    fn select<F>(mut open: impl FnMut(&str) -> io::Result<F>) -> io::Result<(Self, F)> {
+        open(Self::Mem.path())
+            .map(|file| (Self::Mem, file))
+            .or_else(|_| open(Self::GpioMem.path()).map(|file| (Self::GpioMem, file)))
+    }
+}
+
+/// The opened device through which the peripheral registers are mapped.
+pub(crate) struct PeripheralMemory {
+    device: MemoryDevice,
+    file: File,
+}
+
+impl PeripheralMemory {
+    pub(crate) fn open() -> io::Result<Self> {
+        let (device, file) =
+            MemoryDevice::select(|path| OpenOptions::new().read(true).write(true).open(path))?;
+        Ok(Self { device, file })
+    }
+
+    pub(crate) fn device(&self) -> MemoryDevice {
+        self.device
+    }
+
+    /// Whether the peripherals other than GPIO can be mapped.
+    pub(crate) fn has_all_peripherals(&self) -> bool {
+        self.device == MemoryDevice::Mem
+    }
+
+    fn map(&self, chip: PiChip, offset: u64, size_bytes: usize) -> Rc<MmapMut> {
+        let offset = match self.device {
+            MemoryDevice::Mem => chip.get_peripherals_base() + offset,
+            MemoryDevice::GpioMem => {
+                assert_eq!(
+                    offset, GP_OFFSET,
+                    "'/dev/gpiomem' only maps the GPIO registers"
+                );
+                0
+            }
+        };
+        let map = unsafe {
+            MmapOptions::new()
+                .offset(offset)
+                .len(size_bytes)
+                .map_mut(&self.file)
+                .unwrap()
+        };
+        Rc::new(map)
+    }
+}
+
+pub(crate) struct GPIORegisters(GPIOBackend);
+
+enum GPIOBackend {
+    /// The GPIO peripheral of the chip, up to the Raspberry Pi 4.
+    Bcm {
+        clr0_reg: MmapPtr<u32>,
+        set0_reg: MmapPtr<u32>,
+        lvl0_reg: MmapPtr<u32>,
+        function_select_registers: GPIOFunctionSelectRegisters,
+    },
+    /// The RP1 I/O controller of the Raspberry Pi 5.
+    Rp1(Rp1GPIORegisters),
 }
 
 impl GPIORegisters {
-    pub(crate) fn new(chip: PiChip) -> Self {
-        let map = mmap_bcm_register(chip, GP_OFFSET, GP_SIZE_BYTES);
+    /// Map the GPIO registers. Without the peripheral memory, which the Raspberry Pi 5 doesn't have, the
+    /// registers of the RP1 are mapped through `/dev/gpiomem0`, which fails if it can't be opened.
+    pub(crate) fn new(chip: PiChip, memory: Option<&PeripheralMemory>) -> io::Result<Self> {
+        let Some(memory) = memory else {
+            return Ok(Self(GPIOBackend::Rp1(Rp1GPIORegisters::new()?)));
+        };
+        let map = memory.map(chip, GP_OFFSET, GP_SIZE_BYTES);
         let clr0_reg = MmapPtr::new(map.clone(), GP_CLR0);
         let set0_reg = MmapPtr::new(map.clone(), GP_SET0);
         let lvl0_reg = MmapPtr::new(map.clone(), GP_LEV0);
         let function_select_registers = GPIOFunctionSelectRegisters::new(map, GP_FSEL0);
-        Self {
+        Ok(Self(GPIOBackend::Bcm {
             clr0_reg,
             set0_reg,
             lvl0_reg,
             function_select_registers,
-        }
+        }))
     }
 
     pub(crate) fn write_clr_bits(&mut self, value: u32) {
-        self.clr0_reg.write(value);
+        match &self.0 {
+            GPIOBackend::Bcm { clr0_reg, .. } => clr0_reg.write(value),
+            GPIOBackend::Rp1(registers) => registers.out_clr_reg.write(value),
+        }
     }
 
     pub(crate) fn write_set_bits(&mut self, value: u32) {
-        self.set0_reg.write(value);
+        match &self.0 {
+            GPIOBackend::Bcm { set0_reg, .. } => set0_reg.write(value),
+            GPIOBackend::Rp1(registers) => registers.out_set_reg.write(value),
+        }
     }
 
     pub(crate) fn select_function(&mut self, pin: u8, function: GPIOFunction) {
-        self.function_select_registers.set_function(pin, function);
+        match &mut self.0 {
+            GPIOBackend::Bcm {
+                function_select_registers,
+                ..
+            } => function_select_registers.set_function(pin, function),
+            GPIOBackend::Rp1(registers) => registers.select_function(pin, function),
+        }
     }
 
     pub(crate) fn read_pin_level0(&self) -> u32 {
-        self.lvl0_reg.read()
+        match &self.0 {
+            GPIOBackend::Bcm { lvl0_reg, .. } => lvl0_reg.read(),
+            GPIOBackend::Rp1(registers) => registers.in_reg.read(),
+        }
+    }
+}
+
+// RP1 GPIO of the Raspberry Pi 5, see the RP1 peripherals datasheet. `/dev/gpiomem0` maps the user bank of the
+// GPIO pins, followed by its registered I/O and pad control.
+const RP1_GPIO_MEM: &str = "/dev/gpiomem0";
+const RP1_GPIO_SIZE_BYTES: usize = 0x30000;
+const RP1_IO_BANK0: usize = 0x0;
+const RP1_SYS_RIO0: usize = 0x10000;
+const RP1_PADS_BANK0: usize = 0x20000;
+const RP1_RIO_OUT: usize = 0x0;
+const RP1_RIO_OE: usize = 0x4;
+/// The input levels, synchronized to the clock of the RP1.
+const RP1_RIO_SYNC_IN: usize = 0xC;
+/// The offsets of the aliases of a register that atomically set or clear the written bits.
+const RP1_SET_ALIAS: usize = 0x2000;
+const RP1_CLR_ALIAS: usize = 0x3000;
+const RP1_CTRL_FUNCSEL_MASK: u32 = 0x1F;
+/// The function that connects a pin to the registered I/O.
+const RP1_FUNCSEL_SYS_RIO: u32 = 5;
+const RP1_PAD_INPUT_ENABLE: u32 = 1 << 6;
+const RP1_PAD_OUTPUT_DISABLE: u32 = 1 << 7;
+
+struct Rp1GPIORegisters {
+    map: Rc<MmapMut>,
+    out_set_reg: MmapPtr<u32>,
+    out_clr_reg: MmapPtr<u32>,
+    in_reg: MmapPtr<u32>,
+}
+
+impl Rp1GPIORegisters {
+    fn new() -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(RP1_GPIO_MEM)?;
+        let map = unsafe { MmapOptions::new().len(RP1_GPIO_SIZE_BYTES).map_mut(&file)? };
+        let map = Rc::new(map);
+        let rio = |offset| MmapPtr::new(map.clone(), RP1_SYS_RIO0 + offset);
+        Ok(Self {
+            out_set_reg: rio(RP1_SET_ALIAS + RP1_RIO_OUT),
+            out_clr_reg: rio(RP1_CLR_ALIAS + RP1_RIO_OUT),
+            in_reg: rio(RP1_RIO_SYNC_IN),
+            map,
+        })
+    }
+
+    /// Connect the pin to the registered I/O as an input or output. The alternative functions of the older
+    /// models don't exist.
+    fn select_function(&mut self, pin: u8, function: GPIOFunction) {
+        let pin = pin as usize;
+        let bit = 1 << pin;
+        let pad: MmapPtr<u32> = MmapPtr::new(self.map.clone(), RP1_PADS_BANK0 + 4 + 4 * pin);
+        let output_enable_alias = match function {
+            GPIOFunction::Input => RP1_CLR_ALIAS,
+            GPIOFunction::Output => RP1_SET_ALIAS,
+            _ => panic!("The RP1 does not support the function {function:?}."),
+        };
+        MmapPtr::<u32>::new(
+            self.map.clone(),
+            RP1_SYS_RIO0 + output_enable_alias + RP1_RIO_OE,
+        )
+        .write(bit);
+        pad.write((pad.read() & !RP1_PAD_OUTPUT_DISABLE) | RP1_PAD_INPUT_ENABLE);
+        let ctrl: MmapPtr<u32> = MmapPtr::new(self.map.clone(), RP1_IO_BANK0 + 8 * pin + 4);
+        ctrl.write((ctrl.read() & !RP1_CTRL_FUNCSEL_MASK) | RP1_FUNCSEL_SYS_RIO);
     }
 }
 
@@ -176,24 +325,39 @@ impl TimeRegister {
     }
 }
 
+enum TimeSource {
+    /// The free running microsecond counter of the system timer.
+    SystemTimer(MmapPtr<TimeRegister>),
+    /// The monotonic clock of the operating system, on the Raspberry Pi 5 or without `/dev/mem`.
+    Monotonic(Instant),
+}
+
 // Time measurement.
 pub(crate) struct TimeRegisters {
-    time_reg: MmapPtr<TimeRegister>,
+    time_source: TimeSource,
     sleep_factor: f32,
 }
 
 impl TimeRegisters {
-    pub(crate) fn new(chip: PiChip) -> Self {
-        let map = mmap_bcm_register(chip, ST_OFFSET, ST_SIZE_BYTES);
-        let time_reg = MmapPtr::new(map, ST_CLO);
+    pub(crate) fn new(chip: PiChip, memory: Option<&PeripheralMemory>) -> Self {
+        let time_source = match memory.filter(|memory| memory.has_all_peripherals()) {
+            Some(memory) => {
+                let map = memory.map(chip, ST_OFFSET, ST_SIZE_BYTES);
+                TimeSource::SystemTimer(MmapPtr::new(map, ST_CLO))
+            }
+            None => TimeSource::Monotonic(Instant::now()),
+        };
         Self {
-            time_reg,
+            time_source,
             sleep_factor: 0.4,
         }
     }
 
     pub(crate) fn get_time(&self) -> u64 {
-        self.time_reg.read().get_u64()
+        match &self.time_source {
+            TimeSource::SystemTimer(time_reg) => time_reg.read().get_u64(),
+            TimeSource::Monotonic(start) => start.elapsed().as_micros() as u64,
+        }
     }
 
     pub(crate) fn sleep(&mut self, duration_us: u64) {
@@ -238,8 +402,8 @@ pub(crate) struct PWMRegisters {
 }
 
 impl PWMRegisters {
-    pub(crate) fn new(chip: PiChip) -> Self {
-        let map = mmap_bcm_register(chip, PWM_OFFSET, PWM_SIZE_BYTES);
+    pub(crate) fn new(chip: PiChip, memory: &PeripheralMemory) -> Self {
+        let map = memory.map(chip, PWM_OFFSET, PWM_SIZE_BYTES);
         let pwm_ctl_reg = MmapPtr::new(map.clone(), PWM_CTL);
         let pwm_rng1_reg = MmapPtr::new(map.clone(), PWM_RNG1);
         let pwm_fif1_reg = MmapPtr::new(map.clone(), PWM_FIF1);
@@ -252,16 +416,6 @@ impl PWMRegisters {
         }
     }
 
-    /// Channel 1: Use FIFO | Polarity (1=low, 0=high) | Enable Channel
-    pub(crate) fn enable_pwm(&mut self) {
-        self.set_pwm_ctl(PWM_CTL_USEF1 | PWM_CTL_POLA1 | PWM_CTL_PWEN1);
-    }
-
-    /// Channel 1: Use FIFO | Polarity (1=low, 0=high) | Clear FIFO
-    pub(crate) fn reset_pwm(&mut self) {
-        self.set_pwm_ctl(PWM_CTL_USEF1 | PWM_CTL_POLA1 | PWM_CTL_CLRF1);
-    }
-
     pub(crate) fn set_pwm_ctl(&mut self, value: u32) {
         self.pwm_ctl_reg.write(value);
     }
@@ -315,8 +469,8 @@ pub(crate) struct ClkRegisters {
 }
 
 impl ClkRegisters {
-    pub(crate) fn new(chip: PiChip) -> Self {
-        let map = mmap_bcm_register(chip, CM_OFFSET, CM_SIZE_BYTES);
+    pub(crate) fn new(chip: PiChip, memory: &PeripheralMemory) -> Self {
+        let map = memory.map(chip, CM_OFFSET, CM_SIZE_BYTES);
         let pwm_ctl_reg = MmapPtr::new(map.clone(), CM_PWMCTL);
         let pwm_div_reg = MmapPtr::new(map, CM_PWMDIV);
         Self {
@@ -343,3 +497,29 @@ impl ClkRegisters {
             .write(CM_PASSWD | CM_PWMCTL_ENAB | cm_ctl_src(CM_SRC_PLLD));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_device_fallback() {
+        let mut opened = Vec::new();
+        let selected = MemoryDevice::select(|path| {
+            opened.push(path.to_string());
+            match path {
+                "/dev/mem" => Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+                _ => Ok(()),
+            }
+        });
+        assert_eq!(selected.unwrap(), (MemoryDevice::GpioMem, ()));
+        assert_eq!(opened, ["/dev/mem", "/dev/gpiomem"]);
+
+        let selected = MemoryDevice::select(|_| Ok(()));
+        assert_eq!(selected.unwrap(), (MemoryDevice::Mem, ()));
+
+        let selected =
+            MemoryDevice::select::<()>(|_| Err(io::Error::from(io::ErrorKind::NotFound)));
+        assert_eq!(selected.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+}
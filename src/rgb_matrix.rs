@@ -1,35 +1,52 @@
 use std::{
     error::Error,
     fmt::{Display, Formatter},
-    fs::{write, OpenOptions},
-    mem::replace,
-    sync::mpsc::{
-        channel, sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender, TryRecvError,
+    fs::{write, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        mpsc::{channel, Receiver, Sender, TryRecvError},
+        Arc,
     },
-    thread::{spawn, JoinHandle},
+    thread::{sleep, spawn, JoinHandle},
     time::Duration,
 };
 
-use thread_priority::{set_current_thread_priority, ThreadPriority};
+use thread_priority::{
+    set_current_thread_priority, set_thread_priority_and_policy, thread_native_id,
+    RealtimeThreadSchedulePolicy, ThreadPriority, ThreadPriorityValue, ThreadSchedulePolicy,
+};
 
 use crate::{
     canvas::{Canvas, PixelDesignator, PixelDesignatorMap},
     chip::PiChip,
+    color::ColorLookup,
+    config::K_BIT_PLANES,
+    frame_exchange::FrameExchange,
     gpio::{Gpio, GpioInitializationError},
-    pixel_mapper::{MultiplexMapperWrapper, NamedPixelMapperWrapper, PixelMapper},
-    utils::{linux_has_isol_cpu, set_thread_affinity, FrameRateMonitor},
-    RGBMatrixConfig,
+    mock_backend::run_mock_update_thread,
+    named_pixel_mapper::{NamedPixelMapper, PixelMapperError},
+    pixel_mapper::{create_shared_mapper, CompiledPixelMapper},
+    registers::{MemoryDevice, PeripheralMemory},
+    utils::{
+        install_interrupt_handler, interrupt_received, linux_has_isol_cpu, set_thread_affinity,
+    },
+    Clock, Color, ConfigError, FrameStats, PanicGuard, RGBMatrixConfig, SelfTestPattern,
+    SystemClock, ThreadedMatrix,
 };
 
-fn initialize_update_thread(chip: &PiChip) {
-    // Pin the thread to the last core to avoid the flicker resulting from context switching.
-    let last_core_id = chip.num_cores() - 1;
-    set_thread_affinity(last_core_id);
+fn initialize_update_thread(chip: &PiChip, config: &RGBMatrixConfig) {
+    // Pin the thread to a single core, the last one by default, to avoid the flicker resulting from context
+    // switching.
+    let core_id = config.cpu_affinity.unwrap_or(chip.num_cores() - 1);
+    if !set_thread_affinity(core_id) {
+        eprintln!("Could not pin the update thread to core {core_id}.");
+    }
 
     // If the user has not setup isolcpus, let them know about the performance improvement.
-    if chip.num_cores() > 1 && !linux_has_isol_cpu(last_core_id) {
+    if chip.num_cores() > 1 && !linux_has_isol_cpu(core_id) {
         eprintln!(
-            "Suggestion: to slightly improve display update, add\n\tisolcpus={last_core_id}\nat the end of /boot/cmdline.txt and reboot"
+            "Suggestion: to slightly improve display update, add\n\tisolcpus={core_id}\nat the end of /boot/cmdline.txt and reboot"
         );
     }
 
@@ -38,31 +55,39 @@ fn initialize_update_thread(chip: &PiChip) {
         eprintln!("Could not disable realtime throttling");
     }
 
-    // Set the last core to performance mode.
+    // Set the core to performance mode.
     if chip.num_cores() > 1
         && write(
-            format!("/sys/devices/system/cpu/cpu{last_core_id}/cpufreq/scaling_governor"),
+            format!("/sys/devices/system/cpu/cpu{core_id}/cpufreq/scaling_governor"),
             "performance",
         )
         .is_err()
     {
-        eprintln!(
-            "Could not set core {} to performance mode.",
-            last_core_id + 1
-        );
+        eprintln!("Could not set core {} to performance mode.", core_id + 1);
     }
 
-    // Set the highest thread priority.
-    if set_current_thread_priority(ThreadPriority::Max).is_err() {
+    // Use the real-time policy if requested, otherwise the highest priority of the normal policy.
+    let priority_result = match config.realtime_priority {
+        Some(priority) => ThreadPriorityValue::try_from(priority)
+            .map_err(thread_priority::Error::Priority)
+            .and_then(|priority| {
+                set_thread_priority_and_policy(
+                    thread_native_id(),
+                    ThreadPriority::Crossplatform(priority),
+                    ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+                )
+            }),
+        None => set_current_thread_priority(ThreadPriority::Max),
+    };
+    if priority_result.is_err() {
         eprintln!("Could not set thread priority. This might lead to reduced performance.",);
     }
 }
 
 #[derive(Debug)]
 pub enum MatrixCreationError {
+    InvalidConfig(ConfigError),
     ChipDeterminationError,
-    TooManyParallelChains(usize),
-    InvalidDitherBits(usize),
     ThreadTimedOut,
     GpioError(GpioInitializationError),
     MemoryAccessError,
@@ -73,15 +98,12 @@ impl Error for MatrixCreationError {}
 impl Display for MatrixCreationError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            MatrixCreationError::InvalidConfig(error) => {
+                write!(f, "Invalid configuration: {error}")
+            }
             MatrixCreationError::ChipDeterminationError => {
                 f.write_str("Failed to automatically determine Raspberry Pi model.")
             }
-            MatrixCreationError::TooManyParallelChains(max) => {
-                write!(f, "GPIO mapping only supports up to {max} parallel panels.")
-            }
-            MatrixCreationError::InvalidDitherBits(value) => {
-                write!(f, "Unsupported dither bits '{value}'.")
-            }
             MatrixCreationError::ThreadTimedOut => {
                 f.write_str("The update thread did not return in time.")
             }
@@ -89,12 +111,19 @@ impl Display for MatrixCreationError {
                 write!(f, "GPIO initialization error: {error}")
             }
             MatrixCreationError::MemoryAccessError => f.write_str(
-                "Failed to access the physical memory. Not running with root privileges?",
+                "Failed to access the physical memory through '/dev/mem' or '/dev/gpiomem'. Run as root, \
+                or add the user to the `gpio` group to use '/dev/gpiomem'.",
             ),
         }
     }
 }
 
+/// Controls the matrix through an update thread that outputs the displayed canvas continuously.
+///
+/// Dropping the matrix stops the update thread after it has output a final black frame, and only returns once
+/// the thread has finished, so the panel is blank and the GPIO lines are idle afterwards. Canvases that were
+/// handed out stay valid, but are no longer displayed. To also blank the panel when the program is stopped with
+/// Ctrl-C, see [`RGBMatrix::install_signal_handler`].
 pub struct RGBMatrix {
     /// The join handle of the update thread.
     thread_handle: Option<JoinHandle<()>>,
@@ -102,14 +131,33 @@ pub struct RGBMatrix {
     shutdown_sender: Sender<()>,
     /// Receiver for GPIO inputs.
     input_receiver: Receiver<u32>,
-    /// Channel to send canvas to update thread.
-    canvas_to_thread_sender: SyncSender<Box<Canvas>>,
-    /// Channel to receive canvas from update thread.
-    canvas_from_thread_receiver: Receiver<Box<Canvas>>,
+    /// Exchange of canvases with the update thread.
+    frame_exchange: Arc<FrameExchange>,
     /// Additional requested inputs that can be received.
     enabled_input_bits: u32,
-    /// Frame rate measurement.
-    frame_rate_monitor: FrameRateMonitor,
+    /// The configuration after applying the multiplexing mapper.
+    config: RGBMatrixConfig,
+    /// The pixel designator for unmapped pixels.
+    pixel_designator: PixelDesignator,
+    /// The current pixel mapping, handed to canvases when they are swapped.
+    shared_mapper: Arc<PixelDesignatorMap>,
+    /// The current color lookup, handed to canvases when they are swapped.
+    color_lookup: Arc<ColorLookup>,
+    /// The brightness set with [`RGBMatrix::set_brightness`], applied to canvases when they are swapped.
+    brightness: Option<u8>,
+    /// The PWM bits set with [`RGBMatrix::set_pwm_bits`], applied to canvases when they are swapped.
+    pwm_bits: Option<usize>,
+    /// The brightness of every panel set with [`RGBMatrix::set_panel_brightness`], applied to canvases when they
+    /// are swapped, or empty if it was never set.
+    panel_brightness: Vec<u8>,
+    /// Whether the measured framerate is drawn on canvases when they are swapped.
+    fps_overlay: bool,
+    /// The canvas set with [`RGBMatrix::set_overlay`], which is drawn on canvases when they are swapped.
+    overlay: Option<Box<Canvas>>,
+    /// The color of the overlay pixels that show the canvas below.
+    overlay_key: Color,
+    /// The front and back buffer of [`RGBMatrix::buffers`], which are created when they are first used.
+    buffers: Option<(Box<Canvas>, Box<Canvas>)>,
 }
 
 impl RGBMatrix {
@@ -120,79 +168,81 @@ impl RGBMatrix {
     /// [`RGBMatrix::receive_new_inputs`]. Only bits that are not already in use for reading or writing by the
     /// matrix are allowed. Use [`RGBMatrix::enabled_input_bits`] after calling this function to check which
     /// bits were actually available.
+    ///
+    /// The peripheral registers are mapped through `/dev/mem`, which needs root privileges. Otherwise,
+    /// `/dev/gpiomem` is used, which is accessible to the members of the `gpio` group, e.g. after
+    /// `sudo usermod -aG gpio $USER`. It only maps the GPIO registers, so the output enable is then timed by busy
+    /// waiting instead of the PWM peripheral. On the Raspberry Pi 5, the GPIO pins are mapped through
+    /// `/dev/gpiomem0`, which is also accessible to the members of the `gpio` group.
     pub fn new(
         mut config: RGBMatrixConfig,
         requested_inputs: u32,
     ) -> Result<(Self, Box<Canvas>), MatrixCreationError> {
-        // Check if we can access the memory before doing anything else.
-        OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open("/dev/mem")
-            .map_err(|_| MatrixCreationError::MemoryAccessError)?;
+        config
+            .validate()
+            .map_err(MatrixCreationError::InvalidConfig)?;
 
         let chip = if let Some(chip) = config.pi_chip {
             chip
         } else {
             PiChip::determine().ok_or(MatrixCreationError::ChipDeterminationError)?
         };
+        config.pi_chip = Some(chip);
+        // The core for the update thread can only be checked once the model is known.
+        config
+            .validate()
+            .map_err(MatrixCreationError::InvalidConfig)?;
 
-        let max_parallel = config.hardware_mapping.max_parallel_chains();
-        if config.parallel > max_parallel {
-            return Err(MatrixCreationError::TooManyParallelChains(max_parallel));
-        }
-
-        let pixel_designator = PixelDesignator::new(&config.hardware_mapping, config.led_sequence);
-        let width = config.cols * config.chain_length;
-        let height = config.rows * config.parallel;
-        let mut shared_mapper = PixelDesignatorMap::new(pixel_designator, width, height, &config);
-
-        // Apply the mapping for the panels first.
-        if let Some(mapper_type) = config.multiplexing.as_ref() {
-            let mut mapper = mapper_type.create();
-            mapper.edit_rows_cols(&mut config.rows, &mut config.cols);
-            let mapper = MultiplexMapperWrapper(mapper);
-            shared_mapper =
-                Self::apply_pixel_mapper(shared_mapper, mapper, &config, pixel_designator);
-        }
-
-        // Apply higher level mappers that might arrange panels.
-        let pixelmappers = config.pixelmapper.clone();
-        for mapper_type in pixelmappers {
-            let mapper: NamedPixelMapperWrapper =
-                NamedPixelMapperWrapper(mapper_type.create(config.chain_length, config.parallel));
-            shared_mapper =
-                Self::apply_pixel_mapper(shared_mapper, mapper, &config, pixel_designator);
-        }
-
-        let dither_start_bits = match config.dither_bits {
-            0 => [0, 0, 0, 0],
-            1 => [0, 1, 0, 1],
-            2 => [0, 1, 2, 2],
-            _ => return Err(MatrixCreationError::InvalidDitherBits(config.dither_bits)),
+        // Check if we can access the memory before starting the update thread. The Raspberry Pi 5 has neither
+        // '/dev/mem' nor '/dev/gpiomem', its GPIO pins are mapped through '/dev/gpiomem0' instead.
+        let memory = if chip.has_rp1() {
+            None
+        } else {
+            let memory =
+                PeripheralMemory::open().map_err(|_| MatrixCreationError::MemoryAccessError)?;
+            if memory.device() != MemoryDevice::Mem {
+                eprintln!(
+                    "Could not open '/dev/mem', using '{}'. The output enable is timed without the PWM peripheral.",
+                    memory.device().path()
+                );
+            }
+            Some(memory)
         };
 
+        let (pixel_designator, shared_mapper) =
+            create_shared_mapper(&mut config).map_err(MatrixCreationError::InvalidConfig)?;
+
         // Create two canvases, one for the display update thread and one for the user to modify. They will be
         // swapped out after each frame.
-        let canvas = Box::new(Canvas::new(&config, shared_mapper));
-        let mut thread_canvas = canvas.clone();
+        let color_lookup = Arc::new(ColorLookup::new(config.gamma, config.white_balance));
+        let canvas = Box::new(Canvas::new(
+            &config,
+            Arc::clone(&shared_mapper),
+            Arc::clone(&color_lookup),
+        ));
+        let matrix_config = config.clone();
 
-        let (canvas_to_thread_sender, canvas_to_thread_receiver) = sync_channel::<Box<Canvas>>(0);
-        let (canvas_from_thread_sender, canvas_from_thread_receiver) =
-            sync_channel::<Box<Canvas>>(1);
+        // The hardware update thread is paced by the timer of the chip, only the framerate is measured with the
+        // clock.
+        let frame_exchange = Arc::new(FrameExchange::new(
+            canvas.clone(),
+            Arc::new(SystemClock::new()),
+        ));
+        let thread_frame_exchange = Arc::clone(&frame_exchange);
         let (shutdown_sender, shutdown_receiver) = channel::<()>();
         let (input_sender, input_receiver) = channel::<u32>();
         let (thread_start_result_sender, thread_start_result_receiver) =
             channel::<Result<u32, MatrixCreationError>>();
 
         let thread_handle = spawn(move || {
-            initialize_update_thread(&chip);
+            initialize_update_thread(&chip, &config);
 
             let mut address_setter = config.row_setter.create(&config);
 
-            let mut gpio = match Gpio::new(chip, &config, address_setter.as_ref()) {
+            let mut gpio = match Gpio::new(chip, memory, &config, address_setter.as_ref()) {
                 Ok(gpio) => gpio,
                 Err(error) => {
+                    thread_frame_exchange.close();
                     thread_start_result_sender
                         .send(Err(MatrixCreationError::GpioError(error)))
                         .expect("Could not send to main thread.");
@@ -207,10 +257,10 @@ impl RGBMatrix {
 
             let mut last_gpio_inputs: u32 = 0;
 
-            // Dither sequence
-            let mut dither_low_bit_sequence = 0;
+            // Selects the dither phase of the canvas.
+            let mut frame = 0usize;
 
-            let frame_time_target_us = (1_000_000.0 / config.refresh_rate as f64) as u64;
+            let frame_time_target_us = (1_000_000.0 / config.frame_rate_target() as f64) as u64;
 
             let color_clk_mask = config
                 .hardware_mapping
@@ -223,48 +273,43 @@ impl RGBMatrix {
 
             'thread: loop {
                 let start_time = gpio.get_time();
-                loop {
-                    // Try to receive a shutdown request.
-                    if shutdown_receiver.try_recv() != Err(TryRecvError::Empty) {
-                        break 'thread;
-                    }
-                    // Read input bits and send them if they have changed.
-                    let new_inputs = gpio.read();
-                    if new_inputs != last_gpio_inputs {
-                        match input_sender.send(new_inputs) {
-                            Ok(()) => {}
-                            Err(_) => {
-                                break 'thread;
-                            }
-                        }
-                        last_gpio_inputs = new_inputs;
-                    }
-                    // Wait for a swap canvas.
-                    match canvas_to_thread_receiver.recv_timeout(Duration::from_millis(1)) {
-                        Ok(new_canvas) => {
-                            let old_canvas = replace(&mut thread_canvas, new_canvas);
-                            match canvas_from_thread_sender.send(old_canvas) {
-                                Ok(_) => break,
-                                Err(_) => {
-                                    break 'thread;
-                                }
-                            };
-                        }
-                        Err(RecvTimeoutError::Disconnected) => {
+                // Try to receive a shutdown request.
+                if shutdown_receiver.try_recv() != Err(TryRecvError::Empty) || interrupt_received()
+                {
+                    break 'thread;
+                }
+                // Read input bits and send them if they have changed.
+                let new_inputs = gpio.read();
+                if new_inputs != last_gpio_inputs {
+                    match input_sender.send(new_inputs) {
+                        Ok(()) => {}
+                        Err(_) => {
                             break 'thread;
                         }
-                        Err(RecvTimeoutError::Timeout) => {}
                     }
+                    last_gpio_inputs = new_inputs;
                 }
+                // Display a new canvas if one was queued, otherwise refresh the current one.
+                thread_frame_exchange.pick_up();
 
-                thread_canvas.dump_to_matrix(
-                    &mut gpio,
-                    &config.hardware_mapping,
-                    address_setter.as_mut(),
-                    dither_start_bits[dither_low_bit_sequence % dither_start_bits.len()],
-                    color_clk_mask,
-                );
-                dither_low_bit_sequence += 1;
+                // While the display is blank, nothing is scanned out, so the output enable stays off after the
+                // last pulse.
+                let stats = if thread_frame_exchange.is_enabled() {
+                    let stats = thread_frame_exchange.with_displayed(|canvas| {
+                        canvas.dump_to_matrix(
+                            &mut gpio,
+                            &config.hardware_mapping,
+                            address_setter.as_mut(),
+                            frame,
+                            color_clk_mask,
+                        )
+                    });
+                    frame = frame.wrapping_add(1);
+                    stats
+                } else {
+                    FrameStats::default()
+                };
+                thread_frame_exchange.frame_finished(stats);
 
                 // Sleep for the rest of the frame.
                 let now_time = gpio.get_time();
@@ -274,15 +319,21 @@ impl RGBMatrix {
                 }
             }
 
-            // Turn it off.
-            thread_canvas.fill(0, 0, 0);
-            thread_canvas.dump_to_matrix(
-                &mut gpio,
-                &config.hardware_mapping,
-                address_setter.as_mut(),
-                0,
-                color_clk_mask,
-            );
+            // Turn it off by outputting a black frame, after which the output enable stays off, and leave the
+            // color, clock and strobe lines low.
+            thread_frame_exchange.with_displayed(|canvas| {
+                canvas.clear();
+                canvas.dump_to_matrix(
+                    &mut gpio,
+                    &config.hardware_mapping,
+                    address_setter.as_mut(),
+                    0,
+                    color_clk_mask,
+                )
+            });
+            gpio.wait_pulse_finished();
+            gpio.clear_bits(color_clk_mask | config.hardware_mapping.strobe);
+            thread_frame_exchange.close();
         });
 
         let enabled_input_bits = thread_start_result_receiver
@@ -293,54 +344,499 @@ impl RGBMatrix {
             thread_handle: Some(thread_handle),
             input_receiver,
             shutdown_sender,
-            canvas_to_thread_sender,
-            canvas_from_thread_receiver,
+            frame_exchange,
             enabled_input_bits,
-            frame_rate_monitor: FrameRateMonitor::new(),
+            config: matrix_config,
+            pixel_designator,
+            shared_mapper,
+            color_lookup,
+            brightness: None,
+            pwm_bits: None,
+            panel_brightness: Vec::new(),
+            fps_overlay: false,
+            overlay: None,
+            overlay_key: Color::BLACK,
+            buffers: None,
         };
 
         Ok((rgbmatrix, canvas))
     }
 
-    fn apply_pixel_mapper(
-        shared_mapper: PixelDesignatorMap,
-        mapper: impl PixelMapper,
-        config: &RGBMatrixConfig,
-        pixel_designator: PixelDesignator,
-    ) -> PixelDesignatorMap {
-        let old_width = shared_mapper.width();
-        let old_height = shared_mapper.height();
-        let [new_width, new_height] = mapper.get_size_mapping(old_width, old_height);
-        let mut new_mapper =
-            PixelDesignatorMap::new(pixel_designator, new_width, new_height, config);
-        for y in 0..new_height {
-            for x in 0..new_width {
-                let [orig_x, orig_y] = mapper.map_visible_to_matrix(old_width, old_height, x, y);
-                if orig_x >= old_width || orig_y >= old_height {
-                    eprintln!("Error in pixel mapper"); // TODO
-                    continue;
-                }
-                let orig_designator = shared_mapper.get(orig_x, orig_y).unwrap();
-                *new_mapper.get_mut(x, y).unwrap() = *orig_designator;
-            }
+    /// Create an RGB matrix controller that does not access any hardware. Instead of being output to the
+    /// matrix, the displayed canvas is kept in memory and can be read with [`RGBMatrix::get_frame`]. This allows
+    /// testing applications on machines other than a Raspberry Pi.
+    ///
+    /// The update thread still picks up canvases at the configured refresh rate, so swapping canvases and frame
+    /// callbacks behave as they do with the hardware. No GPIO inputs are available.
+    pub fn new_mock(config: RGBMatrixConfig) -> Result<(Self, Box<Canvas>), MatrixCreationError> {
+        Self::new_mock_with_clock(config, Arc::new(SystemClock::new()))
+    }
+
+    /// Create an RGB matrix controller like [`RGBMatrix::new_mock`], whose update thread is paced by the given
+    /// clock and whose framerate is measured with it. With a [`FakeClock`](crate::FakeClock), the number of
+    /// frames that are output only depends on how far the clock is advanced.
+    pub fn new_mock_with_clock(
+        mut config: RGBMatrixConfig,
+        clock: Arc<dyn Clock>,
+    ) -> Result<(Self, Box<Canvas>), MatrixCreationError> {
+        config
+            .validate()
+            .map_err(MatrixCreationError::InvalidConfig)?;
+
+        let (pixel_designator, shared_mapper) =
+            create_shared_mapper(&mut config).map_err(MatrixCreationError::InvalidConfig)?;
+
+        let color_lookup = Arc::new(ColorLookup::new(config.gamma, config.white_balance));
+        let canvas = Box::new(Canvas::new(
+            &config,
+            Arc::clone(&shared_mapper),
+            Arc::clone(&color_lookup),
+        ));
+
+        let frame_exchange = Arc::new(FrameExchange::new(canvas.clone(), Arc::clone(&clock)));
+        let thread_frame_exchange = Arc::clone(&frame_exchange);
+        let (shutdown_sender, shutdown_receiver) = channel::<()>();
+        // The sender is dropped right away, since there are no inputs to send.
+        let (_, input_receiver) = channel::<u32>();
+        let thread_config = config.clone();
+
+        let thread_handle = spawn(move || {
+            run_mock_update_thread(
+                &thread_config,
+                &thread_frame_exchange,
+                &shutdown_receiver,
+                clock.as_ref(),
+            );
+        });
+
+        let rgbmatrix = Self {
+            thread_handle: Some(thread_handle),
+            input_receiver,
+            shutdown_sender,
+            frame_exchange,
+            enabled_input_bits: 0,
+            config,
+            pixel_designator,
+            shared_mapper,
+            color_lookup,
+            brightness: None,
+            pwm_bits: None,
+            panel_brightness: Vec::new(),
+            fps_overlay: false,
+            overlay: None,
+            overlay_key: Color::BLACK,
+            buffers: None,
+        };
+
+        Ok((rgbmatrix, canvas))
+    }
+
+    /// Apply a user provided pixel mapper on top of the mappers that are already in use, including the ones
+    /// from [`RGBMatrixConfig::pixelmapper`]. Canvases returned from [`RGBMatrix::update_on_vsync`] after
+    /// this call use the new mapping.
+    ///
+    /// The mapping is left unchanged if the mapper returns an empty visible size or maps a visible pixel outside
+    /// of the layout it is applied to.
+    pub fn apply_named_pixel_mapper(
+        &mut self,
+        mapper: Box<dyn NamedPixelMapper>,
+    ) -> Result<(), PixelMapperError> {
+        let mapper = CompiledPixelMapper::new_custom(
+            mapper,
+            self.shared_mapper.width(),
+            self.shared_mapper.height(),
+        )?;
+        self.shared_mapper = Arc::new(mapper.apply(&self.shared_mapper, self.pixel_designator));
+        Ok(())
+    }
+
+    /// Draw the framerate measured by [`RGBMatrix::get_framerate`] in the top left corner of every canvas that
+    /// is passed to [`RGBMatrix::update_on_vsync`] or [`RGBMatrix::swap`], e.g. to see the effect of timing
+    /// options on the panel. The number is drawn in white on a black background with [`Canvas::draw_number`],
+    /// so it stays on the canvas until it is drawn over.
+    pub fn with_fps_overlay(mut self) -> Self {
+        self.fps_overlay = true;
+        self
+    }
+
+    /// Draw an overlay, e.g. a clock or status display, on top of every canvas that is passed to
+    /// [`RGBMatrix::update_on_vsync`] or [`RGBMatrix::swap`], until it is replaced or removed with `None`. Pixels
+    /// of the overlay in the color set with [`RGBMatrix::set_overlay_key`], black by default, are transparent and
+    /// show the canvas below. The overlay is drawn on the canvases themselves, so its pixels stay on a canvas
+    /// until they are drawn over. Overlay pixels beyond the size of the matrix are ignored.
+    pub fn set_overlay(&mut self, overlay: Option<Canvas>) {
+        self.overlay = overlay.map(Box::new);
+    }
+
+    /// Set the color of the overlay pixels that are transparent, see [`RGBMatrix::set_overlay`].
+    pub fn set_overlay_key(&mut self, key: Color) {
+        self.overlay_key = key;
+    }
+
+    /// Latch frames on the ticks of an external timing source, e.g. to keep the frames of panels driven by
+    /// several processes in lockstep. Queued canvases are only picked up by the update thread at the start of
+    /// the first frame after a tick was received, so [`RGBMatrix::update_on_vsync`] waits for the next tick.
+    /// Ticks that arrive while no canvas is queued are dropped. The displayed canvas is still refreshed at
+    /// [`RGBMatrixConfig::refresh_rate`] in between, since the rows of the panels have to be scanned out
+    /// continuously, so ticks are picked up with a delay of up to one refresh period.
+    ///
+    /// Once the sender is dropped, canvases are picked up at the start of every frame again.
+    pub fn set_external_vsync(&mut self, receiver: Receiver<()>) {
+        self.frame_exchange.set_external_vsync(receiver);
+    }
+
+    /// Show every [`SelfTestPattern`] for the given duration, to check the wiring of the panels: full fields of
+    /// red, green, blue and white, followed by a walking bit in each channel. If a pattern looks wrong, the GPIO
+    /// output can be compared with [`SelfTestPattern::gpio_states`]. Blocks until the last pattern is shown,
+    /// which stays on the display until the next canvas is swapped in.
+    pub fn self_test(&mut self, pattern_duration: Duration) {
+        for pattern in SelfTestPattern::ALL {
+            let mut canvas = self.new_canvas();
+            self.prepare_returned_canvas(&mut canvas);
+            pattern.draw(&mut canvas);
+            self.update_on_vsync(canvas);
+            sleep(pattern_duration);
+        }
+    }
+
+    /// Set the gamma value used to correct the perceived brightness of the color channels. Canvases returned
+    /// from [`RGBMatrix::update_on_vsync`] after this call use the new value for pixels drawn afterwards. The
+    /// gamma value is kept if the new one is not a positive number.
+    pub fn set_gamma(&mut self, gamma: f32) -> Result<(), ConfigError> {
+        RGBMatrixConfig::validate_gamma(gamma)?;
+        self.config.gamma = gamma;
+        self.color_lookup = Arc::new(ColorLookup::new(gamma, self.config.white_balance));
+        Ok(())
+    }
+
+    /// Set the brightness of the display in percent, clamped to 0 to 100. The brightness is applied to all
+    /// pixels of the canvas passed to the next [`RGBMatrix::update_on_vsync`] or [`RGBMatrix::swap`] call and of
+    /// all canvases returned from them, replacing the brightness set with [`Canvas::set_brightness`].
+    pub fn set_brightness(&mut self, percent: u8) {
+        let brightness = percent.min(100);
+        self.config.brightness = brightness;
+        self.brightness = Some(brightness);
+    }
+
+    /// Set the brightness of a single panel in percent, clamped to 0 to 100, e.g. to match panels of different
+    /// batches in a video wall. It scales the output of the panel on top of the brightness of the display. The
+    /// panels are counted from the start of the first chain, followed by the ones of the next parallel chains.
+    /// Like [`RGBMatrix::set_brightness`], it is applied to the canvas passed to the next
+    /// [`RGBMatrix::update_on_vsync`] or [`RGBMatrix::swap`] call and all canvases returned from them.
+    pub fn set_panel_brightness(&mut self, index: usize, percent: u8) -> Result<(), ConfigError> {
+        let panels = self.config.chain_length * self.config.parallel;
+        if index >= panels {
+            return Err(ConfigError::InvalidPanelIndex { index, panels });
+        }
+        if self.panel_brightness.is_empty() {
+            self.panel_brightness = vec![100; panels];
+        }
+        self.panel_brightness[index] = percent.min(100);
+        Ok(())
+    }
+
+    /// Get the brightness of the display in percent.
+    pub fn brightness(&self) -> u8 {
+        self.config.brightness
+    }
+
+    /// Install a handler for SIGINT and SIGTERM, replacing the default one that exits the program right away
+    /// and leaves the panel showing a frame. Once a signal was received, the update threads of all matrices
+    /// output a final black frame and stop, and [`RGBMatrix::interrupt_received`] returns `true`, so the program
+    /// can leave its drawing loop and exit.
+    pub fn install_signal_handler() -> io::Result<()> {
+        install_interrupt_handler()
+    }
+
+    /// Whether SIGINT or SIGTERM was received after [`RGBMatrix::install_signal_handler`] was called.
+    pub fn interrupt_received() -> bool {
+        interrupt_received()
+    }
+
+    /// The width and height of the matrix as the panels are chained, before the pixel mappers are applied:
+    /// the columns of all chained panels and the rows times the number of parallel chains. If a multiplexing is
+    /// configured, the rows and columns are the ones of the panels as they are seen after the multiplexing.
+    /// This is the size that a [`NamedPixelMapper`] is given as the matrix size.
+    pub fn physical_dimensions(&self) -> (usize, usize) {
+        (
+            self.config.chain_width(),
+            self.config.rows * self.config.parallel,
+        )
+    }
+
+    /// The width and height of the canvases, after the pixel mappers are applied. For the built-in mappers,
+    /// with `w` x `h` physical dimensions, `c` panels per chain and `p` parallel chains:
+    ///
+    /// - `Mirror`, `Offset` and `Serpentine` keep the size: `w` x `h`.
+    /// - `Rotate` by 90 or 270 degrees and `Transpose` swap the dimensions: `h` x `w`. Other rotations by
+    ///   multiples of 90 degrees keep the size.
+    /// - `Scale` by a factor `f` divides both dimensions: `w / f` x `h / f`.
+    /// - `U-mapper` bends the chains in half: `w / 2` x `2 * h`.
+    /// - `V-mapper` bends the chains in half sideways: `2 * h` x `w / 2`.
+    /// - `Grid` with `n` columns and `m` rows of cells has the size of as many panels: `n * (w / c)` x
+    ///   `m * (h / p)`.
+    ///
+    /// Several mappers are applied one after the other.
+    pub fn visible_dimensions(&self) -> (usize, usize) {
+        (self.shared_mapper.width(), self.shared_mapper.height())
+    }
+
+    /// Create a guard that blanks the display if it is dropped during a panic, e.g. to hold on a render thread.
+    /// See [`PanicGuard`].
+    pub fn panic_guard(&self) -> PanicGuard {
+        PanicGuard::new(Arc::clone(&self.frame_exchange))
+    }
+
+    /// Blank the display or turn it back on. While it is off, the update thread keeps running and picking up
+    /// canvases, but does not scan them out, so all LEDs stay dark and little CPU time is used. Turning it on
+    /// again resumes with the latest canvas.
+    pub fn set_enabled(&mut self, on: bool) {
+        self.frame_exchange.set_enabled(on);
+    }
+
+    /// Whether the display is on, see [`RGBMatrix::set_enabled`].
+    pub fn is_enabled(&self) -> bool {
+        self.frame_exchange.is_enabled()
+    }
+
+    /// Set the number of bits used for PWM, from 1 to 11. Fewer bits reduce the color depth but take less time
+    /// to output, which allows higher refresh rates. Like [`RGBMatrix::set_brightness`], the bits are applied to
+    /// the canvas passed to the next [`RGBMatrix::update_on_vsync`] call and all canvases returned from it,
+    /// replacing the value set with [`Canvas::set_pwm_bits`].
+    ///
+    /// Applying the bits redraws every pixel of a canvas, so the call to [`RGBMatrix::update_on_vsync`] that
+    /// applies them takes longer and the current frame may be displayed slightly longer than usual, which can
+    /// show as a brief flicker.
+    pub fn set_pwm_bits(&mut self, pwm_bits: usize) -> Result<(), ConfigError> {
+        if !(1..=K_BIT_PLANES).contains(&pwm_bits) {
+            return Err(ConfigError::InvalidPwmBits(pwm_bits));
         }
-        new_mapper
+        self.config.pwm_bits = pwm_bits;
+        self.pwm_bits = Some(pwm_bits);
+        Ok(())
+    }
+
+    /// Get the number of bits used for PWM.
+    pub fn pwm_bits(&self) -> usize {
+        self.config.pwm_bits
     }
 
     /// Updates the matrix with the new canvas. Blocks until the end of the current frame.
-    pub fn update_on_vsync(&mut self, canvas: Box<Canvas>) -> Box<Canvas> {
-        let Self {
-            canvas_to_thread_sender,
-            canvas_from_thread_receiver,
-            frame_rate_monitor,
-            ..
-        } = self;
+    ///
+    /// Returns the canvas that was displayed before, with its previous contents, to draw the next frame on. No
+    /// canvas is allocated, so a loop that passes the returned canvas back in does not allocate once it runs.
+    pub fn update_on_vsync(&mut self, mut canvas: Box<Canvas>) -> Box<Canvas> {
+        self.prepare_queued_canvas(&mut canvas);
+        let mut canvas = self.frame_exchange.swap_blocking(canvas);
+        self.prepare_returned_canvas(&mut canvas);
+        canvas
+    }
 
-        canvas_to_thread_sender.send(canvas).unwrap();
+    /// Queues the new canvas to be displayed from the start of the next frame and returns a canvas for drawing
+    /// the following one without waiting. If the previously queued canvas was not displayed yet, it is replaced
+    /// by the new one and returned. This allows rendering at a different pace than the refresh rate, at the cost
+    /// of a third canvas that is allocated when needed.
+    pub fn swap(&mut self, mut canvas: Box<Canvas>) -> Box<Canvas> {
+        self.prepare_queued_canvas(&mut canvas);
+        let mut canvas = self
+            .frame_exchange
+            .swap(canvas)
+            .unwrap_or_else(|| self.new_canvas());
+        self.prepare_returned_canvas(&mut canvas);
+        canvas
+    }
 
-        frame_rate_monitor.update();
+    /// Get the front buffer, which holds the frame that was last shown with [`RGBMatrix::present`], and the back
+    /// buffer to draw the next frame on, e.g. for effects that derive each frame from the previous one. Before
+    /// the first call to [`RGBMatrix::present`], the front buffer is black.
+    ///
+    /// The front buffer is a copy of the displayed canvas, which is owned by the update thread while it is
+    /// displayed, so it can be read while the frame is output and does not change until the next call to
+    /// [`RGBMatrix::present`]. It does not include the frames passed to [`RGBMatrix::update_on_vsync`] or
+    /// [`RGBMatrix::swap`] directly.
+    pub fn buffers(&mut self) -> (&Canvas, &mut Canvas) {
+        if self.buffers.is_none() {
+            let mut back = self.new_canvas();
+            self.prepare_returned_canvas(&mut back);
+            self.buffers = Some((self.new_canvas(), back));
+        }
+        let (front, back) = self.buffers.as_mut().unwrap();
+        (front, back)
+    }
+
+    /// Display the back buffer of [`RGBMatrix::buffers`], which becomes the new front buffer. Blocks until the
+    /// end of the current frame like [`RGBMatrix::update_on_vsync`]. The new back buffer keeps the contents of
+    /// the frame before, since the displayed canvases are swapped. The front buffer is copied into its existing
+    /// buffers, so presenting does not allocate memory.
+    pub fn present(&mut self) {
+        self.buffers();
+        let (mut front, mut back) = self.buffers.take().unwrap();
+        self.prepare_queued_canvas(&mut back);
+        front.clone_from(&back);
+        let mut back = self.frame_exchange.swap_blocking(back);
+        self.prepare_returned_canvas(&mut back);
+        self.buffers = Some((front, back));
+    }
+
+    /// Turn the matrix into a handle for producing frames without ever waiting for the update thread, e.g. from
+    /// a render thread. See [`ThreadedMatrix`] for how frames are dropped when they are produced faster than
+    /// they are displayed.
+    pub fn into_threaded(self) -> ThreadedMatrix {
+        ThreadedMatrix::new(self)
+    }
+
+    /// Queue a canvas without waiting. Returns whether the previously queued canvas was dropped before being
+    /// displayed.
+    pub(crate) fn submit_canvas(&mut self, mut canvas: Box<Canvas>) -> bool {
+        self.prepare_queued_canvas(&mut canvas);
+        self.frame_exchange.submit(canvas)
+    }
+
+    /// Get a canvas for drawing without waiting, reusing one that is no longer displayed if possible.
+    pub(crate) fn free_canvas(&mut self) -> Box<Canvas> {
+        let mut canvas = self
+            .frame_exchange
+            .take_released()
+            .unwrap_or_else(|| self.new_canvas());
+        self.prepare_returned_canvas(&mut canvas);
+        canvas
+    }
+
+    /// Allocate `n` canvases with the current mapping and settings up front, e.g. to cycle through more than two
+    /// canvases or to keep drawn frames around without allocating while displaying.
+    #[allow(clippy::vec_box)]
+    pub fn create_canvas_pool(&self, n: usize) -> Vec<Box<Canvas>> {
+        (0..n)
+            .map(|_| {
+                let mut canvas = self.new_canvas();
+                self.prepare_returned_canvas(&mut canvas);
+                canvas
+            })
+            .collect()
+    }
+
+    /// Allocate an additional canvas with the current mapping and color lookup.
+    fn new_canvas(&self) -> Box<Canvas> {
+        Box::new(Canvas::new(
+            &self.config,
+            Arc::clone(&self.shared_mapper),
+            Arc::clone(&self.color_lookup),
+        ))
+    }
+
+    /// Register a callback that is called after each frame has been output to the matrix. The callback runs on
+    /// the update thread, so it should return quickly to not delay the next frame, e.g. by only sending a
+    /// message or notifying a condition variable.
+    pub fn on_frame<F: FnMut() + Send + 'static>(&mut self, callback: F) {
+        self.frame_exchange.add_callback(Box::new(callback));
+    }
+
+    /// Draw the overlays on a canvas that is about to be displayed and apply the runtime settings to it.
+    fn prepare_queued_canvas(&self, canvas: &mut Canvas) {
+        if let Some(overlay) = &self.overlay {
+            overlay
+                .pixels()
+                .filter(|(_, _, &color)| color != self.overlay_key)
+                .for_each(|(x, y, color)| canvas.set_pixel(x, y, color.r, color.g, color.b));
+        }
+        if self.fps_overlay {
+            let framerate = self.get_framerate().round() as u32;
+            let digits = framerate.checked_ilog10().unwrap_or(0) as i32 + 1;
+            canvas.fill_rect(0, 0, 4 * digits + 1, 7, Color::BLACK);
+            canvas.draw_number(1, 1, framerate, Color::WHITE);
+        }
+        self.apply_settings(canvas);
+    }
+
+    /// Apply the runtime settings to a canvas, and start tracking the pixels drawn on it from here.
+    fn apply_settings(&self, canvas: &mut Canvas) {
+        canvas.commit_pixels();
+        if let Some(brightness) = self.brightness {
+            canvas.apply_brightness(brightness);
+        }
+        if let Some(pwm_bits) = self.pwm_bits {
+            canvas.apply_pwm_bits(pwm_bits);
+        }
+        if !self.panel_brightness.is_empty() {
+            canvas.apply_panel_brightness(&self.panel_brightness);
+        }
+        if let (Some(budget), Some(profile)) =
+            (self.config.power_limit_ma, self.config.power_profile)
+        {
+            canvas.limit_power(&profile, budget);
+        }
+        canvas.reset_dirty();
+    }
+
+    /// Apply the current mapping and runtime settings to a canvas that is handed out for drawing.
+    fn prepare_returned_canvas(&self, canvas: &mut Canvas) {
+        canvas.set_shared_mapper(&self.shared_mapper);
+        canvas.set_color_lookup(&self.color_lookup);
+        self.apply_settings(canvas);
+    }
+
+    /// Get the colors of the canvas that is currently displayed, row by row. The colors are the ones that were
+    /// drawn, before brightness and gamma correction are applied. While the display is off, all colors are
+    /// black.
+    pub fn get_frame(&self) -> Vec<Color> {
+        self.frame_exchange.displayed_colors()
+    }
+
+    /// Save the currently displayed frame as a binary PPM image. Like [`RGBMatrix::get_frame`], the image has the
+    /// size of the canvas and contains the colors as they were drawn.
+    pub fn save_ppm(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let (width, height, colors) = self.displayed_frame();
+        let mut writer = BufWriter::new(File::create(path)?);
+        write!(writer, "P6\n{width} {height}\n255\n")?;
+        for color in colors {
+            writer.write_all(&[color.r, color.g, color.b])?;
+        }
+        writer.flush()
+    }
+
+    /// Render the currently displayed frame like it looks on the panel, e.g. to show it in a desktop window
+    /// while developing without a panel. Every pixel is drawn as a square LED of `led_size` pixels, with `gap`
+    /// black pixels between the LEDs and around the edges.
+    ///
+    /// Returns the width and height of the rendering and its pixels row by row, in the `0RGB` format that window
+    /// libraries like `minifb` take.
+    pub fn preview_frame(&self, led_size: usize, gap: usize) -> (usize, usize, Vec<u32>) {
+        let (width, height, colors) = self.displayed_frame();
+        let pitch = led_size + gap;
+        let [preview_width, preview_height] = [width, height].map(|size| size * pitch + gap);
+        let mut pixels = vec![0; preview_width * preview_height];
+        for (index, color) in colors.iter().enumerate() {
+            let [left, top] = [index % width, index / width].map(|position| gap + position * pitch);
+            let value = u32::from_be_bytes([0, color.r, color.g, color.b]);
+            for row in pixels
+                .chunks_exact_mut(preview_width)
+                .skip(top)
+                .take(led_size)
+            {
+                row[left..left + led_size].fill(value);
+            }
+        }
+        (preview_width, preview_height, pixels)
+    }
+
+    /// Get the width, height and colors of the currently displayed frame.
+    pub(crate) fn displayed_frame(&self) -> (usize, usize, Vec<Color>) {
+        self.frame_exchange
+            .with_displayed(|canvas| (canvas.width(), canvas.height(), canvas.colors()))
+    }
 
-        canvas_from_thread_receiver.recv().unwrap()
+    /// Get an image of the currently displayed frame. The image has the size of the canvas, so it shows the
+    /// frame as it was drawn before the pixel mappers were applied.
+    #[cfg(feature = "image")]
+    pub fn snapshot(&self) -> image::RgbImage {
+        self.frame_exchange
+            .with_displayed(|canvas| canvas.to_rgb_image())
+    }
+
+    /// Get the Raspberry Pi model the matrix runs on, either as configured or as detected automatically. Returns
+    /// `None` for a matrix created with [`RGBMatrix::new_mock`] without a configured model.
+    pub fn pi_chip(&self) -> Option<PiChip> {
+        self.config.pi_chip
     }
 
     /// Get the bits that were available for input.
@@ -353,9 +849,18 @@ impl RGBMatrix {
         self.input_receiver.recv_timeout(timeout).ok()
     }
 
-    /// Get the average frame rate over the last 60 frames.
-    pub fn get_framerate(&self) -> usize {
-        self.frame_rate_monitor.get_fps().round() as usize
+    /// Get the statistics of the last frame that was output, e.g. to tune
+    /// [`RGBMatrixConfig::pwm_lsb_nanoseconds`] and [`RGBMatrixConfig::slowdown`].
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_exchange.frame_stats()
+    }
+
+    /// Get the refresh rate that is actually achieved by the update thread, averaged over the last 60 frames.
+    /// It does not exceed [`RGBMatrixConfig::refresh_rate`] or [`RGBMatrixConfig::refresh_rate_limit`], and is
+    /// lower if outputting a frame takes longer than the frame time of that rate, e.g. because of many PWM bits,
+    /// long chains or a high GPIO slowdown.
+    pub fn get_framerate(&self) -> f32 {
+        self.frame_exchange.frame_rate()
     }
 }
 
@@ -372,3 +877,341 @@ impl Drop for RGBMatrix {
         }
     }
 }
+
+#[cfg(test)]
+impl RGBMatrix {
+    /// The exchange with the update thread, which outlives the matrix.
+    pub(crate) fn frame_exchange(&self) -> Arc<FrameExchange> {
+        Arc::clone(&self.frame_exchange)
+    }
+
+    /// The output levels of the displayed canvas in the given frame, ordered by row and column.
+    pub(crate) fn output_levels(&self, frame: usize) -> Vec<[u16; 3]> {
+        self.frame_exchange
+            .with_displayed(|canvas| canvas.output_levels(frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{count_allocations, fake_clock_matrix, mock_matrix, test_config},
+        PowerProfile,
+    };
+
+    #[test]
+    fn test_rotated_bounds() {
+        let config = RGBMatrixConfig {
+            rows: 32,
+            cols: 64,
+            pixelmapper: vec!["Rotate:90".parse().unwrap()],
+            ..test_config()
+        };
+        let (_matrix, canvas) = RGBMatrix::new_mock(config).unwrap();
+        assert_eq!(canvas.bounds(), (32, 64));
+        assert_eq!([canvas.width(), canvas.height()], [32, 64]);
+        assert!(canvas.contains(31, 63));
+        assert!(!canvas.contains(32, 0));
+        assert!(!canvas.contains(0, 64));
+    }
+
+    #[test]
+    fn test_u_mapped_dimensions() {
+        let config = RGBMatrixConfig {
+            rows: 32,
+            cols: 32,
+            chain_length: 4,
+            parallel: 2,
+            hardware_mapping: crate::HardwareMapping::REGULAR,
+            pixelmapper: vec!["U-mapper".parse().unwrap()],
+            ..test_config()
+        };
+        let (matrix, canvas) = RGBMatrix::new_mock(config).unwrap();
+        assert_eq!(matrix.physical_dimensions(), (128, 64));
+        assert_eq!(matrix.visible_dimensions(), (64, 128));
+        assert_eq!(canvas.bounds(), matrix.visible_dimensions());
+    }
+
+    #[test]
+    fn test_save_ppm() {
+        let config = RGBMatrixConfig {
+            rows: 8,
+            cols: 2,
+            ..test_config()
+        };
+        let (mut matrix, mut canvas) = RGBMatrix::new_mock(config).unwrap();
+        canvas.set_pixel(1, 0, 1, 2, 3);
+        let _canvas = matrix.update_on_vsync(canvas);
+
+        let path = std::env::temp_dir().join(format!("rpi_led_panel_{}.ppm", std::process::id()));
+        matrix.save_ppm(&path).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let header = b"P6\n2 8\n255\n";
+        assert_eq!(&data[..header.len()], header);
+        assert_eq!(data.len(), header.len() + 2 * 8 * 3);
+        assert_eq!(&data[header.len()..header.len() + 6], [0, 0, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_set_gamma() {
+        let (mut matrix, _canvas) = mock_matrix();
+        assert_eq!(matrix.set_gamma(1.8), Ok(()));
+        assert_eq!(matrix.set_gamma(0.0), Err(ConfigError::InvalidGamma(0.0)));
+        assert_eq!(
+            matrix.set_gamma(f32::INFINITY),
+            Err(ConfigError::InvalidGamma(f32::INFINITY))
+        );
+        assert!(matches!(
+            matrix.set_gamma(f32::NAN),
+            Err(ConfigError::InvalidGamma(_))
+        ));
+    }
+
+    #[test]
+    fn test_power_limit() {
+        let profile = PowerProfile {
+            red_milliamps: 1.5,
+            green_milliamps: 1.0,
+            blue_milliamps: 1.25,
+            idle_milliamps: 120.0,
+        };
+        let config = RGBMatrixConfig {
+            power_limit_ma: Some(1000),
+            power_profile: Some(profile),
+            ..test_config()
+        };
+        let (mut matrix, mut canvas) = RGBMatrix::new_mock(config.clone()).unwrap();
+        let frame_exchange = matrix.frame_exchange();
+        let output_milliamps = || {
+            frame_exchange.with_displayed(|canvas| {
+                let lit: f32 = canvas
+                    .output_levels(0)
+                    .iter()
+                    .map(|[r, g, b]| {
+                        (*r as f32 * profile.red_milliamps
+                            + *g as f32 * profile.green_milliamps
+                            + *b as f32 * profile.blue_milliamps)
+                            / 2047.0
+                    })
+                    .sum();
+                profile.idle_milliamps + lit
+            })
+        };
+
+        // A full-white frame would draw 120 + 512 * 3.75 = 2040 mA and is dimmed to the budget.
+        canvas.fill(Color::WHITE);
+        let mut canvas = matrix.update_on_vsync(canvas);
+        let white = output_milliamps();
+        assert!(white <= 1000.0 && white > 950.0, "{white}");
+        assert_eq!(matrix.get_frame()[0], Color::WHITE);
+
+        // A dim frame is displayed as if there was no limit.
+        let dim = Color::new(64, 64, 64);
+        canvas.fill(dim);
+        let mut canvas = matrix.update_on_vsync(canvas);
+        let mut unlimited = Canvas::from_config(&test_config()).unwrap();
+        unlimited.fill(dim);
+        assert_eq!(
+            frame_exchange.with_displayed(|canvas| canvas.output_levels(0)),
+            unlimited.output_levels(0)
+        );
+
+        // The canvas that was dimmed keeps its brightness and is drawn with it again once its content allows.
+        assert_eq!(canvas.brightness(), 100);
+        canvas.fill(dim);
+        let _canvas = matrix.update_on_vsync(canvas);
+        assert_eq!(
+            frame_exchange.with_displayed(|canvas| canvas.output_levels(0)),
+            unlimited.output_levels(0)
+        );
+    }
+
+    #[test]
+    fn test_external_vsync() {
+        let (mut matrix, mut canvas, clock) = fake_clock_matrix();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        matrix.set_external_vsync(receiver);
+        canvas.fill(Color::RED);
+        let _canvas = matrix.swap(canvas);
+        let next_frame = || {
+            clock.advance(Duration::from_millis(1));
+            clock.wait_for_sleeper();
+        };
+
+        // The update thread refreshes the old canvas for several frames, but does not latch the new one.
+        (0..5).for_each(|_| next_frame());
+        assert!(matrix
+            .get_frame()
+            .iter()
+            .all(|&color| color == Color::BLACK));
+
+        // The canvas is latched in the first frame after the tick.
+        sender.send(()).unwrap();
+        next_frame();
+        assert_eq!(matrix.get_frame()[0], Color::RED);
+    }
+
+    #[test]
+    fn test_preview_frame() {
+        let (mut matrix, mut canvas) = mock_matrix();
+        canvas.set_pixel(1, 0, 255, 128, 0);
+        let _canvas = matrix.update_on_vsync(canvas);
+
+        let (width, height, pixels) = matrix.preview_frame(2, 1);
+        assert_eq!((width, height), (32 * 3 + 1, 16 * 3 + 1));
+        let lit = (0..height)
+            .flat_map(|y| (0..width).map(move |x| [x, y]))
+            .filter(|&[x, y]| pixels[y * width + x] != 0)
+            .collect::<Vec<_>>();
+        assert_eq!(lit, [[4, 1], [5, 1], [4, 2], [5, 2]]);
+        assert_eq!(pixels[width + 4], 0xff8000);
+    }
+
+    #[test]
+    fn test_fps_overlay() {
+        let (matrix, mut canvas) = mock_matrix();
+        let mut matrix = matrix.with_fps_overlay();
+        canvas.fill(Color::RED);
+        let _canvas = matrix.update_on_vsync(canvas);
+        let frame = matrix.get_frame();
+        // The first digit is drawn on a black background, and the rest of the canvas is kept.
+        assert_eq!(frame[0], Color::BLACK);
+        assert_eq!(frame[32 + 1], Color::WHITE);
+        assert_eq!(frame[32 * 8], Color::RED);
+    }
+
+    #[test]
+    fn test_overlay() {
+        let (mut matrix, mut canvas) = mock_matrix();
+        let mut overlay = Canvas::from_config(&test_config()).unwrap();
+        overlay.set_pixel(3, 2, 255, 0, 0);
+        overlay.set_pixel(4, 2, 0, 255, 0);
+        matrix.set_overlay(Some(overlay));
+        matrix.set_overlay_key(Color::LIME);
+
+        canvas.fill(Color::BLUE);
+        let mut canvas = matrix.update_on_vsync(canvas);
+        let frame = matrix.get_frame();
+        assert_eq!(frame[32 * 2 + 3], Color::RED);
+        // Key colored overlay pixels show the main canvas, and the other ones cover it.
+        assert_eq!(frame[32 * 2 + 4], Color::BLUE);
+        assert_eq!(frame[0], Color::BLACK);
+        assert_eq!(
+            frame.iter().filter(|&&color| color == Color::RED).count(),
+            1
+        );
+
+        // The overlay stays until it is removed.
+        canvas.fill(Color::BLUE);
+        let mut canvas = matrix.update_on_vsync(canvas);
+        assert_eq!(matrix.get_frame()[32 * 2 + 3], Color::RED);
+        // With the default key, black pixels are transparent.
+        matrix.set_overlay_key(Color::BLACK);
+        canvas.fill(Color::BLUE);
+        let mut canvas = matrix.update_on_vsync(canvas);
+        let frame = matrix.get_frame();
+        assert_eq!(frame[0], Color::BLUE);
+        assert_eq!(frame[32 * 2 + 4], Color::LIME);
+
+        matrix.set_overlay(None);
+        canvas.fill(Color::BLUE);
+        let _canvas = matrix.update_on_vsync(canvas);
+        assert_eq!(matrix.get_frame()[32 * 2 + 3], Color::BLUE);
+    }
+
+    #[test]
+    fn test_buffers() {
+        let (mut matrix, _canvas) = mock_matrix();
+        let (front, back) = matrix.buffers();
+        assert_eq!(front.lit_pixels().len(), 0);
+        back.set_pixel(1, 2, 255, 0, 0);
+        matrix.present();
+        assert_eq!(matrix.get_frame()[32 * 2 + 1], Color::RED);
+
+        // The presented frame is the new front, and the back buffer holds the frame before it.
+        let (front, back) = matrix.buffers();
+        assert_eq!(front.get_pixel(1, 2), Some(Color::RED));
+        assert_eq!(back.get_pixel(1, 2), Some(Color::BLACK));
+        // Derive the next frame from the front buffer.
+        let previous = front.get_pixel(1, 2).unwrap();
+        back.set_pixel(2, 2, previous.r, previous.g, previous.b);
+        matrix.present();
+        let frame = matrix.get_frame();
+        assert_eq!(frame[32 * 2 + 2], Color::RED);
+        assert_eq!(frame[32 * 2 + 1], Color::BLACK);
+        assert_eq!(matrix.buffers().0.get_pixel(2, 2), Some(Color::RED));
+    }
+
+    #[test]
+    fn test_panel_brightness() {
+        let config = RGBMatrixConfig {
+            chain_length: 2,
+            ..test_config()
+        };
+        let (mut matrix, mut canvas) = RGBMatrix::new_mock(config).unwrap();
+        matrix.set_panel_brightness(0, 50).unwrap();
+        assert_eq!(
+            matrix.set_panel_brightness(2, 50),
+            Err(ConfigError::InvalidPanelIndex {
+                index: 2,
+                panels: 2
+            })
+        );
+
+        canvas.fill(Color::WHITE);
+        let _canvas = matrix.update_on_vsync(canvas);
+        let levels = matrix.output_levels(0);
+        let full = (1 << K_BIT_PLANES) - 1;
+        let half = full / 2 + 1;
+        for y in [0, 15] {
+            assert_eq!(levels[64 * y], [half; 3]);
+            assert_eq!(levels[64 * y + 31], [half; 3]);
+            assert_eq!(levels[64 * y + 32], [full; 3]);
+            assert_eq!(levels[64 * y + 63], [full; 3]);
+        }
+        // The colors are kept at their full brightness.
+        assert!(matrix
+            .get_frame()
+            .iter()
+            .all(|&color| color == Color::WHITE));
+    }
+
+    #[test]
+    fn test_canvas_pool_does_not_allocate() {
+        let (mut matrix, canvas) = mock_matrix();
+        let mut pool = matrix.create_canvas_pool(2);
+        pool.push(canvas);
+        let mut swap = |matrix: &mut RGBMatrix, i: usize| {
+            let mut canvas = pool.pop().unwrap();
+            canvas.fill(Color::BLACK);
+            canvas.set_pixel(i % 32, 0, 255, 255, 255);
+            pool.insert(0, matrix.update_on_vsync(canvas));
+        };
+
+        // Warm up, so that the lists of canvases have grown to their final size.
+        (0..10).for_each(|i| swap(&mut matrix, i));
+        let allocations = count_allocations(|| (0..100).for_each(|i| swap(&mut matrix, i)));
+
+        assert_eq!(allocations, 0);
+        assert_eq!(matrix.get_frame()[99 % 32], Color::WHITE);
+    }
+
+    #[test]
+    fn test_present_does_not_allocate() {
+        let (mut matrix, _canvas) = mock_matrix();
+        matrix.present();
+
+        let allocations = count_allocations(|| {
+            for x in 0..10 {
+                matrix.buffers().1.set_pixel(x, 0, 255, 255, 255);
+                matrix.present();
+            }
+        });
+
+        assert_eq!(allocations, 0);
+        assert_eq!(matrix.buffers().0.get_pixel(9, 0), Some(Color::WHITE));
+    }
+}
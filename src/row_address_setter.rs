@@ -1,13 +1,26 @@
-use std::{error::Error, str::FromStr};
+use alloc::{boxed::Box, format};
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
-use crate::{gpio::Gpio, RGBMatrixConfig};
+use crate::{gpio_output::GpioOutput, RGBMatrixConfig};
 
+/// How the panels latch the address of the row that is output. A setter that doesn't match the panels results
+/// in rows being shown in the wrong place, often seen as every other panel or block of rows being scrambled.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RowAddressSetterType {
+    /// The row address is set in binary on the address lines A to E, as used by most panels.
     Direct,
+    /// The rows are selected by a shift register, with A as the clock and B as the data, as used by AB addressed
+    /// panels with a 74HC164 shift register.
     ShiftRegister,
+    /// Each of the four rows of a 1:4 multiplexed panel is selected by pulling one of the lines A to D low.
     DirectABCDLine,
+    /// Like [`RowAddressSetterType::ShiftRegister`], but with C as the data line.
     ABCShiftRegister,
+    /// An SM5266 shift register on A to C selects a row within a group of eight, D and E select the group.
     SM5266,
 }
 
@@ -26,6 +39,18 @@ impl FromStr for RowAddressSetterType {
     }
 }
 
+impl Display for RowAddressSetterType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            RowAddressSetterType::Direct => "Direct",
+            RowAddressSetterType::ShiftRegister => "ShiftRegister",
+            RowAddressSetterType::DirectABCDLine => "DirectABCDLine",
+            RowAddressSetterType::ABCShiftRegister => "ABCShiftRegister",
+            RowAddressSetterType::SM5266 => "SM5266",
+        })
+    }
+}
+
 impl RowAddressSetterType {
     pub(crate) fn create(&self, config: &RGBMatrixConfig) -> Box<dyn RowAddressSetter> {
         match self {
@@ -47,7 +72,7 @@ impl RowAddressSetterType {
 /// Different panel types use different techniques to set the row address.
 pub(crate) trait RowAddressSetter {
     fn used_bits(&self) -> u32;
-    fn set_row_address(&mut self, gpio: &mut Gpio, row: usize);
+    fn set_row_address(&mut self, gpio: &mut dyn GpioOutput, row: usize);
 }
 
 pub(crate) struct DirectRowAddressSetter {
@@ -95,7 +120,7 @@ impl RowAddressSetter for DirectRowAddressSetter {
         self.row_mask
     }
 
-    fn set_row_address(&mut self, gpio: &mut Gpio, row: usize) {
+    fn set_row_address(&mut self, gpio: &mut dyn GpioOutput, row: usize) {
         if self.last_row == Some(row) {
             return;
         }
@@ -157,7 +182,7 @@ impl RowAddressSetter for SM5266RowAddressSetter {
         self.row_mask
     }
 
-    fn set_row_address(&mut self, gpio: &mut Gpio, row: usize) {
+    fn set_row_address(&mut self, gpio: &mut dyn GpioOutput, row: usize) {
         if self.last_row == Some(row) {
             return;
         }
@@ -208,7 +233,7 @@ impl RowAddressSetter for ShiftRegisterRowAddressSetter {
         self.row_mask
     }
 
-    fn set_row_address(&mut self, gpio: &mut Gpio, row: usize) {
+    fn set_row_address(&mut self, gpio: &mut dyn GpioOutput, row: usize) {
         if self.last_row == Some(row) {
             return;
         }
@@ -257,7 +282,7 @@ impl RowAddressSetter for ABCShiftRegisterRowAddressSetter {
         self.row_mask
     }
 
-    fn set_row_address(&mut self, gpio: &mut Gpio, row: usize) {
+    fn set_row_address(&mut self, gpio: &mut dyn GpioOutput, row: usize) {
         if self.last_row == Some(row) {
             return;
         }
@@ -314,7 +339,7 @@ impl RowAddressSetter for DirectABCDLineRowAddressSetter {
         self.row_mask
     }
 
-    fn set_row_address(&mut self, gpio: &mut Gpio, row: usize) {
+    fn set_row_address(&mut self, gpio: &mut dyn GpioOutput, row: usize) {
         if self.last_row == Some(row) {
             return;
         }
@@ -323,3 +348,76 @@ impl RowAddressSetter for DirectABCDLineRowAddressSetter {
         self.last_row = Some(row);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::*;
+    use crate::gpio_output::RecordingGpio;
+
+    fn test_config() -> RGBMatrixConfig {
+        RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_direct() {
+        let config = test_config();
+        let h = config.hardware_mapping;
+        let mut setter = RowAddressSetterType::Direct.create(&config);
+        assert_eq!(setter.used_bits(), h.a | h.b | h.c);
+
+        let mut gpio = RecordingGpio::default();
+        setter.set_row_address(&mut gpio, 5);
+        assert_eq!(gpio.bits, h.a | h.c);
+        setter.set_row_address(&mut gpio, 2);
+        assert_eq!(gpio.bits, h.b);
+        // Setting the same row again doesn't output anything.
+        let operations = gpio.states.len();
+        setter.set_row_address(&mut gpio, 2);
+        assert_eq!(gpio.states.len(), operations);
+    }
+
+    #[test]
+    fn test_shift_register() {
+        let config = test_config();
+        let h = config.hardware_mapping;
+        let mut setter = RowAddressSetterType::ShiftRegister.create(&config);
+        assert_eq!(setter.used_bits(), h.a | h.b);
+
+        let mut gpio = RecordingGpio::default();
+        setter.set_row_address(&mut gpio, 2);
+        // The data line B is shifted in on every rising edge of the clock line A.
+        let shifted_bits = gpio
+            .clocked_states(h.a)
+            .iter()
+            .map(|state| state & h.b != 0)
+            .collect::<Vec<_>>();
+        // One bit per row, where only the selected row is low, and a final clock to latch.
+        let mut expected = vec![true; 8];
+        expected[8 - 1 - 2] = false;
+        expected.push(true);
+        assert_eq!(shifted_bits, expected);
+    }
+
+    #[test]
+    fn test_direct_abcd_line() {
+        let config = test_config();
+        let h = config.hardware_mapping;
+        let mut setter = RowAddressSetterType::DirectABCDLine.create(&config);
+        assert_eq!(setter.used_bits(), h.a | h.b | h.c | h.d);
+
+        let mut gpio = RecordingGpio::default();
+        for (row, low_line) in [h.a, h.b, h.c, h.d].into_iter().enumerate() {
+            setter.set_row_address(&mut gpio, row);
+            assert_eq!(gpio.bits, (h.a | h.b | h.c | h.d) & !low_line);
+        }
+        // The rows repeat after four.
+        setter.set_row_address(&mut gpio, 5);
+        assert_eq!(gpio.bits, h.a | h.c | h.d);
+    }
+}
@@ -0,0 +1,141 @@
+use alloc::{sync::Arc, vec::Vec};
+
+use crate::{
+    canvas::Canvas, color::ColorLookup, gpio_output::RecordingGpio,
+    pixel_mapper::create_shared_mapper, Color, ConfigError, RGBMatrixConfig,
+};
+
+/// A test pattern that is shown by [`RGBMatrix::self_test`](crate::RGBMatrix::self_test), to tell wiring
+/// problems apart from problems with the generated bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelfTestPattern {
+    /// All pixels red.
+    Red,
+    /// All pixels green.
+    Green,
+    /// All pixels blue.
+    Blue,
+    /// All pixels white.
+    White,
+    /// A diagonal line in the red channel, which is lit in row `x % height` of every column `x`. Every column
+    /// that is clocked in has a single color bit set, which walks through the rows and both halves of the
+    /// panels.
+    WalkingRed,
+    /// Like [`SelfTestPattern::WalkingRed`] in the green channel.
+    WalkingGreen,
+    /// Like [`SelfTestPattern::WalkingRed`] in the blue channel.
+    WalkingBlue,
+}
+
+impl SelfTestPattern {
+    /// All patterns in the order in which they are shown.
+    pub const ALL: [Self; 7] = [
+        Self::Red,
+        Self::Green,
+        Self::Blue,
+        Self::White,
+        Self::WalkingRed,
+        Self::WalkingGreen,
+        Self::WalkingBlue,
+    ];
+
+    /// Draw the pattern on a canvas, replacing its contents.
+    pub fn draw(&self, canvas: &mut Canvas) {
+        let (fill, walking) = match self {
+            Self::Red => (Color::RED, Color::BLACK),
+            Self::Green => (Color::GREEN, Color::BLACK),
+            Self::Blue => (Color::BLUE, Color::BLACK),
+            Self::White => (Color::WHITE, Color::BLACK),
+            Self::WalkingRed => (Color::BLACK, Color::RED),
+            Self::WalkingGreen => (Color::BLACK, Color::GREEN),
+            Self::WalkingBlue => (Color::BLACK, Color::BLUE),
+        };
+        canvas.fill(fill);
+        if walking != Color::BLACK {
+            for x in 0..canvas.width() {
+                canvas.set_pixel(x, x % canvas.height(), walking.r, walking.g, walking.b);
+            }
+        }
+    }
+
+    /// The states of the GPIO output bits after every write while the first frame of the pattern is output
+    /// with the given config, with bit `n` for GPIO `n`. They can be compared with the capture of a logic
+    /// analyzer. The output enable pulses are not included, since they are timed by the PWM peripheral or a
+    /// timer, and nor are the writes of a panel initialization sequence.
+    pub fn gpio_states(&self, config: &RGBMatrixConfig) -> Result<Vec<u32>, ConfigError> {
+        config.validate()?;
+        let mut config = config.clone();
+        let (_, shared_mapper) = create_shared_mapper(&mut config)?;
+        let mut canvas = Canvas::new(
+            &config,
+            shared_mapper,
+            Arc::new(ColorLookup::new(config.gamma, config.white_balance)),
+        );
+        self.draw(&mut canvas);
+
+        let h = &config.hardware_mapping;
+        let mut gpio = RecordingGpio::default();
+        let mut row_setter = config.row_setter.create(&config);
+        let color_clk_mask = h.get_color_clock_mask(config.parallel);
+        canvas.dump_to_matrix(&mut gpio, h, row_setter.as_mut(), 0, color_clk_mask);
+        Ok(gpio.states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::test_utils::mock_matrix;
+    use crate::test_utils::test_config;
+
+    #[test]
+    fn test_walking_bit_gpio_states() {
+        let config = test_config();
+        let states = SelfTestPattern::WalkingRed.gpio_states(&config).unwrap();
+        let h = &config.hardware_mapping;
+        let [r1, r2] = [h.panels.color_bits[0].r1, h.panels.color_bits[0].r2];
+        let clock = h.clock;
+        // Every column clears the previous color and clock bits, sets the new color bits and then the clock.
+        // Of the first double row, column 0 is lit in the upper half and column 8 in the lower half.
+        assert_eq!(states[..6], [0, r1, r1 | clock, 0, 0, clock]);
+        assert_eq!(states[3 * 8..3 * 9], [0, r2, r2 | clock]);
+        // Every bit plane clocks in 32 columns and then latches them.
+        let clocked = RecordingGpio { bits: 0, states }.clocked_states(clock);
+        assert_eq!(clocked.len(), 8 * 11 * 32);
+    }
+
+    #[test]
+    fn test_draw_patterns() {
+        let config = test_config();
+        let mut canvas = Canvas::from_config(&config).unwrap();
+        SelfTestPattern::White.draw(&mut canvas);
+        assert_eq!(canvas.lit_pixels().len(), 16 * 32);
+        SelfTestPattern::WalkingBlue.draw(&mut canvas);
+        assert_eq!(canvas.get_pixel(17, 1), Some(Color::BLUE));
+        assert_eq!(canvas.get_pixel(17, 0), Some(Color::BLACK));
+        assert_eq!(canvas.lit_pixels().len(), 32);
+
+        let config = RGBMatrixConfig { rows: 7, ..config };
+        assert_eq!(
+            SelfTestPattern::Red.gpio_states(&config),
+            Err(ConfigError::InvalidRows(7))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_self_test() {
+        let (mut matrix, _canvas) = mock_matrix();
+        matrix.self_test(core::time::Duration::ZERO);
+        // The walking bit in the blue channel is shown last.
+        let frame = matrix.get_frame();
+        assert_eq!(frame[0], Color::BLUE);
+        assert_eq!(frame[32 + 1], Color::BLUE);
+        assert_eq!(frame[1], Color::BLACK);
+        assert_eq!(
+            frame.iter().filter(|&&color| color == Color::BLUE).count(),
+            32
+        );
+    }
+}
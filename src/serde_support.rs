@@ -0,0 +1,160 @@
+use std::fmt::Formatter;
+
+use serde::{
+    de::{Error, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    Dithering, HardwareMapping, LedSequence, MultiplexMapperType, NamedPixelMapperType, PanelType,
+    PiChip, RowAddressSetterType,
+};
+
+/// Implement `Serialize` and `Deserialize` with the same string representation as on the command line, using
+/// the `Display` and `FromStr` implementations of the type.
+macro_rules! impl_serde_via_str {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    struct StrVisitor;
+
+                    impl Visitor<'_> for StrVisitor {
+                        type Value = $ty;
+
+                        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                            write!(f, "a {} string", stringify!($ty))
+                        }
+
+                        fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+                            value.parse().map_err(E::custom)
+                        }
+                    }
+
+                    deserializer.deserialize_str(StrVisitor)
+                }
+            }
+        )*
+    };
+}
+
+impl_serde_via_str!(
+    Dithering,
+    HardwareMapping,
+    LedSequence,
+    MultiplexMapperType,
+    NamedPixelMapperType,
+    PanelType,
+    PiChip,
+    RowAddressSetterType,
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::{PanelSpec, PowerProfile, RGBMatrixConfig, WhiteBalance};
+
+    use super::*;
+
+    #[test]
+    fn test_config_round_trip() {
+        let config = RGBMatrixConfig {
+            hardware_mapping: HardwareMapping::regular_pi1(),
+            rows: 32,
+            cols: 128,
+            refresh_rate: 200,
+            refresh_rate_limit: Some(100),
+            pi_chip: Some(PiChip::BCM2711),
+            pwm_bits: 8,
+            pwm_lsb_nanoseconds: 200,
+            slowdown: Some(3),
+            cpu_affinity: Some(2),
+            realtime_priority: Some(50),
+            brightness: 75,
+            gamma: 1.8,
+            white_balance: WhiteBalance::new(1.0, 0.9, 0.75),
+            interlaced: true,
+            dither_bits: 1,
+            dithering: Dithering::OrderedBayer,
+            chain_length: 4,
+            panels: vec![
+                PanelSpec::new(32, 128),
+                PanelSpec::new(32, 64),
+                PanelSpec::new(32, 64),
+                PanelSpec::new(32, 128),
+            ],
+            parallel: 2,
+            panel_type: Some(PanelType::FM6127),
+            multiplexing: Some(MultiplexMapperType::P10Outdoor32x16HalfScan),
+            pixelmapper: vec![
+                NamedPixelMapperType::Mirror(false),
+                NamedPixelMapperType::Rotate(270),
+                NamedPixelMapperType::Offset(-3, 5),
+                NamedPixelMapperType::UMapper,
+            ],
+            row_setter: RowAddressSetterType::ABCShiftRegister,
+            led_sequence: LedSequence::Gbr,
+            inverse_colors: true,
+            red_enabled: true,
+            green_enabled: true,
+            blue_enabled: false,
+            power_limit_ma: Some(4000),
+            power_profile: Some(PowerProfile {
+                red_milliamps: 1.5,
+                green_milliamps: 1.0,
+                blue_milliamps: 1.25,
+                idle_milliamps: 120.0,
+            }),
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""hardware_mapping":"RegularPi1""#));
+        assert!(
+            json.contains(r#""pixelmapper":["Mirror:V","Rotate:270","Offset:-3,5","U-mapper"]"#)
+        );
+        assert!(json.contains(r#""led_sequence":"GBR""#));
+        let deserialized: RGBMatrixConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, config);
+    }
+
+    #[test]
+    fn test_config_missing_fields_use_defaults() {
+        let config: RGBMatrixConfig =
+            serde_json::from_str(r#"{"rows": 32, "multiplexing": "Checkered"}"#).unwrap();
+        assert_eq!(
+            config,
+            RGBMatrixConfig {
+                rows: 32,
+                multiplexing: Some(MultiplexMapperType::Checkered),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_enum_string() {
+        let error = serde_json::from_str::<RGBMatrixConfig>(r#"{"row_setter": "Nope"}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("'Nope' is not a valid row address setter type."));
+    }
+
+    #[test]
+    fn test_hardware_mapping_names_round_trip() {
+        for name in [
+            "AdafruitHat",
+            "AdafruitHatPwm",
+            "Regular",
+            "RegularPi1",
+            "Classic",
+            "ClassicPi1",
+        ] {
+            let mapping: HardwareMapping = name.parse().unwrap();
+            assert_eq!(mapping.to_string(), name);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use alloc::vec::Vec;
+use core::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use crate::{Canvas, Color};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpriteError {
+    /// The number of pixels does not match the size of the sprite.
+    InvalidPixelCount { expected: usize, actual: usize },
+}
+
+impl Error for SpriteError {}
+
+impl Display for SpriteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SpriteError::InvalidPixelCount { expected, actual } => write!(
+                f,
+                "Expected {expected} pixels for the size of the sprite, but got {actual}."
+            ),
+        }
+    }
+}
+
+/// A bitmap that can be drawn onto a canvas repeatedly with [`Canvas::draw_sprite`], e.g. for the frames of an
+/// animation. Pixels of the transparent color key are skipped, so that the canvas shows through them.
+///
+/// ```
+/// use rpi_led_panel::{Canvas, Color, RGBMatrixConfig, Sprite};
+///
+/// let mut canvas = Canvas::from_config(&RGBMatrixConfig::default()).unwrap();
+/// let arrow = Sprite::from_fn(3, 3, |x, y| if x == 1 || y == 1 { Color::RED } else { Color::BLACK })
+///     .with_transparent(Color::BLACK);
+/// for x in (0..60).step_by(4) {
+///     canvas.draw_sprite(&arrow, x, 10);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sprite {
+    width: usize,
+    height: usize,
+    /// The colors row by row.
+    pixels: Vec<Color>,
+    transparent: Option<Color>,
+}
+
+impl Sprite {
+    /// Create a sprite from its colors row by row, without a transparent color.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Result<Self, SpriteError> {
+        if pixels.len() != width * height {
+            return Err(SpriteError::InvalidPixelCount {
+                expected: width * height,
+                actual: pixels.len(),
+            });
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            transparent: None,
+        })
+    }
+
+    /// Create a sprite with the color that `f` returns for every (x, y), without a transparent color.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> Color) -> Self {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| [x, y]))
+            .map(|[x, y]| f(x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+            transparent: None,
+        }
+    }
+
+    /// Skip the pixels of this color when drawing the sprite.
+    pub fn with_transparent(mut self, transparent: Color) -> Self {
+        self.transparent = Some(transparent);
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the transparent color key, if any.
+    pub fn transparent(&self) -> Option<Color> {
+        self.transparent
+    }
+
+    /// Create a copy of the sprite that is mirrored left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        self.map_pixels(self.width, self.height, |x, y| [self.width - 1 - x, y])
+    }
+
+    /// Create a copy of the sprite that is mirrored top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        self.map_pixels(self.width, self.height, |x, y| [x, self.height - 1 - y])
+    }
+
+    /// Create a copy of the sprite that is rotated by 90 degrees clockwise, like the `Rotate:90` pixel mapper,
+    /// which swaps its width and height.
+    pub fn rotate_90(&self) -> Self {
+        self.map_pixels(self.height, self.width, |x, y| [y, self.height - 1 - x])
+    }
+
+    /// Create a sprite of the given size with the same transparent color, taking every pixel (x, y) from the
+    /// pixel of this sprite that `source` returns for it.
+    fn map_pixels(
+        &self,
+        width: usize,
+        height: usize,
+        source: impl Fn(usize, usize) -> [usize; 2],
+    ) -> Self {
+        let sprite = Self::from_fn(width, height, |x, y| {
+            let [source_x, source_y] = source(x, y);
+            self.pixels[source_y * self.width + source_x]
+        });
+        Self {
+            transparent: self.transparent,
+            ..sprite
+        }
+    }
+
+    /// Get the color of a pixel, or `None` if it is outside of the sprite.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.pixels[y * self.width + x])
+    }
+}
+
+impl Canvas {
+    /// Draw a sprite with its top left corner at (`x`, `y`). Pixels of the transparent color of the sprite are
+    /// skipped, and parts of the sprite that are outside of the canvas are discarded.
+    pub fn draw_sprite(&mut self, sprite: &Sprite, x: i32, y: i32) {
+        let [x, y] = [x, y].map(i64::from);
+        let x_range = x.max(0)..(x + sprite.width as i64).min(self.width() as i64);
+        let y_range = y.max(0)..(y + sprite.height as i64).min(self.height() as i64);
+        for dst_y in y_range {
+            let row = (dst_y - y) as usize * sprite.width;
+            for dst_x in x_range.clone() {
+                let color = sprite.pixels[row + (dst_x - x) as usize];
+                if Some(color) != sprite.transparent {
+                    self.set_pixel(dst_x as usize, dst_y as usize, color.r, color.g, color.b);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::RGBMatrixConfig;
+
+    fn test_canvas() -> Canvas {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            ..Default::default()
+        };
+        Canvas::new_for_test(&config)
+    }
+
+    /// A 3x2 sprite with a transparent magenta pixel in the middle of the top row.
+    fn test_sprite() -> Sprite {
+        let magenta = Color::new(255, 0, 255);
+        Sprite::new(
+            3,
+            2,
+            vec![
+                Color::RED,
+                magenta,
+                Color::GREEN,
+                Color::BLUE,
+                Color::WHITE,
+                Color::RED,
+            ],
+        )
+        .unwrap()
+        .with_transparent(magenta)
+    }
+
+    #[test]
+    fn test_draw_sprite() {
+        let mut canvas = test_canvas();
+        canvas.fill(Color::new(1, 2, 3));
+        canvas.draw_sprite(&test_sprite(), 4, 5);
+        assert_eq!(canvas.get_pixel(4, 5), Some(Color::RED));
+        assert_eq!(canvas.get_pixel(5, 5), Some(Color::new(1, 2, 3)));
+        assert_eq!(canvas.get_pixel(6, 5), Some(Color::GREEN));
+        assert_eq!(canvas.get_pixel(5, 6), Some(Color::WHITE));
+        assert_eq!(canvas.get_pixel(7, 6), Some(Color::new(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_draw_sprite_clipped() {
+        let mut canvas = test_canvas();
+        canvas.draw_sprite(&test_sprite(), -1, 15);
+        // Only the transparent pixel and the green one of the top row are on the canvas.
+        assert_eq!(canvas.lit_pixels(), [[1, 15]]);
+        assert_eq!(canvas.get_pixel(1, 15), Some(Color::GREEN));
+
+        let mut canvas = test_canvas();
+        canvas.draw_sprite(&test_sprite(), 30, -1);
+        assert_eq!(canvas.lit_pixels(), [[30, 0], [31, 0]]);
+        assert_eq!(canvas.get_pixel(30, 0), Some(Color::BLUE));
+
+        let mut canvas = test_canvas();
+        canvas.draw_sprite(&test_sprite(), 32, 0);
+        canvas.draw_sprite(&test_sprite(), -3, 0);
+        assert!(canvas.lit_pixels().is_empty());
+    }
+
+    #[test]
+    fn test_flip() {
+        let sprite = test_sprite();
+        let flipped = sprite.flip_horizontal();
+        assert_eq!(flipped.get_pixel(0, 0), Some(Color::GREEN));
+        assert_eq!(flipped.get_pixel(2, 1), Some(Color::BLUE));
+        assert_eq!(flipped.transparent(), sprite.transparent());
+        assert_eq!(flipped.flip_horizontal(), sprite);
+
+        let flipped = sprite.flip_vertical();
+        assert_eq!(flipped.get_pixel(0, 0), Some(Color::BLUE));
+        assert_eq!(flipped.get_pixel(2, 1), Some(Color::GREEN));
+        assert_eq!(flipped.flip_vertical(), sprite);
+    }
+
+    #[test]
+    fn test_rotate_90() {
+        let sprite = test_sprite();
+        let rotated = sprite.rotate_90();
+        assert_eq!([rotated.width(), rotated.height()], [2, 3]);
+        // The bottom left corner moves to the top left and the top left corner to the top right.
+        assert_eq!(rotated.get_pixel(0, 0), Some(Color::BLUE));
+        assert_eq!(rotated.get_pixel(1, 0), Some(Color::RED));
+        assert_eq!(rotated.get_pixel(0, 2), Some(Color::RED));
+        assert_eq!(rotated.get_pixel(1, 2), Some(Color::GREEN));
+        assert_eq!(
+            rotated.rotate_90().rotate_90().rotate_90().rotate_90(),
+            rotated
+        );
+        assert_eq!(
+            rotated.rotate_90(),
+            sprite.flip_horizontal().flip_vertical()
+        );
+    }
+
+    #[test]
+    fn test_sprite_new() {
+        assert_eq!(
+            Sprite::new(2, 2, vec![Color::RED; 3]),
+            Err(SpriteError::InvalidPixelCount {
+                expected: 4,
+                actual: 3
+            })
+        );
+        let sprite = Sprite::from_fn(2, 3, |x, y| Color::new(x as u8, y as u8, 0));
+        assert_eq!(sprite.get_pixel(1, 2), Some(Color::new(1, 2, 0)));
+        assert_eq!(sprite.get_pixel(2, 0), None);
+        assert_eq!(sprite.transparent(), None);
+    }
+}
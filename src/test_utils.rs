@@ -0,0 +1,102 @@
+#[cfg(feature = "std")]
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use crate::RGBMatrixConfig;
+#[cfg(feature = "std")]
+use crate::{Canvas, Clock, FakeClock, RGBMatrix};
+
+/// A chain of a single 32x16 panel, which is refreshed at 1000Hz so that mock matrices pick up canvases quickly.
+pub(crate) fn test_config() -> RGBMatrixConfig {
+    RGBMatrixConfig {
+        rows: 16,
+        cols: 32,
+        refresh_rate: 1000,
+        ..Default::default()
+    }
+}
+
+/// A mock matrix of the [`test_config`] along with its canvas.
+#[cfg(feature = "std")]
+pub(crate) fn mock_matrix() -> (RGBMatrix, Box<Canvas>) {
+    RGBMatrix::new_mock(test_config()).unwrap()
+}
+
+/// A mock matrix of the [`test_config`] that is paced by a fake clock, along with its canvas and the clock.
+/// The first frame was output, and the update thread waits for the clock to be advanced.
+#[cfg(feature = "std")]
+pub(crate) fn fake_clock_matrix() -> (RGBMatrix, Box<Canvas>, Arc<FakeClock>) {
+    let clock = Arc::new(FakeClock::new());
+    let (matrix, canvas) =
+        RGBMatrix::new_mock_with_clock(test_config(), Arc::clone(&clock) as Arc<dyn Clock>)
+            .unwrap();
+    clock.wait_for_sleeper();
+    (matrix, canvas, clock)
+}
+
+/// Count the frames that are output from now on.
+#[cfg(feature = "std")]
+pub(crate) fn count_frames(matrix: &mut RGBMatrix) -> Arc<AtomicUsize> {
+    let frames = Arc::new(AtomicUsize::new(0));
+    let callback_frames = Arc::clone(&frames);
+    matrix.on_frame(move || {
+        callback_frames.fetch_add(1, Ordering::Relaxed);
+    });
+    frames
+}
+
+/// Wait for a condition that the update thread of a mock matrix paced by the system clock fulfills.
+#[cfg(feature = "std")]
+pub(crate) fn wait_until(mut condition: impl FnMut() -> bool) {
+    let start = Instant::now();
+    while !condition() {
+        assert!(start.elapsed() < Duration::from_secs(5));
+        sleep(Duration::from_millis(1));
+    }
+}
+
+/// Counts the allocations of the threads that enabled counting, on top of the system allocator.
+#[cfg(feature = "std")]
+struct CountingAllocator;
+
+#[cfg(feature = "std")]
+thread_local! {
+    static COUNT_ALLOCATIONS: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+#[cfg(feature = "std")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNT_ALLOCATIONS.with(|count| count.get()) {
+            ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[cfg(feature = "std")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run the function and return the number of allocations it made on the current thread.
+#[cfg(feature = "std")]
+pub(crate) fn count_allocations(f: impl FnOnce()) -> usize {
+    ALLOCATIONS.with(|allocations| allocations.set(0));
+    COUNT_ALLOCATIONS.with(|count| count.set(true));
+    f();
+    COUNT_ALLOCATIONS.with(|count| count.set(false));
+    ALLOCATIONS.with(|allocations| allocations.get())
+}
@@ -0,0 +1,113 @@
+use crate::{Canvas, RGBMatrix};
+
+/// A handle for producing frames without ever waiting for the update thread, created with
+/// [`RGBMatrix::into_threaded`]. The update thread keeps displaying at the configured refresh rate, while the
+/// frames are rendered at their own pace, e.g. on a separate render thread that owns the handle.
+///
+/// The queue holds a single frame. If a frame is submitted before the update thread picked up the previous one,
+/// the previous one is dropped without being displayed, so only the most recent frame is shown and the latency
+/// never grows beyond one frame. Canvases of displayed and dropped frames are handed out again by
+/// [`ThreadedMatrix::next_canvas`], so at most three canvases are used: one displayed, one queued and one being
+/// drawn.
+pub struct ThreadedMatrix {
+    matrix: RGBMatrix,
+    /// The number of frames that were dropped without being displayed.
+    dropped_frames: u64,
+}
+
+impl ThreadedMatrix {
+    pub(crate) fn new(matrix: RGBMatrix) -> Self {
+        Self {
+            matrix,
+            dropped_frames: 0,
+        }
+    }
+
+    /// Queue a canvas to be displayed from the start of the next frame, replacing the queued canvas if it was not
+    /// displayed yet. Never waits for the update thread.
+    pub fn submit(&mut self, canvas: Box<Canvas>) {
+        if self.matrix.submit_canvas(canvas) {
+            self.dropped_frames += 1;
+        }
+    }
+
+    /// Get a canvas for drawing the next frame. It is a canvas that is no longer displayed or a dropped one, with
+    /// its previous contents, or a new canvas if all canvases are in use. Never waits for the update thread.
+    pub fn next_canvas(&mut self) -> Box<Canvas> {
+        self.matrix.free_canvas()
+    }
+
+    /// The number of submitted frames that were replaced by a newer frame before being displayed.
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames
+    }
+
+    /// The matrix, e.g. to read the displayed frame.
+    pub fn matrix(&self) -> &RGBMatrix {
+        &self.matrix
+    }
+
+    /// The matrix, e.g. to change the brightness of the following frames.
+    pub fn matrix_mut(&mut self) -> &mut RGBMatrix {
+        &mut self.matrix
+    }
+
+    /// Turn the handle back into the matrix.
+    pub fn into_inner(self) -> RGBMatrix {
+        self.matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::mpsc::channel,
+        thread::{sleep, spawn},
+        time::{Duration, Instant},
+    };
+
+    use crate::{Color, RGBMatrix, RGBMatrixConfig};
+
+    #[test]
+    fn test_flooded_queue() {
+        let config = RGBMatrixConfig {
+            rows: 16,
+            cols: 32,
+            refresh_rate: 100,
+            ..Default::default()
+        };
+        let (matrix, canvas) = RGBMatrix::new_mock(config).unwrap();
+        let mut threaded = matrix.into_threaded();
+
+        let (done_sender, done_receiver) = channel();
+        let producer = spawn(move || {
+            let mut canvases = HashSet::new();
+            let mut canvas = canvas;
+            for i in 0..1000 {
+                canvases.insert(&*canvas as *const _ as usize);
+                canvas.fill(Color::new((i % 256) as u8, 0, 255));
+                threaded.submit(canvas);
+                canvas = threaded.next_canvas();
+            }
+            done_sender.send(()).unwrap();
+            (threaded, canvases.len())
+        });
+
+        // The producer never waits for the update thread, which only picks up a frame every 10 ms.
+        done_receiver
+            .recv_timeout(Duration::from_secs(5))
+            .expect("The producer is blocked.");
+        let (threaded, canvases) = producer.join().unwrap();
+        assert!(canvases <= 3);
+        assert!(threaded.dropped_frames() > 900);
+
+        // The latest frame is displayed in the end.
+        let last_color = Color::new((999 % 256) as u8, 0, 255);
+        let start = Instant::now();
+        while threaded.matrix().get_frame()[0] != last_color {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            sleep(Duration::from_millis(1));
+        }
+    }
+}
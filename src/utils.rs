@@ -1,23 +1,13 @@
 use std::{
     fs::File,
-    io::{BufRead, BufReader},
-    time::Instant,
+    io::{self, BufRead, BufReader},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
 };
 
-use libc::{cpu_set_t, sched_setaffinity, CPU_SET};
-
-/// Sets the bits that are passed as arguments.
-#[macro_export]
-macro_rules! gpio_bits {
-    ( $( $x:expr ),* ) => {
-        {
-            0
-            $(
-                | (1 << $x)
-            )*
-        }
-    };
-}
+use libc::{
+    c_int, cpu_set_t, sched_setaffinity, sighandler_t, signal, CPU_SET, SIGINT, SIGTERM, SIG_ERR,
+};
 
 pub(crate) fn linux_has_module_loaded(name: &str) -> bool {
     let file = match File::open("/proc/modules") {
@@ -34,24 +24,52 @@ pub(crate) fn linux_has_isol_cpu(cpu: usize) -> bool {
         Err(_) => return false,
     };
     let reader = BufReader::new(file);
-    reader.lines().any(|line| line.unwrap().contains(&cpu.to_string()))
+    reader
+        .lines()
+        .any(|line| line.unwrap().contains(&cpu.to_string()))
 }
 
-pub fn set_thread_affinity(core_id: usize) -> bool {
+/// Pin the current thread to the given core. Returns whether it succeeded.
+pub(crate) fn set_thread_affinity(core_id: usize) -> bool {
     let mut set: cpu_set_t = unsafe { std::mem::zeroed() };
     unsafe { CPU_SET(core_id, &mut set) }
     let cpusetsize = std::mem::size_of::<cpu_set_t>();
     let mask = &set;
     let res = unsafe { sched_setaffinity(0, cpusetsize, mask) };
-    res != 0
+    res == 0
+}
+
+/// Set by the signal handler once SIGINT or SIGTERM was received.
+static INTERRUPT_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handle_interrupt(_signal: c_int) {
+    // Storing to an atomic is async-signal-safe.
+    INTERRUPT_RECEIVED.store(true, Ordering::Relaxed);
+}
+
+/// Replace the handlers of SIGINT and SIGTERM with one that only records the signal.
+pub(crate) fn install_interrupt_handler() -> io::Result<()> {
+    for signum in [SIGINT, SIGTERM] {
+        let handler = handle_interrupt as extern "C" fn(c_int) as sighandler_t;
+        if unsafe { signal(signum, handler) } == SIG_ERR {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Whether SIGINT or SIGTERM was received since the handler was installed.
+pub(crate) fn interrupt_received() -> bool {
+    INTERRUPT_RECEIVED.load(Ordering::Relaxed)
 }
 
 const WINDOW_LENGTH: usize = 60;
 
+/// Averages the frame rate over the last [`WINDOW_LENGTH`] frames.
 pub(crate) struct FrameRateMonitor {
     times: [f32; WINDOW_LENGTH],
     index: usize,
-    last_time: Option<Instant>,
+    last_time: Option<Duration>,
 }
 
 impl FrameRateMonitor {
@@ -63,23 +81,16 @@ impl FrameRateMonitor {
         }
     }
 
-    pub(crate) fn update(&mut self) {
+    /// Record that a frame was finished at the given time of the clock.
+    pub(crate) fn update(&mut self, now: Duration) {
         if let Some(last_time) = self.last_time.take() {
-            self.times[self.index % WINDOW_LENGTH] = last_time.elapsed().as_secs_f32();
+            self.times[self.index % WINDOW_LENGTH] = now.saturating_sub(last_time).as_secs_f32();
             self.index += 1;
         }
-        self.last_time = Some(Instant::now());
+        self.last_time = Some(now);
     }
 
     pub(crate) fn get_fps(&self) -> f32 {
         WINDOW_LENGTH as f32 / self.times.iter().sum::<f32>()
     }
 }
-
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn test_gpio_bits() {
-        assert_eq!(gpio_bits!(1, 4, 5), 1 << 1 | 1 << 4 | 1 << 5);
-    }
-}